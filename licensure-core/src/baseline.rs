@@ -0,0 +1,165 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+//! The findings a project had when it adopted licensure, recorded so they're
+//! left alone and only new findings fail.
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::processor::{LicenseStats, Rule};
+use crate::utils::project_path;
+
+// The version of the baseline format written.
+const BASELINE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct BaselineFile {
+    version: u32,
+    findings: Vec<BaselineFinding>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BaselineFinding {
+    file: String,
+    rule: String,
+}
+
+/// A set of findings, each a file with the rule it was found by.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Baseline {
+    findings: BTreeSet<(String, Rule)>,
+}
+
+impl Baseline {
+    /// The findings of a run, including those left alone because they were
+    /// already in its baseline, so rewriting a baseline keeps them.
+    pub fn from_stats(stats: &LicenseStats) -> Baseline {
+        Baseline {
+            findings: stats
+                .findings
+                .iter()
+                .chain(&stats.files_baselined)
+                .map(|(file, rule)| (project_path(file), *rule))
+                .collect(),
+        }
+    }
+
+    /// Parse a baseline written by render, read from source.
+    pub fn parse(content: &str, source: &str) -> Result<Baseline, String> {
+        let file: BaselineFile = serde_json::from_str(content)
+            .map_err(|e| format!("{} isn't a valid baseline: {}", source, e))?;
+        if file.version > BASELINE_VERSION {
+            return Err(format!(
+                "{} is baseline version {} but this licensure only supports up to version {}, please upgrade licensure",
+                source, file.version, BASELINE_VERSION
+            ));
+        }
+
+        let findings = file
+            .findings
+            .into_iter()
+            .map(|finding| {
+                let rule = finding
+                    .rule
+                    .parse()
+                    .map_err(|e| format!("{} isn't a valid baseline: {}", source, e))?;
+                Ok((project_path(&finding.file), rule))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Baseline { findings })
+    }
+
+    /// Render the baseline as JSON, sorted by file so it diffs well.
+    pub fn render(&self) -> String {
+        let file = BaselineFile {
+            version: BASELINE_VERSION,
+            findings: self
+                .findings
+                .iter()
+                .map(|(file, rule)| BaselineFinding {
+                    file: file.clone(),
+                    rule: rule.id().to_string(),
+                })
+                .collect(),
+        };
+        serde_json::to_string_pretty(&file).expect("baseline is valid JSON")
+    }
+
+    /// Returns true if file was found by rule when the baseline was written.
+    pub fn contains(&self, file: &str, rule: Rule) -> bool {
+        self.findings.contains(&(project_path(file), rule))
+    }
+
+    /// The findings of the files beneath dir, relative to it, for licensing
+    /// a nested repository from within it.
+    pub fn within(&self, dir: &str) -> Baseline {
+        let dir = project_path(dir);
+        let dir = Path::new(&dir);
+        Baseline {
+            findings: self
+                .findings
+                .iter()
+                .filter_map(|(file, rule)| {
+                    let file = Path::new(file).strip_prefix(dir).ok()?;
+                    Some((project_path(&file.to_string_lossy()), *rule))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_baseline() {
+        let mut stats = LicenseStats::default();
+        stats
+            .findings
+            .push(("./src/main.rs".to_string(), Rule::OutdatedYear));
+        stats
+            .files_baselined
+            .push(("vendor/lib/a.c".to_string(), Rule::MissingHeader));
+
+        let baseline = Baseline::from_stats(&stats);
+        let rendered = baseline.render();
+        assert!(rendered.contains("\"file\": \"src/main.rs\""));
+        assert_eq!(Ok(baseline.clone()), Baseline::parse(&rendered, "b.json"));
+
+        assert!(baseline.contains("src/main.rs", Rule::OutdatedYear));
+        assert!(!baseline.contains("src/main.rs", Rule::MissingHeader));
+        assert!(baseline
+            .within("vendor")
+            .contains("lib/a.c", Rule::MissingHeader));
+        assert_eq!(1, baseline.within("./vendor").len());
+
+        assert!(Baseline::parse(
+            r#"{"version": 1, "findings": [{"file": "a", "rule": "X"}]}"#,
+            "b.json"
+        )
+        .unwrap_err()
+        .contains("unknown rule X"));
+        assert!(Baseline::parse(r#"{"version": 2, "findings": []}"#, "b.json").is_err());
+    }
+}
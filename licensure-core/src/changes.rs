@@ -0,0 +1,132 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+//! The lines of each file a branch changed, read from the hunks of a diff
+//! against its base, so findings in headers the branch didn't touch can be
+//! told apart from those it introduced.
+use std::collections::HashMap;
+
+use crate::utils::project_path;
+
+/// How a file was changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// The file is new.
+    Added,
+    /// The lines of the file, first and last counted from 1, which were
+    /// added or replaced. Where lines were only removed the lines either side
+    /// of them count as changed.
+    Lines(Vec<(usize, usize)>),
+}
+
+impl Change {
+    /// Returns true if the change touches any of the lines first to last.
+    pub fn touches(&self, first: usize, last: usize) -> bool {
+        match self {
+            Change::Added => true,
+            Change::Lines(lines) => lines
+                .iter()
+                .any(|(start, end)| *start <= last && first <= *end),
+        }
+    }
+}
+
+// The new lines of a hunk header like "@@ -12,3 +14,0 @@".
+fn hunk_lines(header: &str) -> Option<(usize, usize)> {
+    let new = header.split_whitespace().nth(2)?.strip_prefix('+')?;
+    let (start, count) = match new.split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (new.parse().ok()?, 1),
+    };
+    Some(match count {
+        0 => (start, start + 1),
+        count => (start, start + count - 1),
+    })
+}
+
+/// The changes of every file in a diff without context lines, as git diff
+/// -U0 prints, keyed by their path as project_path gives it.
+pub fn parse_diff(diff: &str) -> HashMap<String, Change> {
+    let mut changes = HashMap::new();
+    let mut added = false;
+    let mut file: Option<String> = None;
+    for line in diff.lines() {
+        if line.starts_with("diff ") {
+            added = false;
+            file = None;
+        } else if line.starts_with("new file mode") {
+            added = true;
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.trim_matches('"');
+            file = path.strip_prefix("b/").map(project_path);
+            if let Some(file) = &file {
+                let change = if added {
+                    Change::Added
+                } else {
+                    Change::Lines(Vec::new())
+                };
+                changes.insert(file.clone(), change);
+            }
+        } else if line.starts_with("@@ ") {
+            let lines = hunk_lines(line);
+            if let (Some(Change::Lines(changed)), Some(lines)) =
+                (file.as_ref().and_then(|file| changes.get_mut(file)), lines)
+            {
+                changed.push(lines);
+            }
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diff() {
+        let diff = r#"diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1 @@
+-// Copyright 2019 The Tester
++// Copyright 2024 The Tester
+@@ -40,2 +40,0 @@ fn main() {
+-    old();
+-    older();
+@@ -90,0 +89,3 @@ fn run() {
++    a();
++    b();
++    c();
+diff --git a/src/new.rs b/src/new.rs
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/src/new.rs
+@@ -0,0 +1,2 @@
++fn new() {}
++
+"#;
+        let changes = parse_diff(diff);
+        assert_eq!(Some(&Change::Added), changes.get("src/new.rs"));
+
+        let main = changes.get("src/main.rs").expect("main.rs changed");
+        assert_eq!(&Change::Lines(vec![(1, 1), (40, 41), (89, 91)]), main);
+        assert!(main.touches(1, 3));
+        assert!(main.touches(41, 50));
+        assert!(!main.touches(2, 39));
+        assert!(!main.touches(92, 100));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::comments::line_comment::LineComment;
+use crate::utils::{fill, strip_verbatim};
+
+use super::{indent_lines, indent_width, Comment};
+
+pub struct BlockComment {
+    start: String,
+    end: String,
+    per_line: Option<String>,
+    // Written in place of the end marker where the text contains it, so the
+    // comment isn't closed early.
+    end_escape: Option<String>,
+    trailing_lines: usize,
+    cols: Option<usize>,
+    indent: String,
+}
+
+impl BlockComment {
+    pub fn new(start: &str, end: &str, cols: Option<usize>) -> BlockComment {
+        BlockComment {
+            start: String::from(start),
+            end: String::from(end),
+            per_line: None,
+            end_escape: None,
+            trailing_lines: 0,
+            cols,
+            indent: String::new(),
+        }
+    }
+
+    pub fn set_trailing_lines(mut self, num_lines: usize) -> BlockComment {
+        self.trailing_lines = num_lines;
+        self
+    }
+
+    pub fn with_per_line(mut self, per_line: &str) -> BlockComment {
+        self.per_line = Some(per_line.to_string());
+        self
+    }
+
+    pub fn with_end_escape(mut self, escape: &str) -> BlockComment {
+        self.end_escape = Some(escape.to_string());
+        self
+    }
+
+    pub fn with_indent(mut self, indent: &str) -> BlockComment {
+        self.indent = indent.to_string();
+        self
+    }
+}
+
+impl Comment for BlockComment {
+    fn comment(&self, text: &str) -> Result<String, String> {
+        let escaped;
+        let text = match &self.end_escape {
+            Some(escape) if !self.end.trim().is_empty() => {
+                escaped = text.replace(self.end.trim(), escape);
+                escaped.as_str()
+            }
+            _ => text,
+        };
+        let mut new_text = self.start.clone();
+        let wrapped_text;
+        let cols = self
+            .cols
+            .map(|cols| cols.saturating_sub(indent_width(&self.indent)));
+
+        match self.per_line {
+            Some(ref per_line) => {
+                let commented_text = LineComment::new(per_line, cols)
+                    .skip_trailing_lines()
+                    .comment(text)?;
+                new_text.push_str(&commented_text);
+            }
+            None => new_text.push_str(match cols {
+                Some(cols) => {
+                    wrapped_text = fill(text, cols);
+                    wrapped_text.as_str()
+                }
+                None => {
+                    wrapped_text = strip_verbatim(text);
+                    wrapped_text.as_str()
+                }
+            }),
+        };
+
+        new_text.push_str(&self.end);
+
+        let mut new_text = indent_lines(&new_text, &self.indent);
+        for _ in 0..self.trailing_lines {
+            new_text.push('\n');
+        }
+
+        Ok(new_text)
+    }
+
+    fn leading_comment_len(&self, content: &str) -> Option<usize> {
+        let start = self.start.trim();
+        let end = self.end.trim();
+        // Without start and end markers, as with sidecar files, the whole of
+        // content is the comment.
+        if start.is_empty() || end.is_empty() {
+            return if content.is_empty() {
+                None
+            } else {
+                Some(content.len())
+            };
+        }
+
+        let body = content.trim_start();
+        let offset = content.len() - body.len();
+        let body = body.strip_prefix(start)?;
+        let len = offset + start.len() + body.find(end)? + end.len();
+        Some(match content[len..].find('\n') {
+            Some(newline) if content[len..len + newline].trim().is_empty() => len + newline + 1,
+            _ => len,
+        })
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::io::{self, Write};
+use std::process::Stdio;
+
+use crate::utils::{shell, strip_verbatim};
+
+use super::Comment;
+
+/// Comments a header by running a command which is given the header on stdin
+/// and prints the commented header on stdout, for formats licensure has no
+/// built in commenter for.
+pub struct ExternalComment {
+    command: String,
+    cols: Option<usize>,
+}
+
+impl ExternalComment {
+    pub fn new(command: &str, cols: Option<usize>) -> ExternalComment {
+        ExternalComment {
+            command: command.to_string(),
+            cols,
+        }
+    }
+
+    fn run(&self, text: &str) -> Result<String, io::Error> {
+        let mut cmd = shell(&self.command);
+        if let Some(cols) = self.cols {
+            cmd.env("LICENSURE_COLUMNS", cols.to_string());
+        }
+
+        let mut child = cmd.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A command which doesn't read all of stdin may exit before it is
+            // written.
+            if let Err(e) = stdin.write_all(strip_verbatim(text).as_bytes()) {
+                if e.kind() != io::ErrorKind::BrokenPipe {
+                    return Err(e);
+                }
+            }
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!("exited with {}", output.status)));
+        }
+
+        String::from_utf8(output.stdout)
+            .map_err(|_| io::Error::other("printed a header which isn't UTF-8"))
+    }
+}
+
+impl Comment for ExternalComment {
+    fn comment(&self, text: &str) -> Result<String, String> {
+        self.run(text)
+            .map_err(|e| format!("external commenter {} failed: {}", self.command, e))
+    }
+
+    // There is no way to tell where a comment written in an unknown format
+    // ends so existing headers are never detected.
+    fn leading_comment_len(&self, _content: &str) -> Option<usize> {
+        None
+    }
+}
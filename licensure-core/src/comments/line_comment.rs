@@ -11,12 +11,15 @@
 // You should have received a copy of the GNU General Public License along with
 // this program. If not, see <https://www.gnu.org/licenses/>.
 //
-use super::Comment;
+use crate::utils::{display_width, fill, strip_verbatim};
+
+use super::{indent_lines, indent_width, Comment};
 
 pub struct LineComment {
     character: String,
     trailing_lines: usize,
     cols: Option<usize>,
+    indent: String,
 }
 
 impl LineComment {
@@ -25,9 +28,15 @@ impl LineComment {
             character: String::from(character),
             trailing_lines: 0,
             cols,
+            indent: String::new(),
         }
     }
 
+    pub fn with_indent(mut self, indent: &str) -> LineComment {
+        self.indent = indent.to_string();
+        self
+    }
+
     pub fn set_trailing_lines(mut self, num_lines: usize) -> LineComment {
         self.trailing_lines = num_lines;
         self
@@ -40,14 +49,15 @@ impl LineComment {
 }
 
 impl Comment for LineComment {
-    fn comment(&self, text: &str) -> String {
+    fn comment(&self, text: &str) -> Result<String, String> {
         let local_copy = match self.cols {
             Some(cols) => {
-                // Subtract two columns to account for the comment
-                // character and space we will add later.
-                textwrap::fill(text, if cols > 2 { cols - 2 } else { cols })
+                // Subtract the width of the indent, comment character and
+                // the space we will add later.
+                let gutter = indent_width(&self.indent) + display_width(&self.character) + 1;
+                fill(text, cols.saturating_sub(gutter))
             }
-            None => text.to_string(),
+            None => strip_verbatim(text),
         };
 
         let mut lines: Vec<&str> = local_copy.split('\n').collect();
@@ -67,10 +77,26 @@ impl Comment for LineComment {
             new_text.push_str(&new_line);
         }
 
+        let mut new_text = indent_lines(&new_text, &self.indent);
         for _ in 0..self.trailing_lines {
             new_text.push('\n');
         }
 
-        new_text
+        Ok(new_text)
+    }
+
+    fn leading_comment_len(&self, content: &str) -> Option<usize> {
+        let character = self.character.trim();
+        let len: usize = content
+            .split_inclusive('\n')
+            .take_while(|line| line.trim_start().starts_with(character))
+            .map(str::len)
+            .sum();
+
+        if len == 0 {
+            None
+        } else {
+            Some(len)
+        }
     }
 }
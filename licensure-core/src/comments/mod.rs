@@ -0,0 +1,241 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+pub use block_comment::BlockComment;
+pub use external_comment::ExternalComment;
+pub use line_comment::LineComment;
+
+mod block_comment;
+mod external_comment;
+mod line_comment;
+
+pub trait Comment {
+    /// Returns text commented, failing if it can't be, as when an external
+    /// commenter fails.
+    fn comment(&self, text: &str) -> Result<String, String>;
+
+    /// Returns the length in bytes of the comment content starts with, if it
+    /// starts with one, so an existing header can be found and removed.
+    fn leading_comment_len(&self, content: &str) -> Option<usize>;
+}
+
+// Tabs in an indent are assumed to be this wide when calculating how much
+// room is left on a line for the header text.
+const TAB_WIDTH: usize = 8;
+
+fn indent_width(indent: &str) -> usize {
+    indent
+        .chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+// Prefix every line which isn't blank with indent.
+fn indent_lines(text: &str, indent: &str) -> String {
+    if indent.is_empty() {
+        return text.to_string();
+    }
+
+    text.split_inclusive('\n')
+        .map(|line| {
+            if line.trim_end_matches('\n').is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", indent, line)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EX_TEXT: &str = "There once was a man
+with a very nice cat
+the cat wore a top hat
+it looked super dapper
+";
+
+    #[cfg(unix)]
+    #[test]
+    fn test_comment_external() {
+        assert_eq!(
+            "C There once was a man
+C with a very nice cat
+C the cat wore a top hat
+C it looked super dapper
+",
+            ExternalComment::new("sed 's/^/C /'", None)
+                .comment(EX_TEXT)
+                .unwrap()
+        )
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_comment_external_failure() {
+        assert_eq!(
+            Err("external commenter exit 3 failed: exited with exit status: 3".to_string()),
+            ExternalComment::new("exit 3", None).comment(EX_TEXT)
+        )
+    }
+
+    #[test]
+    fn test_comment_python() {
+        assert_eq!(
+            "# There once was a man
+# with a very nice cat
+# the cat wore a top hat
+# it looked super dapper
+",
+            LineComment::new("#", None).comment(EX_TEXT).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_comment_python_w_trailing_lines() {
+        assert_eq!(
+            "# There once was a man
+# with a very nice cat
+# the cat wore a top hat
+# it looked super dapper
+
+
+",
+            LineComment::new("#", None)
+                .set_trailing_lines(2)
+                .comment(EX_TEXT)
+                .unwrap()
+        )
+    }
+
+    #[test]
+    fn test_comment_cpp() {
+        assert_eq!(
+            "/*
+* There once was a man
+* with a very nice cat
+* the cat wore a top hat
+* it looked super dapper
+*/",
+            BlockComment::new("/*\n", "*/", None)
+                .with_per_line("*")
+                .comment(EX_TEXT)
+                .unwrap()
+        )
+    }
+
+    #[test]
+    fn test_comment_cpp_w_trailing_lines() {
+        assert_eq!(
+            "/*
+* There once was a man
+* with a very nice cat
+* the cat wore a top hat
+* it looked super dapper
+*/
+
+",
+            BlockComment::new("/*\n", "*/", None)
+                .with_per_line("*")
+                .set_trailing_lines(2)
+                .comment(EX_TEXT)
+                .unwrap()
+        )
+    }
+
+    #[test]
+    fn test_comment_html() {
+        assert_eq!(
+            "<!--
+There once was a man
+with a very nice cat
+the cat wore a top hat
+it looked super dapper
+-->",
+            BlockComment::new("<!--\n", "-->", None)
+                .comment(EX_TEXT)
+                .unwrap()
+        )
+    }
+
+    #[test]
+    fn test_comment_wraps_to_display_width() {
+        let text = "Copyright © 2024 株式会社サンプル. 本ソフトウェアはライセンスの条件に従って使用してください。";
+        let commented = LineComment::new("//", Some(30)).comment(text).unwrap();
+        for line in commented.lines() {
+            assert!(
+                crate::utils::display_width(line) <= 30,
+                "{:?} is wider than 30 columns",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_comment_indented() {
+        assert_eq!(
+            "    # There once was a man
+    # with a very nice cat
+    # the cat wore a top hat
+    # it looked super dapper
+
+",
+            LineComment::new("#", None)
+                .with_indent("    ")
+                .set_trailing_lines(1)
+                .comment(EX_TEXT)
+                .unwrap()
+        )
+    }
+
+    #[test]
+    fn test_comment_cpp_tab_indented() {
+        assert_eq!(
+            "\t/*
+\t * There once was a man
+\t * with a very nice cat
+\t */",
+            BlockComment::new("/*\n", " */", Some(32))
+                .with_per_line(" *")
+                .with_indent("\t")
+                .comment("There once was a man with a very nice cat")
+                .unwrap()
+        )
+    }
+
+    #[test]
+    fn test_leading_comment_len() {
+        let content = "// Copyright 2001 Someone\n//\n// MIT\n\nfn main() {}\n";
+        assert_eq!(
+            Some(36),
+            LineComment::new("//", None).leading_comment_len(content)
+        );
+        assert_eq!(
+            None,
+            LineComment::new("#", None).leading_comment_len(content)
+        );
+
+        let content = "/*\n * Copyright 2001 Someone\n */\nint main() {}\n";
+        assert_eq!(
+            Some(33),
+            BlockComment::new("/*\n", " */", None).leading_comment_len(content)
+        );
+        assert_eq!(
+            None,
+            BlockComment::new("<!--\n", "-->", None).leading_comment_len(content)
+        );
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::cmp::Reverse;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::template::CopyrightHolder;
+use crate::utils::normalize_path;
+
+const MANIFEST: &str = "Cargo.toml";
+
+/// A crate in a Cargo workspace and the license information from its
+/// Cargo.toml.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Member {
+    path: String,
+    pub license: Option<String>,
+    pub authors: Vec<CopyrightHolder>,
+}
+
+/// The members of the Cargo workspace in the current directory.
+#[derive(Debug, Default)]
+pub struct CargoWorkspace {
+    members: Vec<Member>,
+}
+
+// Returns the raw value of key in the given table of a TOML document, with
+// arrays spanning several lines joined onto one. A full TOML parser isn't
+// used as only a handful of simple keys are needed.
+fn toml_value(content: &str, table: &str, key: &str) -> Option<String> {
+    let mut in_table = false;
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_table = line.trim_matches(|c| c == '[' || c == ']').trim() == table;
+            continue;
+        }
+
+        if !in_table {
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once('=') else {
+            continue;
+        };
+        let name = name.trim();
+        if name == format!("{}.workspace", key) {
+            return Some(format!("{{ workspace = {} }}", value.trim()));
+        }
+        if name != key {
+            continue;
+        }
+
+        let mut value = value.trim().to_string();
+        while value.starts_with('[') && !value.trim_end().ends_with(']') {
+            match lines.next() {
+                Some(next) => value.push_str(next.trim()),
+                None => break,
+            }
+        }
+
+        return Some(value);
+    }
+
+    None
+}
+
+fn is_inherited(value: &str) -> bool {
+    static INHERITED: OnceLock<Regex> = OnceLock::new();
+    INHERITED
+        .get_or_init(|| {
+            Regex::new(r"^\{\s*workspace\s*=\s*true\s*\}$")
+                .expect("workspace inheritance regex didn't compile!")
+        })
+        .is_match(value)
+}
+
+// The quoted strings in a TOML value, which is either a string or an array of
+// strings.
+fn toml_strings(value: &str) -> Vec<String> {
+    static STRING: OnceLock<Regex> = OnceLock::new();
+    STRING
+        .get_or_init(|| Regex::new(r#""([^"]*)""#).expect("toml string regex didn't compile!"))
+        .captures_iter(value)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+// Read key from the package table of a member, or from the workspace.package
+// table of the root manifest when the member inherits it.
+fn package_value(member: &str, root: &str, key: &str) -> Option<String> {
+    let value = toml_value(member, "package", key)?;
+    if is_inherited(&value) {
+        toml_value(root, "workspace.package", key)
+    } else {
+        Some(value)
+    }
+}
+
+// Translate a workspace members glob into a regex matching one path
+// component at a time.
+fn component_regex(component: &str) -> Option<Regex> {
+    let mut re = String::from("^");
+    for c in component.chars() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+
+    Regex::new(&re).ok()
+}
+
+// Expand a workspace members entry, which may contain globs, to the
+// directories it names relative to root.
+fn expand_member(root: &Path, pattern: &str) -> Vec<String> {
+    let mut dirs = vec![String::new()];
+    for component in pattern.trim_matches('/').split('/') {
+        if component.is_empty() || component == "." {
+            continue;
+        }
+
+        if !component.contains(['*', '?']) {
+            for dir in dirs.iter_mut() {
+                if !dir.is_empty() {
+                    dir.push('/');
+                }
+                dir.push_str(component);
+            }
+            continue;
+        }
+
+        let Some(re) = component_regex(component) else {
+            return Vec::new();
+        };
+        let mut expanded = Vec::new();
+        for dir in &dirs {
+            let Ok(entries) = fs::read_dir(root.join(dir)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if entry.path().is_dir() && re.is_match(&name) {
+                    expanded.push(if dir.is_empty() {
+                        name
+                    } else {
+                        format!("{}/{}", dir, name)
+                    });
+                }
+            }
+        }
+        dirs = expanded;
+    }
+
+    dirs.sort();
+    dirs
+}
+
+impl CargoWorkspace {
+    /// Read the workspace whose root manifest is in root. Returns None if
+    /// there is no Cargo.toml or it doesn't define a workspace.
+    pub fn read(root: &Path) -> Option<CargoWorkspace> {
+        let root_manifest = fs::read_to_string(root.join(MANIFEST)).ok()?;
+        let members = toml_value(&root_manifest, "workspace", "members")?;
+        let excludes = toml_value(&root_manifest, "workspace", "exclude")
+            .map(|value| toml_strings(&value))
+            .unwrap_or_default();
+
+        // A root manifest with a package table is itself a member.
+        let mut paths = Vec::new();
+        if toml_value(&root_manifest, "package", "name").is_some() {
+            paths.push(String::new());
+        }
+        for pattern in toml_strings(&members) {
+            for path in expand_member(root, &pattern) {
+                if !excludes.contains(&path) && !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        let mut workspace = CargoWorkspace::default();
+        for path in paths {
+            let Ok(manifest) = fs::read_to_string(root.join(&path).join(MANIFEST)) else {
+                continue;
+            };
+            let license = package_value(&manifest, &root_manifest, "license")
+                .and_then(|value| toml_strings(&value).into_iter().next());
+            let authors = package_value(&manifest, &root_manifest, "authors")
+                .map(|value| {
+                    toml_strings(&value)
+                        .iter()
+                        .map(|author| CopyrightHolder::parse(author))
+                        .collect()
+                })
+                .unwrap_or_default();
+            workspace.members.push(Member {
+                path,
+                license,
+                authors,
+            });
+        }
+
+        // The most deeply nested crate owns a file so sort the longest paths
+        // first.
+        workspace
+            .members
+            .sort_by_key(|member| Reverse(member.path.len()));
+        Some(workspace)
+    }
+
+    /// Load the workspace at root, the current directory when it's empty.
+    pub fn load(root: &Path) -> Option<CargoWorkspace> {
+        if root.as_os_str().is_empty() {
+            CargoWorkspace::read(Path::new("."))
+        } else {
+            CargoWorkspace::read(root)
+        }
+    }
+
+    /// Returns the workspace member which filename belongs to.
+    pub fn member(&self, filename: &str) -> Option<&Member> {
+        let filename = normalize_path(filename);
+        let filename = filename.trim_start_matches("./");
+        self.members.iter().find(|member| {
+            // The root package owns everything not in another member.
+            member.path.is_empty()
+                || filename
+                    .strip_prefix(&member.path)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// Returns every distinct license of the workspace members.
+    pub fn licenses(&self) -> Vec<&str> {
+        let mut licenses = Vec::new();
+        for license in self.members.iter().filter_map(|m| m.license.as_deref()) {
+            if !licenses.contains(&license) {
+                licenses.push(license);
+            }
+        }
+
+        licenses
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn test_toml_value() {
+        let content = r#"
+[package]
+name = "core"
+license.workspace = true
+authors = [
+    "The Tester <tester@example.com>",
+    "Someone Else",
+]
+
+[dependencies]
+license = "not this one"
+"#;
+        assert_eq!(
+            Some("{ workspace = true }".to_string()),
+            toml_value(content, "package", "license")
+        );
+        assert_eq!(
+            vec!["The Tester <tester@example.com>", "Someone Else"],
+            toml_strings(&toml_value(content, "package", "authors").unwrap())
+        );
+        assert_eq!(None, toml_value(content, "package", "description"));
+    }
+
+    #[test]
+    fn test_read_workspace() {
+        let dir = TempDir::new("cargo");
+        dir.write(
+            "Cargo.toml",
+            r#"[package]
+name = "app"
+license = "Apache-2.0"
+
+[workspace]
+members = ["crates/*", "tools/cli"]
+exclude = ["crates/scratch"]
+
+[workspace.package]
+license = "MIT"
+"#,
+        );
+        dir.write(
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"core\"\nlicense = { workspace = true }\n",
+        );
+        dir.write(
+            "crates/secret/Cargo.toml",
+            "[package]\nname = \"secret\"\nlicense = \"LicenseRef-Proprietary\"\nauthors = [\"Example Corp <legal@example.com>\"]\n",
+        );
+        dir.write(
+            "crates/scratch/Cargo.toml",
+            "[package]\nname = \"scratch\"\n",
+        );
+        dir.write("tools/cli/Cargo.toml", "[package]\nname = \"cli\"\n");
+
+        let workspace = CargoWorkspace::read(&dir).expect("Workspace is found");
+        assert_eq!(
+            Some("MIT"),
+            workspace
+                .member("./crates/core/src/lib.rs")
+                .and_then(|m| m.license.as_deref())
+        );
+        let secret = workspace.member("crates/secret/src/lib.rs").unwrap();
+        assert_eq!(Some("LicenseRef-Proprietary"), secret.license.as_deref());
+        assert_eq!(
+            vec![CopyrightHolder::parse("Example Corp <legal@example.com>")],
+            secret.authors
+        );
+        assert_eq!(None, workspace.member("tools/cli/main.rs").unwrap().license);
+        for file in [
+            "crates/scratch/lib.rs",
+            "crates/core-extra/lib.rs",
+            "src/main.rs",
+        ] {
+            assert_eq!(
+                Some("Apache-2.0"),
+                workspace.member(file).and_then(|m| m.license.as_deref())
+            );
+        }
+        assert_eq!(
+            vec!["LicenseRef-Proprietary", "MIT", "Apache-2.0"],
+            workspace.licenses()
+        );
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::utils::normalize_path;
+
+// The places GitHub and GitLab look for a CODEOWNERS file, in the order they
+// look.
+const CODEOWNERS_PATHS: &[&str] = &[
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+#[derive(Debug)]
+struct Rule {
+    pattern: Regex,
+    owners: Vec<String>,
+}
+
+/// The ownership rules of a CODEOWNERS file.
+#[derive(Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+// Translate a gitignore style CODEOWNERS pattern to a regex matching the
+// paths it owns. Patterns containing a slash other than a trailing one are
+// relative to the repository root, others match at any depth, and a pattern
+// matching a directory owns everything beneath it.
+fn pattern_to_regex(pattern: &str) -> Option<Regex> {
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+
+    let mut re = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = trimmed.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                re.push_str(".*");
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push_str("(?:/.*)?$");
+
+    Regex::new(&re).ok()
+}
+
+impl CodeOwners {
+    pub fn parse(content: &str) -> CodeOwners {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            // Skip comments and GitLab section headings.
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let pattern = fields.next()?;
+                let owners = fields
+                    .take_while(|f| !f.starts_with('#'))
+                    .map(str::to_string)
+                    .collect();
+                match pattern_to_regex(pattern) {
+                    Some(pattern) => Some(Rule { pattern, owners }),
+                    None => {
+                        warn!("ignoring invalid CODEOWNERS pattern {}", pattern);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        CodeOwners { rules }
+    }
+
+    /// Load the CODEOWNERS file of the repository at root from the first
+    /// place it is found.
+    pub fn load(root: &Path) -> Option<CodeOwners> {
+        CODEOWNERS_PATHS
+            .iter()
+            .find_map(|path| fs::read_to_string(root.join(path)).ok())
+            .map(|content| CodeOwners::parse(&content))
+    }
+
+    /// Returns the owners of filename. As with GitHub the last matching rule
+    /// wins.
+    pub fn owners(&self, filename: &str) -> &[String] {
+        let filename = normalize_path(filename);
+        let filename = filename.trim_start_matches("./");
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.pattern.is_match(filename))
+            .map_or(&[], |rule| &rule.owners)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CODEOWNERS: &str = "# Default owners
+*       @example/everyone
+*.go    @gopher # Go files
+/docs/  @example/docs-team docs@example.com
+build/  @builder
+
+[Frontend]
+web/**/*.ts @example/frontend
+";
+
+    #[test]
+    fn test_owners() {
+        let owners = CodeOwners::parse(CODEOWNERS);
+        assert_eq!(["@example/everyone"], owners.owners("README.md"));
+        assert_eq!(["@gopher"], owners.owners("cmd/main.go"));
+        assert_eq!(
+            ["@example/docs-team", "docs@example.com"],
+            owners.owners("docs/guide/index.go")
+        );
+        assert_eq!(["@example/everyone"], owners.owners("src/docs/x.md"));
+        assert_eq!(["@builder"], owners.owners("tools/build/run.sh"));
+        assert_eq!(["@example/frontend"], owners.owners("web/src/app/main.ts"));
+        assert!(CodeOwners::default().owners("README.md").is_empty());
+    }
+}
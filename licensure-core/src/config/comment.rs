@@ -0,0 +1,850 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::comments::BlockComment;
+use crate::comments::Comment;
+use crate::comments::ExternalComment;
+use crate::comments::LineComment;
+
+use super::{xdg_config_dir, RegexList};
+
+// The curated comment presets, see presets.yml.
+const PRESETS: &str = include_str!("presets.yml");
+// The file in the licensure config directory users add presets in, or
+// replace curated ones, in the format of presets.yml.
+const USER_PRESETS: &str = "presets.yml";
+
+fn get_basename(filename: &str) -> &str {
+    let separators: &[char] = if cfg!(windows) { &['/', '\\'] } else { &['/'] };
+    filename.rsplit(separators).next().unwrap_or_default()
+}
+
+/// Returns the extension of the file, or the whole file name for files like
+/// Makefile which have no extension.
+pub fn get_filetype(filename: &str) -> &str {
+    let mut iter = get_basename(filename).split('.');
+    iter.next_back().unwrap_or_default()
+}
+
+/// Returns the name of the program from a shebang on the first line of
+/// content. For "#!/usr/bin/env python3" this is python3 and for "#!/bin/bash"
+/// it is bash.
+pub fn get_interpreter(content: &str) -> Option<&str> {
+    let shebang = content.lines().next()?.strip_prefix("#!")?;
+    let mut parts = shebang.split_whitespace();
+    let program = parts.next()?.rsplit('/').next()?;
+    if program == "env" {
+        // Skip over any options or variable assignments given to env.
+        parts.find(|p| !p.starts_with('-') && !p.contains('='))
+    } else {
+        Some(program)
+    }
+}
+
+/// Where in a file its header is added.
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Position {
+    /// At the top, after any shebang.
+    #[default]
+    Top,
+    /// At the end, for formats which carry license notes at the bottom or
+    /// whose first lines are reserved.
+    Bottom,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum Commenter {
+    #[serde(alias = "block")]
+    Block {
+        start_block_char: String,
+        end_block_char: String,
+        per_line_char: Option<String>,
+        // Written in place of end_block_char where the header contains it.
+        #[serde(default)]
+        end_block_char_escape: Option<String>,
+        #[serde(default)]
+        trailing_lines: Option<usize>,
+        #[serde(default)]
+        indent: Option<usize>,
+        #[serde(default)]
+        use_tabs: bool,
+    },
+    #[serde(alias = "line")]
+    Line {
+        comment_char: String,
+        #[serde(default)]
+        trailing_lines: Option<usize>,
+        #[serde(default)]
+        indent: Option<usize>,
+        #[serde(default)]
+        use_tabs: bool,
+    },
+    // Files which can't contain comments get their header as plain text in a
+    // <filename>.license file next to them.
+    #[serde(alias = "sidecar")]
+    Sidecar,
+    // The header is commented by a command which reads it on stdin and
+    // prints the commented header on stdout.
+    #[serde(alias = "external")]
+    External { command: String },
+}
+
+impl Commenter {
+    // Fail if the commenter can't write a comment licensure finds again, such
+    // as one with an empty comment_char, explaining what to change.
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Commenter::Line { comment_char, .. } if comment_char.trim().is_empty() => {
+                Err("the comment_char of a line commenter is empty, use a sidecar commenter for files which can't hold comments".to_string())
+            }
+            Commenter::Block {
+                start_block_char,
+                end_block_char,
+                per_line_char,
+                end_block_char_escape,
+                ..
+            } => {
+                let start = start_block_char.trim();
+                let end = end_block_char.trim();
+                if start.is_empty() || end.is_empty() {
+                    return Err(format!(
+                        "the {} of a block commenter is empty, use a sidecar commenter for files which can't hold comments",
+                        if start.is_empty() { "start_block_char" } else { "end_block_char" }
+                    ));
+                }
+                match end_block_char_escape {
+                    Some(escape) if escape.trim().is_empty() || escape.contains(end) => {
+                        return Err(format!(
+                            "the end_block_char_escape {:?} of a block commenter would still end the comment, use text without {} such as {}",
+                            escape,
+                            end,
+                            escape_example(end)
+                        ));
+                    }
+                    _ => (),
+                }
+
+                let Some(per_line) = per_line_char else {
+                    return Ok(());
+                };
+                if per_line.trim().is_empty() {
+                    return Err("the per_line_char of a block commenter is empty, leave it out to not start each line with one".to_string());
+                }
+                for (name, marker) in [("start_block_char", start), ("end_block_char", end)] {
+                    if per_line.contains(marker) {
+                        return Err(format!(
+                            "the per_line_char {:?} of a block commenter contains its {} {:?}, so the comment can't be found again, use a per_line_char without it",
+                            per_line, name, marker
+                        ));
+                    }
+                }
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Fail if header contains the start or end of a block commenter, which
+    // would nest or end its comment early. name is what the error calls the
+    // comments.
+    fn check_header(&self, header: &str, name: &str) -> Result<(), String> {
+        let Commenter::Block {
+            start_block_char,
+            end_block_char,
+            end_block_char_escape,
+            ..
+        } = self
+        else {
+            return Ok(());
+        };
+
+        let start = start_block_char.trim();
+        let end = end_block_char.trim();
+        if end_block_char_escape.is_none() && header.contains(end) {
+            return Err(format!(
+                "contains {}, which would end {} early, remove it from the template, set end_block_char_escape to write something like {} in its place, or use a line commenter",
+                end,
+                name,
+                escape_example(end)
+            ));
+        }
+        if header.contains(start) {
+            return Err(format!(
+                "contains {}, which would open a comment within {}, remove it from the template or use a line commenter",
+                start, name
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+// How end might be escaped, with a backslash before its last character as
+// in *\/.
+fn escape_example(end: &str) -> String {
+    match end.char_indices().last() {
+        Some((last, _)) => format!("{}\\{}", &end[..last], &end[last..]),
+        None => String::new(),
+    }
+}
+
+// Build the leading whitespace for each line of a header. indent is a number
+// of spaces, or of tabs when use_tabs is set in which case it defaults to one.
+fn indent_string(indent: &Option<usize>, use_tabs: bool) -> String {
+    if use_tabs {
+        "\t".repeat(indent.unwrap_or(1))
+    } else {
+        " ".repeat(indent.unwrap_or(0))
+    }
+}
+
+/// Settings which can be given once at the top level of the config and are
+/// inherited by every comment config that doesn't set them itself.
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct Defaults {
+    #[serde(default)]
+    pub columns: Option<usize>,
+    #[serde(default)]
+    pub trailing_lines: Option<usize>,
+    #[serde(default)]
+    pub case_sensitive_extensions: bool,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(untagged)]
+enum FileType {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl FileType {
+    fn matches(&self, ft: &str, case_sensitive: bool) -> bool {
+        let eq = |ext: &String| {
+            if case_sensitive {
+                ext == ft
+            } else {
+                ext.eq_ignore_ascii_case(ft)
+            }
+        };
+
+        match self {
+            FileType::Single(ext) => ext == "any" || eq(ext),
+            FileType::List(ref extensions) => extensions.iter().any(eq),
+        }
+    }
+}
+
+/// A commenter and the file types commented with it, which a comment config
+/// can use by name.
+#[derive(Deserialize, Debug)]
+struct Preset {
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    filenames: Vec<String>,
+    #[serde(default)]
+    interpreters: Vec<String>,
+    commenter: Commenter,
+}
+
+fn presets() -> Result<&'static BTreeMap<String, Preset>, String> {
+    static PARSED: OnceLock<Result<BTreeMap<String, Preset>, String>> = OnceLock::new();
+    PARSED
+        .get_or_init(|| {
+            let user = xdg_config_dir().map(|dir| dir.join("licensure").join(USER_PRESETS));
+            load_presets(user.as_deref())
+        })
+        .as_ref()
+        .map_err(String::clone)
+}
+
+// The curated presets with those of the file at user, if it exists, merged
+// over them.
+fn load_presets(user: Option<&Path>) -> Result<BTreeMap<String, Preset>, String> {
+    let mut presets: BTreeMap<String, Preset> =
+        serde_yaml::from_str(PRESETS).expect("The comment presets are invalid?");
+    let Some(path) = user.filter(|path| path.exists()) else {
+        return Ok(presets);
+    };
+
+    let content = fs::read_to_string(path).map_err(|e| {
+        format!(
+            "unable to read the comment presets in {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    let user: BTreeMap<String, Preset> = serde_yaml::from_str(&content)
+        .map_err(|e| format!("invalid comment presets in {}: {}", path.display(), e))?;
+    for (name, preset) in user {
+        preset.commenter.validate().map_err(|e| {
+            format!(
+                "invalid comment preset {} in {}: {}",
+                name,
+                path.display(),
+                e
+            )
+        })?;
+        presets.insert(name, preset);
+    }
+
+    Ok(presets)
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(try_from = "RawConfig")]
+pub struct Config {
+    extension: Option<FileType>,
+    filenames: Vec<String>,
+    interpreters: Vec<String>,
+    files: Option<RegexList>,
+    columns: Option<usize>,
+    // Wrap headers at columns, off for formats where wrapping breaks them.
+    wrap: bool,
+    // Overrides the unwrap_text of the license config.
+    unwrap_text: Option<bool>,
+    position: Position,
+    commenter: Commenter,
+}
+
+// A comment config as written, which may name a preset instead of giving a
+// commenter.
+#[derive(Deserialize)]
+struct RawConfig {
+    #[serde(default)]
+    preset: Option<String>,
+    #[serde(alias = "extensions", default)]
+    extension: Option<FileType>,
+    #[serde(default)]
+    filenames: Vec<String>,
+    #[serde(default)]
+    interpreters: Vec<String>,
+    #[serde(default)]
+    files: Option<RegexList>,
+    columns: Option<usize>,
+    #[serde(default = "default_wrap")]
+    wrap: bool,
+    #[serde(default)]
+    unwrap_text: Option<bool>,
+    #[serde(default)]
+    position: Position,
+    #[serde(default)]
+    commenter: Option<Commenter>,
+}
+
+fn default_wrap() -> bool {
+    true
+}
+
+impl TryFrom<RawConfig> for Config {
+    type Error = String;
+
+    // A preset's file types are added to those of the config and its
+    // commenter is used unless the config gives one.
+    fn try_from(raw: RawConfig) -> Result<Config, String> {
+        if let Some(commenter) = &raw.commenter {
+            commenter.validate()?;
+        }
+
+        let Some(name) = raw.preset else {
+            return Ok(Config {
+                extension: raw.extension,
+                filenames: raw.filenames,
+                interpreters: raw.interpreters,
+                files: raw.files,
+                columns: raw.columns,
+                wrap: raw.wrap,
+                unwrap_text: raw.unwrap_text,
+                position: raw.position,
+                commenter: raw
+                    .commenter
+                    .ok_or("a comment config needs a commenter or a preset")?,
+            });
+        };
+
+        let presets = presets()?;
+        let Some(preset) = presets.get(&name) else {
+            return Err(format!(
+                "unknown comment preset {}, available presets are: {}",
+                name,
+                presets.keys().cloned().collect::<Vec<_>>().join(", ")
+            ));
+        };
+
+        let extension = match raw.extension {
+            Some(FileType::Single(ext)) if ext == "any" => FileType::Single(ext),
+            extension => {
+                let mut extensions = preset.extensions.clone();
+                match extension {
+                    Some(FileType::Single(ext)) => extensions.push(ext),
+                    Some(FileType::List(exts)) => extensions.extend(exts),
+                    None => (),
+                }
+                FileType::List(extensions)
+            }
+        };
+
+        Ok(Config {
+            extension: Some(extension),
+            filenames: [preset.filenames.clone(), raw.filenames].concat(),
+            interpreters: [preset.interpreters.clone(), raw.interpreters].concat(),
+            files: raw.files,
+            columns: raw.columns,
+            wrap: raw.wrap,
+            unwrap_text: raw.unwrap_text,
+            position: raw.position,
+            commenter: raw.commenter.unwrap_or_else(|| preset.commenter.clone()),
+        })
+    }
+}
+
+impl Config {
+    pub fn default() -> Config {
+        Config {
+            extension: Some(FileType::Single("any".to_string())),
+            filenames: Vec::new(),
+            interpreters: Vec::new(),
+            files: None,
+            columns: None,
+            wrap: true,
+            unwrap_text: None,
+            position: Position::Top,
+            commenter: Commenter::Line {
+                comment_char: "#".to_string(),
+                trailing_lines: None,
+                indent: None,
+                use_tabs: false,
+            },
+        }
+    }
+
+    pub fn matches(
+        &self,
+        file_type: &str,
+        filename: &str,
+        interpreter: Option<&str>,
+        case_sensitive: bool,
+    ) -> bool {
+        let classified = self
+            .extension
+            .as_ref()
+            .is_some_and(|ext| ext.matches(file_type, case_sensitive))
+            || self.filenames.iter().any(|f| f == get_basename(filename))
+            || interpreter.is_some_and(|i| self.interpreters.iter().any(|x| x == i));
+
+        if classified {
+            if let Some(files) = &self.files {
+                files.is_match(filename)
+            } else {
+                true
+            }
+        } else {
+            false
+        }
+    }
+
+    /// The width headers for files with this config are wrapped at, None
+    /// when they aren't wrapped.
+    pub fn columns(&self, defaults: &Defaults) -> Option<usize> {
+        self.columns.or(defaults.columns).filter(|_| self.wrap)
+    }
+
+    pub fn commenter(&self, defaults: &Defaults) -> Box<dyn Comment> {
+        let columns = self.columns(defaults);
+        let trailing = |trailing_lines: &Option<usize>| {
+            trailing_lines.or(defaults.trailing_lines).unwrap_or(0)
+        };
+
+        match &self.commenter {
+            Commenter::Line {
+                comment_char,
+                trailing_lines,
+                indent,
+                use_tabs,
+            } => Box::new(
+                LineComment::new(comment_char.as_str(), columns)
+                    .set_trailing_lines(trailing(trailing_lines))
+                    .with_indent(&indent_string(indent, *use_tabs)),
+            ),
+            Commenter::Block {
+                start_block_char,
+                end_block_char,
+                per_line_char,
+                end_block_char_escape,
+                trailing_lines,
+                indent,
+                use_tabs,
+            } => {
+                let mut bc =
+                    BlockComment::new(start_block_char.as_str(), end_block_char.as_str(), columns)
+                        .set_trailing_lines(trailing(trailing_lines))
+                        .with_indent(&indent_string(indent, *use_tabs));
+
+                if let Some(ch) = per_line_char {
+                    bc = bc.with_per_line(ch.as_str());
+                }
+                if let Some(escape) = end_block_char_escape {
+                    bc = bc.with_end_escape(escape);
+                }
+
+                Box::new(bc)
+            }
+            Commenter::Sidecar => Box::new(BlockComment::new("", "\n", columns)),
+            Commenter::External { command } => Box::new(ExternalComment::new(command, columns)),
+        }
+    }
+
+    /// Whether the license text is unwrapped for files with this config
+    /// instead of as the license config says, None to leave it to the
+    /// license config.
+    pub fn unwrap_text(&self) -> Option<bool> {
+        self.unwrap_text
+    }
+
+    /// Where headers are added to files with this config.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// A made up file name this config applies to, named after its first
+    /// extension or file name. A config for any extension gets a name without
+    /// one.
+    pub fn sample_filename(&self) -> Option<String> {
+        let extension = match &self.extension {
+            Some(FileType::Single(ext)) => Some(ext),
+            Some(FileType::List(extensions)) => extensions.first(),
+            None => None,
+        };
+
+        match extension {
+            Some(ext) if ext == "any" => Some("example".to_string()),
+            Some(ext) => Some(format!("example.{}", ext)),
+            None => self.filenames.first().cloned(),
+        }
+    }
+
+    /// Fail if header, uncommented, contains the start or end of the block
+    /// commenter of this config, which would break the comment it's written
+    /// in. name is what the error calls the comments of this config.
+    pub fn check_header(&self, header: &str, name: &str) -> Result<(), String> {
+        self.commenter.check_header(header, name)
+    }
+
+    pub fn is_sidecar(&self) -> bool {
+        matches!(self.commenter, Commenter::Sidecar)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn test_get_filetype() {
+        assert_eq!("py", get_filetype("test.py"));
+        assert_eq!("Makefile", get_filetype("build.d/Makefile"));
+    }
+
+    #[test]
+    fn test_get_interpreter() {
+        assert_eq!(Some("bash"), get_interpreter("#!/bin/bash\necho hi"));
+        assert_eq!(Some("python3"), get_interpreter("#!/usr/bin/env python3\n"));
+        assert_eq!(
+            Some("node"),
+            get_interpreter("#!/usr/bin/env -S NODE_ENV=dev node --harmony")
+        );
+        assert_eq!(None, get_interpreter("echo hi\n#!/bin/bash"));
+    }
+
+    static COMMENT_CONFIG_PY: &str = r##"columns: 80
+extensions:
+    - py
+commenter:
+    type: line
+    comment_char: "#""##;
+
+    static COMMENT_CONFIG_PY_EXAMPLE: &str = r##"columns: 80
+extensions:
+    - py
+files:
+    - example/.*
+commenter:
+    type: line
+    comment_char: "#""##;
+    #[test]
+    fn test_matches() {
+        let config_py: Config =
+            serde_yaml::from_str(COMMENT_CONFIG_PY).expect("Parsing static config");
+        let config_py_example: Config =
+            serde_yaml::from_str(COMMENT_CONFIG_PY_EXAMPLE).expect("Parsing static config");
+
+        let file = "example/foo.py";
+        assert!(config_py.matches(get_filetype(file), file, None, false));
+        assert!(config_py_example.matches(get_filetype(file), file, None, false));
+
+        let file = "example/foo.c";
+        assert!(!config_py.matches(get_filetype(file), file, None, false));
+        assert!(!config_py_example.matches(get_filetype(file), file, None, false));
+
+        let file = "another_dir/foo.py";
+        assert!(config_py.matches(get_filetype(file), file, None, false));
+        assert!(!config_py_example.matches(get_filetype(file), file, None, false));
+    }
+
+    #[test]
+    fn test_commenter_inherits_defaults() {
+        let config_py: Config =
+            serde_yaml::from_str(COMMENT_CONFIG_PY).expect("Parsing static config");
+        let defaults = Defaults {
+            columns: Some(20),
+            trailing_lines: Some(2),
+            ..Default::default()
+        };
+
+        // columns is set on the comment config so it wins over the default
+        // but trailing_lines is inherited.
+        assert_eq!(
+            "# one two three four five six\n\n\n",
+            config_py
+                .commenter(&defaults)
+                .comment("one two three four five six")
+                .unwrap()
+        );
+
+        let config: Config = serde_yaml::from_str(
+            "extension: any\ncommenter:\n  type: line\n  comment_char: \"#\"\n  trailing_lines: 0",
+        )
+        .expect("Parsing static config");
+        assert_eq!(
+            "# one two three four\n# five six\n",
+            config
+                .commenter(&defaults)
+                .comment("one two three four five six")
+                .unwrap()
+        );
+
+        // wrap: false turns off the columns inherited from the defaults.
+        let config: Config = serde_yaml::from_str(
+            "extension: any\nwrap: false\ncommenter:\n  type: line\n  comment_char: \"#\"\n  trailing_lines: 0",
+        )
+        .expect("Parsing static config");
+        assert_eq!(
+            "# one two three four five six\n",
+            config
+                .commenter(&defaults)
+                .comment("one two three four five six")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_commenter_indent() {
+        let config: Config = serde_yaml::from_str(
+            "extension: any\ncommenter:\n  type: line\n  comment_char: \"#\"\n  indent: 2",
+        )
+        .expect("Parsing static config");
+        assert_eq!(
+            "  # text\n",
+            config
+                .commenter(&Defaults::default())
+                .comment("text")
+                .unwrap()
+        );
+
+        let config: Config = serde_yaml::from_str(
+            "extension: any\ncommenter:\n  type: line\n  comment_char: \"#\"\n  use_tabs: true",
+        )
+        .expect("Parsing static config");
+        assert_eq!(
+            "\t# text\n",
+            config
+                .commenter(&Defaults::default())
+                .comment("text")
+                .unwrap()
+        );
+    }
+
+    static COMMENT_CONFIG_SHELL: &str = r##"filenames:
+    - Dockerfile
+    - Makefile
+interpreters:
+    - bash
+commenter:
+    type: line
+    comment_char: "#""##;
+
+    #[test]
+    fn test_matches_filenames_and_interpreters() {
+        let config: Config =
+            serde_yaml::from_str(COMMENT_CONFIG_SHELL).expect("Parsing static config");
+
+        let file = "docker/Dockerfile";
+        assert!(config.matches(get_filetype(file), file, None, false));
+
+        let file = "scripts/deploy";
+        assert!(config.matches(get_filetype(file), file, Some("bash"), false));
+        assert!(!config.matches(get_filetype(file), file, Some("python3"), false));
+        assert!(!config.matches(get_filetype(file), file, None, false));
+
+        let file = "Makefile.am";
+        assert!(!config.matches(get_filetype(file), file, None, false));
+    }
+
+    #[test]
+    fn test_presets() {
+        let config: Config = serde_yaml::from_str("preset: hash").expect("Parsing static config");
+        for file in ["Containerfile", "docker/Dockerfile", "env.sh", "app.yml"] {
+            assert!(
+                config.matches(get_filetype(file), file, None, false),
+                "{}",
+                file
+            );
+        }
+        assert!(config.matches("", "deploy", Some("bash"), false));
+        assert!(!config.matches(get_filetype("main.c"), "main.c", None, false));
+        assert_eq!(
+            "# text\n",
+            config
+                .commenter(&Defaults::default())
+                .comment("text")
+                .unwrap()
+        );
+
+        // The file types of the config are added to the preset's and its
+        // commenter wins.
+        let config: Config = serde_yaml::from_str(
+            "preset: c-block\nextension: ino\ncommenter:\n  type: line\n  comment_char: \"//\"",
+        )
+        .expect("Parsing static config");
+        assert!(config.matches("ino", "sketch.ino", None, false));
+        assert!(config.matches("c", "main.c", None, false));
+        assert_eq!(
+            "// text\n",
+            config
+                .commenter(&Defaults::default())
+                .comment("text")
+                .unwrap()
+        );
+
+        let err = serde_yaml::from_str::<Config>("preset: nope").unwrap_err();
+        assert!(err.to_string().starts_with(
+            "unknown comment preset nope, available presets are: c-block, c-line, dash, hash, xml"
+        ));
+        assert!(serde_yaml::from_str::<Config>("extension: py").is_err());
+    }
+
+    #[test]
+    fn test_user_presets() {
+        let dir = TempDir::new("presets");
+        let path = dir.join("presets.yml");
+        fs::write(
+            &path,
+            "hash:\n  extensions: [py]\n  commenter:\n    type: line\n    comment_char: '#'\nfortran:\n  extensions: [f90]\n  commenter:\n    type: line\n    comment_char: '!'\n",
+        )
+        .expect("Can write presets");
+        let presets = load_presets(Some(&path)).expect("Can load presets");
+        assert_eq!(vec!["py".to_string()], presets["hash"].extensions);
+        assert_eq!(vec!["f90".to_string()], presets["fortran"].extensions);
+        assert!(presets.contains_key("c-line"));
+
+        fs::write(
+            &path,
+            "blank:\n  commenter:\n    type: line\n    comment_char: ''\n",
+        )
+        .expect("Can write presets");
+        let err = load_presets(Some(&path)).unwrap_err();
+        assert!(
+            err.starts_with("invalid comment preset blank in "),
+            "{}",
+            err
+        );
+
+        fs::remove_file(&path).expect("Can remove presets");
+        assert_eq!(
+            5,
+            load_presets(Some(&path)).expect("Can load presets").len()
+        );
+    }
+
+    #[test]
+    fn test_matches_extension_case() {
+        let config_py: Config =
+            serde_yaml::from_str(COMMENT_CONFIG_PY).expect("Parsing static config");
+
+        let file = "LEGACY/FOO.PY";
+        assert!(config_py.matches(get_filetype(file), file, None, false));
+        assert!(!config_py.matches(get_filetype(file), file, None, true));
+    }
+
+    #[test]
+    fn test_validate_commenters() {
+        let parse = |commenter: &str| {
+            serde_yaml::from_str::<Config>(&format!("extension: c\ncommenter:\n{}", commenter))
+                .map_err(|e| e.to_string())
+        };
+
+        let config = parse(
+            "  type: block\n  start_block_char: |\n    /*\n  end_block_char: \"*/\\n\"\n  per_line_char: \" *\"",
+        )
+        .expect("Parsing static config");
+        assert!(config.check_header("Copyright 2024 Tester", "c").is_ok());
+        assert_eq!(
+            Err("contains */, which would end c early, remove it from the template, set end_block_char_escape to write something like *\\/ in its place, or use a line commenter".to_string()),
+            config.check_header("See docs/*/README", "c")
+        );
+        assert!(config
+            .check_header("Files in /* are MIT", "c")
+            .unwrap_err()
+            .contains("which would open a comment within c"));
+
+        assert!(parse("  type: line\n  comment_char: \"\"")
+            .unwrap_err()
+            .contains("the comment_char of a line commenter is empty"));
+        assert!(
+            parse("  type: block\n  start_block_char: \"\\n\"\n  end_block_char: \"*/\"")
+                .unwrap_err()
+                .contains("the start_block_char of a block commenter is empty")
+        );
+        assert!(
+            parse("  type: block\n  start_block_char: \"/*\"\n  end_block_char: \" \"")
+                .unwrap_err()
+                .contains("the end_block_char of a block commenter is empty")
+        );
+        assert!(parse(
+            "  type: block\n  start_block_char: \"/*\"\n  end_block_char: \"*/\"\n  per_line_char: \"\""
+        )
+        .unwrap_err()
+        .contains("the per_line_char of a block commenter is empty"));
+        assert!(parse(
+            "  type: block\n  start_block_char: \"/*\"\n  end_block_char: \"*/\"\n  per_line_char: \" */\""
+        )
+        .unwrap_err()
+        .contains("contains its end_block_char \"*/\""));
+        assert!(parse(
+            "  type: block\n  start_block_char: \"/*\"\n  end_block_char: \"*/\"\n  end_block_char_escape: \"*/\""
+        )
+        .unwrap_err()
+        .contains("would still end the comment, use text without */ such as *\\/"));
+    }
+}
@@ -0,0 +1,326 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+// Simply contains the default YAML config for generation and consumption
+pub const DEFAULT_CONFIG: &str = r#"
+# The version of the config schema, configs from older versions of licensure
+# can be upgraded with licensure config migrate.
+version: 1
+# Regexes which if matched by a file path will always be excluded from
+# getting a license header
+excludes:
+  - \.gitignore
+  - .*lock
+  - \.git/.*
+  - \.licensure\.yml
+  - README.*
+  - LICENSE.*
+  - .*\.(md|rst|txt)
+# Default column width and number of trailing blank lines for every
+# comment configuration below which doesn't set its own.
+# columns: 80
+# trailing_lines: 0
+# Whether --project includes hidden files and follows symlinks.
+# include_hidden: true
+# follow_symlinks: false
+# Safety limits, files larger than max_file_size are skipped and licensure
+# refuses to run on more than max_files files.
+# max_file_size: 10MB
+# max_files: 10000
+# Look for a header which was moved below imports or a doc comment in the
+# first header_search_lines lines of a file and update it where it is.
+# header_search_lines: 20
+# Skip files marked as generated with @generated or DO NOT EDIT, which are
+# licensed like any other file by default.
+# skip_generated: true
+# License the files of git submodules and other nested repositories with
+# this config (parent), leave them alone (skip), or license them with the
+# config found in the nested repository (own_config).
+# nested_repos: parent
+# Give files a license config matches which are empty or only whitespace a
+# header and nothing else (header_only), leave them alone (skip), or report
+# them as failed (error).
+# empty_files: header_only
+# Commands run by the shell before and after each file is processed,
+# given the file and action in $LICENSURE_FILE and $LICENSURE_ACTION.
+# hooks:
+#   pre_process: ./scripts/before-license.sh
+#   post_process: ./scripts/after-license.sh
+# A PEM file of extra certificates to trust when fetching license
+# templates from SPDX, for proxies which intercept TLS.
+# ca_bundle: /etc/ssl/certs/corporate-ca.pem
+# Fetch license templates from a mirror or a local directory of SPDX
+# license JSON files instead of https://spdx.org/licenses/.
+# spdx_base_url: https://artifacts.example.com/spdx/licenses/
+# Seconds before a request to SPDX times out and how many times failed
+# requests are retried, with exponential backoff.
+# spdx_timeout: 30
+# spdx_retries: 3
+# Requests started per second when fetching from SPDX, 0 for no limit.
+# spdx_rate_limit: 5
+# A YAML or JSON file of custom licenses, mapping each identifier to its
+# header template and optionally its full text, used by auto_template
+# and license-file like the SPDX licenses. Relative to this file.
+# licenses_registry: licenses.yml
+# Known exceptions to the rules findings are reported by, LIC001 to
+# LIC005, as regexes of the files whose findings of each rule are
+# reported as suppressed instead of failing, optionally until a date.
+# suppressions:
+#   LIC002:
+#     - vendor/.*
+#     - files: third_party/.*
+#       expires: 2025-12-31
+# Text shared by the templates of several licenses, such as a contact
+# block or warranty disclaimer, which a template includes by name with
+# [include:name]. Partials can use the template variables and include
+# other partials.
+# partials:
+#   contact: |
+#     Questions about licensing go to legal@example.com.
+# WASM plugins by name, which classify files for the class of license
+# configs and provide functions templates call with
+# [plugin:name:function]. Running them needs licensure built with the
+# plugins feature.
+# plugins:
+#   acme: tools/licensure/acme.wasm
+# Named profiles, selected with --profile NAME, which replace the
+# excludes, licenses, or comments sections below with their own.
+# profiles:
+#   docs:
+#     excludes: []
+#     licenses: []
+# Definition of the licenses used on this project and to what files
+# they should apply.
+#
+# No default license configuration is provided. This section must be
+# configured by the user.
+#
+# Make sure to delete the [] below when you add your configs.
+licenses: []
+  # Either a regex or the string "any" to determine to what files this
+  # license should apply. It is common for projects to have files
+  # under multiple licenses or with multiple copyright holders. This
+  # provides the ability to automatically license files correctly
+  # based on their file paths.
+  #
+  # If "any" is provided all files will match this license.
+  # - files: any
+  #
+  #   Only match the files a plugin gives this class.
+  #   class: vendored
+  #
+  #   The license identifier, a list of common identifiers can be
+  #   found at: https://spdx.org/licenses/ but existence of the ident
+  #   in this list it is not enforced unless auto_template is set to
+  #   true. Use auto to take the license of each file from the nearest
+  #   LICENSE or LICENSE.* file in its directory or one above it.
+  #   ident: MIT
+  #
+  #   A list of authors who hold copyright over these files
+  #   authors:
+  #       Provide either your full name or company name for copyright purposes
+  #     - name: Your Name Here
+  #       Optionally provide email for copyright purposes
+  #       email: you@yourdomain.com
+  #
+  #   Or read them from a file with one "Name <email>" per line.
+  #   authors:
+  #     file: AUTHORS
+  #
+  #   Or use the user.name and user.email from your git config.
+  #   authors: from_git_config
+  #
+  #   Or give one holder for the whole company, rendered as "Example Corp
+  #   and contributors", or project, rendered as "The Example Authors".
+  #   holder:
+  #     company: Example Corp
+  #
+  #   In a Cargo workspace take the ident, and optionally the authors, of
+  #   each crate's files from its Cargo.toml.
+  #   cargo_workspace: true
+  #   crate_authors: true
+  #
+  #   The template that will be rendered to generate the header before
+  #   comment characters are applied. Available variables are:
+  #    - [year]: substituted with the current year.
+  #    - [name of author]: Substituted with name of the author and email
+  #      if provided. If email is provided the output appears as Full
+  #      Name <email@example.com>. If multiple authors are provided the
+  #      list is concatenated together with commas.
+  #    - [contributors]: Substituted with the contributors, listed like
+  #      the authors. Set contributors to a list or file like authors, or
+  #      to from_git_history for the authors of the commits to each file.
+  #    - [copyright]: Substituted with the whole copyright line, such as
+  #      "Copyright 2024 Your Name", in the language set by locale: en (the
+  #      default), de, es, fr, it, ja, or zh.
+  #   To write a literal placeholder double its brackets, [[year]] is
+  #   rendered as [year]. Any other word in brackets, such as a
+  #   misspelled [yaer], is an error when the config is loaded.
+  #   [include:name] is replaced with the partial called name, see
+  #   partials above, and [plugin:name:function] with what the function
+  #   of a plugin returns for the file, see plugins above.
+  #   template: |
+  #     Copyright [year] [name of author]. All rights reserved. Use of
+  #     this source code is governed by the [ident] license that can be
+  #     found in the LICENSE file.
+  #
+  #   Read the template from a file in the project instead, shared with
+  #   other tools. When the file changes, headers written from the
+  #   versions of it committed to git are replaced. The path is relative
+  #   to the directory of this file.
+  #   template_file: LICENSE_HEADER.txt
+  #
+  #   If auto_template is true then template is ignored and the SPDX
+  #   API will be queried with the ident value to automatically
+  #   determine the license header template. auto_template works best
+  #   with licenses that have a standardLicenseHeader field defined in
+  #   their license info JSON, if it is not then we will use the full
+  #   licenseText to generate the header which works fine for short
+  #   licenses like MIT but can be quite lengthy for other licenses
+  #   like BSD-4-Clause. The above default template is valid for most
+  #   licenses and is recommended for MIT, and BSD licenses. Common
+  #   licenses that work well with the auto_template feature are GPL
+  #   variants, and the Apache 2.0 license.
+  #
+  #   Important Note: this means the ident must be a valid SPDX identifier
+  #   or one defined in the licenses_registry
+  #   auto_template: true
+  #
+  #   If true try to detect the text wrapping of the template, and unwrap it
+  #   by joining the lines of each paragraph. Blank lines are kept and a
+  #   paragraph starting with a [nowrap] line keeps its line breaks.
+  #   Lines between [verbatim] and [/verbatim] lines are never unwrapped
+  #   or wrapped, whether or not unwrap_text is set.
+  #   unwrap_text: false
+  #
+  #   What to do with files which already have some other license header:
+  #   prepend, replace, skip, or error.
+  #   skip_existing: prepend
+  #
+  #   Replace the header of any of these licenses, recognized by how much
+  #   of its SPDX header it contains however it's wrapped or filled in.
+  #   replaces_licenses:
+  #     - GPL-3.0-or-later
+  #
+  #   If true end headers with a License-Header-Hash line holding a hash of
+  #   the header, so a file is checked by looking for that one line.
+  #   fingerprint: false
+  #
+  #   Where headers are added, top or bottom, overriding the position of
+  #   the comment config.
+  #   position: top
+
+# Define type of comment characters to apply based on file extensions.
+comments:
+  # The extension field defines which file extension, or list of
+  # extensions, to apply the commenter to.
+  - extension:
+      - js
+      - rs
+      - go
+    # The commenter field defines the kind of commenter to
+    # generate. There are two types of commenters: line and block.
+    #
+    # This demonstrates a line commenter configuration. A line
+    # commenter type will apply the comment_char to the beginning of
+    # each line in the license header. It will then apply a number of
+    # empty newlines to the end of the header equal to trailing_lines.
+    #
+    # If trailing_lines is omitted it is assumed to be 0.
+    commenter:
+      type: line
+      comment_char: "//"
+      trailing_lines: 0
+  - extension:
+      - css
+      - cpp
+      - c
+    # This demonstrates a block commenter configuration. A block
+    # commenter type will add start_block_char as the first character
+    # in the license header and add end_block_char as the last character
+    # in the license header. If per_line_char is provided each line of
+    # the header between the block start and end characters will be
+    # line commented with the per_line_char
+    #
+    # None of the characters can be empty, per_line_char can't contain
+    # the start or end, and the license headers can't contain them
+    # either, as that would break the comment. Set end_block_char_escape
+    # to write something else in place of the end where a header has it,
+    # such as "*\\/".
+    #
+    # trailing_lines works the same for both block and line commenter
+    # types
+    commenter:
+      type: block
+      start_block_char: "/*\n"
+      end_block_char: "*/"
+      per_line_char: "*"
+      trailing_lines: 0
+  # In this case extension is singular and a single string extension is provided.
+  - extension: html
+    commenter:
+      type: block
+      start_block_char: "<!--\n"
+      end_block_char: "-->"
+  - extension:
+      - el
+      - lisp
+    commenter:
+      type: line
+      comment_char: ";;;"
+      trailing_lines: 0
+  # Instead of extensions and a commenter a comment config can name a
+  # preset: hash, c-line, c-block, xml, or dash. A preset comes with a
+  # commenter and the extensions, file names, and interpreters which use
+  # it, such as Dockerfile and shell scripts for hash. Extensions and file
+  # names given alongside it are added to the preset's. More presets, or
+  # replacements for these, can be defined in presets.yml next to the
+  # global config.
+  #
+  # - preset: xml
+  #
+  # Headers are added at the top of files, after any shebang. Formats
+  # which carry license notes at the bottom can set position: bottom.
+  #
+  # - extension: cls
+  #   position: bottom
+  #   commenter:
+  #     type: line
+  #     comment_char: "%"
+  #
+  # Formats which break when their header is rewrapped, such as
+  # reStructuredText, can turn off wrapping at columns with wrap: false
+  # and override the unwrap_text of the license config.
+  #
+  # - extension: rst
+  #   wrap: false
+  #   unwrap_text: false
+  #   commenter:
+  #     type: line
+  #     comment_char: ".."
+  #
+  # The extension string "any" is special and so will match any file
+  # extensions. Commenter configurations are always checked in the
+  # order they are defined, so if any is used it should be the last
+  # commenter configuration or else it will override all others.
+  #
+  # In this configuration if we can't match the file extension we fall
+  # back to the popular '#' line comment used in most scripting
+  # languages.
+  - extension: any
+    commenter:
+      type: line
+      comment_char: '#'
+      trailing_lines: 0
+
+"#;
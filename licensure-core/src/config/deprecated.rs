@@ -0,0 +1,129 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fmt;
+
+use serde_yaml::Value;
+
+// The section of the config, key, and replacement of every deprecated key.
+const DEPRECATED: &[(&str, &str, &str)] = &[
+    ("licenses", "year", "end_year"),
+    ("comments", "extensions", "extension"),
+];
+
+/// A config key which still works but has been replaced by another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deprecation {
+    pub key: &'static str,
+    pub replacement: &'static str,
+    /// Every entry the key is used in, such as licenses[0] or
+    /// profiles.ci.comments[2].
+    pub locations: Vec<String>,
+}
+
+impl fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the {} config key is deprecated, use {} instead (used in {})",
+            self.key,
+            self.replacement,
+            self.locations.join(", ")
+        )
+    }
+}
+
+fn get<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    value.as_mapping()?.get(&Value::String(key.to_string()))
+}
+
+/// Returns the deprecated keys used in the YAML config in content, each once
+/// with everywhere it's used.
+pub fn find_deprecations(content: &str) -> Vec<Deprecation> {
+    let Ok(root) = serde_yaml::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+
+    // The top level and every profile can set the sections.
+    let mut sections = vec![(String::new(), &root)];
+    if let Some(profiles) = get(&root, "profiles").and_then(Value::as_mapping) {
+        for (name, profile) in profiles {
+            if let Some(name) = name.as_str() {
+                sections.push((format!("profiles.{}.", name), profile));
+            }
+        }
+    }
+
+    let mut deprecations = Vec::new();
+    for (section, key, replacement) in DEPRECATED {
+        let mut locations = Vec::new();
+        for (prefix, value) in &sections {
+            let Some(entries) = get(value, section).and_then(Value::as_sequence) else {
+                continue;
+            };
+            for (i, entry) in entries.iter().enumerate() {
+                if get(entry, key).is_some() {
+                    locations.push(format!("{}{}[{}]", prefix, section, i));
+                }
+            }
+        }
+
+        if !locations.is_empty() {
+            deprecations.push(Deprecation {
+                key,
+                replacement,
+                locations,
+            });
+        }
+    }
+
+    deprecations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_deprecations() {
+        let deprecations = find_deprecations(
+            r##"
+licenses:
+  - files: any
+    ident: MIT
+    year: "2020"
+    template: "year: [year]"
+comments:
+  - extension: py
+    commenter: { type: line, comment_char: "#" }
+  - extensions: [js, rs]
+    commenter: { type: line, comment_char: "//" }
+profiles:
+  ci:
+    licenses:
+      - files: any
+        ident: MIT
+        year: "2021"
+"##,
+        );
+        assert_eq!(2, deprecations.len());
+        assert_eq!(
+            "the year config key is deprecated, use end_year instead (used in licenses[0], profiles.ci.licenses[0])",
+            deprecations[0].to_string()
+        );
+        assert_eq!("extensions", deprecations[1].key);
+        assert_eq!(vec!["comments[1]"], deprecations[1].locations);
+
+        assert!(find_deprecations("licenses:\n  - end_year: '2020'\n").is_empty());
+    }
+}
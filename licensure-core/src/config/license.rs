@@ -0,0 +1,961 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::detect;
+use crate::plugins::Plugins;
+use crate::spdx::{self, LicenseInfo};
+use crate::template::{self, Authors, Context, CopyrightHolder, Holder, Locale, Template};
+use crate::utils::{normalize_path, project_path};
+
+use super::cargo::{CargoWorkspace, Member};
+use super::codeowners::CodeOwners;
+use super::comment::Position;
+use super::vcs::{self, Vcs};
+
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "String")]
+struct FileMatcher {
+    any: bool,
+    regex: Option<Regex>,
+}
+
+impl FileMatcher {
+    fn as_str(&self) -> &str {
+        match &self.regex {
+            Some(r) if !self.any => r.as_str(),
+            _ => "any",
+        }
+    }
+
+    pub fn is_match(&self, s: &str) -> bool {
+        if self.any {
+            return true;
+        }
+
+        match &self.regex {
+            Some(r) => r.is_match(&normalize_path(s)),
+            None => false,
+        }
+    }
+}
+
+impl TryFrom<String> for FileMatcher {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<FileMatcher, String> {
+        if s == "any" {
+            return Ok(FileMatcher {
+                any: true,
+                regex: None,
+            });
+        }
+
+        let r = Regex::new(&s).map_err(|e| format!("invalid files regex: {}", e))?;
+        Ok(FileMatcher {
+            any: false,
+            regex: Some(r),
+        })
+    }
+}
+
+/// What to do with a file which already has a license header that isn't ours
+/// and isn't matched by any of the replaces regexes.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExistingHeaderPolicy {
+    /// Remove the existing header and add ours in its place.
+    Replace,
+    /// Leave the file alone.
+    Skip,
+    /// Add our header above the existing one.
+    #[default]
+    Prepend,
+    /// Report the file as an error.
+    Error,
+}
+
+/// What to do when use_dynamic_year_ranges gets the years of a file from a
+/// shallow clone, whose history is cut short so the created year may be too
+/// late.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ShallowClonePolicy {
+    /// Use the years git gives and warn that they may be wrong.
+    #[default]
+    Warn,
+    /// Fetch the rest of the history with git fetch --unshallow first.
+    Unshallow,
+    /// Use the configured start_year instead of the created year.
+    StartYear,
+}
+
+/// Where per-file years come from.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum YearSource {
+    /// The commits to the file in the configured vcs, when
+    /// use_dynamic_year_ranges is set.
+    #[default]
+    Vcs,
+    /// The creation and modification times of the file, for trees that
+    /// aren't under version control.
+    Filesystem,
+}
+
+// The contributors of a file are either configured like authors or are the
+// authors of the commits to it.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Contributors {
+    History(ContributorsKeyword),
+    Configured(Authors),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum ContributorsKeyword {
+    FromGitHistory,
+}
+
+// The commit trailers whose people trailer_authors credits.
+const TRAILER_KEYS: &[&str] = &["Co-authored-by", "Signed-off-by"];
+
+// The ident which takes the license of each file from the nearest LICENSE
+// file above it.
+const AUTO_IDENT: &str = "auto";
+
+// How many of the commits to a template_file the templates it had before
+// are looked for in.
+const PREVIOUS_TEMPLATES: usize = 20;
+
+// The shallow clones files have been found in.
+#[derive(Debug, Default)]
+struct ShallowClones {
+    // The top level of the shallow clone each directory is in, None for
+    // directories in complete clones.
+    dirs: HashMap<PathBuf, Option<PathBuf>>,
+    // The shallow clones which have already been warned about or
+    // unshallowed.
+    handled: HashSet<PathBuf>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Config {
+    files: FileMatcher,
+    // Only match files a plugin gives this class.
+    class: Option<String>,
+    // The plugins of the config, set once it's loaded, which classify files
+    // and are called by [plugin:name:function] in the template.
+    #[serde(skip)]
+    plugins: Option<Arc<Plugins>>,
+
+    ident: String,
+    #[serde(default)]
+    authors: Authors,
+    holder: Option<Holder>,
+    // CODEOWNERS owners mapped to the copyright holder they stand for.
+    #[serde(default)]
+    codeowners: Option<BTreeMap<String, CopyrightHolder>>,
+    #[serde(skip)]
+    code_owners: OnceLock<Option<CodeOwners>>,
+    // Take the ident of files in a Cargo workspace member, and with
+    // crate_authors the authors, from the member's Cargo.toml.
+    #[serde(default)]
+    cargo_workspace: bool,
+    #[serde(default)]
+    crate_authors: bool,
+    #[serde(skip)]
+    workspace: OnceLock<Option<CargoWorkspace>>,
+    // With ident: auto, the license of the nearest LICENSE file above each
+    // directory, None when there isn't one licensure recognizes.
+    #[serde(skip)]
+    license_files: Mutex<HashMap<PathBuf, Option<&'static str>>>,
+    // The language of the copyright line.
+    #[serde(default)]
+    locale: Locale,
+    // Fills in [contributors].
+    contributors: Option<Contributors>,
+    // Also credit the people in the Co-authored-by and Signed-off-by
+    // trailers of the commits to each file.
+    #[serde(default)]
+    trailer_authors: bool,
+    #[serde(alias = "year")]
+    end_year: Option<String>,
+    start_year: Option<String>,
+    #[serde(default = "default_dynamic_year_ranges")]
+    use_dynamic_year_ranges: bool,
+    #[serde(default)]
+    year_source: YearSource,
+    #[serde(default)]
+    vcs: Vcs,
+    #[serde(default)]
+    shallow_clones: ShallowClonePolicy,
+    #[serde(skip)]
+    shallow: Mutex<ShallowClones>,
+    // The top level of the repository files are relative to, empty for the
+    // directory licensure runs in.
+    #[serde(skip)]
+    root: PathBuf,
+
+    template: Option<String>,
+    // A file in the project holding the template, shared with other tools.
+    template_file: Option<String>,
+    #[serde(skip)]
+    template_file_text: OnceLock<Result<String, String>>,
+    // The templates template_file was committed with before, newest first,
+    // so headers written from them are replaced when it changes.
+    #[serde(skip)]
+    previous_template_texts: OnceLock<Vec<String>>,
+    // The partials of the config, set once it's loaded, which [include:name]
+    // in the template is replaced with. Until then includes are left as is.
+    #[serde(skip)]
+    partials: Option<Arc<BTreeMap<String, String>>>,
+    auto_template: Option<bool>,
+
+    #[serde(with = "serde_regex", default)]
+    replaces: Option<Vec<Regex>>,
+    // The licenses whose headers are replaced, recognized by their
+    // similarity to the SPDX header instead of a regex.
+    #[serde(default)]
+    replaces_licenses: Vec<String>,
+
+    #[serde(default)]
+    skip_existing: ExistingHeaderPolicy,
+
+    #[serde(default = "default_unwrap_text")]
+    unwrap_text: bool,
+
+    // Append a hash of the header to it so checking a file only needs to
+    // find that line.
+    #[serde(default)]
+    fingerprint: bool,
+
+    // Where headers are added, overriding the position of the comment
+    // config.
+    #[serde(default)]
+    position: Option<Position>,
+}
+
+fn default_unwrap_text() -> bool {
+    true
+}
+
+fn default_dynamic_year_ranges() -> bool {
+    false
+}
+
+impl Config {
+    /// Whether headers end with a License-Header-Hash line.
+    pub fn fingerprint(&self) -> bool {
+        self.fingerprint
+    }
+
+    /// The license identifier this config is for.
+    pub fn ident(&self) -> &str {
+        &self.ident
+    }
+
+    /// The files regex, or any.
+    pub fn files(&self) -> &str {
+        self.files.as_str()
+    }
+
+    pub fn file_is_match(&self, s: &str) -> bool {
+        self.files.is_match(s) && self.class_is_match(s)
+    }
+
+    // Whether a plugin gives s the class this config matches, if it matches
+    // one. A file a plugin fails to classify has no class.
+    fn class_is_match(&self, s: &str) -> bool {
+        let (Some(class), Some(plugins)) = (&self.class, &self.plugins) else {
+            return self.class.is_none();
+        };
+        match plugins.classify(s) {
+            Ok(given) => given.as_deref() == Some(class.as_str()),
+            Err(e) => {
+                error!("Unable to classify {}: {}", s, e);
+                false
+            }
+        }
+    }
+
+    /// Make the files given to this config relative to root instead of the
+    /// directory licensure runs in.
+    pub(crate) fn set_root(&mut self, root: &Path) {
+        self.root = root.to_path_buf();
+    }
+
+    /// Make template_file relative to the directory of the config file at
+    /// config_path instead of the directory licensure runs in.
+    pub(crate) fn set_config_path(&mut self, config_path: &Path) {
+        if let Some(path) = &mut self.template_file {
+            *path = super::relative_to_config(path, config_path);
+            self.template_file_text = OnceLock::new();
+        }
+    }
+
+    /// Returns true if the template is read from a template_file.
+    pub(crate) fn has_template_file(&self) -> bool {
+        self.template_file.is_some()
+    }
+
+    // filename, relative to the root, as a path from the directory licensure
+    // runs in for reading it and running version control commands on it.
+    fn path_of(&self, filename: &str) -> String {
+        self.root.join(filename).display().to_string()
+    }
+
+    /// Set the plugins which classify files and templates call, failing if
+    /// the config matches a class without any or calls one that isn't among
+    /// them.
+    pub(crate) fn set_plugins(&mut self, plugins: Arc<Plugins>) -> Result<(), String> {
+        if self.class.is_some() && plugins.names().is_empty() {
+            return Err(format!(
+                "the license config for {} matches files by class but no plugins are configured",
+                self.ident
+            ));
+        }
+        if let Some(template) = self.template_text()? {
+            template::expand_plugin_calls(&template, |name, _| {
+                if plugins.names().contains(&name) {
+                    Ok(String::new())
+                } else {
+                    Err(plugins.undefined(name))
+                }
+            })
+            .map_err(|e| format!("the template for {} {}", self.ident, e))?;
+        }
+
+        self.plugins = Some(plugins);
+        Ok(())
+    }
+
+    /// Set the partials templates include and check the template with them.
+    pub(crate) fn set_partials(
+        &mut self,
+        partials: Arc<BTreeMap<String, String>>,
+    ) -> Result<(), String> {
+        self.partials = Some(partials);
+        self.check_template()
+    }
+
+    // The template given in the config or read from template_file, with its
+    // includes replaced by their partials.
+    fn template_text(&self) -> Result<Option<String>, String> {
+        self.raw_template_text()?
+            .map(|template| self.expand_includes(template))
+            .transpose()
+    }
+
+    fn expand_includes(&self, template: &str) -> Result<String, String> {
+        match &self.partials {
+            Some(partials) => template::expand_includes(template, partials)
+                .map_err(|e| format!("the template for {} has an {}", self.ident, e)),
+            None => Ok(template.to_string()),
+        }
+    }
+
+    // The template given in the config or read from template_file, as
+    // written.
+    fn raw_template_text(&self) -> Result<Option<&str>, String> {
+        let Some(path) = &self.template_file else {
+            return Ok(self.template.as_deref());
+        };
+        if self.template.is_some() {
+            return Err(format!(
+                "the license config for {} has both a template and a template_file, remove one of them",
+                self.ident
+            ));
+        }
+
+        self.template_file_text
+            .get_or_init(|| {
+                fs::read_to_string(path)
+                    .map(|text| text.trim_end_matches('\n').to_string())
+                    .map_err(|e| format!("unable to read the template_file {}: {}", path, e))
+            })
+            .as_deref()
+            .map(Some)
+            .map_err(String::clone)
+    }
+
+    // Replace the [plugin:name:function] calls of template with what they
+    // return for filename.
+    fn call_plugins(&self, template: &str, filename: &str) -> Result<String, String> {
+        let Some(plugins) = &self.plugins else {
+            return Ok(template.to_string());
+        };
+        template::expand_plugin_calls(template, |name, function| {
+            plugins.call(name, function, &project_path(filename))
+        })
+        .map_err(|e| format!("the template for {} {}", self.ident, e))
+    }
+
+    /// Returns the template for filename with context, which should be the
+    /// one get_context returns for filename. Fails if auto_template is
+    /// enabled and the license couldn't be fetched from SPDX or the registry.
+    pub fn get_template(&self, filename: &str, context: Context) -> Result<Template, String> {
+        if self.ident == AUTO_IDENT && self.license_file_ident(filename).is_none() {
+            return Err(format!(
+                "ident is auto but no LICENSE file above {} has a license licensure recognizes",
+                filename
+            ));
+        }
+
+        let mut spdx_template = true;
+        let t = match self.template_text()? {
+            Some(t) => self.call_plugins(&t, filename)?,
+            None => {
+                if self.auto_template.unwrap_or(false) {
+                    let auto_templ;
+                    (auto_templ, spdx_template) =
+                        spdx::fetch_license_header(self.ident_for(filename))?;
+                    auto_templ
+                } else {
+                    return Err(format!("auto_template not enabled and no template provided, please add a template option to the license definition for {}", self.ident));
+                }
+            }
+        };
+
+        let t = Template::new(&t, context);
+
+        if self.auto_template.unwrap_or(false) && spdx_template {
+            return Ok(t.set_spdx_template(true));
+        }
+
+        Ok(t)
+    }
+
+    /// Fails if the template has an unknown placeholder, such as a typo of
+    /// [year], or its template_file can't be read. Templates of SPDX license
+    /// texts aren't checked.
+    pub fn check_template(&self) -> Result<(), String> {
+        match self.template_text()? {
+            Some(template) if !self.auto_template.unwrap_or(false) => {
+                template::check_placeholders(&template)
+                    .map_err(|e| format!("the template for {} has an {}", self.ident, e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn get_context(&self, filename: &str) -> Context {
+        self.get_context_with_warning(filename).0
+    }
+
+    /// Returns the context for filename like get_context, along with a
+    /// warning when use_dynamic_year_ranges is on but git couldn't give the
+    /// years of filename, such as for a file outside the repository. The
+    /// configured years are used for it instead. With trailer_authors a
+    /// failure to read the commit trailers of filename is also warned about.
+    pub fn get_context_with_warning(&self, filename: &str) -> (Context, Option<String>) {
+        let mut warning = None;
+        let dynamic = self.use_dynamic_year_ranges || self.year_source == YearSource::Filesystem;
+        let (end_year, start_year) = if dynamic {
+            let mut configured_start_year = false;
+            let shallow = match (self.year_source, self.vcs) {
+                (YearSource::Vcs, Vcs::Git) => self.shallow_clone_of(filename),
+                _ => None,
+            };
+            if let Some((repo, first)) = shallow {
+                match self.shallow_clones {
+                    ShallowClonePolicy::Warn if first => {
+                        warning = Some(format!(
+                            "{} is a shallow clone so the years git gives may start too late, fetch its full history or set shallow_clones",
+                            repo.display()
+                        ));
+                    }
+                    ShallowClonePolicy::Unshallow if first => {
+                        if let Err(e) = unshallow(&repo) {
+                            warning = Some(format!(
+                                "unable to fetch the full history of the shallow clone {}: {}",
+                                repo.display(),
+                                e
+                            ));
+                        }
+                    }
+                    ShallowClonePolicy::StartYear => configured_start_year = true,
+                    _ => (),
+                }
+            }
+
+            let (years, source) = match self.year_source {
+                YearSource::Vcs => (
+                    get_vcs_years_for_file(self.vcs, &self.path_of(filename)),
+                    self.vcs.to_string(),
+                ),
+                YearSource::Filesystem => (
+                    get_filesystem_years_for_file(&self.path_of(filename)),
+                    "the filesystem".to_string(),
+                ),
+            };
+            match &years {
+                Ok((last_updated_year, created_year)) => trace_file!(
+                    filename,
+                    "{} gives the years {} to {}",
+                    source,
+                    created_year,
+                    last_updated_year
+                ),
+                Err(e) => trace_file!(filename, "{} gives no years: {}", source, e),
+            }
+            match years {
+                Ok((last_updated_year, _)) if configured_start_year => {
+                    (Some(last_updated_year), self.start_year.clone())
+                }
+                Ok((last_updated_year, created_year)) => {
+                    (Some(last_updated_year), Some(created_year))
+                }
+                Err(e) => {
+                    let message = format!(
+                        "unable to get the years of {} from {}, using the configured years: {}",
+                        filename, source, e
+                    );
+                    warn!("{}", message);
+                    warning = Some(message);
+                    (self.end_year.clone(), self.start_year.clone())
+                }
+            }
+        } else {
+            (self.end_year.clone(), self.start_year.clone())
+        };
+
+        let mut context = self.context_with_years(filename, end_year, start_year);
+        if self.trailer_authors {
+            match vcs::commit_trailers(&self.path_of(filename), TRAILER_KEYS) {
+                Ok(trailers) => {
+                    for trailer in trailers {
+                        context.authors.add(CopyrightHolder::parse(&trailer));
+                    }
+                }
+                Err(e) => {
+                    let message = format!(
+                        "unable to get the commit trailers of {} from git: {}",
+                        filename, e
+                    );
+                    warn!("{}", message);
+                    warning.get_or_insert(message);
+                }
+            }
+        }
+
+        if let Some(Contributors::History(ContributorsKeyword::FromGitHistory)) = &self.contributors
+        {
+            match vcs::commit_authors(&self.path_of(filename)) {
+                Ok(authors) => {
+                    for author in authors {
+                        context.contributors.add(CopyrightHolder::parse(&author));
+                    }
+                }
+                Err(e) => {
+                    let message = format!(
+                        "unable to get the contributors of {} from git: {}",
+                        filename, e
+                    );
+                    warn!("{}", message);
+                    warning.get_or_insert(message);
+                }
+            }
+        }
+
+        (context, warning)
+    }
+
+    // The top level of the shallow clone filename is in, if it's in one, and
+    // whether this is the first time it's been found.
+    fn shallow_clone_of(&self, filename: &str) -> Option<(PathBuf, bool)> {
+        let path = self.path_of(filename);
+        let dir = vcs::dir_of(Path::new(&path)).0.to_path_buf();
+        let mut shallow = self.shallow.lock().unwrap_or_else(|e| e.into_inner());
+        let repo = shallow
+            .dirs
+            .entry(dir)
+            .or_insert_with_key(|dir| get_shallow_clone(dir))
+            .clone()?;
+        let first = shallow.handled.insert(repo.clone());
+        Some((repo, first))
+    }
+
+    /// Returns the context for filename with year as the end year unless one
+    /// is configured, without looking at git. Rendering with it always gives
+    /// the same header, which is what tests of templates need.
+    pub fn get_sample_context(&self, filename: &str, year: &str) -> Context {
+        self.context_with_years(
+            filename,
+            self.end_year.clone().or_else(|| Some(year.to_string())),
+            self.start_year.clone(),
+        )
+    }
+
+    fn context_with_years(
+        &self,
+        filename: &str,
+        end_year: Option<String>,
+        start_year: Option<String>,
+    ) -> Context {
+        Context {
+            end_year,
+            start_year,
+            ident: self.ident_for(filename).to_string(),
+            authors: self
+                .codeowners_authors(filename)
+                .or_else(|| self.crate_authors(filename))
+                .unwrap_or_else(|| self.default_authors()),
+            contributors: match &self.contributors {
+                Some(Contributors::Configured(contributors)) => contributors.clone(),
+                _ => Authors::default(),
+            },
+            locale: self.locale,
+            unwrap_text: self.unwrap_text,
+        }
+    }
+
+    // The holder if one is configured, otherwise the list of authors.
+    fn default_authors(&self) -> Authors {
+        match &self.holder {
+            Some(holder) => Authors::from(holder),
+            None => self.authors.clone(),
+        }
+    }
+
+    // The copyright holders the codeowners table maps the CODEOWNERS owners of
+    // filename to, if there are any.
+    fn codeowners_authors(&self, filename: &str) -> Option<Authors> {
+        let table = self.codeowners.as_ref()?;
+        let code_owners = self
+            .code_owners
+            .get_or_init(|| CodeOwners::load(&self.root))
+            .as_ref()?;
+
+        let mut holders: Vec<CopyrightHolder> = Vec::new();
+        for owner in code_owners.owners(filename) {
+            if let Some(holder) = table.get(owner) {
+                if !holders.contains(holder) {
+                    holders.push(holder.clone());
+                }
+            }
+        }
+
+        if holders.is_empty() {
+            None
+        } else {
+            Some(Authors::from(holders))
+        }
+    }
+
+    // The Cargo workspace member filename belongs to, if cargo_workspace is
+    // enabled.
+    fn crate_member(&self, filename: &str) -> Option<&Member> {
+        if !self.cargo_workspace {
+            return None;
+        }
+
+        self.workspace
+            .get_or_init(|| CargoWorkspace::load(&self.root))
+            .as_ref()?
+            .member(filename)
+    }
+
+    // The authors of the crate filename belongs to, if crate_authors is
+    // enabled and the crate lists any.
+    fn crate_authors(&self, filename: &str) -> Option<Authors> {
+        if !self.crate_authors {
+            return None;
+        }
+
+        let member = self.crate_member(filename)?;
+        if member.authors.is_empty() {
+            None
+        } else {
+            Some(Authors::from(member.authors.clone()))
+        }
+    }
+
+    pub fn get_replaces(&self) -> &Option<Vec<Regex>> {
+        &self.replaces
+    }
+
+    /// The licenses whose headers are replaced by this license's.
+    pub fn replaces_licenses(&self) -> Vec<&str> {
+        self.replaces_licenses.iter().map(String::as_str).collect()
+    }
+
+    pub fn skip_existing(&self) -> ExistingHeaderPolicy {
+        self.skip_existing
+    }
+
+    /// Where headers are added, None to leave it to the comment config.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    // With ident: auto, the license of the nearest LICENSE or LICENSE.* file
+    // in the directory of filename or one above it.
+    fn license_file_ident(&self, filename: &str) -> Option<&'static str> {
+        if self.ident != AUTO_IDENT {
+            return None;
+        }
+
+        let path = self.path_of(filename);
+        let dir = Path::new(&path).parent().unwrap_or(Path::new(""));
+        self.dir_license(dir)
+    }
+
+    fn dir_license(&self, dir: &Path) -> Option<&'static str> {
+        let cached = self
+            .license_files
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(dir)
+            .copied();
+        if let Some(ident) = cached {
+            return ident;
+        }
+
+        // The LICENSE files above the project, or the repository dir is in,
+        // aren't theirs.
+        let ident = read_license_file(dir).or_else(|| {
+            if is_root(dir) {
+                return None;
+            }
+            dir.parent().and_then(|d| self.dir_license(d))
+        });
+        self.license_files
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(dir.to_path_buf(), ident);
+        ident
+    }
+
+    /// Returns the license identifier of filename, which is the license of
+    /// its crate when cargo_workspace is enabled and the crate declares one,
+    /// or of the nearest LICENSE file above it when ident is auto.
+    pub fn ident_for(&self, filename: &str) -> &str {
+        if let Some(ident) = self.license_file_ident(filename) {
+            return ident;
+        }
+
+        self.crate_member(filename)
+            .and_then(|member| member.license.as_deref())
+            .unwrap_or(&self.ident)
+    }
+
+    /// Returns every license identifier this config can apply, including the
+    /// licenses of the Cargo workspace members when cargo_workspace is
+    /// enabled.
+    pub fn idents(&self) -> Vec<&str> {
+        let mut idents = vec![self.ident.as_str()];
+        if self.ident == AUTO_IDENT {
+            // Only the LICENSE files found so far are known, along with the
+            // one at the top of the project.
+            self.dir_license(&self.root);
+            idents = self
+                .license_files
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .values()
+                .flatten()
+                .copied()
+                .collect();
+            idents.sort_unstable();
+            idents.dedup();
+        }
+        if self.cargo_workspace {
+            if let Some(workspace) = self
+                .workspace
+                .get_or_init(|| CargoWorkspace::load(&self.root))
+            {
+                for license in workspace.licenses() {
+                    if !idents.contains(&license) {
+                        idents.push(license);
+                    }
+                }
+            }
+        }
+
+        idents
+    }
+
+    /// Returns the license identifiers whose templates are fetched from SPDX
+    /// because auto_template is enabled and no template is given.
+    pub fn fetched_idents(&self) -> Vec<&str> {
+        if self.template.is_none()
+            && self.template_file.is_none()
+            && self.auto_template.unwrap_or(false)
+        {
+            self.idents()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Returns the templates the template_file was committed with in git
+    /// before it last changed, with context, for finding the headers written
+    /// from them. Empty unless the template is read from a template_file.
+    pub fn previous_templates(&self, context: &Context) -> Vec<Template> {
+        let Some(path) = &self.template_file else {
+            return Vec::new();
+        };
+        let Ok(Some(current)) = self.raw_template_text() else {
+            return Vec::new();
+        };
+
+        self.previous_template_texts
+            .get_or_init(|| match vcs::file_versions(path, PREVIOUS_TEMPLATES) {
+                Ok(versions) => versions
+                    .into_iter()
+                    .map(|version| version.trim_end_matches('\n').to_string())
+                    .filter(|version| version != current)
+                    .collect(),
+                Err(e) => {
+                    debug!("unable to read the history of {}: {}", path, e);
+                    Vec::new()
+                }
+            })
+            .iter()
+            .filter_map(|text| {
+                // A version including a partial that's since been removed
+                // can't be rendered.
+                let text = self.expand_includes(text).ok()?;
+                Some(
+                    Template::new(&text, context.clone())
+                        .set_spdx_template(self.auto_template.unwrap_or(false)),
+                )
+            })
+            .collect()
+    }
+
+    /// Fill in the copyright placeholders of the full text of a license, as
+    /// found in the licenseText field, for use in a LICENSE file.
+    pub fn render_license_text(&self, ident: &str, license_info: &LicenseInfo) -> String {
+        Template::new(
+            &license_info.license_text,
+            Context {
+                end_year: self.end_year.clone(),
+                start_year: self.start_year.clone(),
+                ident: ident.to_string(),
+                authors: self.default_authors(),
+                contributors: Authors::default(),
+                locale: self.locale,
+                unwrap_text: false,
+            },
+        )
+        .set_spdx_template(!license_info.custom)
+        .render()
+    }
+}
+
+// The license of the first LICENSE or LICENSE.* file in dir whose text
+// licensure recognizes.
+fn read_license_file(dir: &Path) -> Option<&'static str> {
+    let read_dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let mut paths: Vec<PathBuf> = fs::read_dir(read_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            path.is_file() && (name == "LICENSE" || name.starts_with("LICENSE."))
+        })
+        .collect();
+    paths.sort();
+
+    paths.iter().find_map(|path| {
+        let content = fs::read_to_string(path).ok()?;
+        detect::license_texts(&content).first().copied()
+    })
+}
+
+// Whether dir is the directory licensure runs in, which relative paths are
+// beneath, or the top level of a git repository.
+fn is_root(dir: &Path) -> bool {
+    dir.as_os_str().is_empty()
+        || dir.join(".git").exists()
+        || env::current_dir().is_ok_and(|cwd| cwd == dir)
+}
+
+// The top level of the repository dir is in if it's a shallow clone.
+fn get_shallow_clone(dir: &Path) -> Option<PathBuf> {
+    let proc = Command::new("git")
+        .current_dir(dir)
+        .args(["rev-parse", "--is-shallow-repository", "--show-toplevel"])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8(proc.stdout).ok()?;
+    let mut lines = stdout.lines();
+    match (lines.next(), lines.next()) {
+        (Some("true"), Some(top_level)) => Some(PathBuf::from(top_level)),
+        _ => None,
+    }
+}
+
+fn unshallow(repo: &Path) -> Result<(), String> {
+    info!(
+        "fetching the full history of the shallow clone {}",
+        repo.display()
+    );
+    let proc = Command::new("git")
+        .current_dir(repo)
+        .args(["fetch", "--unshallow"])
+        .output()
+        .map_err(|e| format!("failed to run git fetch: {}", e))?;
+    if proc.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&proc.stderr).trim().to_string())
+    }
+}
+
+// The years filename was last modified and created according to its
+// timestamps. Copies often get a new creation time but keep the modification
+// time so the created year is the earlier of the two.
+fn get_filesystem_years_for_file(filename: &str) -> Result<(String, String), String> {
+    let metadata = fs::metadata(filename).map_err(|e| e.to_string())?;
+    let modified = metadata.modified().map_err(|e| e.to_string())?;
+    let created = metadata
+        .created()
+        .map_or(modified, |created| created.min(modified));
+    let year = |time| DateTime::<Local>::from(time).format("%Y").to_string();
+    Ok((year(modified), year(created)))
+}
+
+// The years filename was last updated and created according to vcs, or the
+// current year for both if vcs doesn't know the file yet.
+fn get_vcs_years_for_file(vcs: Vcs, filename: &str) -> Result<(String, String), String> {
+    let years = vcs.commit_years(filename)?;
+    Ok(match &years[..] {
+        [last_updated_year, .., created_year] => (last_updated_year.clone(), created_year.clone()),
+        [year] => (year.clone(), year.clone()),
+        _ => {
+            debug!("Did not get any dates from {} for file: {}", vcs, filename);
+            let now = Local::now().format("%Y").to_string();
+            (now.clone(), now)
+        }
+    })
+}
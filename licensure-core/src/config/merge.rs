@@ -0,0 +1,78 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use serde_yaml::{Mapping, Value};
+
+// The suffix of a key whose list is appended to the list of the key without
+// it instead of replacing it.
+const APPEND_SUFFIX: char = '+';
+
+/// Merge the config overlay into base. Mappings are merged key by key, a key
+/// ending with + appends its list to the list of the key without the +, and
+/// any other value of overlay replaces the one in base.
+pub fn merge(base: &mut Value, overlay: Value) {
+    let Value::Mapping(overlay) = overlay else {
+        *base = overlay;
+        return;
+    };
+    if !base.is_mapping() {
+        *base = Value::Mapping(Mapping::new());
+    }
+    let Value::Mapping(base) = base else {
+        unreachable!("base was made a mapping");
+    };
+
+    for (key, value) in overlay {
+        let appended = key
+            .as_str()
+            .and_then(|name| name.strip_suffix(APPEND_SUFFIX))
+            .map(|name| Value::String(name.to_string()));
+        match (appended, value) {
+            (Some(name), Value::Sequence(items)) => match base.get_mut(&name) {
+                Some(Value::Sequence(list)) => list.extend(items),
+                _ => {
+                    base.insert(name, Value::Sequence(items));
+                }
+            },
+            (Some(name), value) => merge(base.entry(name).or_insert(Value::Null), value),
+            (None, value) => merge(base.entry(key).or_insert(Value::Null), value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(s: &str) -> Value {
+        serde_yaml::from_str(s).expect("Static YAML to be parsable")
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut config = Value::Null;
+        merge(
+            &mut config,
+            yaml("columns: 80\nexcludes:\n  - a\nhooks:\n  pre_process: one\n  post_process: two\nlicenses:\n  - ident: MIT"),
+        );
+        merge(
+            &mut config,
+            yaml("columns: 100\nexcludes+:\n  - b\nhooks:\n  post_process: three\nlicenses:\n  - ident: Apache-2.0\nprofiles:\n  ci:\n    excludes+:\n      - c"),
+        );
+
+        assert_eq!(
+            yaml("columns: 100\nexcludes:\n  - a\n  - b\nhooks:\n  pre_process: one\n  post_process: three\nlicenses:\n  - ident: Apache-2.0\nprofiles:\n  ci:\n    excludes:\n      - c"),
+            config
+        );
+    }
+}
@@ -0,0 +1,359 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// The version of the config schema this licensure reads. Configs without a
+/// version are from before the schema was versioned.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// A config rewritten to the current schema and a description of each change
+/// which was made.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Migration {
+    pub content: String,
+    pub changes: Vec<String>,
+}
+
+fn version_re() -> &'static Regex {
+    static VERSION: OnceLock<Regex> = OnceLock::new();
+    VERSION.get_or_init(|| {
+        Regex::new(r"^version\s*:\s*(\d+)\s*(#.*)?$").expect("version regex didn't compile!")
+    })
+}
+
+// A mapping key at the start of a line, possibly as the first key of a
+// sequence item.
+fn key_re() -> &'static Regex {
+    static KEY: OnceLock<Regex> = OnceLock::new();
+    KEY.get_or_init(|| {
+        Regex::new(r"^(?P<indent>\s*(?:-\s+)?)(?P<key>[A-Za-z_]+)\s*:(?P<value>.*)$")
+            .expect("config key regex didn't compile!")
+    })
+}
+
+// The value of a key which starts a literal or folded block scalar.
+fn block_scalar_re() -> &'static Regex {
+    static BLOCK: OnceLock<Regex> = OnceLock::new();
+    BLOCK.get_or_init(|| {
+        Regex::new(r"^\s*[|>][-+0-9]*\s*(#.*)?$").expect("block scalar regex didn't compile!")
+    })
+}
+
+// A quoted or plain string given directly as a commenter.
+fn string_commenter_re() -> &'static Regex {
+    static STRING: OnceLock<Regex> = OnceLock::new();
+    STRING.get_or_init(|| {
+        Regex::new(r#"^\s*("[^"]*"|'[^']*'|[^\s#{\[][^#]*?)\s*(#.*)?$"#)
+            .expect("string commenter regex didn't compile!")
+    })
+}
+
+fn indent_width(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+// Returns true if parents, the keys a line is nested in, are the licenses of
+// the config or of one of its profiles.
+fn in_licenses(parents: &[(usize, String)]) -> bool {
+    match parents {
+        [(_, licenses)] => licenses == "licenses",
+        [(_, profiles), _, (_, licenses)] => profiles == "profiles" && licenses == "licenses",
+        _ => false,
+    }
+}
+
+/// Returns the version of the config in content, 0 if it doesn't have one.
+pub fn config_version(content: &str) -> u32 {
+    content
+        .lines()
+        .find_map(|line| version_re().captures(line))
+        .and_then(|caps| caps[1].parse().ok())
+        .unwrap_or(0)
+}
+
+/// Rewrite the config in content to the current schema. This works a line at
+/// a time so comments and formatting are kept:
+///
+/// - `year` in a license is renamed `end_year`, unless the license already
+///   has an `end_year`.
+/// - `extensions` in a comment config is renamed `extension`.
+/// - A commenter given as a string, like `commenter: "#"`, becomes a line
+///   commenter with that comment_char.
+/// - `version` is set to the current version.
+pub fn migrate(content: &str) -> Migration {
+    let mut changes = Vec::new();
+    let version = config_version(content);
+    if version >= CONFIG_VERSION {
+        return Migration {
+            content: content.to_string(),
+            changes,
+        };
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    // Lines indented deeper than this are the contents of a block scalar,
+    // such as a template, and are never rewritten.
+    let mut block_indent: Option<usize> = None;
+    let mut has_version = false;
+    // The keys the current line is nested in, with their columns.
+    let mut parents: Vec<(usize, String)> = Vec::new();
+    // The line number of the license the current line is in.
+    let mut license = 0;
+    // The licenses with an end_year, and each year renamed as the license
+    // it's in, its index in lines, its line number and the line as it was.
+    let mut end_years = HashSet::new();
+    let mut years = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let number = number + 1;
+        if let Some(indent) = block_indent {
+            if line.trim().is_empty() || indent_width(line) > indent {
+                lines.push(line.to_string());
+                continue;
+            }
+            block_indent = None;
+        }
+
+        let Some(caps) = key_re().captures(line) else {
+            lines.push(line.to_string());
+            continue;
+        };
+        let prefix = &caps["indent"];
+        let key = &caps["key"];
+        let value = &caps["value"];
+        let key_column = prefix.len();
+
+        if block_scalar_re().is_match(value) {
+            block_indent = Some(key_column);
+        }
+        while parents
+            .last()
+            .is_some_and(|(column, _)| *column >= key_column)
+        {
+            parents.pop();
+        }
+        let in_license = in_licenses(&parents);
+        if in_license && prefix.contains('-') {
+            license = number;
+        }
+
+        match key {
+            "version" if key_column == 0 => {
+                has_version = true;
+                changes.push(format!(
+                    "line {}: changed version from {} to {}",
+                    number, version, CONFIG_VERSION
+                ));
+                lines.push(format!("version: {}", CONFIG_VERSION));
+            }
+            "year" if in_license => {
+                years.push((license, lines.len(), number, line.to_string()));
+                changes.push(format!("line {}: renamed year to end_year", number));
+                lines.push(format!("{}end_year:{}", prefix, value));
+            }
+            "end_year" if in_license => {
+                end_years.insert(license);
+                lines.push(line.to_string());
+            }
+            "extensions" => {
+                changes.push(format!("line {}: renamed extensions to extension", number));
+                lines.push(format!("{}extension:{}", prefix, value));
+            }
+            "commenter" if !value.trim().is_empty() && !block_scalar_re().is_match(value) => {
+                match string_commenter_re().captures(value) {
+                    Some(commenter) => {
+                        changes.push(format!(
+                            "line {}: changed commenter {} to a line commenter",
+                            number, &commenter[1]
+                        ));
+                        let indent = " ".repeat(key_column + 2);
+                        match commenter.get(2) {
+                            Some(comment) => {
+                                lines.push(format!("{}commenter: {}", prefix, comment.as_str()))
+                            }
+                            None => lines.push(format!("{}commenter:", prefix)),
+                        }
+                        lines.push(format!("{}type: line", indent));
+                        lines.push(format!("{}comment_char: {}", indent, &commenter[1]));
+                    }
+                    None => lines.push(line.to_string()),
+                }
+            }
+            _ => lines.push(line.to_string()),
+        }
+        parents.push((key_column, key.to_string()));
+    }
+
+    // A license with both can't be given two end_years.
+    for (license, index, number, line) in years {
+        if end_years.contains(&license) {
+            lines[index] = line;
+            let renamed = format!("line {}: renamed year to end_year", number);
+            if let Some(change) = changes.iter_mut().find(|change| **change == renamed) {
+                *change = format!(
+                    "line {}: kept year as the license already has an end_year, remove one of them",
+                    number
+                );
+            }
+        }
+    }
+
+    if !has_version {
+        changes.push(format!("added version: {}", CONFIG_VERSION));
+        // Keep any comments describing the file at the top of it.
+        let at = lines
+            .iter()
+            .position(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .unwrap_or(lines.len());
+        lines.insert(at, format!("version: {}", CONFIG_VERSION));
+    }
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    Migration { content, changes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static OLD_CONFIG: &str = r##"# Our licensing.
+excludes:
+  - vendor/.*
+licenses:
+  - files: any
+    ident: MIT
+    year: "2019"
+    template: |
+      Copyright [year] [name of author]
+      year: this line is part of the template
+    authors:
+      - name: The Tester
+comments:
+  - extension: py
+    commenter: "#"
+  - extension: lisp
+    commenter: ;;;  # semicolons
+  - extensions: [rs, c]
+    commenter:
+      type: line
+      comment_char: "//"
+"##;
+
+    #[test]
+    fn test_migrate() {
+        let migration = migrate(OLD_CONFIG);
+        assert_eq!(
+            r##"# Our licensing.
+version: 1
+excludes:
+  - vendor/.*
+licenses:
+  - files: any
+    ident: MIT
+    end_year: "2019"
+    template: |
+      Copyright [year] [name of author]
+      year: this line is part of the template
+    authors:
+      - name: The Tester
+comments:
+  - extension: py
+    commenter:
+      type: line
+      comment_char: "#"
+  - extension: lisp
+    commenter: # semicolons
+      type: line
+      comment_char: ;;;
+  - extension: [rs, c]
+    commenter:
+      type: line
+      comment_char: "//"
+"##,
+            migration.content
+        );
+        assert_eq!(
+            vec![
+                "line 7: renamed year to end_year",
+                "line 15: changed commenter \"#\" to a line commenter",
+                "line 17: changed commenter ;;; to a line commenter",
+                "line 18: renamed extensions to extension",
+                "added version: 1",
+            ],
+            migration.changes
+        );
+        assert_eq!(1, config_version(&migration.content));
+
+        // A migrated config is left alone.
+        let again = migrate(&migration.content);
+        assert!(again.changes.is_empty());
+        assert_eq!(migration.content, again.content);
+    }
+
+    #[test]
+    fn test_migrate_year() {
+        let migration = migrate(
+            r##"licenses:
+  - files: any
+    year: "2019"
+    end_year: "2020"
+  - ident: MIT
+    year: "2021"
+    authors:
+      - name: The Tester
+        year: "2018"
+profiles:
+  docs:
+    licenses:
+      - ident: CC-BY-4.0
+        year: "2022"
+plugins:
+  year: year.wasm
+"##,
+        );
+        assert_eq!(
+            r##"version: 1
+licenses:
+  - files: any
+    year: "2019"
+    end_year: "2020"
+  - ident: MIT
+    end_year: "2021"
+    authors:
+      - name: The Tester
+        year: "2018"
+profiles:
+  docs:
+    licenses:
+      - ident: CC-BY-4.0
+        end_year: "2022"
+plugins:
+  year: year.wasm
+"##,
+            migration.content
+        );
+        assert_eq!(
+            vec![
+                "line 3: kept year as the license already has an end_year, remove one of them",
+                "line 6: renamed year to end_year",
+                "line 14: renamed year to end_year",
+                "added version: 1",
+            ],
+            migration.changes
+        );
+    }
+}
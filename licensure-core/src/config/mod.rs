@@ -0,0 +1,1468 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{Datelike, Local};
+use regex::Regex;
+use regex::RegexSet;
+use serde::Deserialize;
+
+pub use default::DEFAULT_CONFIG;
+pub use deprecated::{find_deprecations, Deprecation};
+pub use license::{ExistingHeaderPolicy, ShallowClonePolicy, YearSource};
+pub use migrate::{config_version, migrate, Migration, CONFIG_VERSION};
+pub use suppression::{Suppression, Suppressions};
+pub use vcs::Vcs;
+
+use crate::comments::Comment;
+use crate::config::comment::Config as CommentConfig;
+use crate::config::comment::Defaults as CommentDefaults;
+pub use crate::config::comment::Position;
+use crate::config::comment::{get_filetype, get_interpreter};
+pub use crate::config::license::Config as LicenseConfig;
+use crate::detect;
+use crate::hooks::Hooks;
+use crate::plugins::Plugins;
+use crate::spdx::LicenseInfo;
+use crate::template::Context;
+use crate::utils::normalize_path;
+
+mod cargo;
+mod codeowners;
+mod comment;
+mod default;
+mod deprecated;
+mod license;
+mod merge;
+mod migrate;
+mod suppression;
+mod vcs;
+
+// The file the headers of license configs are rendered for when they're
+// checked, the name comment configs for any file get.
+const SAMPLE_FILENAME: &str = "example";
+
+// The config path which stands for stdin.
+const STDIN_PATH: &str = "-";
+
+fn default_off() -> bool {
+    false
+}
+
+fn default_on() -> bool {
+    true
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Config {
+    // The version of the config schema, see migrate.
+    #[serde(default)]
+    pub version: u32,
+
+    #[serde(default = "default_off")]
+    pub change_in_place: bool,
+
+    #[serde(default = "default_on")]
+    pub include_hidden: bool,
+    #[serde(default = "default_off")]
+    pub follow_symlinks: bool,
+
+    #[serde(default)]
+    pub max_file_size: Option<FileSize>,
+    #[serde(default)]
+    pub max_files: Option<usize>,
+    // How many lines from the top of a file a header which was moved below
+    // imports or other comments is looked for.
+    #[serde(default)]
+    pub header_search_lines: Option<usize>,
+    // Leave files marked as generated alone, their generator would drop the
+    // header anyway.
+    #[serde(default = "default_off")]
+    pub skip_generated: bool,
+    #[serde(default)]
+    pub nested_repos: NestedRepoPolicy,
+    #[serde(default)]
+    pub empty_files: EmptyFilePolicy,
+
+    #[serde(default)]
+    pub hooks: Hooks,
+
+    #[serde(default)]
+    pub ca_bundle: Option<String>,
+    #[serde(default)]
+    pub spdx_base_url: Option<String>,
+    #[serde(default)]
+    pub spdx_timeout: Option<u64>,
+    #[serde(default)]
+    pub spdx_retries: Option<u32>,
+    #[serde(default)]
+    pub spdx_rate_limit: Option<f64>,
+    #[serde(default)]
+    pub licenses_registry: Option<String>,
+
+    #[serde(flatten)]
+    pub comment_defaults: CommentDefaults,
+
+    #[serde(default)]
+    pub excludes: RegexList,
+    #[serde(default)]
+    pub licenses: LicenseConfigList,
+    #[serde(default)]
+    pub comments: CommentConfigList,
+    // Text shared by the templates of several licenses, which they include
+    // by name with [include:name].
+    #[serde(default)]
+    partials: BTreeMap<String, String>,
+    // The paths of WASM plugins by name, see plugins.rs.
+    #[serde(default)]
+    plugins: BTreeMap<String, String>,
+    // Findings which are known exceptions, by rule ID.
+    #[serde(default)]
+    pub suppressions: Suppressions,
+
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+
+    // The deprecated keys the config was written with.
+    #[serde(skip)]
+    pub deprecations: Vec<Deprecation>,
+    // The top level of the repository files are relative to, empty for the
+    // directory licensure runs in.
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+/// What to do with git submodules and other git repositories nested in the
+/// project.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NestedRepoPolicy {
+    /// License their files with this config like any other project files.
+    #[default]
+    Parent,
+    /// Leave their files alone.
+    Skip,
+    /// License their files with the config found from the root of the
+    /// nested repository, as if licensure was run there.
+    OwnConfig,
+}
+
+/// What to do with files which are empty or only whitespace, such as empty
+/// __init__.py files, which a header would leave holding nothing else.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyFilePolicy {
+    /// Leave them alone.
+    Skip,
+    /// License them like any other file so they hold only the header.
+    #[default]
+    HeaderOnly,
+    /// Report them as failed.
+    Error,
+}
+
+/// A named set of excludes, licenses, and comments which replace the top level
+/// ones when selected with --profile. Sections a profile doesn't set are
+/// inherited from the top level.
+#[derive(Deserialize, Debug)]
+struct Profile {
+    excludes: Option<RegexList>,
+    licenses: Option<LicenseConfigList>,
+    comments: Option<CommentConfigList>,
+}
+
+impl Config {
+    pub fn add_exclude(&mut self, pat: &str) -> Result<(), String> {
+        self.excludes.add_exclude(pat)
+    }
+
+    /// Replace the top level sections with those of the named profile.
+    pub fn use_profile(&mut self, name: &str) -> Result<(), String> {
+        let profile = match self.profiles.remove(name) {
+            Some(profile) => profile,
+            None if self.profiles.is_empty() => {
+                return Err(format!(
+                    "no profile named {}, no profiles are configured",
+                    name
+                ))
+            }
+            None => {
+                return Err(format!(
+                    "no profile named {}, available profiles are: {}",
+                    name,
+                    self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                ))
+            }
+        };
+
+        if let Some(excludes) = profile.excludes {
+            self.excludes = excludes;
+        }
+        if let Some(licenses) = profile.licenses {
+            self.licenses = licenses;
+        }
+        if let Some(comments) = profile.comments {
+            self.comments = comments;
+        }
+
+        self.check_headers()
+    }
+
+    /// Fail if the header of a license config contains the start or end of
+    /// the block commenter of a comment config for files it matches, which
+    /// would break the comment it's written in. The files of a comment config
+    /// are told by the made up name sample_filenames gives them, and those of
+    /// comment configs without one only by licenses for any file; the header
+    /// of every file is checked again when it's licensed. Headers fetched
+    /// from SPDX aren't checked, as they aren't fetched until files are
+    /// licensed.
+    pub fn check_headers(&self) -> Result<(), String> {
+        let year = Local::now().year().to_string();
+        for license in self.licenses.iter() {
+            if !license.fetched_idents().is_empty() {
+                continue;
+            }
+            for (i, cfg) in self.comments.cfgs.iter().enumerate() {
+                let filename = cfg
+                    .sample_filename()
+                    .unwrap_or_else(|| SAMPLE_FILENAME.to_string());
+                if !license.file_is_match(&filename) {
+                    continue;
+                }
+                // A header which can't be rendered fails when files are
+                // licensed, with the reason why.
+                let context = license.get_sample_context(&filename, &year);
+                let Ok(template) = license.get_template(&filename, context) else {
+                    continue;
+                };
+                cfg.check_header(
+                    &template.render(),
+                    &format!("the comments of comment config {}", i + 1),
+                )
+                .map_err(|e| format!("the header of {} {}", license.ident(), e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The top level of the repository the files given to the config are
+    /// relative to, empty for the directory licensure runs in.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Make the files given to the config relative to root, the top level of
+    /// a repository nested in the directory licensure runs in, instead of
+    /// that directory.
+    pub fn set_root(&mut self, root: &Path) {
+        self.root = root.to_path_buf();
+        self.licenses.set_root(root);
+        for profile in self.profiles.values_mut() {
+            if let Some(licenses) = &mut profile.licenses {
+                licenses.set_root(root);
+            }
+        }
+    }
+
+    // Make the paths in the config relative to the directory of the config
+    // file at config_path, which it was read from.
+    fn set_config_path(&mut self, config_path: &Path) {
+        self.licenses_registry = self
+            .licenses_registry
+            .as_ref()
+            .map(|registry| relative_to_config(registry, config_path));
+        self.licenses.set_config_path(config_path);
+        for profile in self.profiles.values_mut() {
+            if let Some(licenses) = &mut profile.licenses {
+                licenses.set_config_path(config_path);
+            }
+        }
+    }
+
+    // Give the license configs, including those of profiles, the partials
+    // their templates include, failing if one includes an undefined partial.
+    fn set_partials(&mut self) -> Result<(), String> {
+        let partials = Arc::new(self.partials.clone());
+        self.licenses.set_partials(&partials)?;
+        for profile in self.profiles.values_mut() {
+            if let Some(licenses) = &mut profile.licenses {
+                licenses.set_partials(&partials)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Load the plugins and give them to the license configs, including those
+    // of profiles.
+    fn set_plugins(&mut self) -> Result<(), String> {
+        let plugins = Arc::new(Plugins::load(&self.plugins)?);
+        self.licenses.set_plugins(&plugins)?;
+        for profile in self.profiles.values_mut() {
+            if let Some(licenses) = &mut profile.licenses {
+                licenses.set_plugins(&plugins)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_commenter(&self, filename: &str, content: &str) -> Box<dyn Comment> {
+        self.comments
+            .get_commenter(filename, content, &self.comment_defaults)
+    }
+
+    /// Returns which comment config applies to filename, None when the
+    /// default commenter is used. Files with the same comment config get the
+    /// same commenter.
+    pub fn commenter_index(&self, filename: &str, content: &str) -> Option<usize> {
+        self.comments
+            .position(filename, content, &self.comment_defaults)
+    }
+
+    /// Returns the width the header of filename is wrapped at, None when it
+    /// isn't wrapped.
+    pub fn columns(&self, filename: &str, content: &str) -> Option<usize> {
+        match self
+            .comments
+            .find(filename, content, &self.comment_defaults)
+        {
+            Some(c) => c.columns(&self.comment_defaults),
+            None => CommentConfig::default().columns(&self.comment_defaults),
+        }
+    }
+
+    /// Returns where the header of filename is added, as its license config
+    /// says or else its comment config.
+    pub fn position(&self, filename: &str, content: &str) -> Position {
+        if let Ok(Some((_, license))) = self.licenses.find_for(filename, content) {
+            if let Some(position) = license.position() {
+                return position;
+            }
+        }
+
+        self.comments
+            .find(filename, content, &self.comment_defaults)
+            .map_or(Position::default(), CommentConfig::position)
+    }
+
+    /// Returns whether the license text of filename's header is unwrapped
+    /// as its comment config says, None to leave it to the license config.
+    pub fn unwrap_text(&self, filename: &str, content: &str) -> Option<bool> {
+        self.comments
+            .find(filename, content, &self.comment_defaults)
+            .and_then(CommentConfig::unwrap_text)
+    }
+
+    /// Trace the license and comment configs filename with content was
+    /// checked against, in order, up to the one which matched.
+    pub fn trace_matchers(&self, filename: &str, content: &str) {
+        match detect::license_pragma(content) {
+            Some(ident) => trace_file!(
+                filename,
+                "a licensure: license={} pragma selects the license config",
+                ident
+            ),
+            None => {
+                for (i, license) in self.licenses.iter().enumerate() {
+                    let matched = license.file_is_match(filename);
+                    trace_file!(
+                        filename,
+                        "license config {} ({}) with files {} {}",
+                        i + 1,
+                        license.ident(),
+                        license.files(),
+                        if matched { "matches" } else { "doesn't match" }
+                    );
+                    if matched {
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.comments
+            .trace(filename, content, &self.comment_defaults);
+    }
+
+    /// Fail if header, uncommented, contains the start or end of the block
+    /// commenter of filename with content, which would break the comment
+    /// it's written in.
+    pub fn check_header(&self, filename: &str, content: &str, header: &str) -> Result<(), String> {
+        match self
+            .comments
+            .position(filename, content, &self.comment_defaults)
+        {
+            Some(idx) => self.comments.cfgs[idx].check_header(
+                header,
+                &format!("the comments of comment config {}", idx + 1),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns true if the header for filename should be written to a
+    /// <filename>.license sidecar file instead of into the file itself.
+    pub fn uses_sidecar(&self, filename: &str) -> bool {
+        self.comments
+            .find(filename, "", &self.comment_defaults)
+            .is_some_and(CommentConfig::is_sidecar)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        serde_yaml::from_str(DEFAULT_CONFIG).expect("The default config is invalid?")
+    }
+}
+
+/// Parse a config from YAML in the format of a .licensure.yml file.
+impl FromStr for Config {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Config, String> {
+        let mut config: Config =
+            serde_yaml::from_str(s).map_err(|e| format!("Invalid YAML: {}", e))?;
+        config.set_partials()?;
+        config.set_plugins()?;
+        config.check_headers()?;
+        config.deprecations = find_deprecations(s);
+        Ok(config)
+    }
+}
+
+// A file size is either a number of bytes or a string such as "10MB".
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FileSizeSource {
+    Bytes(u64),
+    Text(String),
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "FileSizeSource")]
+pub struct FileSize(pub u64);
+
+impl FileSize {
+    /// Parse a size such as "512", "64KB", or "1.5 MiB". Units are
+    /// case-insensitive and are all powers of 1024.
+    fn parse(text: &str) -> Option<FileSize> {
+        let text = text.trim();
+        let split = text
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(text.len());
+        let (number, unit) = text.split_at(split);
+        let number: f64 = number.parse().ok()?;
+        let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kb" | "kib" => 1 << 10,
+            "m" | "mb" | "mib" => 1 << 20,
+            "g" | "gb" | "gib" => 1 << 30,
+            _ => return None,
+        };
+
+        Some(FileSize((number * multiplier as f64) as u64))
+    }
+}
+
+impl TryFrom<FileSizeSource> for FileSize {
+    type Error = String;
+
+    fn try_from(source: FileSizeSource) -> Result<FileSize, String> {
+        match source {
+            FileSizeSource::Bytes(bytes) => Ok(FileSize(bytes)),
+            FileSizeSource::Text(text) => FileSize::parse(&text)
+                .ok_or_else(|| format!("invalid file size {:?}, expected a size like 10MB", text)),
+        }
+    }
+}
+
+impl fmt::Display for FileSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes", self.0)
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(try_from = "Vec<String>")]
+pub struct RegexList {
+    regex: RegexSet,
+}
+
+impl RegexList {
+    pub fn is_match(&self, s: &str) -> bool {
+        self.regex.is_match(&normalize_path(s))
+    }
+
+    /// Returns the patterns which match s.
+    pub fn matching(&self, s: &str) -> Vec<&str> {
+        self.regex
+            .matches(&normalize_path(s))
+            .iter()
+            .map(|i| self.regex.patterns()[i].as_str())
+            .collect()
+    }
+
+    /// Add the exclude pattern pat, failing if it isn't a valid regex.
+    pub fn add_exclude(&mut self, pat: &str) -> Result<(), String> {
+        let mut old_pats = Vec::from(self.regex.patterns());
+        let mut new_pats = vec![pat.to_string()];
+        new_pats.append(&mut old_pats);
+        *self = RegexList::try_from(new_pats)?;
+        Ok(())
+    }
+}
+
+impl TryFrom<Vec<String>> for RegexList {
+    type Error = String;
+
+    fn try_from(rgxs: Vec<String>) -> Result<RegexList, String> {
+        RegexSet::new(rgxs)
+            .map(|regex| RegexList { regex })
+            .map_err(|e| format!("invalid exclude pattern: {}", e))
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(from = "Vec<CommentConfig>")]
+pub struct CommentConfigList {
+    cfgs: Vec<CommentConfig>,
+}
+
+impl From<Vec<CommentConfig>> for CommentConfigList {
+    fn from(cfgs: Vec<CommentConfig>) -> CommentConfigList {
+        CommentConfigList { cfgs }
+    }
+}
+
+impl CommentConfigList {
+    fn find(
+        &self,
+        filename: &str,
+        content: &str,
+        defaults: &CommentDefaults,
+    ) -> Option<&CommentConfig> {
+        self.position(filename, content, defaults)
+            .map(|idx| &self.cfgs[idx])
+    }
+
+    fn position(&self, filename: &str, content: &str, defaults: &CommentDefaults) -> Option<usize> {
+        let file_type = get_filetype(filename);
+        let interpreter = get_interpreter(content);
+
+        self.cfgs.iter().position(|c| {
+            c.matches(
+                file_type,
+                filename,
+                interpreter,
+                defaults.case_sensitive_extensions,
+            )
+        })
+    }
+
+    // Trace the comment configs filename is checked against by position.
+    fn trace(&self, filename: &str, content: &str, defaults: &CommentDefaults) {
+        let file_type = get_filetype(filename);
+        let interpreter = get_interpreter(content);
+        trace_file!(
+            filename,
+            "file type {:?}, interpreter {:?}",
+            file_type,
+            interpreter
+        );
+        for (i, cfg) in self.cfgs.iter().enumerate() {
+            let matched = cfg.matches(
+                file_type,
+                filename,
+                interpreter,
+                defaults.case_sensitive_extensions,
+            );
+            trace_file!(
+                filename,
+                "comment config {} {}",
+                i + 1,
+                if matched { "matches" } else { "doesn't match" }
+            );
+            if matched {
+                return;
+            }
+        }
+        trace_file!(
+            filename,
+            "no comment config matches, the default commenter is used"
+        );
+    }
+
+    // Fail if header contains the start or end of the block commenter of a
+    // comment config, naming the first one it breaks.
+    /// Returns a made up file name for each comment config which is
+    /// classified by its extension or file name, in the order they're
+    /// configured.
+    pub fn sample_filenames(&self) -> Vec<String> {
+        let mut filenames = Vec::new();
+        for filename in self.cfgs.iter().filter_map(CommentConfig::sample_filename) {
+            if !filenames.contains(&filename) {
+                filenames.push(filename);
+            }
+        }
+
+        filenames
+    }
+
+    pub fn get_commenter(
+        &self,
+        filename: &str,
+        content: &str,
+        defaults: &CommentDefaults,
+    ) -> Box<dyn Comment> {
+        match self.find(filename, content, defaults) {
+            Some(c) => c.commenter(defaults),
+            None => CommentConfig::default().commenter(defaults),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(try_from = "Vec<LicenseConfig>")]
+pub struct LicenseConfigList {
+    cfgs: Vec<LicenseConfig>,
+}
+
+impl LicenseConfigList {
+    /// Returns every license config in the order they're configured.
+    pub fn iter(&self) -> impl Iterator<Item = &LicenseConfig> {
+        self.cfgs.iter()
+    }
+
+    /// Returns the first license config which matches filename and its
+    /// position in the list.
+    pub fn find(&self, filename: &str) -> Option<(usize, &LicenseConfig)> {
+        self.cfgs
+            .iter()
+            .enumerate()
+            .find(|(_, cfg)| cfg.file_is_match(filename))
+    }
+
+    /// Returns the license config for filename with content and its position
+    /// in the list, the one a licensure: license=IDENT pragma in content
+    /// selects or else the first which matches filename. Fails if the pragma
+    /// names an ident no license config has.
+    pub fn find_for(
+        &self,
+        filename: &str,
+        content: &str,
+    ) -> Result<Option<(usize, &LicenseConfig)>, String> {
+        match detect::license_pragma(content) {
+            Some(ident) => self.find_ident(ident).map(Some).ok_or_else(|| {
+                format!(
+                    "a licensure: license={} pragma selects a license no license config has",
+                    ident
+                )
+            }),
+            None => Ok(self.find(filename)),
+        }
+    }
+
+    /// Returns the first license config for ident and its position in the
+    /// list.
+    pub fn find_ident(&self, ident: &str) -> Option<(usize, &LicenseConfig)> {
+        self.cfgs
+            .iter()
+            .enumerate()
+            .find(|(_, cfg)| cfg.idents().contains(&ident))
+    }
+
+    fn set_partials(&mut self, partials: &Arc<BTreeMap<String, String>>) -> Result<(), String> {
+        for cfg in &mut self.cfgs {
+            cfg.set_partials(Arc::clone(partials))?;
+        }
+
+        Ok(())
+    }
+
+    fn set_root(&mut self, root: &Path) {
+        for cfg in &mut self.cfgs {
+            cfg.set_root(root);
+        }
+    }
+
+    fn set_config_path(&mut self, config_path: &Path) {
+        for cfg in &mut self.cfgs {
+            cfg.set_config_path(config_path);
+        }
+    }
+
+    fn set_plugins(&mut self, plugins: &Arc<Plugins>) -> Result<(), String> {
+        for cfg in &mut self.cfgs {
+            cfg.set_plugins(Arc::clone(plugins))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_context(&self, filename: &str) -> Option<Context> {
+        self.cfgs
+            .iter()
+            .find(|cfg| cfg.file_is_match(filename))
+            .map(|cfg| cfg.get_context(filename))
+    }
+
+    /// Returns every distinct license identifier in the order they are
+    /// configured.
+    pub fn idents(&self) -> Vec<&str> {
+        let mut idents = Vec::new();
+        for ident in self.cfgs.iter().flat_map(|cfg| cfg.idents()) {
+            if !idents.contains(&ident) {
+                idents.push(ident);
+            }
+        }
+
+        idents
+    }
+
+    /// Returns every distinct license identifier whose headers a license
+    /// config replaces with replaces_licenses.
+    pub fn replaced_idents(&self) -> Vec<&str> {
+        let mut idents = Vec::new();
+        for ident in self.cfgs.iter().flat_map(|cfg| cfg.replaces_licenses()) {
+            if !idents.contains(&ident) {
+                idents.push(ident);
+            }
+        }
+
+        idents
+    }
+
+    /// Returns the license identifiers whose templates are fetched from SPDX.
+    pub fn fetched_idents(&self) -> Vec<&str> {
+        self.cfgs
+            .iter()
+            .flat_map(|cfg| cfg.fetched_idents())
+            .collect()
+    }
+
+    /// Returns the license identifier of the first license config which
+    /// matches filename.
+    pub fn get_ident(&self, filename: &str) -> Option<&str> {
+        self.cfgs
+            .iter()
+            .find(|cfg| cfg.file_is_match(filename))
+            .map(|cfg| cfg.ident_for(filename))
+    }
+
+    pub fn render_license_text(&self, ident: &str, license_info: &LicenseInfo) -> Option<String> {
+        self.cfgs
+            .iter()
+            .find(|cfg| cfg.idents().contains(&ident))
+            .map(|cfg| cfg.render_license_text(ident, license_info))
+    }
+
+    pub fn get_skip_existing(&self, filename: &str) -> ExistingHeaderPolicy {
+        self.cfgs
+            .iter()
+            .find(|cfg| cfg.file_is_match(filename))
+            .map_or(ExistingHeaderPolicy::default(), |cfg| cfg.skip_existing())
+    }
+
+    pub fn get_replaces(&self, filename: &str) -> Option<&Vec<Regex>> {
+        for cfg in &self.cfgs {
+            if cfg.file_is_match(filename) {
+                return cfg.get_replaces().as_ref();
+            }
+        }
+
+        None
+    }
+}
+
+impl TryFrom<Vec<LicenseConfig>> for LicenseConfigList {
+    type Error = String;
+
+    fn try_from(cfgs: Vec<LicenseConfig>) -> Result<LicenseConfigList, String> {
+        // A template_file can't be read until it's made relative to the
+        // config file, it's checked with the partials.
+        for cfg in cfgs.iter().filter(|cfg| !cfg.has_template_file()) {
+            cfg.check_template()?;
+        }
+
+        Ok(LicenseConfigList { cfgs })
+    }
+}
+
+pub fn xdg_config_dir() -> Option<PathBuf> {
+    match env::var("XDG_CONFIG_HOME") {
+        Ok(d) => Some(PathBuf::from(d)),
+        Err(_) => match env::var("HOME") {
+            Ok(home) => {
+                let mut home_dir = PathBuf::from(home);
+                home_dir.push(".config");
+                Some(home_dir)
+            }
+            Err(_) => None,
+        },
+    }
+}
+
+/// Walk up from the current working directory searching for
+/// the first .licensure.yml config file available else find the
+/// global config file.
+pub fn find_config_file() -> Option<PathBuf> {
+    find_config_file_from(Path::new(""))
+}
+
+/// Find the config file like find_config_file but walking up from dir, which
+/// is relative to the current working directory.
+pub fn find_config_file_from(dir: &Path) -> Option<PathBuf> {
+    if let Ok(mut cwd) = env::current_dir().map(|cwd| cwd.join(dir)) {
+        loop {
+            cwd.push(".licensure.yml");
+            if cwd.exists() {
+                return Some(cwd);
+            }
+
+            // Pop the .licensure.yml file we added
+            cwd.pop();
+
+            // Move up a directory checking if we have hit root yet
+            if !cwd.pop() {
+                break;
+            }
+        }
+    }
+
+    if let Some(mut global) = xdg_config_dir() {
+        global.push("licensure");
+        global.push("config.yml");
+        if global.exists() {
+            return Some(global);
+        }
+    }
+
+    None
+}
+
+pub fn load_config() -> Result<Config, io::Error> {
+    load_config_from(Path::new(""))
+}
+
+/// Load the config found from root, the top level of a repository nested in
+/// the current working directory, with root as the root of its files.
+pub fn load_config_from(root: &Path) -> Result<Config, io::Error> {
+    match find_config_file_from(root) {
+        Some(path) => {
+            let content = fs::read_to_string(&path)?;
+            let mut config = parse_config_at(&content, &path.display().to_string(), Some(&path))?;
+            config.set_root(root);
+            Ok(config)
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Config file not found",
+        )),
+    }
+}
+
+/// Load the config files at paths, each one merged over the ones before it.
+/// Mappings are merged key by key, a key ending with + appends its list to
+/// the list of the key without the +, and any other value replaces the one
+/// before it. A path of - is read from stdin.
+pub fn load_configs(paths: &[PathBuf]) -> Result<Config, io::Error> {
+    let mut merged = serde_yaml::Value::Null;
+    let mut sources = Vec::new();
+    for path in paths {
+        let stdin = path.as_os_str() == STDIN_PATH;
+        let source = if stdin {
+            "stdin".to_string()
+        } else {
+            path.display().to_string()
+        };
+        let content = if stdin {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content).map(|_| content)
+        } else {
+            fs::read_to_string(path)
+        }
+        .map_err(|e| io::Error::other(format!("Unable to read {}: {}", source, e)))?;
+        let mut overlay: serde_yaml::Value = serde_yaml::from_str(&content)
+            .map_err(|e| io::Error::other(format!("Invalid YAML in {}: {}", source, e)))?;
+        if !stdin {
+            overlay_relative_to_config(&mut overlay, path);
+        }
+        // An empty file changes nothing.
+        if !overlay.is_null() {
+            merge::merge(&mut merged, overlay);
+        }
+        sources.push(source);
+    }
+
+    let content = serde_yaml::to_string(&merged).map_err(io::Error::other)?;
+    let source = match sources.as_slice() {
+        [source] => source.clone(),
+        _ => format!("the merge of {}", sources.join(", ")),
+    };
+    parse_config(&content, &source)
+}
+
+// Make the licenses_registry and template_file paths of overlay, the config
+// file at config_path, relative to its directory, as Config::set_config_path
+// does once a config is parsed.
+fn overlay_relative_to_config(overlay: &mut serde_yaml::Value, config_path: &Path) {
+    let relative = |value: Option<&mut serde_yaml::Value>| {
+        if let Some(serde_yaml::Value::String(path)) = value {
+            *path = relative_to_config(path, config_path);
+        }
+    };
+    relative(overlay.get_mut("licenses_registry"));
+
+    let mut sections: Vec<&mut serde_yaml::Value> = vec![overlay];
+    let mut licenses = Vec::new();
+    while let Some(section) = sections.pop() {
+        let Some(section) = section.as_mapping_mut() else {
+            continue;
+        };
+        for (key, value) in section.iter_mut() {
+            match key.as_str() {
+                Some("licenses" | "licenses+") => licenses.push(value),
+                Some("profiles" | "profiles+") => {
+                    if let Some(profiles) = value.as_mapping_mut() {
+                        sections.extend(profiles.iter_mut().map(|(_, profile)| profile));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    for license in licenses
+        .into_iter()
+        .filter_map(|licenses| licenses.as_sequence_mut())
+        .flatten()
+    {
+        relative(license.get_mut("template_file"));
+    }
+}
+
+// The path a config file at config_path gives as path, which is relative to
+// the directory of the config file unless it's absolute.
+fn relative_to_config(path: &str, config_path: &Path) -> String {
+    match config_path.parent() {
+        Some(dir) if Path::new(path).is_relative() => dir.join(path).display().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Parse the config in content, which was read from source, refusing configs
+/// of a newer version than this licensure supports.
+pub fn parse_config(content: &str, source: &str) -> Result<Config, io::Error> {
+    parse_config_at(content, source, None)
+}
+
+// Parse the config in content like parse_config, making its paths relative to
+// the directory of config_path, the file it was read from, if it's given.
+fn parse_config_at(
+    content: &str,
+    source: &str,
+    config_path: Option<&Path>,
+) -> Result<Config, io::Error> {
+    match serde_yaml::from_str::<Config>(content) {
+        Ok(c) if c.version > CONFIG_VERSION => Err(io::Error::other(format!(
+            "{} is config version {} but this licensure only supports up to version {}, please upgrade licensure",
+            source, c.version, CONFIG_VERSION
+        ))),
+        Ok(mut c) => {
+            if let Some(config_path) = config_path {
+                c.set_config_path(config_path);
+            }
+            c.set_partials()
+                .and_then(|_| c.set_plugins())
+                .and_then(|_| c.check_headers())
+                .map_err(|e| io::Error::other(format!("Invalid config in {}: {}", source, e)))?;
+            c.deprecations = find_deprecations(content);
+            Ok(c)
+        }
+        Err(e) => Err(io::Error::other(format!(
+            "Invalid YAML in {}: {}",
+            source, e
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::template::Template;
+    use crate::testing::{GitRepo, TempDir};
+
+    static CONFIG_WITH_PROFILES: &str = r##"
+excludes: [vendor/.*]
+licenses:
+  - files: any
+    ident: MIT
+    authors:
+      - name: The Tester
+    template: "[ident]"
+comments: []
+profiles:
+  docs:
+    licenses:
+      - files: any
+        ident: CC-BY-4.0
+        authors:
+          - name: The Tester
+        template: "[ident]"
+  backend:
+    excludes: [frontend/.*]"##;
+
+    #[test]
+    fn test_use_profile() {
+        let mut config: Config =
+            serde_yaml::from_str(CONFIG_WITH_PROFILES).expect("Static config to be parsable");
+        config.use_profile("docs").expect("docs profile exists");
+        assert_eq!(Some("CC-BY-4.0"), config.licenses.get_ident("a.md"));
+        assert!(config.excludes.is_match("vendor/a.md"));
+
+        let mut config: Config =
+            serde_yaml::from_str(CONFIG_WITH_PROFILES).expect("Static config to be parsable");
+        config
+            .use_profile("backend")
+            .expect("backend profile exists");
+        assert_eq!(Some("MIT"), config.licenses.get_ident("a.rs"));
+        assert!(config.excludes.is_match("frontend/a.js"));
+        assert!(!config.excludes.is_match("vendor/a.rs"));
+
+        let err = config.use_profile("web").unwrap_err();
+        assert_eq!("no profile named web, available profiles are: docs", err);
+    }
+
+    #[test]
+    fn test_invalid_regexes() {
+        let err = "licenses:\n  - files: '('\n    ident: MIT\n    template: '[ident]'"
+            .parse::<Config>()
+            .unwrap_err();
+        assert!(err.contains("invalid files regex"), "{}", err);
+        let err = "excludes: ['(']".parse::<Config>().unwrap_err();
+        assert!(err.contains("invalid exclude pattern"), "{}", err);
+
+        let mut config: Config = "excludes: ['vendor/.*']"
+            .parse()
+            .expect("Static config to be parsable");
+        assert!(config.add_exclude("(").is_err());
+        assert!(config.excludes.is_match("vendor/a.c"));
+        config.add_exclude("docs/.*").expect("Can add an exclude");
+        assert!(config.excludes.is_match("docs/a.md"));
+    }
+
+    #[test]
+    fn test_load_configs() {
+        let dir = TempDir::new("configs");
+        let base = dir.join("base.yml");
+        let overrides = dir.join("overrides.yml");
+        fs::write(&base, CONFIG_WITH_PROFILES).unwrap();
+        fs::write(
+            &overrides,
+            "columns: 100\nexcludes+: [build/.*]\nlicenses_registry: licenses.yml\nprofiles:\n  docs:\n    licenses:\n      - files: any\n        ident: CC-BY-4.0\n        authors: []\n        template_file: HEADER.txt\n",
+        )
+        .unwrap();
+        dir.write("HEADER.txt", "Read from [ident]\n");
+        let render = |config: &Config| {
+            let (_, license) = config.licenses.find("a.md").expect("a license matches");
+            license
+                .get_template("a.md", license.get_context("a.md"))
+                .expect("Can read the template_file")
+                .render()
+        };
+
+        let mut config =
+            load_configs(&[base.clone(), overrides.clone()]).expect("Can merge configs");
+        assert_eq!(Some(100), config.comment_defaults.columns);
+        assert!(config.excludes.is_match("vendor/a.rs"));
+        assert!(config.excludes.is_match("build/a.rs"));
+        assert_eq!(Some("MIT"), config.licenses.get_ident("a.rs"));
+        // Paths are relative to the config file giving them.
+        assert_eq!(
+            Some(dir.join("licenses.yml").display().to_string()),
+            config.licenses_registry
+        );
+        config.use_profile("docs").expect("docs profile exists");
+        assert_eq!("Read from CC-BY-4.0", render(&config));
+
+        dir.write(
+            ".licensure.yml",
+            "licenses:\n  - files: any\n    ident: MIT\n    authors: []\n    template_file: HEADER.txt\n",
+        );
+        let config = load_config_from(&dir).expect("Can load the config");
+        assert_eq!("Read from MIT", render(&config));
+
+        let err = load_configs(&[base, dir.join("missing.yml")]).unwrap_err();
+        assert!(err.to_string().starts_with("Unable to read"));
+    }
+
+    #[test]
+    fn test_shallow_clones() {
+        let origin = GitRepo::new("origin");
+        origin.write("a.txt", "one");
+        origin.commit_all_at("one", "2001-01-01T00:00:00");
+        origin.write("a.txt", "two");
+        origin.commit_all_at("two", "2010-01-01T00:00:00");
+        let dir = TempDir::new("shallow");
+        let url = format!("file://{}", origin.display());
+        origin.git(&["clone", "-q", "--depth", "1", &url, &dir.file("clone")]);
+        let file = dir.file("clone/a.txt");
+
+        let license = |policy: &str| -> LicenseConfig {
+            serde_yaml::from_str(&format!(
+                "files: any\nident: MIT\nstart_year: '1999'\nuse_dynamic_year_ranges: true\nshallow_clones: {}",
+                policy
+            ))
+            .expect("Static config to be parsable")
+        };
+
+        let warn = license("warn");
+        let (context, warning) = warn.get_context_with_warning(&file);
+        assert_eq!(Some("2010".to_string()), context.start_year);
+        assert!(warning
+            .expect("shallow clone is warned about")
+            .contains("is a shallow clone"));
+        // Each shallow clone is only warned about once.
+        assert_eq!(None, warn.get_context_with_warning(&file).1);
+
+        let (context, warning) = license("start_year").get_context_with_warning(&file);
+        assert_eq!(Some("1999".to_string()), context.start_year);
+        assert_eq!(Some("2010".to_string()), context.end_year);
+        assert_eq!(None, warning);
+
+        let (context, warning) = license("unshallow").get_context_with_warning(&file);
+        assert_eq!(Some("2001".to_string()), context.start_year);
+        assert_eq!(None, warning);
+    }
+
+    #[test]
+    fn test_auto_ident() {
+        let dir = TempDir::new("auto-ident");
+        fs::create_dir_all(dir.join("apache/src")).expect("Can create temp dir");
+        fs::create_dir_all(dir.join("other")).expect("Can create temp dir");
+        fs::create_dir_all(dir.join("unknown")).expect("Can create temp dir");
+        fs::create_dir_all(dir.join("repo/.git")).expect("Can create temp dir");
+        fs::write(
+            dir.join("LICENSE"),
+            "MIT License\n\nPermission is hereby granted, free of charge, to any person\n",
+        )
+        .expect("Can write LICENSE");
+        fs::write(
+            dir.join("apache/LICENSE.txt"),
+            "                                 Apache License\n                           Version 2.0, January 2004\n",
+        )
+        .expect("Can write LICENSE");
+        // A LICENSE file licensure doesn't recognize defers to the ones above.
+        fs::write(dir.join("unknown/LICENSE"), "All rights reserved.\n")
+            .expect("Can write LICENSE");
+        let file = |path: &str| dir.file(path);
+
+        let license: LicenseConfig = serde_yaml::from_str(
+            "files: any\nident: auto\nauthors:\n  - name: Tester\ntemplate: '[year] [name of author] [ident]'",
+        )
+        .expect("Static config to be parsable");
+        assert_eq!("Apache-2.0", license.ident_for(&file("apache/src/a.rs")));
+        assert_eq!("MIT", license.ident_for(&file("other/a.rs")));
+        assert_eq!("MIT", license.ident_for(&file("unknown/a.rs")));
+        assert_eq!("MIT", license.ident_for(&file("a.rs")));
+        // The search stops at the top of a repository.
+        assert_eq!("auto", license.ident_for(&file("repo/src/a.rs")));
+        let context = license.get_context(&file("apache/a.rs"));
+        assert_eq!("Apache-2.0", context.ident);
+        assert!(license.get_template(&file("a.rs"), context).is_ok());
+
+        // Files with no LICENSE file above them can't be given a header.
+        let outside = env::temp_dir().join("a.rs").to_string_lossy().to_string();
+        let context = license.get_context(&outside);
+        assert!(license
+            .get_template(&outside, context)
+            .err()
+            .expect("no LICENSE file is found")
+            .contains("ident is auto"));
+    }
+
+    #[test]
+    fn test_commit_history_authors() {
+        let dir = GitRepo::new("history");
+        dir.write("a.txt", "one");
+        dir.commit_all(
+            "one\n\nCo-authored-by: Jo Dev <jo@example.com>\nSigned-off-by: Mathew Robinson <CHASINGLOGIC@gmail.com>",
+        );
+        let file = dir.file("a.txt");
+
+        let license: LicenseConfig = serde_yaml::from_str(
+            "files: any
+ident: MIT
+trailer_authors: true
+authors:
+  - name: Mathew Robinson
+    email: chasinglogic@gmail.com",
+        )
+        .expect("Static config to be parsable");
+        let (context, warning) = license.get_context_with_warning(&file);
+        assert_eq!(
+            "Mathew Robinson <chasinglogic@gmail.com>, Jo Dev <jo@example.com>",
+            context.authors.to_string()
+        );
+        assert_eq!(None, warning);
+
+        let license: LicenseConfig = serde_yaml::from_str(
+            "files: any
+ident: MIT
+contributors: from_git_history",
+        )
+        .expect("Static config to be parsable");
+        let (context, warning) = license.get_context_with_warning(&file);
+        assert_eq!(
+            "Tester <tester@example.com>",
+            context.contributors.to_string()
+        );
+        assert_eq!(None, warning);
+    }
+
+    #[test]
+    fn test_template_file() {
+        let dir = GitRepo::new("template-file");
+        let path = dir.join("HEADER.txt");
+        // Files end with a newline, which isn't part of the template.
+        for template in ["Old [ident] header\n", "Current [ident] header\n"] {
+            dir.write("HEADER.txt", template);
+            dir.commit_all(template);
+        }
+
+        let license: LicenseConfig = serde_yaml::from_str(&format!(
+            "files: any\nident: MIT\nauthors: []\ntemplate_file: {}",
+            path.display()
+        ))
+        .expect("Static config to be parsable");
+        license
+            .check_template()
+            .expect("Can read the template_file");
+        let context = license.get_context("a.txt");
+        assert_eq!(
+            "Current MIT header",
+            license
+                .get_template("a.txt", context.clone())
+                .expect("Can render the template_file")
+                .render()
+        );
+        let previous: Vec<String> = license
+            .previous_templates(&context)
+            .iter()
+            .map(Template::render)
+            .collect();
+        assert_eq!(vec!["Old MIT header".to_string()], previous);
+
+        let license: LicenseConfig = serde_yaml::from_str(&format!(
+            "files: any\nident: MIT\nauthors: []\ntemplate: '[ident]'\ntemplate_file: {}",
+            path.display()
+        ))
+        .expect("Static config to be parsable");
+        assert!(license.check_template().is_err());
+    }
+
+    #[test]
+    fn test_partials() {
+        let mut config: Config = r##"
+partials:
+  contact: |
+    Questions go to legal@example.com.
+licenses:
+  - files: any
+    ident: MIT
+    authors: []
+    template: "[ident] license.\n[include:contact]"
+profiles:
+  docs:
+    licenses:
+      - files: any
+        ident: CC-BY-4.0
+        authors: []
+        template: "[include:contact] [[include:contact]]"
+"##
+        .parse()
+        .expect("Static config to be parsable");
+        let render = |config: &Config| {
+            let (_, license) = config.licenses.find("a.txt").expect("a license matches");
+            license
+                .get_template("a.txt", license.get_context("a.txt"))
+                .expect("Can render the template")
+                .render()
+        };
+        assert_eq!(
+            "MIT license. Questions go to legal@example.com.",
+            render(&config)
+        );
+        config.use_profile("docs").expect("docs profile exists");
+        assert_eq!(
+            "Questions go to legal@example.com. [include:contact]",
+            render(&config)
+        );
+
+        let err = "licenses:\n  - files: any\n    ident: MIT\n    template: '[include:contact]'"
+            .parse::<Config>()
+            .unwrap_err();
+        assert_eq!(
+            "the template for MIT has an [include:contact] of an undefined partial, no partials are configured",
+            err
+        );
+        let err = parse_config(
+            "partials:\n  a: '[yaer]'\nlicenses:\n  - files: any\n    ident: MIT\n    template: '[include:a]'",
+            "test.yml",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown placeholder [yaer]"));
+    }
+
+    #[test]
+    fn test_plugins_not_configured() {
+        let err = "licenses:\n  - files: any\n    class: vendored\n    ident: MIT\n    template: '[ident]'"
+            .parse::<Config>()
+            .unwrap_err();
+        assert_eq!(
+            "the license config for MIT matches files by class but no plugins are configured",
+            err
+        );
+        let err = "licenses:\n  - files: any\n    ident: MIT\n    template: '[plugin:acme:owner]'"
+            .parse::<Config>()
+            .unwrap_err();
+        assert_eq!(
+            "the template for MIT calls [plugin:acme:owner], which fails: undefined plugin acme, no plugins are configured",
+            err
+        );
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn test_plugins() {
+        // Classifies paths starting with v as vendored and gives the owner of
+        // any file as Example Corp.
+        let dir = TempDir::new("acme");
+        let plugin = dir.write(
+            "acme.wat",
+            r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "vendored")
+  (data (i32.const 16) "Example Corp")
+  (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "classify") (param $ptr i32) (param i32) (result i64)
+    (if (result i64) (i32.eq (i32.load8_u (local.get $ptr)) (i32.const 118))
+      (then (i64.const 8))
+      (else (i64.const 0))))
+  (func (export "owner") (param i32 i32) (result i64)
+    (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 12))))
+"#,
+        );
+        let config: Config = format!(
+            r##"
+plugins:
+  acme: {}
+licenses:
+  - files: any
+    class: vendored
+    ident: Apache-2.0
+    authors: []
+    template: "[ident]"
+  - files: any
+    ident: MIT
+    authors: []
+    template: "Copyright [plugin:acme:owner]"
+"##,
+            plugin
+        )
+        .parse()
+        .expect("Config with a plugin to be parsable");
+
+        assert_eq!(
+            Some("Apache-2.0"),
+            config.licenses.get_ident("./vendor/a.c")
+        );
+        let (_, license) = config.licenses.find("src/a.c").expect("a license matches");
+        assert_eq!(
+            "Copyright Example Corp",
+            license
+                .get_template("src/a.c", license.get_context("src/a.c"))
+                .expect("Can render the template")
+                .render()
+        );
+    }
+
+    #[test]
+    fn test_check_headers() {
+        let config = |template: &str| {
+            format!(
+                r##"
+licenses:
+  - files: any
+    ident: MIT
+    authors: []
+    template: "{}"
+comments:
+  - extension: py
+    commenter:
+      type: line
+      comment_char: "#"
+  - extension: html
+    commenter:
+      type: block
+      start_block_char: "<!--\n"
+      end_block_char: "-->\n"
+"##,
+                template
+            )
+        };
+
+        assert!(config("[ident] license, see LICENSE.md")
+            .parse::<Config>()
+            .is_ok());
+        assert_eq!(
+            "the header of MIT contains -->, which would end the comments of comment config 2 early, remove it from the template, set end_block_char_escape to write something like --\\> in its place, or use a line commenter",
+            config("[ident] license --> see LICENSE.md")
+                .parse::<Config>()
+                .unwrap_err()
+        );
+        let err = parse_config(&config("<!-- [ident] -->"), "test.yml").unwrap_err();
+        assert!(err.to_string().starts_with("Invalid config in test.yml:"));
+
+        // Only the comment configs for files the license matches count.
+        assert!(r##"
+licenses:
+  - files: '\.py$'
+    ident: MIT
+    authors: []
+    template: "See docs/*/README.md"
+comments:
+  - extension: py
+    commenter:
+      type: line
+      comment_char: "#"
+  - extension: css
+    commenter:
+      type: block
+      start_block_char: "/*\n"
+      end_block_char: "*/\n"
+"##
+        .parse::<Config>()
+        .is_ok());
+    }
+}
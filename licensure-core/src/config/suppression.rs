@@ -0,0 +1,177 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::processor::Rule;
+use crate::utils::normalize_path;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SuppressionSource {
+    Files(String),
+    Entry {
+        files: String,
+        #[serde(default)]
+        expires: Option<String>,
+    },
+}
+
+/// The files whose findings of a rule are known exceptions, which are
+/// reported as suppressed instead of failing.
+#[derive(Debug, Clone)]
+pub struct Suppression {
+    pub rule: Rule,
+    pub files: Regex,
+    /// The last day the suppression applies.
+    pub expires: Option<NaiveDate>,
+}
+
+impl Suppression {
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires.is_some_and(|expires| today > expires)
+    }
+}
+
+impl fmt::Display for Suppression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the suppression of {} for {}",
+            self.rule.id(),
+            self.files.as_str()
+        )
+    }
+}
+
+/// The suppressions section of the config, a list of path regexes for each
+/// rule ID, each either a regex or a map with the regex as files and when it
+/// expires.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(try_from = "BTreeMap<String, Vec<SuppressionSource>>")]
+pub struct Suppressions {
+    entries: Vec<Suppression>,
+}
+
+impl Suppressions {
+    /// The suppression of rule which applies to file on today, if any.
+    pub fn find(&self, rule: Rule, file: &str, today: NaiveDate) -> Option<&Suppression> {
+        let file = normalize_path(file);
+        self.entries.iter().find(|suppression| {
+            suppression.rule == rule
+                && !suppression.is_expired(today)
+                && suppression.files.is_match(&file)
+        })
+    }
+
+    /// The suppressions which no longer apply on today.
+    pub fn expired(&self, today: NaiveDate) -> impl Iterator<Item = &Suppression> {
+        self.entries
+            .iter()
+            .filter(move |suppression| suppression.is_expired(today))
+    }
+}
+
+impl TryFrom<BTreeMap<String, Vec<SuppressionSource>>> for Suppressions {
+    type Error = String;
+
+    fn try_from(rules: BTreeMap<String, Vec<SuppressionSource>>) -> Result<Self, Self::Error> {
+        let mut entries = Vec::new();
+        for (id, sources) in rules {
+            let rule: Rule = id.parse()?;
+            for source in sources {
+                let (files, expires) = match source {
+                    SuppressionSource::Files(files) => (files, None),
+                    SuppressionSource::Entry { files, expires } => (files, expires),
+                };
+                let expires = expires
+                    .map(|expires| {
+                        NaiveDate::parse_from_str(&expires, "%Y-%m-%d").map_err(|_| {
+                            format!(
+                                "the suppression of {} for {} expires on {} which isn't a YYYY-MM-DD date",
+                                id, files, expires
+                            )
+                        })
+                    })
+                    .transpose()?;
+                let files = Regex::new(&files).map_err(|e| {
+                    format!("the suppression of {} has an invalid regex: {}", id, e)
+                })?;
+                entries.push(Suppression {
+                    rule,
+                    files,
+                    expires,
+                });
+            }
+        }
+
+        Ok(Suppressions { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suppressions() {
+        let suppressions: Suppressions = serde_yaml::from_str(
+            r#"
+LIC002:
+  - vendor/.*
+  - files: third_party/.*
+    expires: 2024-06-30
+LIC004: [legacy\.c]
+"#,
+        )
+        .expect("suppressions parse");
+        let day = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+
+        let today = day("2024-06-30");
+        assert!(suppressions
+            .find(Rule::OutdatedYear, "./vendor/a.c", today)
+            .is_some());
+        assert!(suppressions
+            .find(Rule::MissingHeader, "vendor/a.c", today)
+            .is_none());
+        assert!(suppressions
+            .find(Rule::OutdatedYear, "third_party/b.c", today)
+            .is_some());
+        assert!(suppressions.expired(today).next().is_none());
+
+        let today = day("2024-07-01");
+        assert!(suppressions
+            .find(Rule::OutdatedYear, "third_party/b.c", today)
+            .is_none());
+        assert_eq!(
+            vec!["the suppression of LIC002 for third_party/.*".to_string()],
+            suppressions
+                .expired(today)
+                .map(|suppression| suppression.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert!(suppressions
+            .find(Rule::ForeignCopyright, "legacy.c", today)
+            .is_some());
+
+        assert!(serde_yaml::from_str::<Suppressions>("LIC009: [a]").is_err());
+        assert!(
+            serde_yaml::from_str::<Suppressions>("LIC001: [{files: a, expires: soon}]").is_err()
+        );
+    }
+}
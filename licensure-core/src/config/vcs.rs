@@ -0,0 +1,178 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+
+/// The version control system use_dynamic_year_ranges gets the years of a
+/// file from.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Vcs {
+    #[default]
+    Git,
+    Hg,
+    Svn,
+}
+
+impl fmt::Display for Vcs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Vcs::Git => write!(f, "git"),
+            Vcs::Hg => write!(f, "hg"),
+            Vcs::Svn => write!(f, "svn"),
+        }
+    }
+}
+
+impl Vcs {
+    /// The year of every commit to filename, newest first. Renames are
+    /// followed so commits from before a file was moved count.
+    pub fn commit_years(&self, filename: &str) -> Result<Vec<String>, String> {
+        // Run from the directory of the file so the history comes from the
+        // repository the file is in, which may be nested in another.
+        let (dir, name) = dir_of(Path::new(filename));
+        let mut cmd = Command::new(self.to_string());
+        match self {
+            Vcs::Git => cmd.args(["log", "--follow", "--format=%ad", "--date=format:%Y"]),
+            Vcs::Hg => cmd.args(["log", "--follow", "--template", "{date(date, '%Y')}\\n"]),
+            Vcs::Svn => cmd.args(["log", "--quiet"]),
+        };
+        let proc = cmd
+            .current_dir(dir)
+            .arg(name)
+            .output()
+            .map_err(|e| format!("failed to run {} log: {}", self, e))?;
+        if !proc.status.success() {
+            return Err(String::from_utf8_lossy(&proc.stderr).trim().to_string());
+        }
+
+        let stdout = String::from_utf8(proc.stdout)
+            .map_err(|_| format!("{} log output was not UTF-8", self))?;
+        Ok(match self {
+            Vcs::Git | Vcs::Hg => stdout
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Vcs::Svn => svn_years(&stdout),
+        })
+    }
+}
+
+/// The "Name <email>" of the author of every commit to filename in git,
+/// newest first.
+pub fn commit_authors(filename: &str) -> Result<Vec<String>, String> {
+    git_log_lines(filename, "--format=%aN <%aE>")
+}
+
+/// The values of the keys trailers of every commit to filename in git, such as
+/// "Name <email>" for a Co-authored-by trailer, newest first.
+pub fn commit_trailers(filename: &str, keys: &[&str]) -> Result<Vec<String>, String> {
+    let keys: Vec<String> = keys.iter().map(|key| format!("key={}", key)).collect();
+    git_log_lines(
+        filename,
+        &format!("--format=%(trailers:{},valueonly)", keys.join(",")),
+    )
+}
+
+/// The distinct contents filename was committed with in git, newest first,
+/// from no more than limit of the commits to it. Versions which can't be
+/// read, such as those from before it was renamed, are left out.
+pub fn file_versions(filename: &str, limit: usize) -> Result<Vec<String>, String> {
+    let (dir, name) = dir_of(Path::new(filename));
+    let mut versions: Vec<String> = Vec::new();
+    for commit in git_log_lines(filename, "--format=%H")?.iter().take(limit) {
+        let mut spec = OsString::from(format!("{}:./", commit));
+        spec.push(name);
+        let proc = Command::new("git")
+            .current_dir(dir)
+            .arg("show")
+            .arg(spec)
+            .output()
+            .map_err(|e| format!("failed to run git show: {}", e))?;
+        if !proc.status.success() {
+            continue;
+        }
+
+        let version = String::from_utf8_lossy(&proc.stdout).into_owned();
+        if !versions.contains(&version) {
+            versions.push(version);
+        }
+    }
+
+    Ok(versions)
+}
+
+// The non-blank lines git log --follow prints for filename with format.
+fn git_log_lines(filename: &str, format: &str) -> Result<Vec<String>, String> {
+    let (dir, name) = dir_of(Path::new(filename));
+    let proc = Command::new("git")
+        .current_dir(dir)
+        .args(["log", "--follow", format])
+        .arg(name)
+        .output()
+        .map_err(|e| format!("failed to run git log: {}", e))?;
+    if !proc.status.success() {
+        return Err(String::from_utf8_lossy(&proc.stderr).trim().to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&proc.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+// The directory to run a version control command in for path and the name
+// of path in it.
+pub(super) fn dir_of(path: &Path) -> (&Path, &OsStr) {
+    match (path.parent(), path.file_name()) {
+        (Some(dir), Some(name)) if !dir.as_os_str().is_empty() => (dir, name),
+        _ => (Path::new("."), path.as_os_str()),
+    }
+}
+
+// The years of svn log --quiet output, whose revision lines look like
+// "r5 | user | 2024-05-29 04:54:58 +0100 (Wed, 29 May 2024)".
+fn svn_years(log: &str) -> Vec<String> {
+    log.lines()
+        .filter(|line| line.starts_with('r'))
+        .filter_map(|line| line.split(" | ").nth(2))
+        .filter_map(|date| date.get(..4))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svn_years() {
+        let log = "------------------------------------------------------------------------
+r12 | tester | 2024-05-29 04:54:58 +0100 (Wed, 29 May 2024)
+------------------------------------------------------------------------
+r3 | tester | 2019-01-02 10:00:00 +0000 (Wed, 02 Jan 2019)
+------------------------------------------------------------------------
+";
+        assert_eq!(vec!["2024", "2019"], svn_years(log));
+        assert!(svn_years("").is_empty());
+    }
+}
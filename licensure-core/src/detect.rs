@@ -0,0 +1,353 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::spdx;
+
+// Only the start of a file is searched for license information, anything
+// further in is much more likely to be code or documentation which happens to
+// mention a license.
+const HEAD_LINES: usize = 100;
+
+// Markers tools put at the top of files they generate, which would lose a
+// header the next time they're generated.
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT", "<auto-generated"];
+const GENERATED_LINES: usize = 10;
+
+// Pragmas like "licensure: ignore" in a comment near the top of a file, which
+// change how licensure treats just that file.
+const PRAGMA_RE: &str = r"\blicensure:[ \t]*([A-Za-z_-]+(?:=\S+)?)";
+const PRAGMA_LINES: usize = 10;
+const IGNORE_PRAGMA: &str = "ignore";
+const LICENSE_PRAGMA: &str = "license=";
+
+// License expressions are made of identifiers, parentheses, and the AND, OR,
+// and WITH operators so stop at anything else such as a closing comment.
+const SPDX_TAG_RE: &str =
+    r"SPDX-License-Identifier:[ \t]*([A-Za-z0-9(](?:[A-Za-z0-9.+:() \t-]*[A-Za-z0-9+)])?)";
+
+// Phrases which identify the text of common licenses, checked in order so
+// more specific phrases must come before the general ones they contain.
+const LICENSE_PHRASES: &[(&str, &str)] = &[
+    (
+        r"GNU Affero General Public License[^.]*version 3",
+        "AGPL-3.0",
+    ),
+    (
+        r"GNU Lesser General Public License[^.]*version 3",
+        "LGPL-3.0",
+    ),
+    (
+        r"GNU Lesser General Public License[^.]*version 2\.1",
+        "LGPL-2.1",
+    ),
+    (r"GNU General Public License[^.]*version 3", "GPL-3.0"),
+    (r"GNU General Public License[^.]*version 2", "GPL-2.0"),
+    (r"Apache License,? Version 2\.0", "Apache-2.0"),
+    (r"Mozilla Public License,? v(ersion|\.) ?2\.0", "MPL-2.0"),
+    (
+        r"Permission is hereby granted, free of charge, to any person",
+        "MIT",
+    ),
+    (
+        r"Permission to use, copy, modify, and(/or)? distribute this software for any purpose",
+        "ISC",
+    ),
+    (
+        r"Redistribution and use in source and binary forms(.|\n)*Neither the name",
+        "BSD-3-Clause",
+    ),
+    (
+        r"Redistribution and use in source and binary forms",
+        "BSD-2-Clause",
+    ),
+    (
+        r"This is free and unencumbered software released into the public domain",
+        "Unlicense",
+    ),
+];
+
+const COPYRIGHT_RE: &str =
+    r"^Copyright\s+(?:\([cC]\)\s*|©\s*)?[0-9]{4}(?:\s*[-,]\s*[0-9]{4})*,?\s+(.+)$";
+
+/// Returns true if content is empty or only whitespace.
+pub fn is_empty(content: &str) -> bool {
+    content.trim().is_empty()
+}
+
+/// Returns true if content looks like a binary file rather than text.
+pub fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(8000).any(|b| *b == 0)
+}
+
+/// Returns true if one of the first lines of content marks it as generated,
+/// such as with @generated or DO NOT EDIT.
+pub fn is_generated(content: &str) -> bool {
+    content
+        .lines()
+        .take(GENERATED_LINES)
+        .any(|line| GENERATED_MARKERS.iter().any(|marker| line.contains(marker)))
+}
+
+// The licensure pragmas in the first lines of content, such as ignore for a
+// "# licensure: ignore" comment.
+fn pragmas(content: &str, lines: usize) -> Vec<&str> {
+    static PRAGMA: OnceLock<Regex> = OnceLock::new();
+    let pragma =
+        PRAGMA.get_or_init(|| Regex::new(PRAGMA_RE).expect("pragma regex didn't compile!"));
+    content
+        .lines()
+        .take(lines)
+        .flat_map(|line| pragma.captures_iter(line))
+        .filter_map(|captures| captures.get(1))
+        .map(|m| m.as_str())
+        .collect()
+}
+
+/// Returns true if one of the first lines of content opts it out of getting
+/// a header with a licensure: ignore pragma.
+pub fn is_opted_out(content: &str) -> bool {
+    pragmas(content, PRAGMA_LINES).contains(&IGNORE_PRAGMA)
+}
+
+/// Returns the license identifier a licensure: license=IDENT pragma selects
+/// for content. It's looked for in the whole head of the file so it's still
+/// found once a header has been added above it.
+pub fn license_pragma(content: &str) -> Option<&str> {
+    pragmas(content, HEAD_LINES)
+        .into_iter()
+        .find_map(|pragma| pragma.strip_prefix(LICENSE_PRAGMA))
+}
+
+fn head(content: &str) -> String {
+    content
+        .lines()
+        .take(HEAD_LINES)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Return which of idents the header near the top of content is by comparing
+/// it with their SPDX texts, for headers which have no SPDX tag and no text
+/// license_texts recognizes, such as a rewrapped or reworded one.
+pub fn similar_license<'a>(content: &str, idents: &[&'a str]) -> Option<&'a str> {
+    spdx::similar_license(&head(content), idents)
+}
+
+/// Return the license expressions of every SPDX-License-Identifier tag near
+/// the top of content.
+pub fn spdx_tags(content: &str) -> Vec<String> {
+    static SPDX_TAG: OnceLock<Regex> = OnceLock::new();
+    let re =
+        SPDX_TAG.get_or_init(|| Regex::new(SPDX_TAG_RE).expect("SPDX tag regex didn't compile!"));
+    re.captures_iter(&head(content))
+        .map(|caps| caps[1].trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Return the identifiers of licenses whose text appears near the top of
+/// content. Line comment characters and wrapping are ignored by collapsing all
+/// whitespace before matching.
+pub fn license_texts(content: &str) -> Vec<&'static str> {
+    let mut found = Vec::new();
+    let text = head(content)
+        .lines()
+        .map(|line| line.trim_start_matches(|c: char| !c.is_alphanumeric()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    static PHRASES: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let phrases = PHRASES.get_or_init(|| {
+        LICENSE_PHRASES
+            .iter()
+            .map(|(phrase, ident)| {
+                let re =
+                    Regex::new(&format!("(?i){}", phrase)).expect("license phrase didn't compile!");
+                (re, *ident)
+            })
+            .collect()
+    });
+
+    for (re, ident) in phrases {
+        if re.is_match(&text) {
+            found.push(*ident);
+            // Stop at the first match so that a BSD-3-Clause license isn't
+            // also reported as BSD-2-Clause.
+            break;
+        }
+    }
+
+    found
+}
+
+/// Return the holder of every copyright notice near the top of content, for
+/// "Copyright (C) 2019-2024 Your Name. All rights reserved." this is "Your
+/// Name".
+pub fn copyright_holders(content: &str) -> Vec<String> {
+    static COPYRIGHT: OnceLock<Regex> = OnceLock::new();
+    let re = COPYRIGHT
+        .get_or_init(|| Regex::new(COPYRIGHT_RE).expect("copyright regex didn't compile!"));
+    content
+        .lines()
+        .take(HEAD_LINES)
+        .filter_map(|line| {
+            let line = line.trim_start_matches(|c: char| !c.is_alphanumeric());
+            re.captures(line.trim_end())
+        })
+        .map(|caps| {
+            let holder = caps[1].trim();
+            let holder = holder
+                .strip_suffix("All rights reserved.")
+                .unwrap_or(holder)
+                .trim_end()
+                .trim_end_matches([',', '.']);
+            holder.to_string()
+        })
+        .filter(|holder| !holder.is_empty())
+        .collect()
+}
+
+/// Returns every distinct license found near the top of content, tags first.
+pub fn detect_licenses(content: &str) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    for ident in spdx_tags(content)
+        .into_iter()
+        .chain(license_texts(content).into_iter().map(str::to_string))
+    {
+        if !found.iter().any(|f| same_license(f, &ident)) {
+            found.push(ident);
+        }
+    }
+
+    found
+}
+
+// Strip the suffixes SPDX uses to distinguish the "only" and "or later"
+// variants of a license which we treat as the same license when comparing.
+fn base_ident(ident: &str) -> &str {
+    ident
+        .trim_end_matches('+')
+        .trim_end_matches("-only")
+        .trim_end_matches("-or-later")
+}
+
+/// Returns true if the two identifiers name the same license, ignoring case
+/// and the "only" or "or later" variants.
+pub fn same_license(a: &str, b: &str) -> bool {
+    base_ident(a).eq_ignore_ascii_case(base_ident(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spdx_tags() {
+        assert_eq!(
+            vec!["MIT OR Apache-2.0"],
+            spdx_tags("// SPDX-License-Identifier: MIT OR Apache-2.0\nfn main() {}")
+        );
+        assert_eq!(
+            vec!["GPL-2.0-only"],
+            spdx_tags("/* SPDX-License-Identifier: GPL-2.0-only */\n")
+        );
+        assert_eq!(
+            vec!["MIT"],
+            spdx_tags("<!-- SPDX-License-Identifier: MIT -->")
+        );
+        assert!(spdx_tags("fn main() {}").is_empty());
+        assert!(spdx_tags("const TAG: &str = \"SPDX-License-Identifier:\";").is_empty());
+    }
+
+    #[test]
+    fn test_pragmas() {
+        assert!(is_opted_out("# licensure: ignore\nprint('hi')\n"));
+        assert!(is_opted_out("/* licensure:ignore */\nint x;\n"));
+        assert!(!is_opted_out("print('licensure: ignored')\n"));
+        assert!(!is_opted_out(&format!(
+            "{}# licensure: ignore\n",
+            "\n".repeat(20)
+        )));
+        assert_eq!(
+            Some("Apache-2.0"),
+            license_pragma(&format!(
+                "{}<!-- licensure: license=Apache-2.0 -->\n",
+                "\n".repeat(20)
+            ))
+        );
+        assert_eq!(None, license_pragma("# licensure: ignore\n"));
+    }
+
+    #[test]
+    fn test_is_generated() {
+        assert!(is_generated(
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage pb\n"
+        ));
+        assert!(is_generated("# @generated by pip-compile\nrequests==2.0\n"));
+        assert!(!is_generated("fn main() {}\n"));
+        assert!(!is_generated(&format!(
+            "{}// DO NOT EDIT\n",
+            "\n".repeat(20)
+        )));
+    }
+
+    #[test]
+    fn test_license_texts() {
+        let gpl = "// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.";
+        assert_eq!(vec!["GPL-3.0"], license_texts(gpl));
+
+        let bsd = "# Redistribution and use in source and binary forms, with or without
+# modification, are permitted provided that the following conditions are met:
+# 3. Neither the name of the copyright holder nor the names of its";
+        assert_eq!(vec!["BSD-3-Clause"], license_texts(bsd));
+
+        assert!(license_texts("print('hello world')").is_empty());
+    }
+
+    #[test]
+    fn test_detect_licenses_dedups() {
+        let content = "# SPDX-License-Identifier: Apache-2.0
+# Licensed under the Apache License, Version 2.0 (the \"License\");";
+        assert_eq!(vec!["Apache-2.0"], detect_licenses(content));
+    }
+
+    #[test]
+    fn test_copyright_holders() {
+        let content = "// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+# Copyright 2018, 2020-2023 Example Corp. All rights reserved.
+ * Copyright © 2001 Someone Else.
+// The copyright holders may not be used to endorse products.";
+        assert_eq!(
+            vec![
+                "Mathew Robinson <chasinglogic@gmail.com>",
+                "Example Corp",
+                "Someone Else",
+            ],
+            copyright_holders(content)
+        );
+    }
+
+    #[test]
+    fn test_same_license() {
+        assert!(same_license("GPL-3.0", "GPL-3.0-or-later"));
+        assert!(same_license("gpl-3.0-only", "GPL-3.0"));
+        assert!(!same_license("GPL-3.0", "GPL-2.0"));
+    }
+}
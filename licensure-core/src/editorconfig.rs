@@ -0,0 +1,498 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::utils::unchanged_lines;
+
+const FILENAME: &str = ".editorconfig";
+const BOM: char = '\u{feff}';
+
+/// The line ending an .editorconfig asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndOfLine {
+    Lf,
+    Crlf,
+    Cr,
+}
+
+impl EndOfLine {
+    fn as_str(&self) -> &'static str {
+        match self {
+            EndOfLine::Lf => "\n",
+            EndOfLine::Crlf => "\r\n",
+            EndOfLine::Cr => "\r",
+        }
+    }
+}
+
+/// The settings of the .editorconfig files which apply to a file that affect
+/// how its header is written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Settings {
+    pub end_of_line: Option<EndOfLine>,
+    // Whether charset is utf-8-bom, None when no charset is set.
+    pub bom: Option<bool>,
+}
+
+/// What Settings::decode took out of content, to put back when an update of
+/// it is encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Decoded {
+    pub bom: bool,
+    // The lines of content and the line endings they had.
+    lines: Vec<(String, &'static str)>,
+}
+
+impl Settings {
+    /// Decode content for processing: strip its byte order mark and turn the
+    /// line endings into newlines when end_of_line is set.
+    pub fn decode(&self, content: &mut String) -> Decoded {
+        let bom = content.starts_with(BOM);
+        if bom {
+            content.remove(0);
+        }
+        let lines = match self.end_of_line {
+            Some(eol) => split_lines(content, eol),
+            None => Vec::new(),
+        };
+        if self.end_of_line.is_some() {
+            *content = lines
+                .iter()
+                .map(|(text, ending)| newline_ended(text, ending))
+                .collect();
+        }
+
+        Decoded { bom, lines }
+    }
+
+    /// Encode content, an update of what decode gave, for writing. Only the
+    /// lines which changed are given the line endings end_of_line asks for,
+    /// the rest keep the ones they had. A byte order mark is added if the
+    /// content had one or charset is utf-8-bom.
+    pub fn encode(&self, content: &str, decoded: &Decoded) -> String {
+        let mut encoded = String::new();
+        if decoded.bom || self.bom == Some(true) {
+            encoded.push(BOM);
+        }
+        let Some(eol) = self.end_of_line else {
+            encoded.push_str(content);
+            return encoded;
+        };
+
+        let old_lines: Vec<String> = decoded
+            .lines
+            .iter()
+            .map(|(text, ending)| newline_ended(text, ending))
+            .collect();
+        let old_lines: Vec<&str> = old_lines.iter().map(String::as_str).collect();
+        let new_lines: Vec<&str> = content.split_inclusive('\n').collect();
+        let (prefix, suffix) = unchanged_lines(&old_lines, &new_lines);
+
+        for (text, ending) in &decoded.lines[..prefix] {
+            encoded.push_str(text);
+            encoded.push_str(ending);
+        }
+        for line in &new_lines[prefix..new_lines.len() - suffix] {
+            match line.strip_suffix('\n') {
+                Some(text) => {
+                    encoded.push_str(text);
+                    encoded.push_str(eol.as_str());
+                }
+                None => encoded.push_str(line),
+            }
+        }
+        for (text, ending) in &decoded.lines[decoded.lines.len() - suffix..] {
+            encoded.push_str(text);
+            encoded.push_str(ending);
+        }
+
+        encoded
+    }
+}
+
+// The lines of content and their line endings, \n or \r\n, or a lone \r too
+// when eol is cr. A last line without one has an empty line ending.
+fn split_lines(content: &str, eol: EndOfLine) -> Vec<(String, &'static str)> {
+    let bytes = content.as_bytes();
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let ending = match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => Some("\r\n"),
+            b'\r' if eol == EndOfLine::Cr => Some("\r"),
+            b'\n' => Some("\n"),
+            _ => None,
+        };
+        match ending {
+            Some(ending) => {
+                lines.push((content[start..i].to_string(), ending));
+                i += ending.len();
+                start = i;
+            }
+            None => i += 1,
+        }
+    }
+    if start < content.len() {
+        lines.push((content[start..].to_string(), ""));
+    }
+
+    lines
+}
+
+// text with a newline for its line ending, if it has one.
+fn newline_ended(text: &str, ending: &str) -> String {
+    if ending.is_empty() {
+        text.to_string()
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+// The properties of one section of an .editorconfig.
+struct Section {
+    // None when the glob couldn't be understood.
+    pattern: Option<Regex>,
+    end_of_line: Option<EndOfLine>,
+    bom: Option<bool>,
+}
+
+/// A parsed .editorconfig file.
+pub struct EditorConfig {
+    root: bool,
+    sections: Vec<Section>,
+}
+
+impl EditorConfig {
+    /// Parse the content of an .editorconfig file. Sections with globs which
+    /// can't be understood are ignored.
+    pub fn parse(content: &str) -> EditorConfig {
+        let mut config = EditorConfig {
+            root: false,
+            sections: Vec::new(),
+        };
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let pattern = glob_pattern(glob);
+                if pattern.is_none() {
+                    warn!(
+                        "ignoring .editorconfig section with invalid glob [{}]",
+                        glob
+                    );
+                }
+                // A section which matches nothing still takes the properties
+                // which follow it.
+                config.sections.push(Section {
+                    pattern,
+                    end_of_line: None,
+                    bom: None,
+                });
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            match config.sections.last_mut() {
+                // Only root may be set before the first section.
+                None => {
+                    if key == "root" {
+                        config.root = value == "true";
+                    }
+                }
+                Some(section) => match key.as_str() {
+                    "end_of_line" => {
+                        section.end_of_line = match value.as_str() {
+                            "lf" => Some(EndOfLine::Lf),
+                            "crlf" => Some(EndOfLine::Crlf),
+                            "cr" => Some(EndOfLine::Cr),
+                            _ => None,
+                        }
+                    }
+                    "charset" => section.bom = Some(value == "utf-8-bom"),
+                    _ => (),
+                },
+            }
+        }
+
+        config
+    }
+
+    // Apply the sections matching path, relative to the directory of this
+    // file, to settings with later sections winning.
+    fn apply(&self, path: &str, settings: &mut Settings) {
+        for section in self
+            .sections
+            .iter()
+            .filter(|s| s.pattern.as_ref().is_some_and(|p| p.is_match(path)))
+        {
+            if section.end_of_line.is_some() {
+                settings.end_of_line = section.end_of_line;
+            }
+            if section.bom.is_some() {
+                settings.bom = section.bom;
+            }
+        }
+    }
+}
+
+// Translate an EditorConfig glob into a regex matching paths relative to the
+// directory of the .editorconfig. Globs without a / match file names in any
+// directory.
+pub(crate) fn glob_pattern(glob: &str) -> Option<Regex> {
+    let (anchored, glob) = match glob.strip_prefix('/') {
+        Some(glob) => (true, glob),
+        None => (glob.contains('/'), glob),
+    };
+
+    let prefix = if anchored { "^" } else { "^(?:.*/)?" };
+    Regex::new(&format!("{}{}$", prefix, glob_regex(glob)?)).ok()
+}
+
+/// Translate glob into a regex matching the same paths, leaving anchoring it
+/// to the caller. * and ? don't match a /, ** does and **/ also matches no
+/// directory at all. Numeric ranges such as {1..3} match any number. Returns
+/// None if a [ or { isn't closed.
+pub fn glob_regex(glob: &str) -> Option<String> {
+    let mut pattern = String::new();
+    let chars: Vec<char> = glob.chars().collect();
+    let mut braces = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    pattern.push_str("(?:.*/)?");
+                    i += 2;
+                } else {
+                    pattern.push_str(".*");
+                    i += 1;
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            '[' => {
+                let end = chars[i..].iter().position(|&c| c == ']')? + i;
+                let class: String = chars[i + 1..end].iter().collect();
+                let class = match class.strip_prefix('!') {
+                    Some(negated) => format!("^{}", negated),
+                    None => class,
+                };
+                pattern.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+                i = end;
+            }
+            '{' => {
+                let end = chars[i..].iter().position(|&c| c == '}')? + i;
+                let inner: String = chars[i + 1..end].iter().collect();
+                let is_range = inner
+                    .split_once("..")
+                    .is_some_and(|(a, b)| a.parse::<i64>().is_ok() && b.parse::<i64>().is_ok());
+                if is_range {
+                    pattern.push_str("[+-]?[0-9]+");
+                    i = end;
+                } else if inner.contains(',') {
+                    pattern.push_str("(?:");
+                    braces += 1;
+                } else {
+                    pattern.push_str(&regex::escape(&format!("{{{}}}", inner)));
+                    i = end;
+                }
+            }
+            ',' if braces > 0 => pattern.push('|'),
+            '}' if braces > 0 => {
+                pattern.push(')');
+                braces -= 1;
+            }
+            '\\' if i + 1 < chars.len() => {
+                pattern.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 1;
+            }
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    Some(pattern)
+}
+
+/// The settings for file from the .editorconfig files in its directory and
+/// those above it, up to the first one with root = true. read returns the
+/// parsed .editorconfig at a path, if there is one. Relative paths are only
+/// searched up to the current directory.
+pub fn settings_for(
+    file: &str,
+    read: impl Fn(&Path) -> Option<std::rc::Rc<EditorConfig>>,
+) -> Settings {
+    let path = Path::new(file);
+    let mut configs = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        if let Some(config) = read(&dir.join(FILENAME)) {
+            let root = config.root;
+            configs.push((dir, config));
+            if root {
+                break;
+            }
+        }
+    }
+
+    // Closer files win so they're applied last.
+    let mut settings = Settings::default();
+    for (dir, config) in configs.iter().rev() {
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        config.apply(
+            &relative.to_string_lossy().replace('\\', "/"),
+            &mut settings,
+        );
+    }
+
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn test_glob_pattern() {
+        let matches = |glob: &str, path: &str| glob_pattern(glob).unwrap().is_match(path);
+        assert!(matches("*", "a.rs"));
+        assert!(matches("*", "src/a.rs"));
+        assert!(matches("*.{bat,cmd}", "scripts/run.cmd"));
+        assert!(!matches("*.{bat,cmd}", "scripts/run.sh"));
+        assert!(matches("src/*.rs", "src/a.rs"));
+        assert!(!matches("src/*.rs", "lib/src/a.rs"));
+        assert!(!matches("src/*.rs", "src/deep/a.rs"));
+        assert!(matches("src/**.rs", "src/deep/a.rs"));
+        assert!(matches("src/**/*.rs", "src/a.rs"));
+        assert!(matches("src/**/*.rs", "src/deep/a.rs"));
+        assert!(matches("/Makefile", "Makefile"));
+        assert!(!matches("/Makefile", "sub/Makefile"));
+        assert!(matches("[!a]?.txt", "bc.txt"));
+        assert!(!matches("[!a]?.txt", "ac.txt"));
+        assert!(matches("file{1..3}.md", "file2.md"));
+    }
+
+    #[test]
+    fn test_settings_for() {
+        let mut files = HashMap::new();
+        files.insert(
+            PathBuf::from("/repo/.editorconfig"),
+            Rc::new(EditorConfig::parse(
+                "root = true\n\n[*]\nend_of_line = lf\n\n[*.{bat,cmd}]\nend_of_line = CRLF\ncharset = utf-8-bom\n",
+            )),
+        );
+        files.insert(
+            PathBuf::from("/repo/win/.editorconfig"),
+            Rc::new(EditorConfig::parse("[*.ps1]\nend_of_line = crlf\n")),
+        );
+        files.insert(
+            PathBuf::from("/.editorconfig"),
+            Rc::new(EditorConfig::parse("[*]\ncharset = utf-8-bom\n")),
+        );
+        let read = |path: &Path| files.get(path).cloned();
+
+        assert_eq!(
+            Settings {
+                end_of_line: Some(EndOfLine::Lf),
+                bom: None
+            },
+            settings_for("/repo/a.sh", read)
+        );
+        assert_eq!(
+            Settings {
+                end_of_line: Some(EndOfLine::Crlf),
+                bom: Some(true)
+            },
+            settings_for("/repo/win/run.cmd", read)
+        );
+        assert_eq!(
+            Some(EndOfLine::Crlf),
+            settings_for("/repo/win/run.ps1", read).end_of_line
+        );
+    }
+
+    #[test]
+    fn test_decode_and_encode() {
+        let settings = Settings {
+            end_of_line: Some(EndOfLine::Crlf),
+            bom: Some(true),
+        };
+        let mut content = "\u{feff}a\r\nb\r\n".to_string();
+        let decoded = settings.decode(&mut content);
+        assert!(decoded.bom);
+        assert_eq!("a\nb\n", content);
+        assert_eq!(
+            "\u{feff}h\r\na\r\nb\r\n",
+            settings.encode("h\na\nb\n", &decoded)
+        );
+        assert_eq!("\u{feff}h\r\n", settings.encode("h\n", &Decoded::default()));
+
+        // A byte order mark is kept even when charset doesn't ask for it.
+        let settings = Settings::default();
+        let mut content = "\u{feff}a\n".to_string();
+        let decoded = settings.decode(&mut content);
+        assert!(decoded.bom);
+        assert_eq!(
+            "\u{feff}h\na\n",
+            settings.encode(&format!("h\n{}", content), &decoded)
+        );
+    }
+
+    #[test]
+    fn test_encode_only_changed_lines() {
+        // The lines of a crlf file aren't split in two under cr.
+        let settings = Settings {
+            end_of_line: Some(EndOfLine::Cr),
+            bom: None,
+        };
+        let mut content = "#!/bin/sh\r\necho a\r\necho b".to_string();
+        let decoded = settings.decode(&mut content);
+        assert_eq!("#!/bin/sh\necho a\necho b", content);
+        assert_eq!(
+            "#!/bin/sh\r\n# h\r\recho a\r\necho b",
+            settings.encode("#!/bin/sh\n# h\n\necho a\necho b", &decoded)
+        );
+
+        // An lf file under crlf only has the header changed.
+        let settings = Settings {
+            end_of_line: Some(EndOfLine::Crlf),
+            bom: None,
+        };
+        let mut content = "a\nb\n".to_string();
+        let decoded = settings.decode(&mut content);
+        assert_eq!("a\nb\n", content);
+        assert_eq!(
+            "// h\r\n\r\na\nb\n",
+            settings.encode("// h\n\na\nb\n", &decoded)
+        );
+        // As does replacing its old one.
+        let mut content = "// old\na\nb\n".to_string();
+        let decoded = settings.decode(&mut content);
+        assert_eq!("// h\r\na\nb\n", settings.encode("// h\na\nb\n", &decoded));
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use crate::editorconfig::EndOfLine;
+
+// A git check-attr process answering for one path at a time.
+struct CheckAttr {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl CheckAttr {
+    fn spawn(dir: &Path) -> io::Result<CheckAttr> {
+        let mut child = Command::new("git")
+            .current_dir(dir)
+            .args(["check-attr", "--stdin", "-z", "text", "eol"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin was piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout was piped"));
+        Ok(CheckAttr {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    // The values of text and eol for path, each answered as the three fields
+    // path, attribute, and value.
+    fn check(&mut self, path: &str) -> io::Result<(String, String)> {
+        self.stdin.write_all(path.as_bytes())?;
+        self.stdin.write_all(b"\0")?;
+        self.stdin.flush()?;
+
+        let mut fields = Vec::with_capacity(6);
+        for _ in 0..6 {
+            let mut field = Vec::new();
+            if self.stdout.read_until(b'\0', &mut field)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "git check-attr exited",
+                ));
+            }
+            field.pop();
+            fields.push(String::from_utf8_lossy(&field).to_string());
+        }
+
+        Ok((fields[2].clone(), fields[5].clone()))
+    }
+}
+
+impl Drop for CheckAttr {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// The line endings git gives files in the working tree of a repository, from
+/// their text and eol attributes and the core.autocrlf and core.eol settings.
+pub struct GitAttributes {
+    // The directory relative paths are relative to.
+    dir: PathBuf,
+    top_level: PathBuf,
+    autocrlf: Option<String>,
+    core_eol: Option<String>,
+    check_attr: Option<CheckAttr>,
+}
+
+impl GitAttributes {
+    /// The attributes of the repository dir is in, None if it isn't in one.
+    /// Relative paths are looked up relative to dir.
+    pub fn open(dir: &Path) -> Option<GitAttributes> {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(["rev-parse", "--show-toplevel"])
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let top_level = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        Some(GitAttributes {
+            dir: dir.to_path_buf(),
+            top_level: PathBuf::from(top_level),
+            autocrlf: git_config(dir, "core.autocrlf"),
+            core_eol: git_config(dir, "core.eol"),
+            check_attr: None,
+        })
+    }
+
+    /// The line ending git checks file out with, None if git leaves its line
+    /// endings alone.
+    pub fn end_of_line(&mut self, file: &str) -> Option<EndOfLine> {
+        // check-attr exits when given a path outside the repository.
+        if Path::new(file).is_absolute() && !Path::new(file).starts_with(&self.top_level) {
+            return None;
+        }
+
+        let (text, eol) = self.check(file)?;
+        let autocrlf = self.autocrlf.as_deref();
+        match (text.as_str(), eol.as_str()) {
+            ("unset", _) => None,
+            (_, "crlf") => Some(EndOfLine::Crlf),
+            (_, "lf") => Some(EndOfLine::Lf),
+            ("unspecified", _) => match autocrlf {
+                Some("true") => Some(EndOfLine::Crlf),
+                Some("input") => Some(EndOfLine::Lf),
+                _ => None,
+            },
+            _ => match (autocrlf, self.core_eol.as_deref()) {
+                (Some("true"), _) => Some(EndOfLine::Crlf),
+                (Some("input"), _) => Some(EndOfLine::Lf),
+                (_, Some("crlf")) => Some(EndOfLine::Crlf),
+                (_, Some("lf")) => Some(EndOfLine::Lf),
+                _ if cfg!(windows) => Some(EndOfLine::Crlf),
+                _ => Some(EndOfLine::Lf),
+            },
+        }
+    }
+
+    // The text and eol attributes of file, starting check-attr again if it
+    // exited on an earlier path.
+    fn check(&mut self, file: &str) -> Option<(String, String)> {
+        if self.check_attr.is_none() {
+            match CheckAttr::spawn(&self.dir) {
+                Ok(check_attr) => self.check_attr = Some(check_attr),
+                Err(e) => {
+                    warn!("unable to run git check-attr: {}", e);
+                    return None;
+                }
+            }
+        }
+
+        let check_attr = self.check_attr.as_mut()?;
+        match check_attr.check(file) {
+            Ok(attrs) => Some(attrs),
+            Err(e) => {
+                debug!("unable to check the attributes of {}: {}", file, e);
+                self.check_attr = None;
+                None
+            }
+        }
+    }
+}
+
+// The value of key in the git config, lowercased, None if it isn't set.
+fn git_config(dir: &Path, key: &str) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["config", "--get", key])
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .to_lowercase();
+    Some(value).filter(|value| output.status.success() && !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GitRepo;
+
+    #[test]
+    fn test_end_of_line() {
+        let dir = GitRepo::new("gitattributes");
+        // Keep a global core.autocrlf from applying.
+        dir.git(&["config", "core.autocrlf", "false"]);
+        dir.write(
+            ".gitattributes",
+            "*.bat text eol=crlf\n*.sh text eol=lf\n*.bin -text\n*.txt text\n",
+        );
+
+        let mut attrs = GitAttributes::open(&dir).expect("Can open attributes");
+        assert_eq!(Some(EndOfLine::Crlf), attrs.end_of_line("run.bat"));
+        assert_eq!(Some(EndOfLine::Lf), attrs.end_of_line("sub/run.sh"));
+        assert_eq!(None, attrs.end_of_line("data.bin"));
+        assert_eq!(None, attrs.end_of_line("main.c"));
+        // Paths outside the repository don't stop later paths being checked.
+        assert_eq!(None, attrs.end_of_line("/outside/main.c"));
+        assert_eq!(None, attrs.end_of_line("../outside/main.c"));
+        assert_eq!(Some(EndOfLine::Crlf), attrs.end_of_line("run.bat"));
+
+        dir.git(&["config", "core.autocrlf", "true"]);
+        let mut attrs = GitAttributes::open(&dir).expect("Can open attributes");
+        assert_eq!(Some(EndOfLine::Crlf), attrs.end_of_line("main.c"));
+        assert_eq!(Some(EndOfLine::Crlf), attrs.end_of_line("notes.txt"));
+        assert_eq!(Some(EndOfLine::Lf), attrs.end_of_line("run.sh"));
+        assert_eq!(None, attrs.end_of_line("data.bin"));
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::io::{self, Write};
+use std::process::Stdio;
+
+use serde::Deserialize;
+
+use crate::utils::shell;
+
+/// Commands run for every file licensure processes. They are run by the
+/// shell with the file and action in the LICENSURE_HOOK, LICENSURE_FILE, and
+/// LICENSURE_ACTION environment variables and as a line of JSON on stdin.
+#[derive(Deserialize, Debug, Default)]
+pub struct Hooks {
+    /// Run before a file is read, the action is "check" or "license".
+    pre_process: Option<String>,
+    /// Run after a file is processed, the action is what happened to it.
+    post_process: Option<String>,
+}
+
+fn run(hook: &str, command: &str, file: &str, action: &str) -> Result<(), io::Error> {
+    debug!("running {} hook for {}: {}", hook, file, command);
+    let mut child = shell(command)
+        .env("LICENSURE_HOOK", hook)
+        .env("LICENSURE_FILE", file)
+        .env("LICENSURE_ACTION", action)
+        .stdin(Stdio::piped())
+        // Headers are printed to stdout when not changing files in place so
+        // keep hook output out of it.
+        .stdout(io::stderr())
+        .spawn()?;
+
+    let event = serde_json::json!({ "hook": hook, "file": file, "action": action });
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook which doesn't read stdin may exit before it is written.
+        if let Err(e) = writeln!(stdin, "{}", event) {
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                return Err(e);
+            }
+        }
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "{} hook failed for {}: {}",
+            hook, file, status
+        )));
+    }
+
+    Ok(())
+}
+
+impl Hooks {
+    pub fn pre_process(&self, file: &str, action: &str) -> Result<(), io::Error> {
+        match &self.pre_process {
+            Some(command) => run("pre_process", command, file, action),
+            None => Ok(()),
+        }
+    }
+
+    pub fn post_process(&self, file: &str, action: &str) -> Result<(), io::Error> {
+        match &self.post_process {
+            Some(command) => run("post_process", command, file, action),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn test_hooks_receive_file_and_action() {
+        let dir = TempDir::new("hooks");
+        let out = dir.file("out");
+
+        let hooks = Hooks {
+            pre_process: None,
+            post_process: Some(format!(
+                "echo \"$LICENSURE_HOOK $LICENSURE_FILE $LICENSURE_ACTION\" > {0}; cat >> {0}",
+                out
+            )),
+        };
+        hooks
+            .pre_process("a.rs", "license")
+            .expect("No pre_process hook");
+        hooks
+            .post_process("a.rs", "updated")
+            .expect("Hook succeeds");
+        assert_eq!(
+            "post_process a.rs updated\n{\"action\":\"updated\",\"file\":\"a.rs\",\"hook\":\"post_process\"}\n",
+            std::fs::read_to_string(&out).unwrap()
+        );
+
+        let failing = Hooks {
+            pre_process: Some("exit 3".to_string()),
+            post_process: None,
+        };
+        let err = failing.pre_process("a.rs", "check").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("pre_process hook failed for a.rs"));
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! The engine behind the licensure command line tool, for embedding license
+//! header management in other tools such as build systems and bots.
+//!
+//! A [`Config`] is parsed from the same YAML as a `.licensure.yml` file and
+//! handed to a [`Processor`] which checks or adds the license header of each
+//! file it is given:
+//!
+//! ```no_run
+//! use std::str::FromStr;
+//!
+//! use licensure_core::{Action, Config, Processor};
+//!
+//! let config = Config::from_str(
+//!     r#"
+//! change_in_place: true
+//! licenses:
+//!   - files: any
+//!     ident: MIT
+//!     authors:
+//!       - name: Example Corp
+//!     template: "Copyright [year] [name of author]. Licensed under [ident]."
+//! comments:
+//!   - extension: rs
+//!     commenter:
+//!       type: line
+//!       comment_char: "//"
+//! "#,
+//! )
+//! .expect("config is valid");
+//!
+//! let mut processor = Processor::new(config).with_check_mode(true);
+//! if processor.process_file("src/main.rs")? == Some(Action::NeedsUpdate) {
+//!     println!("src/main.rs needs a license header");
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+#[macro_use]
+extern crate log;
+
+// Declared first so its macro can be used by the modules after it.
+#[macro_use]
+pub mod trace;
+
+pub use baseline::Baseline;
+pub use config::Config;
+pub use processor::{Action, FilePatch, Fix, LicenseStats, Processor, Rule};
+pub use source::Source;
+pub use tree::GitTree;
+pub use visitor::{Event, SkipReason, Verdict, Visitor};
+
+pub mod baseline;
+pub mod changes;
+pub mod comments;
+pub mod config;
+pub mod detect;
+pub mod editorconfig;
+mod gitattributes;
+pub mod hooks;
+mod normalize;
+pub mod plugins;
+pub mod processor;
+mod source;
+pub mod spdx;
+pub mod template;
+#[cfg(any(test, feature = "testing"))]
+#[doc(hidden)]
+pub mod testing;
+mod tree;
+pub mod utils;
+pub mod visitor;
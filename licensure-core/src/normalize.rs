@@ -0,0 +1,191 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use regex::Regex;
+
+// Comment markers of the languages licensure is commonly used with. The
+// header may have been written with any commenter so none of them are
+// assumed.
+const OPENERS: &[&str] = &[
+    "/*", "<!--", "(*", "{-", "\"\"\"", "'''", "//", "--", "#", ";", "%", "*", "!", "::", "REM ",
+    "rem ",
+];
+const CLOSERS: &[&str] = &["*/", "-->", "*)", "-}", "\"\"\"", "'''"];
+// Openers which start a comment that continues until a closer.
+const BLOCK_OPENERS: &[(&str, &str)] = &[
+    ("/*", "*/"),
+    ("<!--", "-->"),
+    ("(*", "*)"),
+    ("{-", "-}"),
+    ("\"\"\"", "\"\"\""),
+    ("'''", "'''"),
+];
+
+// The words of a line of a comment without its comment markers.
+fn comment_words(line: &str) -> Vec<&str> {
+    let mut text = line.trim();
+    // Closers are tried first so a line of "*/" isn't left with a "/".
+    while let Some(rest) = CLOSERS
+        .iter()
+        .chain(OPENERS)
+        .find_map(|marker| text.strip_prefix(marker))
+    {
+        text = rest.trim_start();
+    }
+    while let Some(rest) = CLOSERS.iter().find_map(|c| text.strip_suffix(c)) {
+        text = rest.trim_end();
+    }
+
+    text.split_whitespace().collect()
+}
+
+/// Finds a header at the start of content whose words, without comment
+/// markers, match pattern as returned by Template::words_pattern. Returns
+/// the length of the header, including any blank lines and comment markers
+/// after it, and the year it was written with.
+pub fn find_header(content: &str, pattern: &Regex) -> Option<(usize, String)> {
+    let mut words: Vec<&str> = Vec::new();
+    // The closer of a block comment which is still open.
+    let mut open_block: Option<&str> = None;
+    let mut len = 0;
+    let mut found: Option<String> = None;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let in_comment = trimmed.is_empty()
+            || open_block.is_some()
+            || OPENERS.iter().any(|o| trimmed.starts_with(o));
+        if !in_comment {
+            break;
+        }
+
+        match open_block {
+            Some(closer) => {
+                if trimmed.ends_with(closer) || trimmed.starts_with(closer) {
+                    open_block = None;
+                }
+            }
+            None => {
+                open_block = BLOCK_OPENERS
+                    .iter()
+                    .find(|(opener, closer)| {
+                        trimmed.starts_with(opener)
+                            && !trimmed[opener.len()..].trim_end().ends_with(closer)
+                    })
+                    .map(|(_, closer)| *closer);
+            }
+        }
+
+        let line_words = comment_words(line);
+        if found.is_some() {
+            // Take the blank lines and comment markers ending the header.
+            if !line_words.is_empty() {
+                break;
+            }
+            len += line.len();
+            continue;
+        }
+
+        len += line.len();
+        if line_words.is_empty() {
+            continue;
+        }
+        words.extend(line_words);
+        if let Some(caps) = pattern.captures(&words.join(" ")) {
+            found = Some(caps["year"].to_string());
+        }
+    }
+
+    found.map(|year| (len, year))
+}
+
+/// Finds a header like find_header which starts on one of the first lines
+/// of content. Returns the start and length of the header and the year it
+/// was written with.
+pub fn find_header_within(
+    content: &str,
+    pattern: &Regex,
+    lines: usize,
+) -> Option<(usize, usize, String)> {
+    let mut start = 0;
+    for _ in 0..lines {
+        let rest = &content[start..];
+        // A header found from a blank line would take it along.
+        if !rest.starts_with(['\n', '\r']) {
+            if let Some((len, year)) = find_header(rest, pattern) {
+                return Some((start, len, year));
+            }
+        }
+        start += content[start..].find('\n')? + 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::{test_context, Template};
+
+    #[test]
+    fn test_find_header() {
+        let pattern = Template::new(
+            "Copyright [year] The Tester\n\nLicensed under the MIT license.",
+            test_context("2024"),
+        )
+        .words_pattern();
+
+        let content = "/*\n * Copyright 2019, 2021 The Tester\n *\n * Licensed under the MIT\n * license.\n */\n\nint main() {}\n";
+        assert_eq!(
+            Some((content.find("int").unwrap(), "2019, 2021".to_string())),
+            find_header(content, &pattern)
+        );
+
+        let content = "# Copyright 2020 The Tester\n# Licensed under the\n# MIT license.\n# Module docs.\nimport os\n";
+        assert_eq!(
+            Some((content.find("# Module").unwrap(), "2020".to_string())),
+            find_header(content, &pattern)
+        );
+
+        assert_eq!(
+            None,
+            find_header(
+                "# Copyright 2020 Someone Else\n# Licensed under the MIT license.\n",
+                &pattern
+            )
+        );
+        assert_eq!(
+            None,
+            find_header("import os\n# Copyright 2020 The Tester\n", &pattern)
+        );
+    }
+
+    #[test]
+    fn test_find_header_within() {
+        let pattern =
+            Template::new("Copyright [year] The Tester", test_context("2024")).words_pattern();
+
+        let content =
+            "//! Module docs.\nuse std::io;\n\n// Copyright\n// 2020 The Tester\n\nfn main() {}\n";
+        let start = content.find("// Copyright").unwrap();
+        assert_eq!(
+            Some((
+                start,
+                content.find("fn").unwrap() - start,
+                "2020".to_string()
+            )),
+            find_header_within(content, &pattern, 5)
+        );
+        assert_eq!(None, find_header_within(content, &pattern, 3));
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+//! WASM plugins declared in the config, which classify files for license
+//! configs to match by class and provide functions templates call with
+//! [plugin:name:function].
+//!
+//! A plugin is a WASM module, binary or text, which exports its `memory`, an
+//! `alloc(len: i32) -> i32` function returning where len bytes can be written,
+//! and functions taking the pointer and length of a UTF-8 string and returning
+//! the pointer of a UTF-8 string in the high 32 bits of an i64 and its length
+//! in the low 32 bits. `classify` is given the path of a file and returns its
+//! class, or an empty string if it has none. Template functions are given the
+//! path of the file the header is for and return the text put in its place.
+//!
+//! Plugins are sandboxed: they can't import anything, so they have no access
+//! to files, the network, or the clock, and each call runs in a new instance
+//! with limited memory and fuel, so it can't keep state or run forever.
+//! Running plugins needs licensure built with the plugins feature.
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::utils::project_path;
+
+/// The plugins of a config, by name.
+pub struct Plugins {
+    #[cfg(feature = "plugins")]
+    engine: wasmtime::Engine,
+    #[cfg(feature = "plugins")]
+    modules: BTreeMap<String, wasmtime::Module>,
+    // The class of each file classified so far.
+    classes: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl std::fmt::Debug for Plugins {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl Plugins {
+    /// Load the plugins at paths, by name, failing if one can't be read or
+    /// compiled or imports anything.
+    #[cfg(feature = "plugins")]
+    pub fn load(paths: &BTreeMap<String, String>) -> Result<Plugins, String> {
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = wasmtime::Engine::new(&config)
+            .map_err(|e| format!("unable to set up plugins: {}", e))?;
+
+        let mut modules = BTreeMap::new();
+        for (name, path) in paths {
+            let module = wasmtime::Module::from_file(&engine, path)
+                .map_err(|e| format!("unable to load plugin {} from {}: {:#}", name, path, e))?;
+            if let Some(import) = module.imports().next() {
+                return Err(format!(
+                    "plugin {} imports {}.{}, plugins can't import anything",
+                    name,
+                    import.module(),
+                    import.name()
+                ));
+            }
+            modules.insert(name.clone(), module);
+        }
+
+        Ok(Plugins {
+            engine,
+            modules,
+            classes: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Fails if any plugins are declared, licensure was built without the
+    /// plugins feature.
+    #[cfg(not(feature = "plugins"))]
+    pub fn load(paths: &BTreeMap<String, String>) -> Result<Plugins, String> {
+        match paths.keys().next() {
+            Some(name) => Err(format!(
+                "plugin {} can't be loaded, licensure was built without the plugins feature",
+                name
+            )),
+            None => Ok(Plugins {
+                classes: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// The names of the plugins.
+    pub fn names(&self) -> Vec<&str> {
+        #[cfg(feature = "plugins")]
+        return self.modules.keys().map(String::as_str).collect();
+        #[cfg(not(feature = "plugins"))]
+        Vec::new()
+    }
+
+    /// Returns the class of file given by the first plugin, in name order,
+    /// which exports classify and gives it one. Plugins are given the path
+    /// relative to the project, with forward slashes.
+    pub fn classify(&self, file: &str) -> Result<Option<String>, String> {
+        let file = project_path(file);
+        if let Some(class) = self.classes.lock().unwrap().get(&file) {
+            return Ok(class.clone());
+        }
+
+        let mut class = None;
+        for name in self.names() {
+            if !self.exports(name, "classify") {
+                continue;
+            }
+            let given = self.call(name, "classify", &file)?;
+            if !given.is_empty() {
+                class = Some(given);
+                break;
+            }
+        }
+
+        self.classes.lock().unwrap().insert(file, class.clone());
+        Ok(class)
+    }
+
+    #[cfg(feature = "plugins")]
+    fn exports(&self, name: &str, function: &str) -> bool {
+        self.modules
+            .get(name)
+            .is_some_and(|module| module.get_export(function).is_some())
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    fn exports(&self, _name: &str, _function: &str) -> bool {
+        false
+    }
+
+    /// Returns what function of the plugin name returns given arg.
+    #[cfg(feature = "plugins")]
+    pub fn call(&self, name: &str, function: &str, arg: &str) -> Result<String, String> {
+        let Some(module) = self.modules.get(name) else {
+            return Err(self.undefined(name));
+        };
+        run(&self.engine, module, function, arg)
+            .map_err(|e| format!("plugin {} failed in {}: {:#}", name, function, e))
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    pub fn call(&self, name: &str, _function: &str, _arg: &str) -> Result<String, String> {
+        Err(self.undefined(name))
+    }
+
+    /// Describes why the plugin name can't be used.
+    pub fn undefined(&self, name: &str) -> String {
+        let names = self.names();
+        if names.is_empty() {
+            format!("undefined plugin {}, no plugins are configured", name)
+        } else {
+            format!(
+                "undefined plugin {}, the plugins are: {}",
+                name,
+                names.join(", ")
+            )
+        }
+    }
+}
+
+// The memory and fuel each call of a plugin gets.
+#[cfg(feature = "plugins")]
+const MAX_MEMORY: usize = 64 << 20;
+#[cfg(feature = "plugins")]
+const FUEL: u64 = 100_000_000;
+
+// Call function of a new instance of module with arg.
+#[cfg(feature = "plugins")]
+fn run(
+    engine: &wasmtime::Engine,
+    module: &wasmtime::Module,
+    function: &str,
+    arg: &str,
+) -> wasmtime::Result<String> {
+    use wasmtime::{Instance, Store, StoreLimitsBuilder};
+
+    let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+    let mut store = Store::new(engine, limits);
+    store.limiter(|limits| limits);
+    store.set_fuel(FUEL)?;
+
+    let instance = Instance::new(&mut store, module, &[])?;
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| wasmtime::Error::msg("it exports no memory"))?;
+    let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+    let func = instance.get_typed_func::<(i32, i32), i64>(&mut store, function)?;
+
+    let len = i32::try_from(arg.len())?;
+    let ptr = alloc.call(&mut store, len)?;
+    memory.write(&mut store, usize::try_from(ptr)?, arg.as_bytes())?;
+    let result = func.call(&mut store, (ptr, len))? as u64;
+
+    let mut out = vec![0; (result & 0xffff_ffff) as usize];
+    memory.read(&store, (result >> 32) as usize, &mut out)?;
+    String::from_utf8(out).map_err(|_| wasmtime::Error::msg("it returned text which isn't UTF-8"))
+}
+
+#[cfg(all(test, feature = "plugins"))]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    // Classifies files under vendor/ as vendored, and gives the owner of any
+    // file as "Example Corp".
+    const PLUGIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "vendored")
+  (data (i32.const 16) "Example Corp")
+  (data (i32.const 32) "vendor/")
+  (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "classify") (param $ptr i32) (param $len i32) (result i64)
+    (if (i32.lt_u (local.get $len) (i32.const 7))
+      (then (return (i64.const 0))))
+    (if (i32.ne (i32.load (local.get $ptr)) (i32.load (i32.const 32)))
+      (then (return (i64.const 0))))
+    (if (i32.ne (i32.load offset=3 (local.get $ptr)) (i32.load (i32.const 35)))
+      (then (return (i64.const 0))))
+    (i64.const 8))
+  (func (export "owner") (param i32 i32) (result i64)
+    (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 12)))
+  (func (export "spin") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0)))
+"#;
+
+    fn plugins(name: &str, source: &str) -> Plugins {
+        let dir = TempDir::new("plugin");
+        let path = dir.write(&format!("{}.wat", name), source);
+        let paths = BTreeMap::from([(name.to_string(), path)]);
+        Plugins::load(&paths).expect("Can load plugin")
+    }
+
+    #[test]
+    fn test_plugins() {
+        let plugins = plugins("acme", PLUGIN);
+        assert_eq!(
+            Ok(Some("vendored".to_string())),
+            plugins.classify("./vendor/lib.c")
+        );
+        assert_eq!(Ok(None), plugins.classify("src/main.c"));
+        assert_eq!(
+            Ok("Example Corp".to_string()),
+            plugins.call("acme", "owner", "src/main.c")
+        );
+
+        let spin = plugins.call("acme", "spin", "").unwrap_err();
+        assert!(spin.starts_with("plugin acme failed in spin: "), "{}", spin);
+        assert_eq!(
+            Err("undefined plugin other, the plugins are: acme".to_string()),
+            plugins.call("other", "owner", "src/main.c")
+        );
+    }
+
+    #[test]
+    fn test_plugins_cant_import() {
+        let dir = TempDir::new("plugin");
+        let path = dir.write(
+            "fs.wat",
+            r#"(module (import "wasi_snapshot_preview1" "fd_write" (func)))"#,
+        );
+        let paths = BTreeMap::from([("fs".to_string(), path)]);
+        let err = Plugins::load(&paths).unwrap_err();
+        assert_eq!(
+            "plugin fs imports wasi_snapshot_preview1.fd_write, plugins can't import anything",
+            err
+        );
+    }
+}
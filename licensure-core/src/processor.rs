@@ -0,0 +1,2935 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::cell::{OnceCell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, prelude::*};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use chrono::{Local, NaiveDate};
+use regex::Regex;
+
+use crate::baseline::Baseline;
+use crate::changes::Change;
+use crate::comments::Comment;
+use crate::config::{Config, EmptyFilePolicy, ExistingHeaderPolicy, LicenseConfig, Position};
+use crate::detect;
+use crate::editorconfig::{self, Decoded, EditorConfig};
+use crate::gitattributes::GitAttributes;
+use crate::normalize;
+use crate::source::Source;
+use crate::spdx;
+use crate::template::{year_varying_regex, Context, Template};
+use crate::trace;
+use crate::utils::{
+    diff_hunk, display_width, git_diff, long_path, project_path, replacement, unified_diff,
+    Replacement,
+};
+use crate::visitor::{Event, SkipReason, Verdict, Visitor};
+
+const SIDECAR_EXTENSION: &str = "license";
+const FINGERPRINT_KEY: &str = "License-Header-Hash";
+
+// The line which fingerprints a commented header, a short hash of it.
+fn fingerprint_line(header: &str) -> String {
+    let hash = sha1_smol::Sha1::from(header).digest().to_string();
+    format!("{}: {}", FINGERPRINT_KEY, &hash[..8])
+}
+
+// A fingerprint line with its comment markers, from any version of a header.
+fn fingerprint_re() -> &'static Regex {
+    static FINGERPRINT: OnceLock<Regex> = OnceLock::new();
+    FINGERPRINT.get_or_init(|| {
+        Regex::new(&format!(r"(?m)^.*{}: [0-9a-f]+.*(\n|$)", FINGERPRINT_KEY))
+            .expect("fingerprint regex didn't compile!")
+    })
+}
+
+// Removes a fingerprint which doesn't match the header from the lines of
+// content in region, counted from 1, where the header is, so the header it
+// was written with is found like any other. Fingerprint lines elsewhere, such
+// as in code handling them, are left alone.
+fn strip_fingerprint(content: &mut String, (first, last): (usize, usize)) {
+    let found = fingerprint_re().find_iter(content).find(|m| {
+        let line = content[..m.start()].matches('\n').count() + 1;
+        (first..=last).contains(&line)
+    });
+    if let Some(m) = found {
+        content.replace_range(m.range(), "");
+    }
+}
+
+// Whether a line of content in region, counted from 1, has fingerprint. One
+// elsewhere, such as in a string of code copying the header, doesn't count.
+fn has_fingerprint(content: &str, fingerprint: &str, (first, last): (usize, usize)) -> bool {
+    content
+        .lines()
+        .enumerate()
+        .any(|(i, line)| (first..=last).contains(&(i + 1)) && line.contains(fingerprint))
+}
+
+/// Checks or adds the license headers of files according to a [`Config`].
+pub struct Processor {
+    config: Config,
+    stats: LicenseStats,
+    check_mode: bool,
+    visitor: Option<Box<dyn Visitor>>,
+    headers: RefCell<HashMap<HeaderKey, Rc<RenderedHeader>>>,
+    // Warnings about the file being processed, moved to the stats once it's
+    // done.
+    warnings: RefCell<Vec<String>>,
+    // Record the change to each file needing an update in the stats.
+    patch: bool,
+    // Record a fix for each file needing an update in the stats.
+    fixes: bool,
+    // Only update the years of headers which are otherwise right.
+    years_only: bool,
+    // Where files are read from instead of the working tree.
+    source: Option<Box<dyn Source>>,
+    // Where files are written with their header when they aren't changed in
+    // place.
+    output: Option<Box<dyn Write>>,
+    // The .editorconfig files read so far by path, None where there isn't
+    // one.
+    editorconfigs: RefCell<HashMap<String, Option<Rc<EditorConfig>>>>,
+    // The attributes of the repository in the current directory, opened the
+    // first time a file needs them.
+    gitattributes: OnceCell<Option<RefCell<GitAttributes>>>,
+    // The day suppressions are checked against.
+    today: NaiveDate,
+    // The findings which are left alone because they were already there.
+    baseline: Baseline,
+    // The changes being checked by project path, findings in headers they
+    // don't touch are left alone.
+    changes: Option<HashMap<String, Change>>,
+}
+
+// Identifies everything a rendered header depends on: the license config and
+// comment config, by their position in the config file, and the context.
+#[derive(PartialEq, Eq, Hash)]
+struct HeaderKey {
+    license: usize,
+    comment: Option<usize>,
+    context: Context,
+}
+
+struct RenderedHeader {
+    templ: Template,
+    commenter: Box<dyn Comment>,
+    header: String,
+    // The fingerprint line the header ends with, if it has one.
+    fingerprint: Option<String>,
+    // The headers rendered from the templates the template_file had before.
+    previous: Vec<RenderedHeader>,
+    // The header with a token in place of the year, and the patterns matching
+    // it with any year, compiled the first time a file needs them.
+    year_varying: String,
+    outdated_re: OnceLock<Regex>,
+    trimmed_outdated_re: OnceLock<Regex>,
+    words_re: OnceLock<Regex>,
+}
+
+impl RenderedHeader {
+    fn new(
+        templ: Template,
+        commenter: Box<dyn Comment>,
+        fingerprint: bool,
+    ) -> Result<RenderedHeader, String> {
+        let text = templ.render();
+        let mut header = commenter.comment(&text)?;
+        let mut fingerprint_of = None;
+        if fingerprint {
+            let line = fingerprint_line(&header);
+            header = commenter.comment(&format!("{}\n{}", text.trim_end_matches('\n'), line))?;
+            fingerprint_of = Some(line);
+        }
+        let year_varying = templ.year_varying_header(commenter.as_ref())?;
+
+        Ok(RenderedHeader {
+            templ,
+            commenter,
+            header,
+            fingerprint: fingerprint_of,
+            previous: Vec::new(),
+            year_varying,
+            outdated_re: OnceLock::new(),
+            trimmed_outdated_re: OnceLock::new(),
+            words_re: OnceLock::new(),
+        })
+    }
+
+    // Returns content with this header in place of a copy of it, formatted
+    // any way, which starts on one of the first lines of content.
+    fn moved_replacement(&self, content: &str, lines: usize) -> Option<String> {
+        let words_re = self.words_re.get_or_init(|| self.templ.words_pattern());
+        let (start, len, _) = normalize::find_header_within(content, words_re, lines)?;
+        // Keep the blank lines separating the header from what follows.
+        let found = &content[start..start + len];
+        let kept = found.trim_end().len();
+        let end = start + kept + usize::from(found[kept..].starts_with('\n'));
+
+        let mut update = content.to_string();
+        update.replace_range(start..end, &self.header);
+        Some(update)
+    }
+
+    // Returns content with this header in place of a copy of it with an
+    // outdated year.
+    fn outdated_replacement(&self, content: &str) -> Option<String> {
+        self.replace_outdated(content, &self.header)
+    }
+
+    // Returns content with this header, with any year, replaced by header.
+    fn replace_outdated(&self, content: &str, header: &str) -> Option<String> {
+        let outdated_re = self
+            .outdated_re
+            .get_or_init(|| year_varying_regex(&self.year_varying, false));
+        trace!("Content: {}", content);
+        trace!("Outdated Regex: {:?}", outdated_re);
+        trace!("Header: {:?}", header);
+        if outdated_re.is_match(content) {
+            return Some(outdated_re.replace(content, header).to_string());
+        }
+
+        // Account for possible whitespace changes
+        let trimmed_outdated_re = self
+            .trimmed_outdated_re
+            .get_or_init(|| year_varying_regex(&self.year_varying, true));
+        trace!("trimmed_outdated_re Regex: {:?}", trimmed_outdated_re);
+        if trimmed_outdated_re.is_match(content) {
+            Some(trimmed_outdated_re.replace(content, header).to_string())
+        } else {
+            None
+        }
+    }
+}
+
+// The sample file roundtrip_sample licenses, and the years of the header it
+// writes and of the outdated copy it updates.
+const ROUNDTRIP_BODY: &str = "licensure roundtrip\n";
+const ROUNDTRIP_YEAR: &str = "2000";
+const ROUNDTRIP_OLD_YEAR: &str = "1999";
+
+fn shebang_re() -> &'static Regex {
+    static SHEBANG: OnceLock<Regex> = OnceLock::new();
+    SHEBANG.get_or_init(|| Regex::new(r"^#!.*\n").expect("shebang regex didn't compile!"))
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum LicenseStatus {
+    NeedsUpdate(String, Rule),
+    AlreadyLicensed,
+    NoConfigMatched,
+    HasOtherHeader,
+    Skipped(SkipReason),
+    TemplateError(String),
+    Suppressed(Rule),
+    Baselined(Rule),
+    PreExisting(Rule),
+}
+
+/// What happened to a file given to [`Processor::process_file`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// The file's header is missing or outdated, found in check mode.
+    NeedsUpdate,
+    /// The file's header was added or updated in place.
+    Updated,
+    /// The file with its new header was printed to stdout.
+    Printed,
+    /// The file already has the right header.
+    AlreadyLicensed,
+    /// No license config matches the file.
+    NotLicensed,
+    /// The file has another license header which it isn't allowed to have.
+    HasOtherHeader,
+    /// The file has another license header and was left alone.
+    Skipped,
+    /// The template for the file couldn't be loaded.
+    TemplateError,
+    /// The file's header is missing, outdated, or another license's, but the
+    /// finding is suppressed so the file was left alone.
+    Suppressed,
+    /// The file's header is missing, outdated, or another license's, but the
+    /// finding is in the baseline so the file was left alone.
+    Baselined,
+    /// The file's header is missing, outdated, or another license's, but the
+    /// changes being checked don't touch it so the file was left alone.
+    PreExisting,
+}
+
+impl Action {
+    /// The name of the action as given to the post_process hook.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::NeedsUpdate => "needs_update",
+            Action::Updated => "updated",
+            Action::Printed => "printed",
+            Action::AlreadyLicensed => "already_licensed",
+            Action::NotLicensed => "not_licensed",
+            Action::HasOtherHeader => "has_other_header",
+            Action::Skipped => "skipped",
+            Action::TemplateError => "template_error",
+            Action::Suppressed => "suppressed",
+            Action::Baselined => "baselined",
+            Action::PreExisting => "pre_existing",
+        }
+    }
+}
+
+/// The kind of problem found with a file's header, each with a stable ID
+/// reports give so suppressions and dashboards can key off it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Rule {
+    /// The file has no header, or none where it belongs.
+    MissingHeader,
+    /// The file has the header with outdated years.
+    OutdatedYear,
+    /// The file has the header of another license, or of an earlier version
+    /// of its template.
+    WrongLicense,
+    /// The file has a license header which isn't ours.
+    ForeignCopyright,
+    /// The license and copyright comments of the file have a line wider than
+    /// columns.
+    WideHeader,
+}
+
+impl Rule {
+    /// Every rule, in the order of their IDs.
+    pub const ALL: [Rule; 5] = [
+        Rule::MissingHeader,
+        Rule::OutdatedYear,
+        Rule::WrongLicense,
+        Rule::ForeignCopyright,
+        Rule::WideHeader,
+    ];
+
+    /// The stable ID of the rule.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::MissingHeader => "LIC001",
+            Rule::OutdatedYear => "LIC002",
+            Rule::WrongLicense => "LIC003",
+            Rule::ForeignCopyright => "LIC004",
+            Rule::WideHeader => "LIC005",
+        }
+    }
+
+    /// A short description of what the rule finds.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::MissingHeader => "missing header",
+            Rule::OutdatedYear => "outdated year",
+            Rule::WrongLicense => "wrong license",
+            Rule::ForeignCopyright => "foreign copyright",
+            Rule::WideHeader => "wide header",
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .into_iter()
+            .find(|rule| rule.id() == id)
+            .ok_or_else(|| {
+                format!(
+                    "unknown rule {}, the rules are {}",
+                    id,
+                    Rule::ALL.map(|rule| rule.id()).join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.id(), self.description())
+    }
+}
+
+impl Processor {
+    pub fn new(config: Config) -> Processor {
+        Processor {
+            config,
+            check_mode: false,
+            visitor: None,
+            stats: LicenseStats::new(),
+            headers: RefCell::new(HashMap::new()),
+            warnings: RefCell::new(Vec::new()),
+            patch: false,
+            fixes: false,
+            years_only: false,
+            source: None,
+            output: None,
+            editorconfigs: RefCell::new(HashMap::new()),
+            gitattributes: OnceCell::new(),
+            today: Local::now().date_naive(),
+            baseline: Baseline::default(),
+            changes: None,
+        }
+    }
+
+    /// In check mode files are never changed or printed, the files which
+    /// need a header are only recorded in the stats.
+    pub fn with_check_mode(mut self, check_mode: bool) -> Processor {
+        self.check_mode = check_mode;
+        self
+    }
+
+    /// Record the change to the header of every file which needs one in the
+    /// patches of the stats, whether or not it's written.
+    pub fn with_patch(mut self, patch: bool) -> Processor {
+        self.patch = patch;
+        self
+    }
+
+    /// Record a fix for the header of every file which needs one in the
+    /// stats, with the header and the text replacing part of the file, for
+    /// editors and review bots to apply.
+    pub fn with_fixes(mut self, fixes: bool) -> Processor {
+        self.fixes = fixes;
+        self
+    }
+
+    /// Only update the years of headers which are otherwise right, files
+    /// with no header or any other difference in it are skipped unchanged.
+    pub fn with_years_only(mut self, years_only: bool) -> Processor {
+        self.years_only = years_only;
+        self
+    }
+
+    /// Leave the files whose finding is in baseline alone, recording them
+    /// in the stats instead, so only new findings fail.
+    pub fn with_baseline(mut self, baseline: Baseline) -> Processor {
+        self.baseline = baseline;
+        self
+    }
+
+    /// Only report findings in files changes added or in headers whose lines
+    /// they touch, leaving the others alone as they predate the changes.
+    pub fn with_changes(mut self, changes: HashMap<String, Change>) -> Processor {
+        self.changes = Some(changes);
+        self
+    }
+
+    /// Read files from source instead of the working tree. Files can't be
+    /// written back to a source so this turns on check mode.
+    pub fn with_source(mut self, source: Box<dyn Source>) -> Processor {
+        self.source = Some(source);
+        self.check_mode = true;
+        self
+    }
+
+    /// Write each file which needs a header to output with it, when files
+    /// aren't changed in place. Without an output they're left as they are.
+    pub fn with_output(mut self, output: Box<dyn Write>) -> Processor {
+        self.output = Some(output);
+        self
+    }
+
+    /// Send the events of every file processed to visitor, which can veto
+    /// writing headers.
+    pub fn with_visitor(mut self, visitor: Box<dyn Visitor>) -> Processor {
+        self.visitor = Some(visitor);
+        self
+    }
+
+    fn visit(&mut self, event: Event) -> Verdict {
+        match &mut self.visitor {
+            Some(visitor) => visitor.visit(&event),
+            None => Verdict::Allow,
+        }
+    }
+
+    /// The config files are processed with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The results of every file processed so far.
+    pub fn stats(&self) -> &LicenseStats {
+        &self.stats
+    }
+
+    /// Process every file, returning the results for them. Fails if there
+    /// are more files than max_files allows. Files which can't be read or
+    /// written are recorded in the stats and the rest are still processed.
+    pub fn license_files(mut self, files: &[String]) -> Result<LicenseStats, io::Error> {
+        self.stats = LicenseStats::new();
+
+        if let Some(max_files) = self.config.max_files {
+            let count = files
+                .iter()
+                .filter(|file| !self.config.excludes.is_match(file))
+                .count();
+            if count > max_files {
+                return Err(io::Error::other(format!(
+                    "{} files matched which is more than max_files ({}), check your excludes or raise max_files",
+                    count, max_files
+                )));
+            }
+        }
+
+        spdx::prefetch(&self.config.licenses.fetched_idents());
+        for suppression in self.config.suppressions.expired(self.today) {
+            let warning = format!(
+                "{} expired on {}, its findings are no longer suppressed",
+                suppression,
+                suppression.expires.expect("expired suppressions expire")
+            );
+            self.stats.warnings.push(warning);
+        }
+
+        // A file which can't be processed doesn't stop the others.
+        for file in files {
+            if let Err(e) = self.process_file(file) {
+                error!("failed to process {}: {}", file, e);
+                self.stats.files_failed.push((file.clone(), e));
+            }
+        }
+
+        Ok(self.stats)
+    }
+
+    /// Check or add the license header of file, recording the result in the
+    /// stats. Returns what happened to the file, or None if it was skipped
+    /// without being read because it's excluded, too large, or the sidecar
+    /// of another file.
+    pub fn process_file(&mut self, file: &str) -> Result<Option<Action>, io::Error> {
+        let file = file.to_string();
+        let excluded_by = self.config.excludes.matching(&file);
+        if excluded_by.is_empty() {
+            trace_file!(&file, "no exclude pattern matches");
+        } else {
+            trace_file!(&file, "excluded by {}", excluded_by.join(", "));
+        }
+        if self.config.excludes.is_match(&file) {
+            info!("skipping {} because it is excluded.", file);
+            self.skip(&file, SkipReason::Excluded);
+            return Ok(None);
+        }
+
+        if let Some(max_file_size) = self.config.max_file_size {
+            let size = self.size(&file)?;
+            if size > max_file_size.0 {
+                info!(
+                    "skipping {} because it is {} bytes which is larger than max_file_size ({}).",
+                    file, size, max_file_size
+                );
+                self.skip(&file, SkipReason::TooLarge);
+                return Ok(None);
+            }
+        }
+
+        if self.is_sidecar_file(&file) {
+            info!(
+                "skipping {} because it is the license sidecar of another file.",
+                file
+            );
+            self.skip(&file, SkipReason::Sidecar);
+            return Ok(None);
+        }
+
+        trace!("Working on file: {}", &file);
+
+        // Headers for files which can't hold comments live in a sidecar
+        // file, which may not exist yet.
+        let target = if self.config.uses_sidecar(&file) {
+            format!("{}.{}", file, SIDECAR_EXTENSION)
+        } else {
+            file.clone()
+        };
+        if target != file {
+            trace_file!(&file, "the header is kept in the sidecar {}", target);
+        }
+
+        self.config
+            .hooks
+            .pre_process(&target, if self.check_mode { "check" } else { "license" })?;
+
+        let mut content = String::new();
+        if target == file || self.exists(&target) {
+            let bytes = self.read(&target)?;
+            // Binary files only get a header through a sidecar, which is
+            // always text.
+            if target == file && detect::is_binary(&bytes) {
+                info!("skipping {} because it is a binary file.", file);
+                return self.skip_read(&file, SkipReason::Binary);
+            }
+            content = String::from_utf8(bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        }
+
+        if target == file && self.config.skip_generated && detect::is_generated(&content) {
+            info!("skipping {} because it is a generated file.", file);
+            return self.skip_read(&file, SkipReason::Generated);
+        }
+        if target == file && detect::is_opted_out(&content) {
+            info!(
+                "skipping {} because it opts out with licensure: ignore.",
+                file
+            );
+            return self.skip_read(&file, SkipReason::Exempt);
+        }
+        // Adding a header may change content so keep what was read for the
+        // diff visitors are shown and the patch.
+        let original =
+            (self.visitor.is_some() || self.patch || self.fixes).then(|| content.clone());
+        let new_file = target != file && !self.exists(&target);
+
+        // Headers are matched and rendered with newlines and no byte order
+        // mark, the update is written the way git and .editorconfig ask for.
+        let settings = self.line_settings(&target);
+        let decoded = settings.decode(&mut content);
+        trace_file!(
+            &file,
+            "line endings {:?}, byte order mark {}",
+            settings.end_of_line,
+            decoded.bom || settings.bom == Some(true)
+        );
+        if trace::is_traced(&file) {
+            self.config.trace_matchers(&file, &content);
+        }
+
+        // Empty files no license config covers are reported as such.
+        let covered = matches!(self.config.licenses.find_for(&file, &content), Ok(Some(_)));
+        if target == file && detect::is_empty(&content) && covered {
+            match self.config.empty_files {
+                EmptyFilePolicy::Skip => {
+                    info!("skipping {} because it is empty.", file);
+                    return self.skip_read(&file, SkipReason::Empty);
+                }
+                EmptyFilePolicy::Error => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "the file is empty and empty_files is error",
+                    ));
+                }
+                EmptyFilePolicy::HeaderOnly => (),
+            }
+        }
+
+        // Headers are only looked at as they are before any update.
+        let wide_line = if self.check_mode && !self.years_only && target == file {
+            self.wide_header_line(&file, &content)
+        } else {
+            None
+        };
+        let header = self
+            .fixes
+            .then(|| self.rendered_header(&file, &content))
+            .flatten()
+            .and_then(Result::ok)
+            .map(|rendered| settings.encode(&rendered.header, &Decoded::default()));
+        // Where the header is can only be told before it's updated.
+        let region = match self.changes {
+            Some(_) if target == file => self.header_region(&file, &content),
+            _ => None,
+        };
+        let status = match self.add_license_header(&file, &mut content) {
+            LicenseStatus::NeedsUpdate(update, rule) => {
+                LicenseStatus::NeedsUpdate(settings.encode(&update, &decoded), rule)
+            }
+            status => status,
+        };
+        // Known exceptions are left alone.
+        let rule = match status {
+            LicenseStatus::NeedsUpdate(_, rule) => Some(rule),
+            LicenseStatus::HasOtherHeader => Some(Rule::ForeignCopyright),
+            _ => None,
+        };
+        let status = rule
+            .and_then(|rule| self.left_alone(&file, &target, rule, region))
+            .unwrap_or(status);
+        self.stats.warnings.append(&mut self.warnings.borrow_mut());
+        if status != LicenseStatus::NoConfigMatched {
+            self.visit(Event::FileMatched { file: &file });
+        }
+
+        if let Some(line) = wide_line {
+            let rule = Rule::WideHeader;
+            match self.left_alone(&file, &target, rule, region) {
+                Some(LicenseStatus::Suppressed(_)) => {
+                    self.stats.files_suppressed.push((target.clone(), rule))
+                }
+                Some(LicenseStatus::Baselined(_)) => {
+                    self.stats.files_baselined.push((target.clone(), rule))
+                }
+                Some(_) => self.stats.files_pre_existing.push((target.clone(), rule)),
+                None => {
+                    info!(
+                        "{} has a header line wider than columns on line {}",
+                        file, line
+                    );
+                    self.stats
+                        .files_with_wide_headers
+                        .push((file.clone(), line));
+                    self.stats.findings.push((target.clone(), rule));
+                }
+            }
+        }
+
+        let mut action = self.action(&status);
+        trace_file!(&file, "{}", action.as_str());
+        match status {
+            LicenseStatus::NeedsUpdate(update, rule) => {
+                trace_file!(&file, "found {}", rule);
+                self.visit(Event::HeaderMissing { file: &target });
+                self.stats.files_needing_license_update.push(target.clone());
+                self.stats.findings.push((target.clone(), rule));
+                if self.patch {
+                    self.stats.patches.push(FilePatch {
+                        file: target.clone(),
+                        hunk: diff_hunk(original.as_deref().unwrap_or(""), &update),
+                        new_file,
+                    });
+                }
+                if let Some(header) = header {
+                    self.stats.fixes.push(Fix {
+                        file: target.clone(),
+                        rule,
+                        header,
+                        replacement: replacement(original.as_deref().unwrap_or(""), &update),
+                    });
+                }
+                if !self.check_mode {
+                    let diff = unified_diff(&target, original.as_deref().unwrap_or(""), &update);
+                    match self.visit(Event::HeaderUpdated {
+                        file: &target,
+                        diff,
+                    }) {
+                        Verdict::Allow => {
+                            self.handle_update(&target, &update)?;
+                            if self.config.change_in_place {
+                                self.stats.files_updated.push(target.clone());
+                            }
+                        }
+                        Verdict::Veto => {
+                            trace_file!(&file, "the update was vetoed");
+                            info!("not updating {} because it was vetoed", target);
+                            action = Action::NeedsUpdate;
+                        }
+                    }
+                }
+            }
+            LicenseStatus::NoConfigMatched => {
+                self.skip(&file, SkipReason::NoMatcher);
+                self.stats.files_not_licensed.push(file)
+            }
+            LicenseStatus::AlreadyLicensed => self.stats.files_already_licensed.push(file),
+            LicenseStatus::HasOtherHeader => {
+                self.stats.files_with_other_header.push(target.clone());
+                self.stats
+                    .findings
+                    .push((target.clone(), Rule::ForeignCopyright));
+            }
+            LicenseStatus::Skipped(reason) => self.skip(&file, reason),
+            LicenseStatus::TemplateError(e) => {
+                self.skip(&file, SkipReason::TemplateError(e.clone()));
+                self.stats.template_errors.entry(e).or_default().push(file)
+            }
+            LicenseStatus::PreExisting(rule) => {
+                self.stats.files_pre_existing.push((target.clone(), rule))
+            }
+            LicenseStatus::Baselined(rule) => {
+                self.stats.files_baselined.push((target.clone(), rule))
+            }
+            LicenseStatus::Suppressed(rule) => {
+                self.stats.files_suppressed.push((target.clone(), rule))
+            }
+        }
+
+        self.config.hooks.post_process(&target, action.as_str())?;
+        Ok(Some(action))
+    }
+
+    // Why the finding of rule in target, which file is licensed by, is left
+    // alone, if it is: it's suppressed, it's in the baseline, or it's in a
+    // header the changes being checked don't touch.
+    fn left_alone(
+        &self,
+        file: &str,
+        target: &str,
+        rule: Rule,
+        region: Option<(usize, usize)>,
+    ) -> Option<LicenseStatus> {
+        if let Some(suppression) = self.config.suppressions.find(rule, target, self.today) {
+            trace_file!(file, "found {}, suppressed by {}", rule, suppression);
+            Some(LicenseStatus::Suppressed(rule))
+        } else if self.baseline.contains(target, rule) {
+            trace_file!(file, "found {}, which is in the baseline", rule);
+            Some(LicenseStatus::Baselined(rule))
+        } else if !self.touches_header(file, target, region) {
+            trace_file!(file, "found {}, in a header the changes don't touch", rule);
+            Some(LicenseStatus::PreExisting(rule))
+        } else {
+            None
+        }
+    }
+
+    // The lines the header of file is in or belongs at, counted from 1 with
+    // a line of margin.
+    fn header_region(&self, file: &str, content: &str) -> Option<(usize, usize)> {
+        let rendered = self.rendered_header(file, content)?.ok()?;
+        let lines = rendered.header.lines().count();
+        match self.config.position(file, content) {
+            Position::Top => {
+                let shebang = usize::from(shebang_re().is_match(content));
+                let last = shebang + lines + 1;
+                Some((1, last.max(self.config.header_search_lines.unwrap_or(0))))
+            }
+            Position::Bottom => {
+                let total = content.lines().count();
+                Some((total.saturating_sub(lines).max(1), total + 1))
+            }
+        }
+    }
+
+    // Whether the changes being checked, if any, add file or touch the lines
+    // of its header in region. Any change to a sidecar touches its header.
+    fn touches_header(&self, file: &str, target: &str, region: Option<(usize, usize)>) -> bool {
+        let Some(changes) = &self.changes else {
+            return true;
+        };
+        if changes.get(&project_path(file)) == Some(&Change::Added) {
+            return true;
+        }
+        match changes.get(&project_path(target)) {
+            Some(_) if target != file => true,
+            Some(change) => region.is_some_and(|(first, last)| change.touches(first, last)),
+            None => false,
+        }
+    }
+
+    // file, which is relative to the root of the config, as a path which can
+    // be opened from the directory licensure runs in.
+    fn disk_path(&self, file: &str) -> PathBuf {
+        long_path(&self.config.root().join(file).to_string_lossy())
+    }
+
+    fn exists(&self, file: &str) -> bool {
+        match &self.source {
+            Some(source) => source.exists(file),
+            None => self.disk_path(file).exists(),
+        }
+    }
+
+    fn size(&self, file: &str) -> Result<u64, io::Error> {
+        match &self.source {
+            Some(source) => source.size(file),
+            None => Ok(fs::metadata(self.disk_path(file))?.len()),
+        }
+    }
+
+    fn read(&self, file: &str) -> Result<Vec<u8>, io::Error> {
+        match &self.source {
+            Some(source) => source.read(file),
+            None => fs::read(self.disk_path(file)),
+        }
+    }
+
+    // How file's lines end and whether it has a byte order mark. The line
+    // endings git checks a file out with win over those .editorconfig asks
+    // for, files read from a source are as git stores them so only
+    // .editorconfig applies to them.
+    fn line_settings(&self, file: &str) -> editorconfig::Settings {
+        let mut settings = self.editorconfig(file);
+        if self.source.is_none() {
+            let gitattributes = self
+                .gitattributes
+                .get_or_init(|| GitAttributes::open(&self.disk_path(".")).map(RefCell::new));
+            if let Some(eol) = gitattributes
+                .as_ref()
+                .and_then(|attrs| attrs.borrow_mut().end_of_line(file))
+            {
+                settings.end_of_line = Some(eol);
+            }
+        }
+
+        settings
+    }
+
+    // The .editorconfig settings for file, reading .editorconfig files from
+    // the source if there is one.
+    fn editorconfig(&self, file: &str) -> editorconfig::Settings {
+        editorconfig::settings_for(file, |path| {
+            let path = path.to_string_lossy().to_string();
+            if let Some(config) = self.editorconfigs.borrow().get(&path) {
+                return config.clone();
+            }
+
+            let config = if self.exists(&path) {
+                match self.read(&path).map(String::from_utf8) {
+                    Ok(Ok(content)) => Some(Rc::new(EditorConfig::parse(&content))),
+                    _ => {
+                        warn!("unable to read {}, ignoring it", path);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            self.editorconfigs.borrow_mut().insert(path, config.clone());
+            config
+        })
+    }
+
+    // A sidecar is only recognised as such if the file it describes exists,
+    // otherwise it's just a file with a .license extension.
+    fn is_sidecar_file(&self, file: &str) -> bool {
+        match file.strip_suffix(SIDECAR_EXTENSION) {
+            Some(described) => {
+                described.ends_with('.') && self.exists(&described[..described.len() - 1])
+            }
+            None => false,
+        }
+    }
+
+    // Tell the visitor file was skipped for reason and record it in the
+    // stats.
+    fn skip(&mut self, file: &str, reason: SkipReason) {
+        trace_file!(file, "skipped: {}", reason);
+        self.visit(Event::FileSkipped {
+            file,
+            reason: reason.clone(),
+        });
+        self.stats.files_skipped.push((file.to_string(), reason));
+    }
+
+    // Skip a file which has been read, after its pre_process hook ran.
+    fn skip_read(&mut self, file: &str, reason: SkipReason) -> Result<Option<Action>, io::Error> {
+        self.skip(file, reason);
+        self.config
+            .hooks
+            .post_process(file, Action::Skipped.as_str())?;
+        Ok(Some(Action::Skipped))
+    }
+
+    // What happened to a file with status.
+    fn action(&self, status: &LicenseStatus) -> Action {
+        match status {
+            LicenseStatus::NeedsUpdate(..) if self.check_mode => Action::NeedsUpdate,
+            LicenseStatus::NeedsUpdate(..) if self.config.change_in_place => Action::Updated,
+            LicenseStatus::NeedsUpdate(..) => Action::Printed,
+            LicenseStatus::AlreadyLicensed => Action::AlreadyLicensed,
+            LicenseStatus::NoConfigMatched => Action::NotLicensed,
+            LicenseStatus::HasOtherHeader => Action::HasOtherHeader,
+            LicenseStatus::Skipped(_) => Action::Skipped,
+            LicenseStatus::TemplateError(_) => Action::TemplateError,
+            LicenseStatus::Suppressed(_) => Action::Suppressed,
+            LicenseStatus::Baselined(_) => Action::Baselined,
+            LicenseStatus::PreExisting(_) => Action::PreExisting,
+        }
+    }
+
+    fn handle_update(&mut self, file: &str, content: &str) -> Result<(), io::Error> {
+        if self.check_mode {
+            return Result::Ok(());
+        }
+
+        if self.config.change_in_place {
+            let mut f = File::create(self.disk_path(file))?;
+            return f.write_all(content.as_bytes());
+        }
+
+        match &mut self.output {
+            Some(output) => writeln!(output, "{}", content),
+            None => Result::Ok(()),
+        }
+    }
+
+    fn strip_shebang_if_found(content: &mut String) -> Option<String> {
+        // Can't use Option::map because of double borrow.
+        #[allow(clippy::manual_map)]
+        match shebang_re().find(content) {
+            // If we idenfied a shebang, strip it from content (we'll add it back at the end)
+            Some(shebang_match) => Some(content.drain(..shebang_match.end()).collect()),
+            None => None,
+        }
+    }
+
+    fn get_replaces_replacement(
+        &self,
+        file: &str,
+        replaces: &Vec<Regex>,
+        content: &str,
+        header: &str,
+    ) -> Option<String> {
+        for old in replaces {
+            if old.is_match(content) {
+                trace_file!(file, "the replaces regex {} matches", old.as_str());
+                return Some(old.replace(content, header).to_string());
+            }
+            // TODO: Add a check here with comments stripped from content
+        }
+        None
+    }
+
+    // Returns the start and length of the comment at the top of content,
+    // after any shebang, and which of idents it's the header of, if it's the
+    // header of one of them.
+    fn similar_header_len<'a>(
+        commenter: &dyn Comment,
+        content: &str,
+        idents: &[&'a str],
+    ) -> Option<(usize, usize, &'a str)> {
+        if idents.is_empty() {
+            return None;
+        }
+
+        let start = shebang_re().find(content).map_or(0, |m| m.end());
+        let len = commenter.leading_comment_len(&content[start..])?;
+        let ident = spdx::similar_license(&content[start..start + len], idents)?;
+        Some((start, len, ident))
+    }
+
+    // Returns the start and length of the comment at the top of content, after any
+    // shebang, if it holds a license or copyright notice.
+    fn other_header_len(commenter: &dyn Comment, content: &str) -> Option<(usize, usize)> {
+        let start = shebang_re().find(content).map_or(0, |m| m.end());
+        let len = commenter.leading_comment_len(&content[start..])?;
+        let comment = &content[start..start + len];
+        if detect::detect_licenses(comment).is_empty()
+            && detect::copyright_holders(comment).is_empty()
+        {
+            None
+        } else {
+            Some((start, len))
+        }
+    }
+
+    // The number of the first line of the license and copyright comments at
+    // the top of content which is wider than columns. Lines of the header
+    // licensure writes itself are left out since they're only wider when
+    // they can't be wrapped, such as for long URLs.
+    fn wide_header_line(&self, file: &str, content: &str) -> Option<usize> {
+        let columns = self.config.columns(file, content)?;
+        let rendered = self.rendered_header(file, content)?.ok()?;
+        let ours: HashSet<&str> = rendered.header.lines().map(str::trim_end).collect();
+
+        let mut offset = shebang_re().find(content).map_or(0, |m| m.end());
+        loop {
+            offset += content[offset..].len() - content[offset..].trim_start_matches('\n').len();
+            let len = rendered.commenter.leading_comment_len(&content[offset..])?;
+            let comment = &content[offset..offset + len];
+            if len == 0
+                || (detect::detect_licenses(comment).is_empty()
+                    && detect::copyright_holders(comment).is_empty())
+            {
+                return None;
+            }
+
+            let first_line = content[..offset].matches('\n').count() + 1;
+            let wide = comment.lines().position(|line| {
+                let line = line.trim_end();
+                display_width(line) > columns && !ours.contains(line)
+            });
+            if let Some(idx) = wide {
+                return Some(first_line + idx);
+            }
+            offset += len;
+        }
+    }
+
+    fn add_header(&self, mut header: String, content: &mut String) -> String {
+        if let Some(value) = Self::strip_shebang_if_found(content) {
+            debug!("found shebang {}", value.trim_end());
+            header.insert_str(0, &value);
+        }
+
+        header.push_str(content);
+        header
+    }
+
+    // Returns content with header on lines of its own at the end.
+    fn append_header(header: &str, content: &str) -> String {
+        let mut appended = content.to_string();
+        if !appended.is_empty() && !appended.ends_with('\n') {
+            appended.push('\n');
+        }
+        appended.push_str(header);
+        appended
+    }
+
+    // The template, commenter, and commented header for file, or None if no
+    // license config matches it. These only depend on the license config,
+    // comment config, and context which apply to file so they're rendered
+    // once for each combination and shared by every file with it.
+    fn rendered_header(
+        &self,
+        file: &str,
+        content: &str,
+    ) -> Option<Result<Rc<RenderedHeader>, String>> {
+        match self.config.licenses.find_for(file, content) {
+            Ok(Some((license_idx, license))) => {
+                Some(self.render_header(file, content, license_idx, license))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    // Comment configs can override how the license config renders text.
+    fn apply_comment_config(&self, file: &str, content: &str, context: &mut Context) {
+        if let Some(unwrap_text) = self.config.unwrap_text(file, content) {
+            context.unwrap_text = unwrap_text;
+        }
+    }
+
+    fn render_header(
+        &self,
+        file: &str,
+        content: &str,
+        license_idx: usize,
+        license: &LicenseConfig,
+    ) -> Result<Rc<RenderedHeader>, String> {
+        let (mut context, warning) = license.get_context_with_warning(file);
+        self.warnings.borrow_mut().extend(warning);
+        self.apply_comment_config(file, content, &mut context);
+        let key = HeaderKey {
+            license: license_idx,
+            comment: self.config.commenter_index(file, content),
+            context,
+        };
+        if let Some(rendered) = self.headers.borrow().get(&key) {
+            return Ok(Rc::clone(rendered));
+        }
+
+        let templ = self.template_for(license, file, content, key.context.clone())?;
+        let commenter = self.config.get_commenter(file, content);
+        let mut rendered = RenderedHeader::new(templ, commenter, license.fingerprint())?;
+        // Their fingerprints are stripped before they're looked for.
+        rendered.previous = license
+            .previous_templates(&key.context)
+            .into_iter()
+            .map(|templ| {
+                RenderedHeader::new(templ, self.config.get_commenter(file, content), false)
+            })
+            .collect::<Result<_, _>>()?;
+        let rendered = Rc::new(rendered);
+        self.headers.borrow_mut().insert(key, Rc::clone(&rendered));
+        Ok(rendered)
+    }
+
+    // The template license gives file with context, failing, naming the
+    // license, if its header would break the block comment it's written in.
+    fn template_for(
+        &self,
+        license: &LicenseConfig,
+        file: &str,
+        content: &str,
+        context: Context,
+    ) -> Result<Template, String> {
+        let ident = context.ident.clone();
+        let templ = license.get_template(file, context)?;
+        self.config
+            .check_header(file, content, &templ.render())
+            .map_err(|e| format!("the header of {} {}", ident, e))?;
+        Ok(templ)
+    }
+
+    /// Returns content with a header which is ours, but wrapped, spaced, or
+    /// commented differently than the config would write it now, rewritten
+    /// the way the config writes it. The header keeps the year it has. None
+    /// means there is no such header or it's already written that way.
+    pub fn normalize(&self, file: &str, content: &str) -> Result<Option<String>, String> {
+        if self.config.uses_sidecar(file) {
+            return Ok(None);
+        }
+        let Some((_, license)) = self.config.licenses.find_for(file, content)? else {
+            return Ok(None);
+        };
+
+        // The fingerprint is rendered again with the rest of the header.
+        let mut stripped = content.to_string();
+        if let Some(region) = self.header_region(file, content) {
+            strip_fingerprint(&mut stripped, region);
+        }
+
+        let mut context = license.get_context(file);
+        self.apply_comment_config(file, content, &mut context);
+        let pattern = license.get_template(file, context.clone())?.words_pattern();
+        let start = shebang_re().find(&stripped).map_or(0, |m| m.end());
+        let Some((len, year)) = normalize::find_header(&stripped[start..], &pattern) else {
+            return Ok(None);
+        };
+
+        context.end_year = Some(year);
+        context.start_year = None;
+        let header = RenderedHeader::new(
+            license.get_template(file, context)?,
+            self.config.get_commenter(file, content),
+            license.fingerprint(),
+        )?
+        .header;
+        let normalized = format!(
+            "{}{}{}",
+            &stripped[..start],
+            header,
+            &stripped[start + len..]
+        );
+
+        Ok(if normalized == content {
+            None
+        } else {
+            Some(normalized)
+        })
+    }
+
+    /// Returns the commented header license gives file, with the end year
+    /// pinned to year unless the license sets one so it's the same whenever
+    /// it's rendered.
+    pub fn render_sample(
+        &self,
+        license: &LicenseConfig,
+        file: &str,
+        year: &str,
+    ) -> Result<String, String> {
+        let mut context = license.get_sample_context(file, year);
+        self.apply_comment_config(file, "", &mut context);
+        let templ = self.template_for(license, file, "", context)?;
+        let commenter = self.config.get_commenter(file, "");
+        Ok(RenderedHeader::new(templ, commenter, license.fingerprint())?.header)
+    }
+
+    /// Checks that the header license gives file survives the round trip of
+    /// being written: added to a sample of file it must be updated from a
+    /// copy with an older year, and unless it's written to a sidecar file it
+    /// must be removable as the leading comment of the file. Fails describing the first step which doesn't.
+    pub fn roundtrip_sample(&self, license: &LicenseConfig, file: &str) -> Result<(), String> {
+        let render = |year: &str| -> Result<RenderedHeader, String> {
+            let mut context = license.get_sample_context(file, year);
+            self.apply_comment_config(file, "", &mut context);
+            let templ = self.template_for(license, file, "", context)?;
+            let commenter = self.config.get_commenter(file, "");
+            RenderedHeader::new(templ, commenter, license.fingerprint())
+        };
+        let rendered = render(ROUNDTRIP_YEAR)?;
+        let licensed = format!("{}{}", rendered.header, ROUNDTRIP_BODY);
+
+        let mut outdated = format!("{}{}", render(ROUNDTRIP_OLD_YEAR)?.header, ROUNDTRIP_BODY);
+        if rendered.fingerprint.is_some() {
+            strip_fingerprint(&mut outdated, (1, rendered.header.lines().count()));
+        }
+        match rendered.outdated_replacement(&outdated) {
+            Some(updated) if updated == licensed => (),
+            Some(updated) => {
+                return Err(format!(
+                    "updating the header from an older year gives:\n{}",
+                    updated
+                ))
+            }
+            None => return Err("the header isn't found once its year is out of date".to_string()),
+        }
+
+        // Sidecar headers are the whole of their own file.
+        if self.config.uses_sidecar(file) {
+            return Ok(());
+        }
+
+        let len = rendered
+            .commenter
+            .leading_comment_len(&licensed)
+            .ok_or_else(|| {
+                "the header isn't found as a comment so it can't be removed".to_string()
+            })?;
+        if licensed[len..].trim_start() != ROUNDTRIP_BODY {
+            return Err(format!("removing the header leaves:\n{}", &licensed[len..]));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the commented header file, with content, would be given
+    /// without changing anything. When ident is given the license config for
+    /// it is used instead of the one matching file.
+    pub fn preview(
+        &self,
+        file: &str,
+        content: &str,
+        ident: Option<&str>,
+    ) -> Result<String, String> {
+        let (license_idx, license) = match ident {
+            Some(ident) => self
+                .config
+                .licenses
+                .find_ident(ident)
+                .ok_or_else(|| format!("no license config has the ident {}", ident))?,
+            None => self
+                .config
+                .licenses
+                .find_for(file, content)?
+                .ok_or_else(|| format!("no license config matches {}", file))?,
+        };
+
+        let rendered = self.render_header(file, content, license_idx, license)?;
+        Ok(rendered.header.clone())
+    }
+
+    fn add_license_header(&self, file: &str, content: &mut String) -> LicenseStatus {
+        let rendered = match self.rendered_header(file, content) {
+            Some(Ok(rendered)) => rendered,
+            Some(Err(e)) => {
+                info!(
+                    "skipping {} because its template is unavailable: {}",
+                    file, e
+                );
+                return LicenseStatus::TemplateError(e);
+            }
+            None => {
+                info!("skipping {} because no license config matched.", file);
+                return LicenseStatus::NoConfigMatched;
+            }
+        };
+
+        trace_file!(file, "the rendered header is:\n{}", rendered.header);
+        if let Some(fingerprint) = &rendered.fingerprint {
+            if let Some(region) = self.header_region(file, content) {
+                if has_fingerprint(content, fingerprint, region) {
+                    trace_file!(file, "the file has the header's fingerprint");
+                    info!("{} already licensed", file);
+                    return LicenseStatus::AlreadyLicensed;
+                }
+                strip_fingerprint(content, region);
+            }
+        }
+
+        let commenter = &rendered.commenter;
+        let header = &rendered.header;
+        if content.contains(header) || content.contains(header.trim_end()) {
+            trace_file!(file, "the file has the rendered header");
+            info!("{} already licensed", file);
+            return LicenseStatus::AlreadyLicensed;
+        }
+
+        if let Some(update) = rendered.outdated_replacement(content) {
+            trace_file!(file, "the file has the header with other years");
+            info!("{} licensed, but year is outdated", file);
+            return LicenseStatus::NeedsUpdate(update, Rule::OutdatedYear);
+        }
+
+        if self.years_only {
+            info!("skipping {} because it has no header to update", file);
+            return LicenseStatus::Skipped(SkipReason::NoHeader);
+        }
+
+        for previous in &rendered.previous {
+            if let Some(update) = previous.replace_outdated(content, header) {
+                trace_file!(
+                    file,
+                    "the file has the header of a previous template_file:\n{}",
+                    previous.header
+                );
+                info!("{} licensed, but template_file has changed since", file);
+                return LicenseStatus::NeedsUpdate(update, Rule::WrongLicense);
+            }
+        }
+
+        let license = self
+            .config
+            .licenses
+            .find_for(file, content)
+            .ok()
+            .flatten()
+            .map(|(_, license)| license);
+        if let Some(replaces) = license.and_then(|license| license.get_replaces().as_ref()) {
+            if let Some(update) = self.get_replaces_replacement(file, replaces, content, header) {
+                info!("{} licensed, but license is outdated", file);
+                return LicenseStatus::NeedsUpdate(update, Rule::WrongLicense);
+            }
+        }
+
+        if let Some(license) = license {
+            let idents = license.replaces_licenses();
+            if let Some((start, len, ident)) =
+                Self::similar_header_len(commenter.as_ref(), content, &idents)
+            {
+                trace_file!(file, "the leading comment is the header of {}", ident);
+                info!("{} has a {} header, replacing it", file, ident);
+                content.replace_range(start..start + len, "");
+                return LicenseStatus::NeedsUpdate(
+                    self.insert_header(file, header, content),
+                    Rule::WrongLicense,
+                );
+            }
+        }
+
+        if let Some(lines) = self.config.header_search_lines {
+            if let Some(update) = rendered.moved_replacement(content, lines) {
+                trace_file!(file, "the header was found below the top of the file");
+                info!("{} licensed below the top of the file, updating it", file);
+                return LicenseStatus::NeedsUpdate(update, Rule::MissingHeader);
+            }
+        }
+
+        let policy = license.map_or(
+            ExistingHeaderPolicy::default(),
+            LicenseConfig::skip_existing,
+        );
+        let mut rule = Rule::MissingHeader;
+        if policy != ExistingHeaderPolicy::Prepend {
+            if let Some((start, len)) = Self::other_header_len(commenter.as_ref(), content) {
+                trace_file!(
+                    file,
+                    "the leading comment is another license header, skip_existing is {:?}",
+                    policy
+                );
+                match policy {
+                    ExistingHeaderPolicy::Replace => {
+                        info!("{} has another license header, replacing it", file);
+                        content.replace_range(start..start + len, "");
+                        rule = Rule::ForeignCopyright;
+                    }
+                    ExistingHeaderPolicy::Skip => {
+                        info!("skipping {} because it has another license header", file);
+                        return LicenseStatus::Skipped(SkipReason::ForeignCopyright);
+                    }
+                    ExistingHeaderPolicy::Error => {
+                        info!("{} has another license header", file);
+                        return LicenseStatus::HasOtherHeader;
+                    }
+                    ExistingHeaderPolicy::Prepend => (),
+                }
+            }
+        }
+
+        LicenseStatus::NeedsUpdate(self.insert_header(file, header, content), rule)
+    }
+
+    // Add header to content at the position configured for file.
+    fn insert_header(&self, file: &str, header: &str, content: &mut String) -> String {
+        let position = self.config.position(file, content);
+        trace_file!(file, "adding the header at the {:?}", position);
+        match position {
+            Position::Top => self.add_header(header.to_string(), content),
+            Position::Bottom => Self::append_header(header, content),
+        }
+    }
+}
+
+/// The files a [`Processor`] has processed, grouped by what happened to them.
+#[derive(Default)]
+pub struct LicenseStats {
+    pub files_not_licensed: Vec<String>,
+    pub files_needing_license_update: Vec<String>,
+    // The files whose header was written in place, a subset of
+    // files_needing_license_update.
+    pub files_updated: Vec<String>,
+    pub files_already_licensed: Vec<String>,
+    pub files_with_other_header: Vec<String>,
+    // Files whose header comments have a line wider than columns, with the
+    // number of the first such line, found when checking.
+    pub files_with_wide_headers: Vec<(String, usize)>,
+    // Every file which was skipped, with why, in the order they were
+    // processed.
+    pub files_skipped: Vec<(String, SkipReason)>,
+    // Files which couldn't be licensed keyed by why their template couldn't
+    // be loaded.
+    pub template_errors: BTreeMap<String, Vec<String>>,
+    // Files which couldn't be processed, such as because they couldn't be
+    // read or aren't UTF-8, with why.
+    pub files_failed: Vec<(String, io::Error)>,
+    // Problems which didn't stop a file from being processed, such as git
+    // not knowing the years of a file.
+    pub warnings: Vec<String>,
+    // The change to each file needing an update, with with_patch.
+    pub patches: Vec<FilePatch>,
+    // The fix for each file needing an update, with with_fixes.
+    pub fixes: Vec<Fix>,
+    // Every file needing an update or with another header it isn't allowed
+    // to have, with the rule it was found by.
+    pub findings: Vec<(String, Rule)>,
+    // Files whose finding is suppressed, which were left alone.
+    pub files_suppressed: Vec<(String, Rule)>,
+    // Files whose finding is in the baseline, which were left alone.
+    pub files_baselined: Vec<(String, Rule)>,
+    // Files whose finding is in a header the changes being checked don't
+    // touch, which were left alone.
+    pub files_pre_existing: Vec<(String, Rule)>,
+}
+
+/// The change licensing one file makes, as a hunk of a unified diff.
+pub struct FilePatch {
+    pub file: String,
+    pub hunk: String,
+    // The file doesn't exist yet, as for a new sidecar file.
+    pub new_file: bool,
+}
+
+/// A fix for the header of one file, for editors and review bots to apply
+/// without running licensure.
+pub struct Fix {
+    pub file: String,
+    /// The rule the file was found by.
+    pub rule: Rule,
+    /// The header the file should have, with the file's line endings.
+    pub header: String,
+    /// The text replacing the part of the file the header is in or belongs
+    /// at, which may add, update, or replace a header.
+    pub replacement: Replacement,
+}
+
+impl fmt::Display for FilePatch {
+    /// Formats the change as a diff git apply reads.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", git_diff(&self.file, &self.hunk, self.new_file))
+    }
+}
+
+impl LicenseStats {
+    /// Add the results of other, whose files are relative to dir, to these.
+    pub fn merge(&mut self, other: LicenseStats, dir: &str) {
+        let dir = Path::new(dir);
+        let join = |file: String| dir.join(file).to_string_lossy().to_string();
+        let join_all = |files: Vec<String>| files.into_iter().map(join);
+
+        self.files_not_licensed
+            .extend(join_all(other.files_not_licensed));
+        self.files_needing_license_update
+            .extend(join_all(other.files_needing_license_update));
+        self.files_updated.extend(join_all(other.files_updated));
+        self.files_already_licensed
+            .extend(join_all(other.files_already_licensed));
+        self.files_with_other_header
+            .extend(join_all(other.files_with_other_header));
+        self.files_with_wide_headers.extend(
+            other
+                .files_with_wide_headers
+                .into_iter()
+                .map(|(file, line)| (join(file), line)),
+        );
+        self.files_skipped.extend(
+            other
+                .files_skipped
+                .into_iter()
+                .map(|(file, reason)| (join(file), reason)),
+        );
+        for (error, files) in other.template_errors {
+            self.template_errors
+                .entry(error)
+                .or_default()
+                .extend(join_all(files));
+        }
+        self.files_failed.extend(
+            other
+                .files_failed
+                .into_iter()
+                .map(|(file, e)| (join(file), e)),
+        );
+        self.warnings.extend(other.warnings);
+        self.patches
+            .extend(other.patches.into_iter().map(|patch| FilePatch {
+                file: join(patch.file),
+                ..patch
+            }));
+        self.findings.extend(
+            other
+                .findings
+                .into_iter()
+                .map(|(file, rule)| (join(file), rule)),
+        );
+        self.files_suppressed.extend(
+            other
+                .files_suppressed
+                .into_iter()
+                .map(|(file, rule)| (join(file), rule)),
+        );
+        self.files_baselined.extend(
+            other
+                .files_baselined
+                .into_iter()
+                .map(|(file, rule)| (join(file), rule)),
+        );
+        self.fixes.extend(other.fixes.into_iter().map(|fix| Fix {
+            file: join(fix.file),
+            ..fix
+        }));
+        self.files_pre_existing.extend(
+            other
+                .files_pre_existing
+                .into_iter()
+                .map(|(file, rule)| (join(file), rule)),
+        );
+    }
+
+    fn new() -> Self {
+        Self {
+            files_not_licensed: Vec::new(),
+            files_needing_license_update: Vec::new(),
+            files_updated: Vec::new(),
+            files_already_licensed: Vec::new(),
+            files_with_other_header: Vec::new(),
+            files_with_wide_headers: Vec::new(),
+            files_skipped: Vec::new(),
+            template_errors: BTreeMap::new(),
+            files_failed: Vec::new(),
+            warnings: Vec::new(),
+            patches: Vec::new(),
+            findings: Vec::new(),
+            files_suppressed: Vec::new(),
+            files_baselined: Vec::new(),
+            files_pre_existing: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    use crate::config::{Config, FileSize};
+    use crate::template::test_context_with_range;
+    use crate::testing::{GitRepo, TempDir};
+    use crate::GitTree;
+    use crate::{
+        comments::LineComment,
+        template::{test_context, Template},
+    };
+
+    #[test]
+    fn test_detects_outdated_year() {
+        let templ = Template::new("License [year]\n\ntext", test_context("2024"));
+        let commenter = LineComment::new("#", None);
+        let rendered = RenderedHeader::new(templ, Box::new(commenter), false).unwrap();
+        let content = "# License 2020\n#\n# text";
+        let result = rendered.outdated_replacement(content);
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn test_detects_outdated_year_range() {
+        let templ = Template::new(
+            "License [year]\n\ntext",
+            test_context_with_range("2020", "2024"),
+        );
+        let commenter = LineComment::new("#", None);
+        let rendered = RenderedHeader::new(templ, Box::new(commenter), false).unwrap();
+        let content = "# License 2020, 2023\n#\n# text";
+        let result = rendered.outdated_replacement(content);
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn test_detects_outdated_year_range_when_previous_header_wasnt_a_range() {
+        let templ = Template::new(
+            "License [year]\n\ntext",
+            test_context_with_range("2020", "2024"),
+        );
+        let commenter = LineComment::new("#", None);
+        let rendered = RenderedHeader::new(templ, Box::new(commenter), false).unwrap();
+        let content = "# License 2020\n#\n# text";
+        let result = rendered.outdated_replacement(content);
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn test_detects_outdated_year_trailing_whitespace() {
+        let templ = Template::new("License [year]\n\ntext", test_context("2024"));
+        let commenter = LineComment::new("#", None);
+        let rendered = RenderedHeader::new(templ, Box::new(commenter), false).unwrap();
+        let content = "# License 2020\n#\n# text\n";
+        let result = rendered.outdated_replacement(content);
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn test_detects_replaces() {
+        let l = Processor::new(Config::default());
+        let replaces = vec![
+            Regex::new("This first regex is not going to hit").expect("Can compile static regex"),
+            Regex::new("(// *)?foo \\(C\\) .* another thing\n?").expect("Can compile static regex"),
+        ];
+        let templ = Template::new("License [year]\n\ntext", test_context("2024"));
+        let commenter = LineComment::new("//", None);
+        let header = commenter.comment(&templ.render()).unwrap();
+        let content = "BEFORE// foo (C) fill fill fill another thing\nAFTER";
+        let result = l.get_replaces_replacement("test.py", &replaces, content, &header);
+        eprintln!("{:?}", result);
+        assert!(result.is_some());
+        assert!(result
+            .unwrap()
+            .eq("BEFORE// License 2024\n//\n// text\nAFTER"));
+    }
+
+    #[test]
+    fn test_add_header() {
+        let l = Processor::new(Config::default());
+        let templ = Template::new("License [year]\n\ntext", test_context("2024"));
+        let commenter = LineComment::new("#", None);
+        let header = commenter.comment(&templ.render()).unwrap();
+        let mut content = r#"
+def main():
+    print('hello world')
+
+if __name__ == '__main__':
+    main()
+"#
+        .to_string();
+        let result = l.add_header(header, &mut content);
+        assert_eq!(
+            result,
+            r#"# License 2024
+#
+# text
+
+def main():
+    print('hello world')
+
+if __name__ == '__main__':
+    main()
+"#
+        )
+    }
+
+    #[test]
+    fn test_add_header_handles_shebang() {
+        let l = Processor::new(Config::default());
+        let templ = Template::new("License [year]\n\ntext", test_context("2024"));
+        let commenter = LineComment::new("#", None);
+        let header = commenter.comment(&templ.render()).unwrap();
+        let mut content = r#"#!/usr/bin/env python3
+
+def main():
+    print('hello world')
+
+if __name__ == '__main__':
+    main()
+"#
+        .to_string();
+        let expected = r#"#!/usr/bin/env python3
+# License 2024
+#
+# text
+
+def main():
+    print('hello world')
+
+if __name__ == '__main__':
+    main()
+"#;
+
+        let result = l.add_header(header, &mut content);
+        println!("result: {}", result);
+        println!("----------------------");
+        println!("expected: {}", expected);
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test_add_header_ignores_shebang_in_middle_of_file() {
+        let l = Processor::new(Config::default());
+        let templ = Template::new("License [year]\n\ntext", test_context("2024"));
+        let commenter = LineComment::new("#", None);
+        let header = commenter.comment(&templ.render()).unwrap();
+        let mut content = r#"
+def main():
+    print('hello world')
+
+#!/usr/bin/env python3
+
+if __name__ == '__main__':
+    main()
+"#
+        .to_string();
+        let expected = r#"# License 2024
+#
+# text
+
+def main():
+    print('hello world')
+
+#!/usr/bin/env python3
+
+if __name__ == '__main__':
+    main()
+"#;
+
+        let result = l.add_header(header, &mut content);
+        assert_eq!(result, expected)
+    }
+
+    static CONFIG_WITH_REPLACES: &str = r##"
+excludes: []
+licenses:
+  - files: any
+    ident: TESTING
+    authors:
+      - name: The Tester
+    template: "New Test License [name of author]\nOnly For Testing"
+    replaces:
+      - "(# *)?Before replacement\n?"
+comments:
+  - columns: 80
+    extensions:
+      - py
+    commenter:
+      type: line
+      comment_char: "#""##;
+
+    #[test]
+    fn test_add_license_header_with_replaces() {
+        let config: Config =
+            serde_yaml::from_str(CONFIG_WITH_REPLACES).expect("Static config to be parsable");
+        let l = Processor::new(config);
+        let mut content = r#"
+# Before replacement
+def main():
+    print('hello world')
+
+if __name__ == '__main__':
+    main()
+"#
+        .to_string();
+        let result = l.add_license_header("test_file.py", &mut content);
+        assert_eq!(
+            result,
+            LicenseStatus::NeedsUpdate(
+                r#"
+# New Test License The Tester Only For Testing
+def main():
+    print('hello world')
+
+if __name__ == '__main__':
+    main()
+"#
+                .to_string(),
+                Rule::WrongLicense
+            )
+        )
+    }
+
+    static CONFIG_WITH_SIDECAR: &str = r##"
+change_in_place: true
+excludes: []
+licenses:
+  - files: any
+    ident: TESTING
+    authors:
+      - name: The Tester
+    template: "SPDX-FileCopyrightText: [name of author]\n\nSPDX-License-Identifier: [ident]"
+comments:
+  - extension: json
+    commenter:
+      type: sidecar"##;
+
+    #[test]
+    fn test_license_files_writes_sidecar() {
+        let dir = TempDir::new("sidecar");
+        let data = dir.file("data.json");
+        let sidecar = format!("{}.license", data);
+        std::fs::write(&data, "{}").expect("Can write data file");
+
+        let config: Config =
+            serde_yaml::from_str(CONFIG_WITH_SIDECAR).expect("Static config to be parsable");
+        let stats = Processor::new(config)
+            .license_files(std::slice::from_ref(&data))
+            .expect("Can license files");
+        assert_eq!(vec![sidecar.clone()], stats.files_needing_license_update);
+        assert_eq!("{}", std::fs::read_to_string(&data).unwrap());
+        assert_eq!(
+            "SPDX-FileCopyrightText: The Tester\n\nSPDX-License-Identifier: TESTING\n",
+            std::fs::read_to_string(&sidecar).unwrap()
+        );
+
+        // The sidecar itself is never licensed and the data file is now
+        // compliant.
+        let config: Config =
+            serde_yaml::from_str(CONFIG_WITH_SIDECAR).expect("Static config to be parsable");
+        let stats = Processor::new(config)
+            .with_check_mode(true)
+            .license_files(&[data.clone(), sidecar.clone()])
+            .expect("Can check files");
+        assert!(stats.files_needing_license_update.is_empty());
+        assert!(stats.files_not_licensed.is_empty());
+    }
+
+    // Records the events it's sent as strings and vetoes every write.
+    struct VetoingVisitor(Rc<RefCell<Vec<String>>>);
+
+    impl Visitor for VetoingVisitor {
+        fn visit(&mut self, event: &Event) -> Verdict {
+            let name = match event {
+                Event::FileMatched { .. } => "matched".to_string(),
+                Event::HeaderMissing { .. } => "missing".to_string(),
+                Event::HeaderUpdated { diff, .. } => {
+                    format!("updated {}", diff.lines().nth(3).unwrap_or_default())
+                }
+                Event::FileSkipped { reason, .. } => format!("skipped {:?}", reason),
+            };
+            self.0.borrow_mut().push(name);
+            Verdict::Veto
+        }
+    }
+
+    #[test]
+    fn test_visitor_can_veto_writes() {
+        let dir = TempDir::new("visitor");
+        let file = dir.file("a.py");
+        std::fs::write(&file, "print('hi')\n").expect("Can write file");
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut processor = Processor::new(config_with_limits("change_in_place: true"))
+            .with_visitor(Box::new(VetoingVisitor(Rc::clone(&events))));
+        assert_eq!(
+            Some(Action::NeedsUpdate),
+            processor.process_file(&file).unwrap()
+        );
+        assert_eq!(None, processor.process_file("ignored.py").unwrap());
+        assert_eq!(
+            vec![
+                "matched",
+                "missing",
+                "updated +# Copyright The Tester, TESTING",
+                "skipped Excluded"
+            ],
+            *events.borrow()
+        );
+        assert_eq!("print('hi')\n", std::fs::read_to_string(&file).unwrap());
+    }
+
+    #[test]
+    fn test_block_comment_end_in_header() {
+        let config = |escape: &str| {
+            format!(
+                r##"
+licenses:
+  - files: any
+    ident: TESTING
+    authors: []
+    template: "Matches src/*/lib.c, [ident]"
+comments:
+  - extension: c
+    commenter:
+      type: block
+      start_block_char: "/**\n"
+      end_block_char: "*/\n"
+      {}"##,
+                escape
+            )
+        };
+
+        // As a header fetched from SPDX would be, unchecked when loaded.
+        let unchecked: Config =
+            serde_yaml::from_str(&config("")).expect("Static config to be parsable");
+        let processor = Processor::new(unchecked);
+        let license = processor.config().licenses.iter().next().unwrap();
+        assert!(processor
+            .render_sample(license, "a.c", "2024")
+            .unwrap_err()
+            .starts_with("the header of TESTING contains */, which would end the comments of comment config 1 early"));
+        assert!(Config::from_str(&config("")).is_err());
+
+        let processor = Processor::new(
+            Config::from_str(&config(r#"end_block_char_escape: "*\\/""#))
+                .expect("Static config to be parsable"),
+        );
+        let license = processor.config().licenses.iter().next().unwrap();
+        assert_eq!(
+            Ok("/**\nMatches src/*\\/lib.c, TESTING*/\n".to_string()),
+            processor.render_sample(license, "a.c", "2024")
+        );
+        assert_eq!(Ok(()), processor.roundtrip_sample(license, "a.c"));
+    }
+
+    fn config_with_limits(limits: &str) -> Config {
+        serde_yaml::from_str(&format!(
+            r##"
+{}
+excludes: [ignored\.py]
+licenses:
+  - files: any
+    ident: TESTING
+    authors:
+      - name: The Tester
+    template: "Copyright The Tester, [ident]"
+comments:
+  - extension: any
+    commenter:
+      type: line
+      comment_char: "#""##,
+            limits
+        ))
+        .expect("Static config to be parsable")
+    }
+
+    #[test]
+    fn test_max_file_size() {
+        let dir = TempDir::new("max-size");
+        let small = dir.file("small.py");
+        let large = dir.file("large.py");
+        std::fs::write(&small, "print('hi')\n").expect("Can write small file");
+        std::fs::write(&large, "#".repeat(2048)).expect("Can write large file");
+
+        let stats = Processor::new(config_with_limits("max_file_size: 1KB"))
+            .with_check_mode(true)
+            .license_files(&[small.clone(), large.clone()])
+            .expect("Can check files");
+        assert_eq!(vec![(large, SkipReason::TooLarge)], stats.files_skipped);
+        assert_eq!(vec![small], stats.files_needing_license_update);
+    }
+
+    #[test]
+    fn test_editorconfig() {
+        let dir = TempDir::new("editorconfig");
+        std::fs::write(
+            dir.join(".editorconfig"),
+            "root = true\n\n[*.py]\nend_of_line = crlf\n\n[*.sh]\ncharset = utf-8-bom\n",
+        )
+        .expect("Can write .editorconfig");
+        let crlf = dir.file("crlf.py");
+        let bom = dir.file("bom.sh");
+        std::fs::write(&crlf, "print('hi')\r\n").expect("Can write crlf file");
+        std::fs::write(&bom, "echo hi\n").expect("Can write bom file");
+        let files = [crlf.clone(), bom.clone()];
+
+        let stats = Processor::new(config_with_limits("change_in_place: true"))
+            .license_files(&files)
+            .expect("Can license files");
+        assert_eq!(files.to_vec(), stats.files_updated);
+        assert_eq!(
+            "# Copyright The Tester, TESTING\r\nprint('hi')\r\n",
+            std::fs::read_to_string(&crlf).unwrap()
+        );
+        assert_eq!(
+            "\u{feff}# Copyright The Tester, TESTING\necho hi\n",
+            std::fs::read_to_string(&bom).unwrap()
+        );
+
+        // The headers written are recognised.
+        let stats = Processor::new(config_with_limits("change_in_place: true"))
+            .with_check_mode(true)
+            .license_files(&files)
+            .expect("Can check files");
+        assert_eq!(files.to_vec(), stats.files_already_licensed);
+    }
+
+    #[test]
+    fn test_skip_reasons() {
+        let dir = TempDir::new("skips");
+        let binary = dir.file("data.py");
+        let generated = dir.file("gen.py");
+        std::fs::write(&binary, b"\0\x01").expect("Can write binary file");
+        std::fs::write(&generated, "# @generated by a tool\n").expect("Can write generated file");
+        let exempt = dir.file("vendored.py");
+        std::fs::write(&exempt, "# licensure: ignore\n").expect("Can write exempt file");
+
+        let files = vec![
+            "ignored.py".to_string(),
+            binary.clone(),
+            generated.clone(),
+            exempt.clone(),
+        ];
+        let stats = Processor::new(config_with_limits("skip_generated: true"))
+            .with_check_mode(true)
+            .license_files(&files)
+            .expect("Can check files");
+        assert_eq!(
+            vec![
+                ("ignored.py".to_string(), SkipReason::Excluded),
+                (binary, SkipReason::Binary),
+                (generated.clone(), SkipReason::Generated),
+                (exempt, SkipReason::Exempt),
+            ],
+            stats.files_skipped
+        );
+
+        let stats = Processor::new(config_with_limits(""))
+            .with_check_mode(true)
+            .license_files(&files[2..3])
+            .expect("Can check files");
+        assert_eq!(vec![generated], stats.files_needing_license_update);
+    }
+
+    #[test]
+    fn test_empty_files() {
+        let dir = TempDir::new("empty");
+        let empty = dir.file("__init__.py");
+        let blank = dir.file("blank.py");
+        std::fs::write(&empty, "").expect("Can write empty file");
+        std::fs::write(&blank, " \n\n").expect("Can write blank file");
+        let files = vec![empty.clone(), blank.clone()];
+
+        let stats = Processor::new(config_with_limits(""))
+            .with_check_mode(true)
+            .license_files(&files)
+            .expect("Can check files");
+        assert_eq!(files, stats.files_needing_license_update);
+
+        let stats = Processor::new(config_with_limits("empty_files: skip"))
+            .with_check_mode(true)
+            .license_files(&files)
+            .expect("Can check files");
+        assert_eq!(
+            vec![
+                (empty.clone(), SkipReason::Empty),
+                (blank.clone(), SkipReason::Empty)
+            ],
+            stats.files_skipped
+        );
+
+        let stats = Processor::new(config_with_limits("empty_files: error"))
+            .with_check_mode(true)
+            .license_files(&files)
+            .expect("Can check files");
+        let failed: Vec<&String> = stats.files_failed.iter().map(|(file, _)| file).collect();
+        assert_eq!(vec![&empty, &blank], failed);
+
+        // Files no license config covers aren't failed.
+        let config = r##"
+empty_files: error
+licenses:
+  - files: '\.rs$'
+    ident: MIT
+    authors: []
+    template: "Copyright The Tester""##;
+        let stats = Processor::new(Config::from_str(config).expect("Static config to be parsable"))
+            .with_check_mode(true)
+            .license_files(&files)
+            .expect("Can check files");
+        assert!(stats.files_failed.is_empty());
+        assert_eq!(
+            vec![
+                (empty, SkipReason::NoMatcher),
+                (blank, SkipReason::NoMatcher)
+            ],
+            stats.files_skipped
+        );
+    }
+
+    #[test]
+    fn test_failed_files_dont_stop_the_run() {
+        let dir = TempDir::new("failed");
+        let latin1 = dir.file("latin1.py");
+        let missing = dir.file("missing.py");
+        let good = dir.file("good.py");
+        std::fs::write(&latin1, b"print('caf\xe9')\n").expect("Can write latin1 file");
+        std::fs::write(&good, "print('hi')\n").expect("Can write good file");
+
+        let stats = Processor::new(config_with_limits(""))
+            .with_check_mode(true)
+            .license_files(&[latin1.clone(), missing.clone(), good.clone()])
+            .expect("Failed files are recorded");
+        let failed: Vec<(&str, io::ErrorKind)> = stats
+            .files_failed
+            .iter()
+            .map(|(file, e)| (file.as_str(), e.kind()))
+            .collect();
+        assert_eq!(
+            vec![
+                (latin1.as_str(), io::ErrorKind::InvalidData),
+                (missing.as_str(), io::ErrorKind::NotFound),
+            ],
+            failed
+        );
+        assert_eq!(vec![good], stats.files_needing_license_update);
+    }
+
+    #[test]
+    fn test_git_failure_uses_configured_years() {
+        // Outside of the repository git log fails.
+        let dir = TempDir::new("git-years");
+        let file = dir.file("a.py");
+        std::fs::write(&file, "print('hi')\n").expect("Can write file");
+
+        let config = Config::from_str(
+            r##"
+licenses:
+  - files: any
+    ident: TESTING
+    end_year: "2020"
+    use_dynamic_year_ranges: true
+    template: "Copyright [year]""##,
+        )
+        .expect("Static config to be parsable");
+        let mut processor = Processor::new(config).with_check_mode(true);
+        assert_eq!(
+            Some(Action::NeedsUpdate),
+            processor.process_file(&file).unwrap()
+        );
+        assert_eq!(1, processor.stats().warnings.len());
+        assert!(processor.stats().warnings[0]
+            .starts_with(&format!("unable to get the years of {} from git", file)));
+        assert_eq!(
+            Ok("# Copyright 2020\n".to_string()),
+            processor.preview(&file, "", None)
+        );
+    }
+
+    #[test]
+    fn test_filesystem_years() {
+        let dir = TempDir::new("fs-years");
+        let file = dir.file("a.py");
+        std::fs::write(&file, "print('hi')\n").expect("Can write file");
+        // 2001-09-09
+        let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .and_then(|f| f.set_modified(modified))
+            .expect("Can set modification time");
+
+        let config = Config::from_str(
+            r##"
+licenses:
+  - files: any
+    ident: TESTING
+    year_source: filesystem
+    template: "Copyright [year]""##,
+        )
+        .expect("Static config to be parsable");
+        let mut processor = Processor::new(config).with_check_mode(true);
+        assert_eq!(
+            Some(Action::NeedsUpdate),
+            processor.process_file(&file).unwrap()
+        );
+        assert!(processor.stats().warnings.is_empty());
+        assert_eq!(
+            Ok("# Copyright 2001\n".to_string()),
+            processor.preview(&file, "", None)
+        );
+    }
+
+    #[test]
+    fn test_patch() {
+        let dir = TempDir::new("patch");
+        let file = dir.file("a.py");
+        std::fs::write(&file, "print('hi')\n").expect("Can write file");
+
+        let config = Config::from_str(
+            r##"
+licenses:
+  - files: any
+    ident: TESTING
+    end_year: "2024"
+    template: "Copyright [year]""##,
+        )
+        .expect("Static config to be parsable");
+        let stats = Processor::new(config)
+            .with_check_mode(true)
+            .with_patch(true)
+            .with_fixes(true)
+            .license_files(std::slice::from_ref(&file))
+            .unwrap();
+        assert_eq!(1, stats.patches.len());
+        assert_eq!(1, stats.fixes.len());
+        assert_eq!(Rule::MissingHeader, stats.fixes[0].rule);
+        assert_eq!("# Copyright 2024\n", stats.fixes[0].header);
+        assert_eq!(
+            Replacement {
+                start_line: 1,
+                start_column: 1,
+                end_line: 1,
+                end_column: 1,
+                text: "# Copyright 2024\n".to_string(),
+            },
+            stats.fixes[0].replacement
+        );
+        assert_eq!(
+            format!(
+                "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n@@ -1,1 +1,2 @@\n+# Copyright 2024\n print('hi')\n",
+                file
+            ),
+            stats.patches[0].to_string()
+        );
+        // Check mode leaves the file alone.
+        assert_eq!("print('hi')\n", std::fs::read_to_string(&file).unwrap());
+    }
+
+    #[test]
+    fn test_years_only() {
+        let dir = TempDir::new("years");
+        let path = |name: &str| dir.file(name);
+        let files = vec![path("outdated.py"), path("missing.py"), path("other.py")];
+        std::fs::write(&files[0], "# Copyright 2020 Jo\nprint('hi')\n").unwrap();
+        std::fs::write(&files[1], "print('hi')\n").unwrap();
+        std::fs::write(&files[2], "# Copyright 2020 Someone Else\nprint('hi')\n").unwrap();
+
+        let mut config = Config::from_str(
+            r##"
+licenses:
+  - files: any
+    ident: TESTING
+    authors:
+      - name: Jo
+    end_year: "2024"
+    template: "Copyright [year] [name of author]""##,
+        )
+        .expect("Static config to be parsable");
+        config.change_in_place = true;
+        let stats = Processor::new(config)
+            .with_years_only(true)
+            .license_files(&files)
+            .unwrap();
+
+        assert_eq!(vec![files[0].clone()], stats.files_updated);
+        assert_eq!(
+            "# Copyright 2024 Jo\nprint('hi')\n",
+            std::fs::read_to_string(&files[0]).unwrap()
+        );
+        assert_eq!(
+            vec![
+                (files[1].clone(), SkipReason::NoHeader),
+                (files[2].clone(), SkipReason::NoHeader)
+            ],
+            stats.files_skipped
+        );
+        assert_eq!("print('hi')\n", std::fs::read_to_string(&files[1]).unwrap());
+    }
+
+    #[test]
+    fn test_git_tree() {
+        let dir = GitRepo::new("tree");
+        dir.write("licensed.py", "# Copyright 2024\nprint('hi')\n");
+        dir.write("unlicensed.py", "print('hi')\n");
+        dir.commit_all("first");
+        // Only what was committed is checked.
+        dir.write("unlicensed.py", "# Copyright 2024\nprint('hi')\n");
+
+        let tree = GitTree::open(&dir, "HEAD").expect("Can read HEAD");
+        assert_eq!(vec!["licensed.py", "unlicensed.py"], tree.files());
+        assert_eq!(12, tree.size("unlicensed.py").unwrap());
+        assert!(tree.read("missing.py").is_err());
+
+        let config = Config::from_str(
+            r##"
+licenses:
+  - files: any
+    ident: TESTING
+    end_year: "2024"
+    template: "Copyright [year]""##,
+        )
+        .expect("Static config to be parsable");
+        // Paths are relative to the repository, which is the current
+        // directory when licensure checks a ref.
+        let files = tree.files().to_vec();
+        let mut processor = Processor::new(config).with_source(Box::new(tree));
+        for file in &files {
+            processor.process_file(file).expect("Can check file");
+        }
+        assert_eq!(
+            vec!["licensed.py"],
+            processor.stats().files_already_licensed
+        );
+        assert_eq!(
+            vec!["unlicensed.py"],
+            processor.stats().files_needing_license_update
+        );
+    }
+
+    #[test]
+    fn test_max_files() {
+        let files = vec!["ignored.py".to_string(), "missing.py".to_string()];
+        let err = Processor::new(config_with_limits("max_files: 0"))
+            .with_check_mode(true)
+            .license_files(&files)
+            .err()
+            .expect("More than max_files is an error");
+        assert!(err.to_string().contains("1 files matched"));
+
+        // Excluded files don't count towards the limit.
+        let stats = Processor::new(config_with_limits("max_files: 1"))
+            .with_check_mode(true)
+            .license_files(&files[..1])
+            .expect("Can check files");
+        assert!(stats.files_needing_license_update.is_empty());
+    }
+
+    #[test]
+    fn test_preview() {
+        let config = Config::from_str(
+            r##"
+licenses:
+  - files: any
+    ident: MIT
+    template: "[ident]"
+  - files: vendor/.*
+    ident: Apache-2.0
+    template: "[ident]"
+comments:
+  - extension: rs
+    commenter:
+      type: line
+      comment_char: "//"
+  - extension: any
+    commenter:
+      type: line
+      comment_char: "#""##,
+        )
+        .expect("Static config to be parsable");
+        let processor = Processor::new(config);
+        assert_eq!(
+            Ok("// MIT\n".to_string()),
+            processor.preview("a.rs", "", None)
+        );
+        assert_eq!(
+            Ok("# Apache-2.0\n".to_string()),
+            processor.preview("script", "#!/bin/sh\n", Some("Apache-2.0"))
+        );
+        assert_eq!(
+            Err("no license config has the ident GPL-3.0".to_string()),
+            processor.preview("a.rs", "", Some("GPL-3.0"))
+        );
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let config = Config::from_str(
+            r##"
+licenses:
+  - files: any
+    ident: MIT
+    end_year: "2024"
+    fingerprint: true
+    template: "Copyright [year] The Tester"
+comments:
+  - extension: rs
+    commenter:
+      type: line
+      comment_char: "//""##,
+        )
+        .expect("Static config to be parsable");
+        let processor = Processor::new(config);
+        let header = processor.preview("a.rs", "", None).expect("header renders");
+        assert_eq!(
+            format!(
+                "// Copyright 2024 The Tester\n// {}\n",
+                fingerprint_line("// Copyright 2024 The Tester\n")
+            ),
+            header
+        );
+
+        let licensed = format!("{}fn main() {{}}\n", header);
+        for (content, rule) in [
+            ("fn main() {}\n", Rule::MissingHeader),
+            (
+                "// Copyright 2024 The Tester\nfn main() {}\n",
+                Rule::OutdatedYear,
+            ),
+            (
+                "// Copyright 2019 The Tester\n// License-Header-Hash: 0123abcd\nfn main() {}\n",
+                Rule::OutdatedYear,
+            ),
+        ] {
+            let mut content = content.to_string();
+            assert_eq!(
+                LicenseStatus::NeedsUpdate(licensed.clone(), rule),
+                processor.add_license_header("a.rs", &mut content)
+            );
+        }
+
+        // Only a fingerprint where the header is gets stripped.
+        let body = "fn main() {}\n\n\n\n// License-Header-Hash: 0123abcd\n";
+        let mut content = body.to_string();
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(format!("{}{}", header, body), Rule::MissingHeader),
+            processor.add_license_header("a.rs", &mut content)
+        );
+
+        // Nor does the header's fingerprint count away from the header.
+        let body = format!(
+            "fn main() {{}}\n\n\n\nconst HASH: &str = \"{}\";\n",
+            fingerprint_line("// Copyright 2024 The Tester\n")
+        );
+        let mut content = body.clone();
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(format!("{}{}", header, body), Rule::MissingHeader),
+            processor.add_license_header("a.rs", &mut content)
+        );
+
+        let mut content = licensed.clone();
+        assert_eq!(
+            LicenseStatus::AlreadyLicensed,
+            processor.add_license_header("a.rs", &mut content)
+        );
+        assert_eq!(Ok(None), processor.normalize("a.rs", &licensed));
+    }
+
+    #[test]
+    fn test_header_search_lines() {
+        let config = r##"
+licenses:
+  - files: any
+    ident: MIT
+    end_year: "2024"
+    template: "Copyright [year] The Tester\n\nLicensed under the MIT license."
+comments:
+  - extension: rs
+    commenter:
+      type: line
+      comment_char: "//""##;
+        let content = "//! Docs.\nuse std::io;\n\n/*\n * Copyright 2019 The Tester\n *\n * Licensed under the MIT license.\n */\n\nfn main() {}\n";
+        let header = "// Copyright 2024 The Tester\n//\n// Licensed under the MIT license.\n";
+
+        let processor =
+            Processor::new(Config::from_str(config).expect("Static config to be parsable"));
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(format!("{}{}", header, content), Rule::MissingHeader),
+            processor.add_license_header("a.rs", &mut content.to_string())
+        );
+
+        let processor = Processor::new(
+            Config::from_str(&format!("header_search_lines: 10\n{}", config))
+                .expect("Static config to be parsable"),
+        );
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(
+                format!("//! Docs.\nuse std::io;\n\n{}\nfn main() {{}}\n", header),
+                Rule::MissingHeader
+            ),
+            processor.add_license_header("a.rs", &mut content.to_string())
+        );
+    }
+
+    #[test]
+    fn test_license_pragma() {
+        let config = r##"
+licenses:
+  - files: any
+    ident: Apache-2.0
+    end_year: "2024"
+    template: "Copyright [year] The Tester, [ident]"
+  - files: vendor/.*
+    ident: MIT
+    end_year: "2024"
+    template: "Copyright [year] The Tester, [ident]"
+comments:
+  - extension: any
+    commenter:
+      type: line
+      comment_char: "#""##;
+        let processor =
+            Processor::new(Config::from_str(config).expect("Static config to be parsable"));
+
+        let content = "# licensure: license=MIT\nprint('hi')\n";
+        let licensed = format!("# Copyright 2024 The Tester, MIT\n{}", content);
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(licensed.clone(), Rule::MissingHeader),
+            processor.add_license_header("a.py", &mut content.to_string())
+        );
+        assert_eq!(
+            LicenseStatus::AlreadyLicensed,
+            processor.add_license_header("a.py", &mut licensed.clone())
+        );
+
+        assert_eq!(
+            LicenseStatus::TemplateError(
+                "a licensure: license=GPL-3.0 pragma selects a license no license config has"
+                    .to_string()
+            ),
+            processor.add_license_header("a.py", &mut "# licensure: license=GPL-3.0\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_position_bottom() {
+        let config = r##"
+licenses:
+  - files: '\.asm$'
+    ident: MIT
+    end_year: "2024"
+    template: "Copyright [year] The Tester"
+    position: top
+  - files: any
+    ident: MIT
+    end_year: "2024"
+    template: "Copyright [year] The Tester"
+comments:
+  - extension: [s, asm]
+    position: bottom
+    commenter:
+      type: line
+      comment_char: ";""##;
+        let processor =
+            Processor::new(Config::from_str(config).expect("Static config to be parsable"));
+
+        let licensed = "mov r0, #1\n; Copyright 2024 The Tester\n";
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(licensed.to_string(), Rule::MissingHeader),
+            processor.add_license_header("a.s", &mut "mov r0, #1".to_string())
+        );
+        assert_eq!(
+            LicenseStatus::AlreadyLicensed,
+            processor.add_license_header("a.s", &mut licensed.to_string())
+        );
+        // An outdated header is updated where it is.
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(licensed.to_string(), Rule::OutdatedYear),
+            processor.add_license_header(
+                "a.s",
+                &mut "mov r0, #1\n; Copyright 2019 The Tester\n".to_string()
+            )
+        );
+
+        // The position of the license config wins over the comment config's.
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(
+                "; Copyright 2024 The Tester\nmov r0, #1".to_string(),
+                Rule::MissingHeader
+            ),
+            processor.add_license_header("a.asm", &mut "mov r0, #1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_wide_header_line() {
+        let config = r##"
+columns: 40
+licenses:
+  - files: any
+    ident: MIT
+    end_year: "2024"
+    template: "Copyright [year] The Tester, see https://example.com/a/very/long/license/url"
+comments:
+  - extension: any
+    commenter:
+      type: line
+      comment_char: "#""##;
+        let processor =
+            Processor::new(Config::from_str(config).expect("Static config to be parsable"));
+        let header = processor.preview("a.py", "", None).unwrap();
+
+        // The URL of our own header can't be wrapped.
+        let licensed = format!("#!/usr/bin/env python\n{}print('hi')\n", header);
+        assert_eq!(None, processor.wide_header_line("a.py", &licensed));
+
+        let legacy = format!(
+            "#!/usr/bin/env python\n{}\n# Copyright 2010 Someone Else\n# Released under the MIT license, which is a wide line.\n\n# A comment which isn't a header and is wider than forty columns.\nprint('hi')\n",
+            header
+        );
+        assert_eq!(Some(6), processor.wide_header_line("a.py", &legacy));
+
+        let comment = "# A comment which isn't a header and is wider than forty columns.\n";
+        assert_eq!(None, processor.wide_header_line("a.py", comment));
+    }
+
+    #[test]
+    fn test_comment_unwrap_text() {
+        let config = r##"
+licenses:
+  - files: any
+    ident: MIT
+    end_year: "2024"
+    template: "Copyright [year] The Tester\nLicensed under the MIT license."
+comments:
+  - extension: rst
+    unwrap_text: false
+    wrap: false
+    commenter:
+      type: line
+      comment_char: ".."
+  - extension: any
+    commenter:
+      type: line
+      comment_char: "#""##;
+        let processor =
+            Processor::new(Config::from_str(config).expect("Static config to be parsable"));
+
+        // The rst comment config keeps the lines of the template as they are.
+        assert_eq!(
+            ".. Copyright 2024 The Tester\n.. Licensed under the MIT license.\n",
+            processor.preview("README.rst", "", None).unwrap()
+        );
+        assert_eq!(
+            "# Copyright 2024 The Tester Licensed under the MIT license.\n",
+            processor.preview("main.py", "", None).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let config = Config::from_str(
+            r##"
+licenses:
+  - files: any
+    ident: MIT
+    template: "Copyright [year] The Tester\n\nLicensed under the MIT license."
+comments:
+  - extension: rs
+    commenter:
+      type: line
+      comment_char: "//""##,
+        )
+        .expect("Static config to be parsable");
+        let processor = Processor::new(config);
+        let content = "/*\n * Copyright 2019 The Tester\n *\n * Licensed under the MIT\n * license.\n */\n\nfn main() {}\n";
+        let normalized = processor
+            .normalize("a.rs", content)
+            .expect("header renders")
+            .expect("header is normalized");
+        assert_eq!(
+            "// Copyright 2019 The Tester\n//\n// Licensed under the MIT license.\nfn main() {}\n",
+            normalized
+        );
+        assert_eq!(Ok(None), processor.normalize("a.rs", &normalized));
+        assert_eq!(Ok(None), processor.normalize("a.rs", "fn main() {}\n"));
+    }
+
+    #[test]
+    fn test_process_file() {
+        let dir = TempDir::new("process");
+        let file = dir.file("a.py");
+        std::fs::write(&file, "print('hi')\n").expect("Can write file");
+
+        let config = Config::from_str(
+            "change_in_place: true\nlicenses:\n  - files: any\n    ident: TESTING\n    template: \"[ident]\"",
+        )
+        .expect("Static config to be parsable");
+        let mut processor = Processor::new(config);
+        assert_eq!(
+            Some(Action::Updated),
+            processor.process_file(&file).unwrap()
+        );
+        assert_eq!(
+            Some(Action::AlreadyLicensed),
+            processor.process_file(&file).unwrap()
+        );
+        assert_eq!(vec![file.clone()], processor.stats().files_already_licensed);
+        assert_eq!(vec![file.clone()], processor.stats().files_updated);
+        assert_eq!(
+            "# TESTING\nprint('hi')\n",
+            std::fs::read_to_string(&file).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_suppressed_findings() {
+        let dir = TempDir::new("suppress");
+        let vendored = dir.file("vendored.py");
+        let ours = dir.file("ours.py");
+        for file in [&vendored, &ours] {
+            std::fs::write(file, "print('hi')\n").expect("Can write file");
+        }
+
+        let config = Config::from_str(
+            "change_in_place: true\nsuppressions:\n  LIC001: ['vendored\\.py$']\nlicenses:\n  - files: any\n    ident: TESTING\n    template: \"[ident]\"",
+        )
+        .expect("Static config to be parsable");
+        let mut processor = Processor::new(config);
+        assert_eq!(
+            Some(Action::Suppressed),
+            processor.process_file(&vendored).unwrap()
+        );
+        assert_eq!(
+            Some(Action::Updated),
+            processor.process_file(&ours).unwrap()
+        );
+        assert_eq!(
+            vec![(vendored.clone(), Rule::MissingHeader)],
+            processor.stats().files_suppressed
+        );
+        assert_eq!(
+            vec![ours.clone()],
+            processor.stats().files_needing_license_update
+        );
+        assert_eq!("print('hi')\n", std::fs::read_to_string(&vendored).unwrap());
+    }
+
+    #[test]
+    fn test_wide_header_findings() {
+        let dir = TempDir::new("wide");
+        let legacy = dir.file("legacy.py");
+        let other = dir.file("other.py");
+        for file in [&legacy, &other] {
+            std::fs::write(
+                file,
+                "# Copyright 2024 The Tester\n\n# Copyright 2010 Someone Else, released under a wide line.\nprint('hi')\n",
+            )
+            .expect("Can write file");
+        }
+
+        let config = Config::from_str(
+            "columns: 40\nsuppressions:\n  LIC005: ['legacy\\.py$']\nlicenses:\n  - files: any\n    ident: TESTING\n    end_year: \"2024\"\n    template: \"Copyright [year] The Tester\"\ncomments:\n  - extension: py\n    commenter:\n      type: line\n      comment_char: \"#\"",
+        )
+        .expect("Static config to be parsable");
+        let mut processor = Processor::new(config).with_check_mode(true);
+        for file in [&legacy, &other] {
+            assert_eq!(
+                Some(Action::AlreadyLicensed),
+                processor.process_file(file).unwrap()
+            );
+        }
+        assert_eq!(
+            vec![(legacy.clone(), Rule::WideHeader)],
+            processor.stats().files_suppressed
+        );
+        assert_eq!(
+            vec![(other.clone(), 3)],
+            processor.stats().files_with_wide_headers
+        );
+        assert_eq!(
+            vec![(other.clone(), Rule::WideHeader)],
+            processor.stats().findings
+        );
+    }
+
+    #[test]
+    fn test_changes() {
+        let dir = TempDir::new("changes");
+        let file = dir.file("a.py");
+        let body: String = (0..20).map(|i| format!("print({})\n", i)).collect();
+        std::fs::write(&file, &body).expect("Can write file");
+
+        let config = "licenses:\n  - files: any\n    ident: TESTING\n    template: \"[ident]\"";
+        let check = |change: Change| {
+            let mut processor =
+                Processor::new(Config::from_str(config).expect("Static config to be parsable"))
+                    .with_check_mode(true)
+                    .with_changes(HashMap::from([(project_path(&file), change)]));
+            processor.process_file(&file).unwrap()
+        };
+        assert_eq!(Some(Action::NeedsUpdate), check(Change::Added));
+        assert_eq!(
+            Some(Action::NeedsUpdate),
+            check(Change::Lines(vec![(2, 2)]))
+        );
+        assert_eq!(
+            Some(Action::PreExisting),
+            check(Change::Lines(vec![(15, 16)]))
+        );
+    }
+
+    #[test]
+    fn test_max_file_size_units() {
+        let config = config_with_limits("max_file_size: 1.5 MiB");
+        assert_eq!(Some(FileSize(3 << 19)), config.max_file_size);
+        let config = config_with_limits("max_file_size: 512");
+        assert_eq!(Some(FileSize(512)), config.max_file_size);
+        assert!(serde_yaml::from_str::<FileSize>("ten megabytes").is_err());
+    }
+
+    fn config_with_skip_existing(policy: &str) -> Config {
+        serde_yaml::from_str(&format!(
+            r##"
+excludes: []
+licenses:
+  - files: any
+    ident: TESTING
+    authors:
+      - name: The Tester
+    template: "Copyright The Tester, [ident]"
+    skip_existing: {}
+comments:
+  - extension: any
+    columns: 80
+    commenter:
+      type: line
+      comment_char: "#""##,
+            policy
+        ))
+        .expect("Static config to be parsable")
+    }
+
+    static OTHER_HEADER_CONTENT: &str = "#!/bin/sh
+# Copyright 2001 Someone Else
+# SPDX-License-Identifier: MIT
+
+echo hi
+";
+
+    #[test]
+    fn test_headers_are_rendered_once() {
+        let l = Processor::new(
+            serde_yaml::from_str(
+                r##"
+excludes: []
+licenses:
+  - files: any
+    ident: TESTING
+    authors:
+      - name: The Tester
+    template: "Copyright The Tester, [ident]"
+comments:
+  - extension: rs
+    commenter:
+      type: line
+      comment_char: "//"
+  - extension: any
+    commenter:
+      type: line
+      comment_char: "#""##,
+            )
+            .expect("Static config to be parsable"),
+        );
+
+        for file in ["a.rs", "b.rs", "a.py", "b.sh"] {
+            let status = l.add_license_header(file, &mut String::new());
+            assert!(matches!(status, LicenseStatus::NeedsUpdate(..)));
+        }
+        assert_eq!(2, l.headers.borrow().len());
+
+        let status = l.add_license_header(
+            "c.rs",
+            &mut "// Copyright The Tester, TESTING\n".to_string(),
+        );
+        assert_eq!(LicenseStatus::AlreadyLicensed, status);
+        assert_eq!(2, l.headers.borrow().len());
+    }
+
+    #[test]
+    fn test_skip_existing() {
+        let file = "test.sh".to_string();
+
+        let l = Processor::new(config_with_skip_existing("replace"));
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(
+                "#!/bin/sh\n# Copyright The Tester, TESTING\n\necho hi\n".to_string(),
+                Rule::ForeignCopyright
+            ),
+            l.add_license_header(&file, &mut OTHER_HEADER_CONTENT.to_string())
+        );
+
+        let l = Processor::new(config_with_skip_existing("prepend"));
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(
+                format!(
+                    "#!/bin/sh\n# Copyright The Tester, TESTING\n{}",
+                    &OTHER_HEADER_CONTENT[10..]
+                ),
+                Rule::MissingHeader
+            ),
+            l.add_license_header(&file, &mut OTHER_HEADER_CONTENT.to_string())
+        );
+
+        let l = Processor::new(config_with_skip_existing("skip"));
+        assert_eq!(
+            LicenseStatus::Skipped(SkipReason::ForeignCopyright),
+            l.add_license_header(&file, &mut OTHER_HEADER_CONTENT.to_string())
+        );
+
+        let l = Processor::new(config_with_skip_existing("error"));
+        assert_eq!(
+            LicenseStatus::HasOtherHeader,
+            l.add_license_header(&file, &mut OTHER_HEADER_CONTENT.to_string())
+        );
+
+        // A comment without any license information isn't a header.
+        assert_eq!(
+            LicenseStatus::NeedsUpdate(
+                "# Copyright The Tester, TESTING\n# Just a comment\necho hi\n".to_string(),
+                Rule::MissingHeader
+            ),
+            l.add_license_header(&file, &mut "# Just a comment\necho hi\n".to_string())
+        );
+    }
+}
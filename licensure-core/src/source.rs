@@ -0,0 +1,27 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::io;
+
+/// Somewhere a [`Processor`](crate::Processor) reads files from instead of
+/// the working tree, such as a git commit or an archive.
+pub trait Source {
+    /// Whether file is in the source.
+    fn exists(&self, file: &str) -> bool;
+
+    /// The size of file in bytes.
+    fn size(&self, file: &str) -> Result<u64, io::Error>;
+
+    /// The content of file.
+    fn read(&self, file: &str) -> Result<Vec<u8>, io::Error>;
+}
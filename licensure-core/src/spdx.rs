@@ -0,0 +1,1162 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use regex::Regex;
+use serde::Deserialize;
+
+const SPDX_URL: &str = "https://spdx.org/licenses/";
+// The headers of common licenses used with --offline, see spdx_headers.yml.
+const EMBEDDED_HEADERS: &str = include_str!("spdx_headers.yml");
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_RETRIES: u32 = 3;
+// The delay before the first retry, doubled for each retry after it.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+// The longest a Retry-After header is waited for, so a server asking for
+// hours doesn't hang the run.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+// How long to wait for another run to finish writing to the cache before
+// leaving the license uncached, and how old a lock file has to be before it's
+// taken to be left behind by a run which died.
+const CACHE_LOCK_WAIT: Duration = Duration::from_secs(10);
+const CACHE_LOCK_STALE: Duration = Duration::from_secs(60);
+const CACHE_LOCK_POLL: Duration = Duration::from_millis(50);
+// Requests started per second, across every thread, unless configured.
+const DEFAULT_RATE_LIMIT: f64 = 5.0;
+
+/// Settings for requests to spdx.org which come from the config file.
+#[derive(Debug, Default)]
+pub struct Settings {
+    /// A PEM file of certificates to trust in addition to the built in ones.
+    pub ca_bundle: Option<String>,
+    /// Where to fetch licenses from instead of spdx.org, either a URL or a
+    /// local directory of license JSON files.
+    pub base_url: Option<String>,
+    /// Seconds to wait for each request before giving up on it.
+    pub timeout: Option<u64>,
+    /// How many times to retry a request which failed in a way that may
+    /// succeed on another attempt.
+    pub retries: Option<u32>,
+    /// How many requests may be started per second, 0 for no limit.
+    pub rate_limit: Option<f64>,
+    /// Licenses which aren't fetched from SPDX at all, by identifier.
+    pub registry: BTreeMap<String, CustomLicense>,
+    /// Forbid network access, licenses are only read from the cache of
+    /// previously fetched licenses or a local spdx_base_url directory.
+    pub offline: bool,
+}
+
+impl Settings {
+    // The base URL licenses are fetched from, always ending in a slash.
+    fn base_url(&self) -> String {
+        let mut url = self
+            .base_url
+            .clone()
+            .unwrap_or_else(|| SPDX_URL.to_string());
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+
+        url
+    }
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+// When the next request may start.
+static NEXT_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+static AGENT: OnceLock<Result<ureq::Agent, String>> = OnceLock::new();
+// Every license fetched this run, or why it couldn't be, so each license is
+// only fetched once however many files use it. Threads wanting the same
+// license wait on its slot while one of them fetches it.
+type LicenseSlot = Arc<OnceLock<Result<Arc<LicenseInfo>, String>>>;
+static LICENSES: Mutex<BTreeMap<String, LicenseSlot>> = Mutex::new(BTreeMap::new());
+
+fn settings() -> &'static Settings {
+    SETTINGS.get_or_init(Settings::default)
+}
+
+/// Set the settings used for every request to spdx.org. Only the first call
+/// has any effect. Fails if the proxy or ca_bundle they give can't be used.
+pub fn configure(settings: Settings) -> Result<(), String> {
+    let _ = SETTINGS.set(settings);
+    agent().map(|_| ())
+}
+
+// Returns the host of url without any port or credentials.
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    host.split(':').next().unwrap_or_default()
+}
+
+// Returns the proxy to use for url from the environment, following the
+// conventions of curl: https URLs use HTTPS_PROXY and http URLs use
+// HTTP_PROXY, both falling back to ALL_PROXY, and hosts matching an entry of
+// NO_PROXY, or any host when it is "*", are not proxied. Lower case names take
+// precedence.
+fn proxy_for(url: &str, var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let lookup = |name: &str| {
+        var(&name.to_lowercase())
+            .or_else(|| var(name))
+            .filter(|value| !value.trim().is_empty())
+    };
+
+    let host = url_host(url).to_lowercase();
+    if let Some(no_proxy) = lookup("NO_PROXY") {
+        let bypassed = no_proxy
+            .split(',')
+            .map(|entry| entry.trim().trim_start_matches('.').to_lowercase())
+            .filter(|entry| !entry.is_empty())
+            .any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{}", entry)));
+        if bypassed {
+            return None;
+        }
+    }
+
+    let scheme_var = if url.starts_with("https:") {
+        "HTTPS_PROXY"
+    } else {
+        "HTTP_PROXY"
+    };
+    lookup(scheme_var).or_else(|| lookup("ALL_PROXY"))
+}
+
+// Decode every certificate in a PEM file.
+fn parse_pem_certificates(pem: &str) -> Result<Vec<Vec<u8>>, String> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let mut certificates = Vec::new();
+    let mut rest = pem;
+    while let Some(start) = rest.find(BEGIN) {
+        let body = &rest[start + BEGIN.len()..];
+        let end = body
+            .find(END)
+            .ok_or_else(|| "certificate is missing its END line".to_string())?;
+        let encoded: String = body[..end].split_whitespace().collect();
+        let der = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("certificate is not valid base64: {}", e))?;
+        certificates.push(der);
+        rest = &body[end + END.len()..];
+    }
+
+    if certificates.is_empty() {
+        return Err("no certificates found".to_string());
+    }
+
+    Ok(certificates)
+}
+
+// A TLS config trusting the built in roots and every certificate in the PEM
+// file at path.
+fn tls_config(path: &str) -> Result<rustls::ClientConfig, String> {
+    let pem = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    for der in parse_pem_certificates(&pem)? {
+        roots
+            .add(der.into())
+            .map_err(|e| format!("invalid certificate: {}", e))?;
+    }
+
+    let config = rustls::ClientConfig::builder_with_provider(
+        rustls::crypto::ring::default_provider().into(),
+    )
+    .with_safe_default_protocol_versions()
+    .map_err(|e| e.to_string())?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    Ok(config)
+}
+
+fn build_agent(settings: &Settings) -> Result<ureq::Agent, String> {
+    let mut builder = ureq::AgentBuilder::new().timeout(Duration::from_secs(
+        settings.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS),
+    ));
+
+    if let Some(proxy) = proxy_for(&settings.base_url(), |name| env::var(name).ok()) {
+        debug!("using proxy {} for SPDX requests", proxy);
+        let proxy =
+            ureq::Proxy::new(&proxy).map_err(|e| format!("invalid proxy {}: {}", proxy, e))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(ca_bundle) = &settings.ca_bundle {
+        let config = tls_config(ca_bundle)
+            .map_err(|e| format!("unable to load CA bundle {}: {}", ca_bundle, e))?;
+        builder = builder.tls_config(Arc::new(config));
+    }
+
+    Ok(builder.build())
+}
+
+fn agent() -> Result<&'static ureq::Agent, String> {
+    AGENT
+        .get_or_init(|| build_agent(settings()))
+        .as_ref()
+        .map_err(String::clone)
+}
+
+// The directory to read license JSON files from when the base URL isn't an
+// HTTP(S) URL.
+fn local_dir(base_url: &str) -> Option<&Path> {
+    if base_url.starts_with("http://") || base_url.starts_with("https://") {
+        None
+    } else {
+        Some(Path::new(
+            base_url.strip_prefix("file://").unwrap_or(base_url),
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LicenseInfo {
+    #[serde(alias = "licenseText")]
+    pub license_text: String,
+    #[serde(alias = "standardLicenseHeader")]
+    pub license_header: Option<String>,
+    /// The license is from the licenses_registry, so its header and text use
+    /// the placeholders of config templates rather than those of SPDX.
+    #[serde(skip)]
+    pub custom: bool,
+}
+
+/// A license defined in a licenses_registry file, such as a proprietary
+/// notice, which is used like an SPDX license.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomLicense {
+    /// The header template.
+    pub header: String,
+    /// The full text written by license-file, the header if it isn't given.
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+impl CustomLicense {
+    fn license_info(&self) -> LicenseInfo {
+        LicenseInfo {
+            license_text: self.text.clone().unwrap_or_else(|| self.header.clone()),
+            license_header: Some(self.header.clone()),
+            custom: true,
+        }
+    }
+}
+
+/// Parse a licenses_registry file, a YAML or JSON mapping of license
+/// identifiers to their header and text, which was read from source.
+pub fn parse_registry(
+    content: &str,
+    source: &str,
+) -> Result<BTreeMap<String, CustomLicense>, String> {
+    if content.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    serde_yaml::from_str(content).map_err(|e| format!("Invalid license registry {}: {}", source, e))
+}
+
+/// Read the licenses_registry file at path.
+pub fn load_registry(path: &str) -> Result<BTreeMap<String, CustomLicense>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read the license registry {}: {}", path, e))?;
+    parse_registry(&content, path)
+}
+
+// The directory licenses fetched from SPDX are cached in, following the XDG
+// base directory spec.
+fn cache_dir(var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let base = match var("XDG_CACHE_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(var("HOME")?).join(".cache"),
+    };
+
+    Some(base.join("licensure").join("spdx"))
+}
+
+fn parse_license_info(json: &str, source: &str) -> Result<LicenseInfo, String> {
+    serde_json::from_str(json)
+        .map_err(|e| format!("Failed to deserialize SPDX JSON from {}: {}", source, e))
+}
+
+fn read_license_info(dir: &Path, ident: &str) -> Result<LicenseInfo, String> {
+    let path = dir.join(format!("{}.json", ident));
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(format!(
+                "{} does not appear to be a valid SPDX identifier, {} does not exist",
+                ident,
+                path.display()
+            ))
+        }
+        Err(e) => return Err(format!("Unable to read {}: {}", path.display(), e)),
+    };
+
+    parse_license_info(&content, &path.display().to_string())
+}
+
+fn read_cached_license_info(ident: &str) -> Result<LicenseInfo, String> {
+    let path = cache_dir(|name| env::var(name).ok()).map(|dir| dir.join(format!("{}.json", ident)));
+    match path {
+        Some(path) if path.exists() => match fs::read_to_string(&path) {
+            Ok(content) => parse_license_info(&content, &path.display().to_string()),
+            Err(e) => Err(format!("Unable to read {}: {}", path.display(), e)),
+        },
+        _ => Err(format!(
+            "The {} license is needed but isn't cached and --offline forbids fetching it from {}, run licensure once without --offline to cache it or set spdx_base_url to a directory of license JSON files",
+            ident,
+            settings().base_url()
+        )),
+    }
+}
+
+// Held while writing to the cache so runs sharing it take turns, the lock
+// file is removed when it's dropped.
+struct CacheLock {
+    path: PathBuf,
+}
+
+impl CacheLock {
+    // Wait for the lock file at path, taking over one left behind by a run
+    // which died holding it. Fails if it's still held after CACHE_LOCK_WAIT.
+    fn acquire(path: PathBuf) -> io::Result<CacheLock> {
+        let started = Instant::now();
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(_) => return Ok(CacheLock { path }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if is_stale_lock(&path) {
+                        take_over_lock(&path);
+                    } else if started.elapsed() > CACHE_LOCK_WAIT {
+                        return Err(io::Error::new(
+                            io::ErrorKind::WouldBlock,
+                            format!("{} is held by another run", path.display()),
+                        ));
+                    } else {
+                        thread::sleep(CACHE_LOCK_POLL);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+// Whether the lock file at path was left behind by a run which died.
+fn is_stale_lock(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified.elapsed().unwrap_or_default() > CACHE_LOCK_STALE)
+}
+
+// Remove the stale lock file at path. It's moved aside first so when several
+// runs find it at once only one of them gets it, and one which moved a lock
+// another run has taken since puts it back.
+fn take_over_lock(path: &Path) {
+    static STALE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let moved = PathBuf::from(format!(
+        "{}.{}-{}.stale",
+        path.display(),
+        process::id(),
+        STALE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    if fs::rename(path, &moved).is_err() {
+        return;
+    }
+    if is_stale_lock(&moved) {
+        debug!("removing the stale cache lock {}", path.display());
+    } else {
+        let _ = fs::hard_link(&moved, path);
+    }
+    let _ = fs::remove_file(&moved);
+}
+
+impl Drop for CacheLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The ETag and Last-Modified headers a license was served with, sent back
+/// when it's fetched again so an unchanged license isn't downloaded again.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_response(response: &ureq::Response) -> Validators {
+        Validators {
+            etag: response.header("ETag").map(str::to_string),
+            last_modified: response.header("Last-Modified").map(str::to_string),
+        }
+    }
+
+    // Parse the headers as they're written by to_string.
+    fn parse(content: &str) -> Validators {
+        let mut validators = Validators::default();
+        for line in content.lines() {
+            match line.split_once(": ") {
+                Some(("ETag", value)) => validators.etag = Some(value.to_string()),
+                Some(("Last-Modified", value)) => {
+                    validators.last_modified = Some(value.to_string())
+                }
+                _ => (),
+            }
+        }
+
+        validators
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+impl fmt::Display for Validators {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(etag) = &self.etag {
+            writeln!(f, "ETag: {}", etag)?;
+        }
+        if let Some(last_modified) = &self.last_modified {
+            writeln!(f, "Last-Modified: {}", last_modified)?;
+        }
+        Ok(())
+    }
+}
+
+// Replace path with content by writing a temporary file next to it and
+// renaming it into place.
+fn replace_file(path: &Path, content: &str) -> io::Result<()> {
+    static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let tmp = PathBuf::from(format!(
+        "{}.{}-{}.tmp",
+        path.display(),
+        process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = fs::write(&tmp, content).and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+// Write json to the cache in dir as the license ident, along with the
+// validators it was served with. Each file is written to a temporary file
+// renamed into place, under the lock, so other runs reading or writing the
+// cache never see half of it. The JSON goes first so validators are never
+// newer than the JSON they're read with.
+fn write_cache(dir: &Path, ident: &str, json: &str, validators: &Validators) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let _lock = CacheLock::acquire(dir.join(format!("{}.json.lock", ident)))?;
+
+    replace_file(&dir.join(format!("{}.json", ident)), json)?;
+    let headers = dir.join(format!("{}.json.headers", ident));
+    if validators.is_empty() {
+        match fs::remove_file(&headers) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    } else {
+        replace_file(&headers, &validators.to_string())
+    }
+}
+
+// The cached JSON of the license ident in dir and the validators it was
+// served with, if it's cached with any.
+fn read_cache(dir: &Path, ident: &str) -> Option<(String, Validators)> {
+    let validators =
+        Validators::parse(&fs::read_to_string(dir.join(format!("{}.json.headers", ident))).ok()?);
+    if validators.is_empty() {
+        return None;
+    }
+    let json = fs::read_to_string(dir.join(format!("{}.json", ident))).ok()?;
+    Some((json, validators))
+}
+
+// Save the JSON of a license fetched from SPDX for use by --offline and to
+// check whether it changed the next time it's fetched.
+fn cache_license_info(ident: &str, json: &str, validators: &Validators) {
+    let Some(dir) = cache_dir(|name| env::var(name).ok()) else {
+        return;
+    };
+
+    if let Err(e) = write_cache(&dir, ident, json, validators) {
+        debug!(
+            "unable to cache {} license in {}: {}",
+            ident,
+            dir.display(),
+            e
+        );
+    }
+}
+
+// Whether a failed request may succeed if it's tried again.
+fn is_transient(err: &ureq::Error) -> bool {
+    match err {
+        ureq::Error::Status(code, _) => *code == 429 || *code >= 500,
+        ureq::Error::Transport(_) => true,
+    }
+}
+
+// The delay before retry number attempt, counting from zero, or as long as a
+// 429 or 503 response asks with Retry-After in seconds, up to
+// MAX_RETRY_AFTER.
+fn backoff(attempt: u32, err: &ureq::Error) -> Duration {
+    let retry_after = match err {
+        ureq::Error::Status(_, response) => response
+            .header("Retry-After")
+            .and_then(|secs| secs.trim().parse().ok())
+            .map(|secs| Duration::from_secs(secs).min(MAX_RETRY_AFTER)),
+        ureq::Error::Transport(_) => None,
+    };
+    retry_after.unwrap_or_else(|| RETRY_DELAY * 2u32.saturating_pow(attempt))
+}
+
+// Reserve the next slot to start a request in, at least interval after the
+// one before it, returning how long to wait for it from now.
+fn reserve_slot(next: &mut Option<Instant>, now: Instant, interval: Duration) -> Duration {
+    let slot = next.map_or(now, |next| next.max(now));
+    *next = Some(slot + interval);
+    slot - now
+}
+
+// Wait until another request may start without going over the rate limit.
+fn rate_limit() {
+    let rate = settings().rate_limit.unwrap_or(DEFAULT_RATE_LIMIT);
+    if rate <= 0.0 {
+        return;
+    }
+
+    let wait = reserve_slot(
+        &mut NEXT_REQUEST.lock().unwrap_or_else(|e| e.into_inner()),
+        Instant::now(),
+        Duration::from_secs_f64(1.0 / rate),
+    );
+    if !wait.is_zero() {
+        debug!("waiting {:?} to stay under the SPDX rate limit", wait);
+        thread::sleep(wait);
+    }
+}
+
+fn download_license_info(url: &str, ident: &str) -> Result<LicenseInfo, String> {
+    let cached = cache_dir(|name| env::var(name).ok()).and_then(|dir| read_cache(&dir, ident));
+    let retries = settings().retries.unwrap_or(DEFAULT_RETRIES);
+    let mut attempt = 0;
+    let response = loop {
+        let mut request = agent()?.get(url);
+        if let Some((_, validators)) = &cached {
+            if let Some(etag) = &validators.etag {
+                request = request.set("If-None-Match", etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.set("If-Modified-Since", last_modified);
+            }
+        }
+        rate_limit();
+        match request.call() {
+            Ok(response) => break response,
+            Err(ureq::Error::Status(404, _)) => {
+                return Err(format!(
+                    "{} does not appear to be a valid SPDX identifier, go to {} to view a list of valid identifiers",
+                    ident, SPDX_URL
+                ))
+            }
+            Err(e) if attempt < retries && is_transient(&e) => {
+                let delay = backoff(attempt, &e);
+                warn!("fetching {} failed, retrying in {:?}: {}", url, delay, e);
+                thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(format!(
+                    "Failed to fetch license template from SPDX for {}: {}",
+                    ident, e
+                ))
+            }
+        }
+    };
+
+    if response.status() == 304 {
+        if let Some((json, _)) = cached {
+            debug!("the cached {} license is unchanged", ident);
+            return parse_license_info(&json, url);
+        }
+    }
+
+    let validators = Validators::from_response(&response);
+    let json = response
+        .into_string()
+        .map_err(|e| format!("Failed to read SPDX response for {}: {}", ident, e))?;
+    let license_info = parse_license_info(&json, url)?;
+    cache_license_info(ident, &json, &validators);
+    Ok(license_info)
+}
+
+/// Fetch the SPDX license data for ident, or take it from the registry of
+/// custom licenses. Each license is only fetched once per run, a license
+/// which can't be fetched after retrying fails straight away on later calls.
+pub fn fetch_license_info(ident: &str) -> Result<Arc<LicenseInfo>, String> {
+    let slot = Arc::clone(
+        LICENSES
+            .lock()
+            .unwrap()
+            .entry(ident.to_string())
+            .or_default(),
+    );
+    slot.get_or_init(|| {
+        let base_url = settings().base_url();
+        match local_dir(&base_url) {
+            _ if settings().registry.contains_key(ident) => {
+                Ok(settings().registry[ident].license_info())
+            }
+            Some(dir) => read_license_info(dir, ident),
+            None if settings().offline => read_cached_license_info(ident),
+            None => download_license_info(&format!("{}{}.json", base_url, ident), ident),
+        }
+        .map(Arc::new)
+    })
+    .clone()
+}
+
+// The header embedded in licensure for ident, if it's a common license.
+fn embedded_header(ident: &str) -> Option<&'static str> {
+    static HEADERS: OnceLock<BTreeMap<String, String>> = OnceLock::new();
+    HEADERS
+        .get_or_init(|| {
+            serde_yaml::from_str(EMBEDDED_HEADERS).expect("The embedded headers are invalid?")
+        })
+        .get(ident)
+        .map(String::as_str)
+}
+
+/// Fetch the header template of the license ident, its standard header or
+/// else its full text, and whether it's an SPDX template rather than a
+/// custom license from the registry. With --offline a common license which
+/// isn't cached uses the header embedded in licensure.
+pub fn fetch_license_header(ident: &str) -> Result<(String, bool), String> {
+    match fetch_license_info(ident) {
+        Ok(info) => {
+            let header = info.license_header.as_ref().unwrap_or(&info.license_text);
+            Ok((header.clone(), !info.custom))
+        }
+        Err(e) => match embedded_header(ident) {
+            Some(header) if settings().offline && local_dir(&settings().base_url()).is_none() => {
+                Ok((header.to_string(), true))
+            }
+            _ => Err(e),
+        },
+    }
+}
+
+/// Fetch every license in idents which hasn't been fetched yet concurrently,
+/// so later calls to fetch_license_info don't wait on the network one
+/// license at a time.
+pub fn prefetch(idents: &[&str]) {
+    let mut missing: Vec<&str> = {
+        let licenses = LICENSES.lock().unwrap();
+        idents
+            .iter()
+            .copied()
+            .filter(|ident| licenses.get(*ident).is_none_or(|slot| slot.get().is_none()))
+            .collect()
+    };
+    missing.sort_unstable();
+    missing.dedup();
+
+    thread::scope(|scope| {
+        for ident in missing {
+            scope.spawn(move || {
+                if let Err(e) = fetch_license_info(ident) {
+                    debug!("prefetching {} failed: {}", ident, e);
+                }
+            });
+        }
+    });
+}
+
+// The similarity of a header to a license template at or above which it's
+// taken to be that license.
+const SIMILARITY_THRESHOLD: f64 = 0.9;
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+// The words of a license template with its placeholders, such as <year>,
+// [name of author], and <<var;name="copyright";...>>, left out since they're
+// filled in differently in every header.
+fn template_words(template: &str) -> Vec<String> {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    let re = PLACEHOLDER.get_or_init(|| {
+        Regex::new(r"<<[^>]*>>|<[^<>\n]{1,40}>|\[[^\[\]\n]{1,40}\]")
+            .expect("placeholder regex didn't compile!")
+    });
+    words(&re.replace_all(template, " "))
+}
+
+// Length of the longest common subsequence of words, computed one row at a
+// time so memory use stays linear even for long licenses like the GPL.
+fn common_words(expected: &[String], actual: &[String]) -> usize {
+    let mut previous = vec![0usize; actual.len() + 1];
+    let mut current = vec![0usize; actual.len() + 1];
+    for expected_word in expected {
+        for (j, actual_word) in actual.iter().enumerate() {
+            current[j + 1] = if expected_word == actual_word {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[actual.len()]
+}
+
+/// Returns true if actual contains the text of the license expected. The
+/// comparison ignores case, punctuation, and whitespace and tolerates a few
+/// words being different so that the copyright lines and other placeholders
+/// which are filled in by the copyright holder don't cause a mismatch.
+pub fn text_matches(expected: &str, actual: &str) -> bool {
+    let expected = words(expected);
+    let actual = words(actual);
+    if expected.is_empty() {
+        return actual.is_empty();
+    }
+
+    let common = common_words(&expected, &actual) as f64;
+    common / expected.len() as f64 >= 0.95 && common / actual.len() as f64 >= 0.9
+}
+
+/// Returns how much of the license template appears in text, in order, from
+/// 0 to 1. Case, punctuation, and wrapping are ignored, as are comment
+/// characters and the placeholders of the template, so a header with its
+/// year and names filled in and rewrapped still scores 1. Words text has
+/// beyond the template, such as the code after a header, don't lower it.
+pub fn similarity(template: &str, text: &str) -> f64 {
+    let expected = template_words(template);
+    if expected.is_empty() {
+        return 0.0;
+    }
+
+    common_words(&expected, &words(text)) as f64 / expected.len() as f64
+}
+
+// The ident of the template text is most similar to, if any is similar
+// enough.
+fn most_similar<'a>(text: &str, templates: &[(&'a str, &str)]) -> Option<&'a str> {
+    templates
+        .iter()
+        .map(|(ident, template)| (*ident, similarity(template, text)))
+        .filter(|(_, score)| *score >= SIMILARITY_THRESHOLD)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(ident, _)| ident)
+}
+
+/// Returns which of idents the header in text is, by its similarity to their
+/// standard headers, or their full texts for licenses without one. A header
+/// closer to a similar license, such as GPL-2.0 for GPL-3.0, is left
+/// unrecognized. Licenses which can't be fetched are left out.
+pub fn similar_license<'a>(text: &str, idents: &[&'a str]) -> Option<&'a str> {
+    let headers: Vec<(&str, String)> = idents
+        .iter()
+        .filter_map(|ident| match fetch_license_header(ident) {
+            Ok((header, _)) => Some((*ident, header)),
+            Err(e) => {
+                debug!("unable to compare with the {} license: {}", ident, e);
+                None
+            }
+        })
+        .collect();
+    let templates: Vec<(&str, &str)> = headers
+        .iter()
+        .map(|(ident, header)| (*ident, header.as_str()))
+        .collect();
+
+    most_similar(text, &templates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    // Look up environment variables in vars instead of the real environment.
+    fn env(
+        vars: &'static [(&'static str, &'static str)],
+    ) -> impl Fn(&str) -> Option<String> + Copy {
+        move |name: &str| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_proxy_for() {
+        let proxied = env(&[
+            ("HTTPS_PROXY", "http://proxy:3128"),
+            ("http_proxy", "http://plain:3128"),
+            ("NO_PROXY", "localhost,.internal.example.com"),
+        ]);
+        assert_eq!(
+            Some("http://proxy:3128".to_string()),
+            proxy_for("https://spdx.org/licenses/MIT.json", proxied)
+        );
+        assert_eq!(
+            Some("http://plain:3128".to_string()),
+            proxy_for("http://spdx.org/licenses/MIT.json", proxied)
+        );
+        assert_eq!(
+            None,
+            proxy_for("https://spdx.internal.example.com:8443/MIT.json", proxied)
+        );
+        assert_eq!(None, proxy_for("http://localhost/MIT.json", proxied));
+
+        let all = env(&[("ALL_PROXY", "http://all:3128"), ("no_proxy", "")]);
+        assert_eq!(
+            Some("http://all:3128".to_string()),
+            proxy_for("https://spdx.org/", all)
+        );
+        let none = env(&[("HTTPS_PROXY", "http://proxy:3128"), ("NO_PROXY", "*")]);
+        assert_eq!(None, proxy_for("https://spdx.org/", none));
+    }
+
+    #[test]
+    fn test_local_dir() {
+        assert_eq!(None, local_dir("https://mirror.example.com/spdx/"));
+        assert_eq!(
+            Some(Path::new("/srv/spdx/")),
+            local_dir("file:///srv/spdx/")
+        );
+        assert_eq!(Some(Path::new("licenses/")), local_dir("licenses/"));
+
+        let settings = Settings {
+            base_url: Some("https://mirror.example.com/spdx".to_string()),
+            ..Settings::default()
+        };
+        assert_eq!("https://mirror.example.com/spdx/", settings.base_url());
+        assert_eq!(SPDX_URL, Settings::default().base_url());
+    }
+
+    #[test]
+    fn test_read_license_info() {
+        let dir = TempDir::new("spdx");
+        dir.write(
+            "MIT.json",
+            r#"{"licenseId": "MIT", "licenseText": "MIT License text"}"#,
+        );
+
+        let info = read_license_info(&dir, "MIT").expect("MIT.json is readable");
+        assert_eq!("MIT License text", info.license_text);
+        assert_eq!(None, info.license_header);
+        assert!(read_license_info(&dir, "ISC")
+            .err()
+            .is_some_and(|err| err.contains("ISC.json does not exist")));
+    }
+
+    #[test]
+    fn test_cache_dir() {
+        assert_eq!(
+            Some(PathBuf::from("/var/cache/licensure/spdx")),
+            cache_dir(env(&[
+                ("XDG_CACHE_HOME", "/var/cache"),
+                ("HOME", "/home/me")
+            ]))
+        );
+        assert_eq!(
+            Some(PathBuf::from("/home/me/.cache/licensure/spdx")),
+            cache_dir(env(&[("XDG_CACHE_HOME", ""), ("HOME", "/home/me")]))
+        );
+        assert_eq!(None, cache_dir(env(&[])));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let err = ureq::Error::Status(500, ureq::Response::new(500, "Error", "").unwrap());
+        assert_eq!(Duration::from_millis(500), backoff(0, &err));
+        assert_eq!(Duration::from_secs(2), backoff(2, &err));
+
+        let response = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 7\r\n\r\n"
+            .parse::<ureq::Response>()
+            .unwrap();
+        let err = ureq::Error::Status(429, response);
+        assert_eq!(Duration::from_secs(7), backoff(0, &err));
+
+        let response = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 86400\r\n\r\n"
+            .parse::<ureq::Response>()
+            .unwrap();
+        let err = ureq::Error::Status(429, response);
+        assert_eq!(MAX_RETRY_AFTER, backoff(0, &err));
+    }
+
+    #[test]
+    fn test_parse_registry() {
+        let registry = parse_registry(
+            "Acme-Internal:\n  header: Copyright [year] [name of author], all rights reserved.\n",
+            "registry.yml",
+        )
+        .expect("Can parse a YAML registry");
+        let info = registry["Acme-Internal"].license_info();
+        assert!(info.custom);
+        assert_eq!(
+            Some("Copyright [year] [name of author], all rights reserved."),
+            info.license_header.as_deref()
+        );
+        assert_eq!(
+            info.license_header.as_deref(),
+            Some(info.license_text.as_str())
+        );
+
+        let registry = parse_registry(
+            r#"{"Acme-1.0": {"header": "Acme [ident]", "text": "The Acme License"}}"#,
+            "registry.json",
+        )
+        .expect("Can parse a JSON registry");
+        assert_eq!(
+            Some("The Acme License"),
+            registry["Acme-1.0"].text.as_deref()
+        );
+
+        assert!(parse_registry("", "empty.yml")
+            .expect("Can parse an empty registry")
+            .is_empty());
+        assert!(parse_registry("Acme:\n  heder: typo\n", "typo.yml").is_err());
+    }
+
+    #[test]
+    fn test_reserve_slot() {
+        let now = Instant::now();
+        let interval = Duration::from_millis(200);
+        let mut next = None;
+        assert_eq!(Duration::ZERO, reserve_slot(&mut next, now, interval));
+        assert_eq!(interval, reserve_slot(&mut next, now, interval));
+        assert_eq!(interval * 2, reserve_slot(&mut next, now, interval));
+        // A slot in the past isn't waited for.
+        let later = now + Duration::from_secs(5);
+        assert_eq!(Duration::ZERO, reserve_slot(&mut next, later, interval));
+        assert_eq!(Some(later + interval), next);
+    }
+
+    #[test]
+    fn test_cache_validators() {
+        let dir = TempDir::new("validators");
+        let validators = Validators {
+            etag: Some("\"abc123\"".to_string()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+        };
+        assert_eq!(validators, Validators::parse(&validators.to_string()));
+
+        write_cache(&dir, "MIT", "{}", &validators).expect("Can write the cache");
+        assert_eq!(
+            Some(("{}".to_string(), validators)),
+            read_cache(&dir, "MIT")
+        );
+
+        // A license served without validators is always downloaded again.
+        write_cache(&dir, "MIT", "{}", &Validators::default()).expect("Can write the cache");
+        assert_eq!(None, read_cache(&dir, "MIT"));
+    }
+
+    #[test]
+    fn test_parse_pem_certificates() {
+        let pem = "subject=CN = Example\n-----BEGIN CERTIFICATE-----\nAQID\nBA==\n-----END CERTIFICATE-----\n-----BEGIN CERTIFICATE-----\nBQY=\n-----END CERTIFICATE-----\n";
+        assert_eq!(
+            Ok(vec![vec![1, 2, 3, 4], vec![5, 6]]),
+            parse_pem_certificates(pem)
+        );
+        assert!(parse_pem_certificates("not a certificate").is_err());
+        assert!(parse_pem_certificates("-----BEGIN CERTIFICATE-----\nAQID").is_err());
+    }
+
+    #[test]
+    fn test_build_agent() {
+        assert!(build_agent(&Settings::default()).is_ok());
+        let err = build_agent(&Settings {
+            ca_bundle: Some("/nonexistent/ca.pem".to_string()),
+            ..Settings::default()
+        })
+        .unwrap_err();
+        assert!(err.starts_with("unable to load CA bundle /nonexistent/ca.pem:"));
+    }
+
+    const MIT: &str = "MIT License
+
+Copyright (c) <year> <copyright holders>
+
+Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated documentation files (the \"Software\"), to deal in the Software without restriction, including without limitation the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED \"AS IS\", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.";
+
+    #[test]
+    fn test_text_matches_filled_in_license() {
+        let filled = MIT.replace("<year> <copyright holders>", "2024 Mathew Robinson");
+        let rewrapped = textwrap::fill(&filled, 72);
+        assert!(text_matches(MIT, &rewrapped));
+    }
+
+    #[test]
+    fn test_text_matches_rejects_other_license() {
+        let isc = "ISC License
+
+Copyright (c) 2024 Mathew Robinson
+
+Permission to use, copy, modify, and/or distribute this software for any purpose with or without fee is hereby granted, provided that the above copyright notice and this permission notice appear in all copies.
+
+THE SOFTWARE IS PROVIDED \"AS IS\" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.";
+        assert!(!text_matches(MIT, isc));
+        assert!(!text_matches(MIT, ""));
+    }
+
+    const GPL_3_HEADER: &str = "Copyright (C) <year>  <name of author>
+
+This program is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.";
+
+    #[test]
+    fn test_embedded_header() {
+        assert_eq!(Some(MIT), embedded_header("MIT"));
+        assert_eq!(Some(GPL_3_HEADER), embedded_header("GPL-3.0-or-later"));
+        assert_eq!(
+            embedded_header("GPL-2.0-only"),
+            embedded_header("GPL-2.0-or-later")
+        );
+        for ident in ["Apache-2.0", "BSD-2-Clause", "BSD-3-Clause"] {
+            assert!(embedded_header(ident).is_some_and(|header| header.starts_with("Copyright")));
+        }
+        assert_eq!(None, embedded_header("ISC"));
+    }
+
+    #[test]
+    fn test_similarity() {
+        let header = textwrap::fill(
+            &GPL_3_HEADER.replace("<year>  <name of author>", "2019-2024 Example Corp"),
+            60,
+        );
+        let commented: String = header
+            .lines()
+            .map(|line| format!("// {}\n", line))
+            .collect();
+        let file = format!("#!/bin/sh\n{}\nfn main() {{}}\n", commented);
+        assert_eq!(1.0, similarity(GPL_3_HEADER, &file));
+        assert!(similarity(MIT, &file) < SIMILARITY_THRESHOLD);
+        assert_eq!(0.0, similarity(GPL_3_HEADER, ""));
+        assert_eq!(0.0, similarity("<year>", "2024"));
+    }
+
+    #[test]
+    fn test_most_similar() {
+        let gpl_2_header = GPL_3_HEADER.replace("version 3", "version 2");
+        let templates = [
+            ("MIT", MIT),
+            ("GPL-2.0-or-later", gpl_2_header.as_str()),
+            ("GPL-3.0-or-later", GPL_3_HEADER),
+        ];
+        let header = GPL_3_HEADER.replace("<year>", "2024");
+        assert_eq!(Some("GPL-3.0-or-later"), most_similar(&header, &templates));
+        assert_eq!(
+            Some("GPL-2.0-or-later"),
+            most_similar(&gpl_2_header, &templates)
+        );
+        assert_eq!(None, most_similar("fn main() {}", &templates));
+    }
+
+    #[test]
+    fn test_write_cache_concurrently() {
+        let dir = TempDir::new("cache");
+        let json = format!(
+            r#"{{"licenseId": "MIT", "licenseText": "{}"}}"#,
+            "x".repeat(100_000)
+        );
+
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    write_cache(&dir, "MIT", &json, &Validators::default())
+                        .expect("Can write the cache")
+                });
+            }
+        });
+
+        let info = read_license_info(&dir, "MIT").expect("the cache holds a whole license");
+        assert_eq!(100_000, info.license_text.len());
+        // No lock or temporary files are left behind.
+        assert_eq!(1, fs::read_dir(&dir).expect("Can read cache").count());
+
+        // A lock left by a run which died is taken over.
+        let lock = dir.join("ISC.json.lock");
+        fs::write(&lock, "").expect("Can write lock");
+        let old = std::time::SystemTime::now() - CACHE_LOCK_STALE * 2;
+        fs::File::options()
+            .write(true)
+            .open(&lock)
+            .and_then(|f| f.set_modified(old))
+            .expect("Can age lock");
+        write_cache(&dir, "ISC", &json, &Validators::default())
+            .expect("Can take over a stale lock");
+        assert!(!lock.exists());
+
+        // Runs which find it at the same time take turns holding it.
+        fs::write(&lock, "").expect("Can write lock");
+        fs::File::options()
+            .write(true)
+            .open(&lock)
+            .and_then(|f| f.set_modified(old))
+            .expect("Can age lock");
+        let holders = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    let held = CacheLock::acquire(lock.clone()).expect("Can take the lock");
+                    assert_eq!(0, holders.fetch_add(1, Ordering::SeqCst));
+                    thread::sleep(Duration::from_millis(5));
+                    holders.fetch_sub(1, Ordering::SeqCst);
+                    drop(held);
+                });
+            }
+        });
+        assert!(!lock.exists());
+        assert_eq!(2, fs::read_dir(&dir).expect("Can read cache").count());
+    }
+}
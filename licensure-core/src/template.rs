@@ -0,0 +1,1081 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use chrono::prelude::*;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::comments::Comment;
+use crate::utils::{
+    remove_column_wrapping, remove_nowrap_markers, strip_verbatim, NOWRAP_MARKER, VERBATIM,
+    VERBATIM_END, VERBATIM_START,
+};
+
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub struct CopyrightHolder {
+    name: String,
+    email: Option<String>,
+}
+
+impl fmt::Display for CopyrightHolder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut a = self.name.clone();
+
+        if let Some(email) = &self.email {
+            a.push_str(&format!(" <{}>", email));
+        }
+
+        write!(f, "{}", a)
+    }
+}
+
+impl CopyrightHolder {
+    fn same_person(&self, other: &CopyrightHolder) -> bool {
+        match (&self.email, &other.email) {
+            (Some(email), Some(other_email)) => email.eq_ignore_ascii_case(other_email),
+            _ => self.name == other.name,
+        }
+    }
+
+    // Parse a "Name <email>" line as found in AUTHORS files.
+    pub(crate) fn parse(line: &str) -> CopyrightHolder {
+        match line.split_once('<') {
+            Some((name, email)) if email.ends_with('>') => CopyrightHolder {
+                name: name.trim().to_string(),
+                email: Some(email.trim_end_matches('>').trim().to_string()),
+            },
+            _ => CopyrightHolder {
+                name: line.to_string(),
+                email: None,
+            },
+        }
+    }
+}
+
+/// A single holder for the whole organization or project, used in place of
+/// a list of individual authors.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Holder {
+    /// Rendered as "<Company> and contributors".
+    Company(String),
+    /// Rendered as "The <Project> Authors".
+    Project(String),
+}
+
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Holder::Company(company) => write!(f, "{} and contributors", company),
+            Holder::Project(project) => write!(f, "The {} Authors", project),
+        }
+    }
+}
+
+impl From<&Holder> for Authors {
+    fn from(holder: &Holder) -> Authors {
+        Authors::from(vec![CopyrightHolder {
+            name: holder.to_string(),
+            email: None,
+        }])
+    }
+}
+
+// Authors are either listed in the config, read from a file such as AUTHORS
+// with one "Name <email>" per line, or taken from the git identity of whoever
+// is running licensure.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AuthorsSource {
+    List(Vec<CopyrightHolder>),
+    File { file: String },
+    Keyword(AuthorsKeyword),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AuthorsKeyword {
+    FromGitConfig,
+}
+
+#[derive(Clone, Deserialize, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(try_from = "AuthorsSource")]
+pub struct Authors {
+    authors: Vec<CopyrightHolder>,
+}
+
+impl Authors {
+    /// Add holder unless it's already one of the authors, either by email or,
+    /// when one of them has no email, by name.
+    pub(crate) fn add(&mut self, holder: CopyrightHolder) {
+        if !self
+            .authors
+            .iter()
+            .any(|author| author.same_person(&holder))
+        {
+            self.authors.push(holder);
+        }
+    }
+
+    /// Parse an AUTHORS file. Blank lines and lines starting with # are
+    /// ignored.
+    fn parse(content: &str) -> Authors {
+        Authors {
+            authors: content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(CopyrightHolder::parse)
+                .collect(),
+        }
+    }
+
+    /// The user.name and user.email of the git config of dir as the only
+    /// author.
+    fn from_git_config(dir: &Path) -> Result<Authors, String> {
+        let name = git_config(dir, "user.name")?
+            .ok_or_else(|| "authors is from_git_config but git has no user.name set".to_string())?;
+        Ok(Authors::from(vec![CopyrightHolder {
+            name,
+            email: git_config(dir, "user.email")?,
+        }]))
+    }
+}
+
+impl From<Vec<CopyrightHolder>> for Authors {
+    fn from(authors: Vec<CopyrightHolder>) -> Authors {
+        Authors { authors }
+    }
+}
+
+impl TryFrom<AuthorsSource> for Authors {
+    type Error = String;
+
+    fn try_from(source: AuthorsSource) -> Result<Authors, String> {
+        match source {
+            AuthorsSource::List(authors) => Ok(Authors::from(authors)),
+            AuthorsSource::File { file } => match fs::read_to_string(&file) {
+                Ok(content) => Ok(Authors::parse(&content)),
+                Err(e) => Err(format!("unable to read authors file {}: {}", file, e)),
+            },
+            AuthorsSource::Keyword(AuthorsKeyword::FromGitConfig) => {
+                Authors::from_git_config(Path::new("."))
+            }
+        }
+    }
+}
+
+// The value of key in the git config of dir, None if it isn't set.
+fn git_config(dir: &Path, key: &str) -> Result<Option<String>, String> {
+    let proc = Command::new("git")
+        .current_dir(dir)
+        .args(["config", "--get", key])
+        .output()
+        .map_err(|e| format!("unable to run git config for authors: {}", e))?;
+    let value = String::from_utf8_lossy(&proc.stdout).trim().to_string();
+    Ok(Some(value).filter(|value| proc.status.success() && !value.is_empty()))
+}
+
+impl fmt::Display for Authors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut a = String::new();
+
+        for author in &self.authors {
+            if !a.is_empty() {
+                a.push_str(", ");
+            }
+
+            a.push_str(&author.to_string());
+        }
+
+        write!(f, "{}", a)
+    }
+}
+
+/// The language of the copyright line, for notices which must be given in
+/// something other than English.
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Es,
+    Fr,
+    It,
+    Ja,
+    Zh,
+}
+
+impl Locale {
+    /// The copyright line of holders for year in this language.
+    pub fn copyright_line(&self, year: &str, holders: &str) -> String {
+        match self {
+            Locale::En => format!("Copyright {} {}", year, holders),
+            Locale::De => format!("Copyright © {} {}. Alle Rechte vorbehalten.", year, holders),
+            Locale::Es => format!(
+                "Copyright © {} {}. Todos los derechos reservados.",
+                year, holders
+            ),
+            Locale::Fr => format!("Copyright © {} {}. Tous droits réservés.", year, holders),
+            Locale::It => format!(
+                "Copyright © {} {}. Tutti i diritti riservati.",
+                year, holders
+            ),
+            Locale::Ja => format!("著作権所有 © {} {}。無断転載を禁じます。", year, holders),
+            Locale::Zh => format!("版权所有 © {} {}。保留所有权利。", year, holders),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Context {
+    pub ident: String,
+    pub authors: Authors,
+    pub contributors: Authors,
+    pub locale: Locale,
+    pub end_year: Option<String>,
+    pub start_year: Option<String>,
+    pub unwrap_text: bool,
+}
+
+impl Context {
+    /// The copyright notice for this context without any license text, for
+    /// example "Copyright 2020, 2024 Mathew Robinson", in its locale.
+    pub fn copyright_line(&self) -> String {
+        self.locale
+            .copyright_line(&self.get_year(), &self.get_authors())
+    }
+
+    fn get_authors(&self) -> String {
+        self.authors.to_string()
+    }
+
+    fn get_year(&self) -> String {
+        let end_year = match &self.end_year {
+            Some(year) => year.clone(),
+            None => format!("{}", Local::now().year()),
+        };
+
+        match &self.start_year {
+            Some(start_year) if *start_year != end_year => format!("{}, {}", start_year, end_year),
+            _ => end_year,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Template {
+    spdx_template: bool,
+    content: String,
+    context: Context,
+}
+
+// this token is temporarily used when formatting the template into a comment
+// regex with the correct column width that can match the [year] against /[\d]{4}/
+//
+// this intermediate token needs to be exactly 4 chars long so we can wrap to the
+// correct column width, but also be unique enough so that we can subsequently swap
+// it for a regex pattern while not colliding with any text that might already be
+// in the license text.
+const INTERMEDIATE_YEAR_TOKEN: &str = "@YR@";
+
+// Stand-ins for escaped [[ and ]] while a template is interpolated, from the
+// Unicode private use area so they never occur in a real template.
+const ESCAPED_OPEN: &str = "\u{E000}";
+const ESCAPED_CLOSE: &str = "\u{E001}";
+
+// Replaced with the copyright line in the locale of the file, in every kind of
+// template.
+const COPYRIGHT_TOKEN: &str = "[copyright]";
+
+// Replaced with the contributors of the file, in every kind of template.
+const CONTRIBUTORS_TOKEN: &str = "[contributors]";
+
+// The placeholders of templates which aren't SPDX license texts.
+const PLACEHOLDERS: &[&str] = &[
+    "[year]",
+    "[name of author]",
+    "[ident]",
+    CONTRIBUTORS_TOKEN,
+    COPYRIGHT_TOKEN,
+    NOWRAP_MARKER,
+    VERBATIM_START,
+];
+
+// Matches any full 4-digit year
+const YEAR_RE: &str = "[0-9]{4}(, [0-9]{4})?";
+
+impl Template {
+    pub fn new(template: &str, context: Context) -> Template {
+        Template {
+            spdx_template: false,
+            content: template.to_string(),
+            context,
+        }
+    }
+
+    pub fn set_spdx_template(mut self, yes_or_no: bool) -> Template {
+        self.spdx_template = yes_or_no;
+        self
+    }
+
+    pub fn outdated_license_pattern(&self, commenter: &dyn Comment) -> Result<Regex, String> {
+        Ok(year_varying_regex(
+            &self.year_varying_header(commenter)?,
+            false,
+        ))
+    }
+
+    pub fn outdated_license_trimmed_pattern(
+        &self,
+        commenter: &dyn Comment,
+    ) -> Result<Regex, String> {
+        Ok(year_varying_regex(
+            &self.year_varying_header(commenter)?,
+            true,
+        ))
+    }
+
+    pub fn render(&self) -> String {
+        self.interpolate(&self.context)
+    }
+
+    fn interpolate(&self, context: &Context) -> String {
+        let (year_repl, author_repl, ident_repl) = self.replacement_tokens();
+        let templ = if self.context.unwrap_text {
+            // Some license headers come pre-textwrapped. This regex
+            // replacement removes their wrapping while preserving
+            // intentional line breaks / empty lines.
+            remove_column_wrapping(&self.content.clone())
+        } else {
+            remove_nowrap_markers(&self.content)
+        };
+
+        // Doubled brackets are escaped, so [[year]] is a literal [year]. They
+        // are swapped out until the placeholders have been filled in. SPDX
+        // license texts are used as is.
+        let templ = if self.spdx_template {
+            templ
+        } else {
+            mark_verbatim(&templ)
+                .replace("[[", ESCAPED_OPEN)
+                .replace("]]", ESCAPED_CLOSE)
+        };
+
+        // Perform our substitutions
+        templ
+            .replace(COPYRIGHT_TOKEN, &context.copyright_line())
+            .replace(year_repl, &context.get_year())
+            .replace(author_repl, &context.get_authors())
+            .replace(CONTRIBUTORS_TOKEN, &context.contributors.to_string())
+            .replace(ident_repl, &context.ident)
+            .replace(ESCAPED_OPEN, "[")
+            .replace(ESCAPED_CLOSE, "]")
+    }
+
+    // The header commented by commenter with a token in place of the year,
+    // which year_varying_regex matches any year in place of.
+    pub(crate) fn year_varying_header(&self, commenter: &dyn Comment) -> Result<String, String> {
+        let mut context = self.context.clone();
+
+        // interpolate the header with the intermediate year token
+        context.end_year = Some(INTERMEDIATE_YEAR_TOKEN.to_string());
+        // The year regex accounts for ranges so we don't need to worry about start_year here.
+        context.start_year = None;
+
+        commenter.comment(&self.interpolate(&context))
+    }
+
+    /// Returns a regex matching the words of the rendered header, with any
+    /// whitespace between them and any year, which is captured in the year
+    /// group. Copies of the header which are wrapped or commented differently
+    /// match it once their words are joined by single spaces.
+    pub fn words_pattern(&self) -> Regex {
+        let mut context = self.context.clone();
+        context.end_year = Some(INTERMEDIATE_YEAR_TOKEN.to_string());
+        context.start_year = None;
+
+        let words: Vec<String> = strip_verbatim(&self.interpolate(&context))
+            .split_whitespace()
+            .map(regex::escape)
+            .collect();
+        let pattern = format!("^{}$", words.join(r"\s+"))
+            .replacen(
+                INTERMEDIATE_YEAR_TOKEN,
+                &format!("(?P<year>{})", YEAR_RE),
+                1,
+            )
+            .replace(INTERMEDIATE_YEAR_TOKEN, YEAR_RE);
+
+        Regex::new(&pattern).unwrap()
+    }
+
+    fn replacement_tokens(&self) -> (&'static str, &'static str, &'static str) {
+        if self.spdx_template {
+            // Check if it's the Apache license which has a super
+            // special format.
+            if self.content.contains("[name of copyright owner]") {
+                ("[yyyy]", "[name of copyright owner]", "[ident]")
+            } else {
+                (
+                    "<year>",
+                    if self.content.contains("<copyright holders>") {
+                        "<copyright holders>"
+                    } else if self.content.contains("<owner>") {
+                        "<owner>"
+                    } else {
+                        "<name of author>"
+                    },
+                    "<ident>",
+                )
+            }
+        } else {
+            ("[year]", "[name of author]", "[ident]")
+        }
+    }
+}
+
+// Remove the verbatim block markers of template and mark the lines between
+// them so they're never wrapped.
+fn mark_verbatim(template: &str) -> String {
+    let mut marked = Vec::new();
+    let mut verbatim = false;
+    for line in template.split('\n') {
+        match line.trim() {
+            VERBATIM_START if !verbatim => verbatim = true,
+            VERBATIM_END if verbatim => verbatim = false,
+            _ if verbatim => marked.push(format!("{}{}", VERBATIM, line)),
+            _ => marked.push(line.to_string()),
+        }
+    }
+
+    marked.join("\n")
+}
+
+/// Fails with the placeholder and its line and column if template contains
+/// something that looks like a placeholder but isn't one, such as [yaer].
+/// Escaped brackets are literal text and never placeholders.
+pub fn check_placeholders(template: &str) -> Result<(), String> {
+    static PLACEHOLDER_RE: OnceLock<Regex> = OnceLock::new();
+    let re = PLACEHOLDER_RE.get_or_init(|| Regex::new(r"\[[a-zA-Z][a-zA-Z _-]*\]").unwrap());
+
+    for (idx, line) in template.lines().enumerate() {
+        let unescaped = line
+            .replace("[[", ESCAPED_OPEN)
+            .replace("]]", ESCAPED_CLOSE);
+        for m in re.find_iter(&unescaped) {
+            if PLACEHOLDERS.contains(&m.as_str()) {
+                continue;
+            }
+
+            return Err(format!(
+                "unknown placeholder {} at line {}, column {}, use [{}] for literal text",
+                m.as_str(),
+                idx + 1,
+                unescaped[..m.start()].chars().count() + 1,
+                m.as_str()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace each [include:name] in template with the partial called name,
+/// whose own includes are replaced in turn. The trailing newlines of
+/// partials are dropped, so an include on a line of its own stays one.
+/// Escaped brackets are literal text and never includes. Fails if a partial
+/// isn't defined or includes itself.
+pub fn expand_includes(
+    template: &str,
+    partials: &BTreeMap<String, String>,
+) -> Result<String, String> {
+    expand_includes_within(template, partials, &mut Vec::new())
+}
+
+// Expand the includes of template, which is the partial last in within, if
+// any, included by the one before it and so on.
+fn expand_includes_within<'a>(
+    template: &str,
+    partials: &'a BTreeMap<String, String>,
+    within: &mut Vec<&'a str>,
+) -> Result<String, String> {
+    static INCLUDE_RE: OnceLock<Regex> = OnceLock::new();
+    let re = INCLUDE_RE.get_or_init(|| Regex::new(r"\[\[|\[include:([^\]]*)\]").unwrap());
+
+    let mut expanded = String::new();
+    let mut last = 0;
+    for caps in re.captures_iter(template) {
+        let Some(name) = caps.get(1) else {
+            continue;
+        };
+        let Some((name, partial)) = partials.get_key_value(name.as_str()) else {
+            let defined = if partials.is_empty() {
+                "no partials are configured".to_string()
+            } else {
+                format!(
+                    "the partials are: {}",
+                    partials.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            };
+            return Err(format!(
+                "[include:{}] of an undefined partial, {}",
+                name.as_str(),
+                defined
+            ));
+        };
+        if within.contains(&name.as_str()) {
+            return Err(format!(
+                "partial {} includes itself through {}",
+                name,
+                within.join(", ")
+            ));
+        }
+
+        within.push(name);
+        let partial = expand_includes_within(partial.trim_end_matches('\n'), partials, within)?;
+        within.pop();
+
+        let whole = caps.get(0).expect("captures have a whole match");
+        expanded.push_str(&template[last..whole.start()]);
+        expanded.push_str(&partial);
+        last = whole.end();
+    }
+    expanded.push_str(&template[last..]);
+
+    Ok(expanded)
+}
+
+/// Replace each [plugin:name:function] in template with what call returns
+/// for name and function, without its trailing newlines and with its brackets
+/// escaped so it's literal text. Escaped brackets are literal text and never
+/// calls. Fails with the call if call does.
+pub fn expand_plugin_calls(
+    template: &str,
+    mut call: impl FnMut(&str, &str) -> Result<String, String>,
+) -> Result<String, String> {
+    static CALL_RE: OnceLock<Regex> = OnceLock::new();
+    let re = CALL_RE.get_or_init(|| Regex::new(r"\[\[|\[plugin:([^:\]]*):([^\]]*)\]").unwrap());
+
+    let mut expanded = String::new();
+    let mut last = 0;
+    for caps in re.captures_iter(template) {
+        let (Some(name), Some(function)) = (caps.get(1), caps.get(2)) else {
+            continue;
+        };
+        let whole = caps.get(0).expect("captures have a whole match");
+        let text = call(name.as_str(), function.as_str())
+            .map_err(|e| format!("calls {}, which fails: {}", whole.as_str(), e))?;
+
+        expanded.push_str(&template[last..whole.start()]);
+        expanded.push_str(
+            &text
+                .trim_end_matches('\n')
+                .replace('[', "[[")
+                .replace(']', "]]"),
+        );
+        last = whole.end();
+    }
+    expanded.push_str(&template[last..]);
+
+    Ok(expanded)
+}
+
+// Returns a regex matching the header year_varying_header rendered, with any
+// year.
+pub(crate) fn year_varying_regex(rendered: &str, trim_trailing: bool) -> Regex {
+    let rendered = if trim_trailing {
+        rendered.trim_end()
+    } else {
+        rendered
+    };
+
+    // let's now replace the intermediate year token with a proper
+    // regex for a 4-digit year (see const `YEAR_RE`)
+    let escaped = rendered
+        // split removes all instances of the token, yielding all text fragments
+        // around the locations where tokens were excised
+        .split(INTERMEDIATE_YEAR_TOKEN)
+        // convert to iterable for functional-style chaining
+        .collect::<Vec<_>>()
+        .into_iter()
+        // regex-escape each text fragment so we can match the literal
+        // text via regex
+        .map(regex::escape)
+        // yields a list containing all of the text fragments we want
+        // to match as literals via regex
+        .collect::<Vec<_>>()
+        // joining the fragments with the year-matching regex pattern
+        // effectively inserts itself into all the locations where the
+        // intermediate token existed. We now have a regex that matches
+        // the exact license header text, but with any 4-digit year.
+        //
+        // And we only care about 4-digit years in our lifetime ;).
+        .join(YEAR_RE);
+
+    Regex::new(&escaped).unwrap()
+}
+
+#[cfg(test)]
+pub fn test_context(year: &str) -> Context {
+    Context {
+        ident: String::from("test"),
+        contributors: Authors::default(),
+        locale: Locale::default(),
+        authors: Authors::from(vec![]),
+        end_year: Some(String::from(year)),
+        start_year: None,
+        unwrap_text: true,
+    }
+}
+
+#[cfg(test)]
+pub fn test_context_with_range(start_year: &str, end_year: &str) -> Context {
+    Context {
+        ident: String::from("test"),
+        contributors: Authors::default(),
+        locale: Locale::default(),
+        authors: Authors::from(vec![]),
+        end_year: Some(String::from(end_year)),
+        start_year: Some(String::from(start_year)),
+        unwrap_text: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::comments::LineComment;
+    use crate::testing::GitRepo;
+
+    use super::*;
+
+    #[test]
+    fn test_year_varying_regex_untrimmed() {
+        let context = test_context("2020");
+        let template = Template::new("License [year]\n\ntext", context);
+        let commenter = LineComment::new("#", None);
+        let rgx = template.outdated_license_pattern(&commenter).unwrap();
+        let expected = Regex::new("\\# License [0-9]{4}(, [0-9]{4})?\n\\#\n\\# text\n")
+            .expect("This should have compiled?");
+
+        assert_eq!(rgx.to_string(), expected.to_string());
+        assert!(rgx.is_match(
+            r#"# License 2020
+#
+# text
+"#
+        ));
+    }
+
+    #[test]
+    fn test_year_varying_regex_trimmed() {
+        let context = test_context("2020");
+        let template = Template::new("License [year]\n\ntext", context);
+        let commenter = LineComment::new("#", None);
+        let rgx = template
+            .outdated_license_trimmed_pattern(&commenter)
+            .unwrap();
+        let expected = Regex::new("\\# License [0-9]{4}(, [0-9]{4})?\n\\#\n\\# text")
+            .expect("This should have compiled?");
+
+        assert_eq!(rgx.to_string(), expected.to_string());
+        assert!(rgx.is_match(
+            r#"# License 2020
+#
+# text"#
+        ));
+    }
+
+    #[test]
+    fn test_substitution_at_end_of_line() {
+        let context = test_context("2020");
+        let template = Template::new("License [year]\ntext", context);
+        // Newline is removed because of column unwrapping.
+        let expected = String::from("License 2020 text");
+        assert_eq!(expected, template.render())
+    }
+
+    #[test]
+    fn test_substitutions() {
+        let context = Context {
+            ident: String::from("test"),
+            contributors: Authors::default(),
+            locale: Locale::default(),
+            authors: Authors::from(vec![CopyrightHolder {
+                name: "Mathew Robinson".to_string(),
+                email: Some("chasinglogic@gmail.com".to_string()),
+            }]),
+            end_year: Some(String::from("2020")),
+            start_year: None,
+            unwrap_text: true,
+        };
+        let template = Template::new("Copyright (C) [year] [name of author] This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, version 3. This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details. You should have received a copy of the GNU Affero General Public License along with this program. If not, see <https://www.gnu.org/licenses/>", context);
+        let expected = String::from("Copyright (C) 2020 Mathew Robinson <chasinglogic@gmail.com> This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, version 3. This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details. You should have received a copy of the GNU Affero General Public License along with this program. If not, see <https://www.gnu.org/licenses/>");
+        assert_eq!(expected, template.render())
+    }
+
+    #[test]
+    fn test_outdated_license_matching() {
+        let context = Context {
+            ident: String::from("test"),
+            contributors: Authors::default(),
+            locale: Locale::default(),
+            authors: Authors::from(vec![CopyrightHolder {
+                name: "Mathew Robinson".to_string(),
+                email: Some("chasinglogic@gmail.com".to_string()),
+            }]),
+            end_year: Some(String::from("2022")),
+            start_year: None,
+            unwrap_text: true,
+        };
+        let template = Template::new(
+            "Copyright (C) [year] [name of author] This program is free software.",
+            context,
+        );
+        let commenter: Box<dyn Comment> = Box::new(LineComment::new("#", Option::Some(1000)));
+        let re = template
+            .outdated_license_pattern(commenter.as_ref())
+            .unwrap();
+        assert!(re.is_match("# Copyright (C) 2020 Mathew Robinson <chasinglogic@gmail.com> This program is free software.\n"))
+    }
+
+    #[test]
+    fn test_outdated_license_trimmed_matching() {
+        let context = Context {
+            ident: String::from("test"),
+            contributors: Authors::default(),
+            locale: Locale::default(),
+            authors: Authors::from(vec![CopyrightHolder {
+                name: "Mathew Robinson".to_string(),
+                email: Some("chasinglogic@gmail.com".to_string()),
+            }]),
+            end_year: Some(String::from("2022")),
+            start_year: None,
+            unwrap_text: true,
+        };
+        let template = Template::new(
+            "Copyright (C) [year] [name of author] This program is free software.",
+            context,
+        );
+        let commenter: Box<dyn Comment> =
+            Box::new(LineComment::new("#", Option::Some(1000)).set_trailing_lines(2));
+        let re = template
+            .outdated_license_pattern(commenter.as_ref())
+            .unwrap();
+        assert!(re.is_match("# Copyright (C) 2020 Mathew Robinson <chasinglogic@gmail.com> This program is free software.\n\n\n"));
+        assert!(!re.is_match("# Copyright (C) 2020 Mathew Robinson <chasinglogic@gmail.com> This program is free software."));
+
+        let trimmed = template
+            .outdated_license_trimmed_pattern(commenter.as_ref())
+            .unwrap();
+        assert!(trimmed.is_match("# Copyright (C) 2020 Mathew Robinson <chasinglogic@gmail.com> This program is free software."))
+    }
+
+    #[test]
+    fn test_substitutions_prewrapped() {
+        let context = Context {
+            ident: String::from("test"),
+            contributors: Authors::default(),
+            locale: Locale::default(),
+            authors: Authors::from(vec![CopyrightHolder {
+                name: "Mathew Robinson".to_string(),
+                email: Some("chasinglogic@gmail.com".to_string()),
+            }]),
+            end_year: Some(String::from("2020")),
+            start_year: None,
+            unwrap_text: true,
+        };
+        let template = Template::new(
+            "Copyright (C) [year] [name of author] This
+program is free software: you can redistribute it and/or modify it under
+the terms of the GNU Affero General Public License as published by the
+Free Software Foundation, version 3. This program is distributed in the
+hope that it will be useful, but WITHOUT ANY WARRANTY; without even the
+implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+See the GNU Affero General Public License for more details. You should
+have received a copy of the GNU Affero General Public License along with
+this program. If not, see <https://www.gnu.org/licenses/>",
+            context,
+        );
+        let expected = String::from("Copyright (C) 2020 Mathew Robinson <chasinglogic@gmail.com> This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, version 3. This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details. You should have received a copy of the GNU Affero General Public License along with this program. If not, see <https://www.gnu.org/licenses/>");
+        assert_eq!(expected, template.render())
+    }
+
+    // https://github.com/chasinglogic/licensure/issues/25
+    #[test]
+    fn test_substitutions_unwrap_text_false() {
+        let context = Context {
+            ident: String::from("test"),
+            contributors: Authors::default(),
+            locale: Locale::default(),
+            authors: Authors::from(vec![CopyrightHolder {
+                name: "Mathew Robinson".to_string(),
+                email: Some("chasinglogic@gmail.com".to_string()),
+            }]),
+            end_year: Some(String::from("2020")),
+            start_year: None,
+            unwrap_text: false,
+        };
+        let template = Template::new(
+            "Copyright (c) [name of author]
+ SPDX-License-Identifier: [ident]",
+            context,
+        );
+        let expected = String::from(
+            "Copyright (c) Mathew Robinson <chasinglogic@gmail.com>
+ SPDX-License-Identifier: test",
+        );
+        assert_eq!(expected, template.render())
+    }
+
+    #[test]
+    fn test_substitutions_prewrapped_preserves_linebreaks() {
+        let context = Context {
+            ident: String::from("test"),
+            contributors: Authors::default(),
+            locale: Locale::default(),
+            authors: Authors::from(vec![CopyrightHolder {
+                name: "Mathew Robinson".to_string(),
+                email: Some("chasinglogic@gmail.com".to_string()),
+            }]),
+            end_year: Some(String::from("2020")),
+            start_year: None,
+            unwrap_text: true,
+        };
+        let template = Template::new(
+            "Copyright (C) [year] [name of author] This
+program is free software: you can redistribute it and/or modify it under
+the terms of the GNU Affero General Public License as published by the
+
+Free Software Foundation, version 3. This program is distributed in the
+hope that it will be useful, but WITHOUT ANY WARRANTY; without even the
+implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+See the GNU Affero General Public License for more details. You should
+have received a copy of the GNU Affero General Public License along with
+this program. If not, see <https://www.gnu.org/licenses/>",
+            context,
+        );
+        let expected = String::from("Copyright (C) 2020 Mathew Robinson <chasinglogic@gmail.com> This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the
+
+Free Software Foundation, version 3. This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details. You should have received a copy of the GNU Affero General Public License along with this program. If not, see <https://www.gnu.org/licenses/>");
+        assert_eq!(expected, template.render())
+    }
+
+    #[test]
+    fn test_substitutions_year_ranges() {
+        let context = Context {
+            ident: String::from("test"),
+            contributors: Authors::default(),
+            locale: Locale::default(),
+            authors: Authors::from(vec![CopyrightHolder {
+                name: "Mathew Robinson".to_string(),
+                email: Some("chasinglogic@gmail.com".to_string()),
+            }]),
+            end_year: Some(String::from("2024")),
+            start_year: Some(String::from("2020")),
+            unwrap_text: true,
+        };
+        let template = Template::new("Copyright (C) [year] [name of author] This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, version 3. This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details. You should have received a copy of the GNU Affero General Public License along with this program. If not, see <https://www.gnu.org/licenses/>", context);
+        let expected = String::from("Copyright (C) 2020, 2024 Mathew Robinson <chasinglogic@gmail.com> This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, version 3. This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details. You should have received a copy of the GNU Affero General Public License along with this program. If not, see <https://www.gnu.org/licenses/>");
+        assert_eq!(expected, template.render())
+    }
+
+    #[test]
+    fn test_authors_file() {
+        let authors = Authors::parse(
+            "# The project authors, one per line.
+
+Mathew Robinson <chasinglogic@gmail.com>
+  Example Corp
+",
+        );
+        assert_eq!(
+            "Mathew Robinson <chasinglogic@gmail.com>, Example Corp",
+            authors.to_string()
+        );
+
+        let authors: Result<Authors, _> =
+            serde_yaml::from_str("file: this/file/does/not/exist/AUTHORS");
+        assert!(authors.is_err());
+    }
+
+    #[test]
+    fn test_authors_from_git_config() {
+        let repo = GitRepo::new("git-config");
+        assert_eq!(
+            "Tester <tester@example.com>",
+            Authors::from_git_config(&repo).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_contributors() {
+        let mut context = test_context("2024");
+        context.contributors = serde_yaml::from_str("- name: Jo Dev\n- name: Sam Roe").unwrap();
+        let template = Template::new("Copyright [year]. Contributors: [contributors]", context);
+        assert_eq!(
+            "Copyright 2024. Contributors: Jo Dev, Sam Roe",
+            template.render()
+        );
+    }
+
+    #[test]
+    fn test_locale() {
+        let mut context = test_context("2024");
+        context.authors = serde_yaml::from_str("- name: Exemple SA").unwrap();
+        assert_eq!("Copyright 2024 Exemple SA", context.copyright_line());
+
+        context.locale = serde_yaml::from_str("fr").unwrap();
+        let template = Template::new("[copyright]\n\nLicence [ident].", context);
+        assert_eq!(
+            "Copyright © 2024 Exemple SA. Tous droits réservés.\n\nLicence test.",
+            template.render()
+        );
+        assert!(template
+            .outdated_license_pattern(&crate::comments::LineComment::new("#", None))
+            .unwrap()
+            .is_match(
+                "# Copyright © 2019 Exemple SA. Tous droits réservés.\n#\n# Licence test.\n"
+            ));
+    }
+
+    #[test]
+    fn test_escaped_placeholders() {
+        let template = Template::new(
+            "Copyright [year] [[name of author]]. Replace [[year]] when copying.",
+            test_context("2024"),
+        );
+        assert_eq!(
+            "Copyright 2024 [name of author]. Replace [year] when copying.",
+            template.render()
+        );
+        assert!(template
+            .outdated_license_pattern(&crate::comments::LineComment::new("#", None))
+            .unwrap()
+            .is_match("# Copyright 2019 [name of author]. Replace [year] when copying.\n"));
+    }
+
+    #[test]
+    fn test_check_placeholders() {
+        assert!(
+            check_placeholders("Copyright [year] [name of author]\n\n[ident] [[yaer]]").is_ok()
+        );
+        assert_eq!(
+            Err(
+                "unknown placeholder [yaer] at line 2, column 11, use [[yaer]] for literal text"
+                    .to_string()
+            ),
+            check_placeholders("[copyright]\nCopyright [yaer] [name of author]")
+        );
+        // Only bracketed words look like placeholders.
+        assert!(check_placeholders("See [1] and [LICENSE.md](LICENSE.md)").is_ok());
+        assert!(check_placeholders("Copyright [Project Name]").is_err());
+    }
+
+    #[test]
+    fn test_expand_includes() {
+        let partials: BTreeMap<String, String> = [
+            ("contact", "Contact legal@example.com\n"),
+            ("footer", "[copyright]\n[include:contact]"),
+            ("loop", "[include:loop_back]"),
+            ("loop_back", "[include:loop]"),
+        ]
+        .into_iter()
+        .map(|(name, text)| (name.to_string(), text.to_string()))
+        .collect();
+
+        assert_eq!(
+            Ok("[ident] license\n\n[copyright]\nContact legal@example.com\n".to_string()),
+            expand_includes("[ident] license\n\n[include:footer]\n", &partials)
+        );
+        assert_eq!(
+            Ok("See [[include:footer]]".to_string()),
+            expand_includes("See [[include:footer]]", &partials)
+        );
+        assert!(expand_includes("[include:fotter]", &partials)
+            .unwrap_err()
+            .contains("[include:fotter] of an undefined partial"));
+        assert_eq!(
+            Err("partial loop includes itself through loop, loop_back".to_string()),
+            expand_includes("[include:loop]", &partials)
+        );
+    }
+
+    #[test]
+    fn test_expand_plugin_calls() {
+        let call = |name: &str, function: &str| match (name, function) {
+            ("acme", "owner") => Ok("Example [Corp]\n".to_string()),
+            _ => Err(format!("undefined plugin {}", name)),
+        };
+
+        assert_eq!(
+            Ok("Copyright [year] Example [[Corp]]".to_string()),
+            expand_plugin_calls("Copyright [year] [plugin:acme:owner]", call)
+        );
+        assert_eq!(
+            Ok("See [[plugin:acme:owner]]".to_string()),
+            expand_plugin_calls("See [[plugin:acme:owner]]", call)
+        );
+        assert_eq!(
+            Err("calls [plugin:other:owner], which fails: undefined plugin other".to_string()),
+            expand_plugin_calls("[plugin:other:owner]", call)
+        );
+    }
+
+    #[test]
+    fn test_verbatim_blocks() {
+        let mut context = test_context("2024");
+        context.unwrap_text = true;
+        let template = Template::new(
+            "Copyright [year] Example Corp, all\nrights reserved.\n[verbatim]\n  +----------------------------------+\n  | Example Corp, 1 Main Street      |\n  +----------------------------------+\n[/verbatim]\nContact us at the address above for\nlicensing.",
+            context,
+        );
+        assert_eq!(
+            "# Copyright 2024 Example Corp,\n# all rights reserved.\n#   +----------------------------------+\n#   | Example Corp, 1 Main Street      |\n#   +----------------------------------+\n# Contact us at the address\n# above for licensing.\n",
+            crate::comments::LineComment::new("#", Some(30)).comment(&template.render()).unwrap()
+        );
+        assert_eq!(
+            "# Copyright 2024 Example Corp, all rights reserved.\n#   +----------------------------------+\n#   | Example Corp, 1 Main Street      |\n#   +----------------------------------+\n# Contact us at the address above for licensing.\n",
+            crate::comments::LineComment::new("#", None).comment(&template.render()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_holder() {
+        let holder: Holder = serde_yaml::from_str("company: Example Corp").unwrap();
+        assert_eq!(
+            "Copyright 2024 Example Corp and contributors",
+            Context {
+                ident: String::from("test"),
+                contributors: Authors::default(),
+                locale: Locale::default(),
+                authors: Authors::from(&holder),
+                end_year: Some(String::from("2024")),
+                start_year: None,
+                unwrap_text: true,
+            }
+            .copyright_line()
+        );
+
+        let holder: Holder = serde_yaml::from_str("project: Kubernetes").unwrap();
+        assert_eq!("The Kubernetes Authors", holder.to_string());
+    }
+}
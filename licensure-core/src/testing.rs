@@ -0,0 +1,152 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Temporary directories and git repositories for the tests of licensure and
+//! licensure-core, which clean up after themselves when they're dropped.
+
+use std::env;
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// An empty directory in the temporary directory, removed with everything in
+/// it when it's dropped.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Create a directory named after name which no other test, or run of
+    /// the tests, shares.
+    pub fn new(name: &str) -> TempDir {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!(
+            "licensure-{}-{}-{}",
+            name,
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("Can create temp dir");
+        TempDir { path }
+    }
+
+    /// The path of name in the directory as a string, the way files are
+    /// given to licensure.
+    pub fn file(&self, name: &str) -> String {
+        self.path.join(name).to_string_lossy().to_string()
+    }
+
+    /// Write content to name in the directory, creating the directories it's
+    /// in.
+    pub fn write(&self, name: &str, content: impl AsRef<[u8]>) -> String {
+        let path = self.path.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("Can create dir");
+        }
+        fs::write(&path, content).expect("Can write file");
+        path.to_string_lossy().to_string()
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// A git repository in a [`TempDir`] with a committer configured, so commits
+/// can be made whatever the git config of whoever runs the tests.
+pub struct GitRepo {
+    dir: TempDir,
+}
+
+impl GitRepo {
+    /// Create an empty repository named after name.
+    pub fn new(name: &str) -> GitRepo {
+        let repo = GitRepo {
+            dir: TempDir::new(name),
+        };
+        repo.git(&["init", "-q"]);
+        repo.git(&["config", "user.name", "Tester"]);
+        repo.git(&["config", "user.email", "tester@example.com"]);
+        repo.git(&["config", "commit.gpgsign", "false"]);
+        repo
+    }
+
+    /// Run git with args in the repository, failing the test if it fails.
+    /// Returns what it printed with surrounding whitespace trimmed.
+    pub fn git(&self, args: &[&str]) -> String {
+        self.git_with_env(args, &[])
+    }
+
+    fn git_with_env(&self, args: &[&str], envs: &[(&str, &str)]) -> String {
+        let output = Command::new("git")
+            .current_dir(&self.dir)
+            .args(args)
+            .envs(envs.iter().copied())
+            .output()
+            .expect("Can run git");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout)
+            .expect("git output is UTF-8")
+            .trim()
+            .to_string()
+    }
+
+    /// Stage everything and commit it with message.
+    pub fn commit_all(&self, message: &str) {
+        self.git(&["add", "-A"]);
+        self.git(&["commit", "-q", "-m", message]);
+    }
+
+    /// Stage everything and commit it with message as if it was made at
+    /// date, such as 2001-01-01T00:00:00.
+    pub fn commit_all_at(&self, message: &str, date: &str) {
+        self.git(&["add", "-A"]);
+        self.git_with_env(
+            &["commit", "-q", "-m", message],
+            &[("GIT_AUTHOR_DATE", date), ("GIT_COMMITTER_DATE", date)],
+        );
+    }
+}
+
+impl Deref for GitRepo {
+    type Target = TempDir;
+
+    fn deref(&self) -> &TempDir {
+        &self.dir
+    }
+}
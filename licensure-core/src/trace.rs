@@ -0,0 +1,80 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+//! A trace of every decision made about the files matching a glob, such as
+//! which matchers they were checked against and what was found in their
+//! headers, for finding out why one file is skipped or its header mangled.
+use std::fmt;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::editorconfig::glob_pattern;
+
+/// The log target the trace is written to, at the trace level, so it can be
+/// sent somewhere apart from the rest of the log.
+pub const TARGET: &str = "licensure::trace";
+
+static PATTERN: OnceLock<Regex> = OnceLock::new();
+
+// Write a line of the trace of file, formatted like format!, if it's traced.
+macro_rules! trace_file {
+    ($file:expr, $($arg:tt)+) => {
+        if $crate::trace::is_traced($file) {
+            $crate::trace::write($file, format_args!($($arg)+));
+        }
+    };
+}
+
+/// Trace the files matching glob, which is matched like the section globs of
+/// an .editorconfig: a glob without a / matches file names in any
+/// directory. Only the first glob configured is used. Fails if glob can't be
+/// understood.
+pub fn configure(glob: &str) -> Result<(), String> {
+    let pattern = glob_pattern(glob).ok_or_else(|| format!("invalid glob {}", glob))?;
+    let _ = PATTERN.set(pattern);
+    Ok(())
+}
+
+/// Returns true if decisions about file are traced.
+pub fn is_traced(file: &str) -> bool {
+    PATTERN.get().is_some_and(|pattern| matches(pattern, file))
+}
+
+fn matches(pattern: &Regex, file: &str) -> bool {
+    let file = file.replace('\\', "/");
+    pattern.is_match(file.strip_prefix("./").unwrap_or(&file))
+}
+
+#[doc(hidden)]
+pub fn write(file: &str, message: fmt::Arguments) {
+    log::trace!(target: TARGET, "trace: {}: {}", file, message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let rust = glob_pattern("*.rs").unwrap();
+        assert!(matches(&rust, "main.rs"));
+        assert!(matches(&rust, "./src/main.rs"));
+        assert!(!matches(&rust, "src/main.py"));
+
+        let src = glob_pattern("src/**").unwrap();
+        assert!(matches(&src, "./src/config/mod.rs"));
+        assert!(matches(&src, "src\\config\\mod.rs"));
+        assert!(!matches(&src, "lib/src/mod.rs"));
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::source::Source;
+
+// The mode git gives symlinks, which are skipped like in the working tree.
+const SYMLINK_MODE: &str = "120000";
+
+/// The files of a commit in a git repository, which may be bare.
+#[derive(Debug)]
+pub struct GitTree {
+    repo: PathBuf,
+    rev: String,
+    files: Vec<String>,
+    sizes: HashMap<String, u64>,
+}
+
+impl GitTree {
+    /// List the files of rev, a commit or anything naming one such as a
+    /// branch, in the git repository in repo. Paths are relative to the root
+    /// of the repository. Symlinks and submodules aren't files.
+    pub fn open(repo: &Path, rev: &str) -> Result<GitTree, io::Error> {
+        let listing = git(repo, &["ls-tree", "-r", "-z", "-l", "--full-tree", rev])?;
+        let listing = String::from_utf8(listing)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut files = Vec::new();
+        let mut sizes = HashMap::new();
+        for entry in listing.split('\0').filter(|entry| !entry.is_empty()) {
+            // Each entry looks like "<mode> <type> <object> <size>\t<path>".
+            let Some((meta, path)) = entry.split_once('\t') else {
+                continue;
+            };
+            let meta: Vec<&str> = meta.split_whitespace().collect();
+            if meta.len() != 4 || meta[1] != "blob" || meta[0] == SYMLINK_MODE {
+                continue;
+            }
+
+            files.push(path.to_string());
+            sizes.insert(path.to_string(), meta[3].parse().unwrap_or(0));
+        }
+
+        Ok(GitTree {
+            repo: repo.to_path_buf(),
+            rev: rev.to_string(),
+            files,
+            sizes,
+        })
+    }
+
+    /// The files of the commit, in the order git lists them.
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+}
+
+impl Source for GitTree {
+    fn exists(&self, file: &str) -> bool {
+        self.sizes.contains_key(file)
+    }
+
+    fn size(&self, file: &str) -> Result<u64, io::Error> {
+        self.sizes
+            .get(file)
+            .copied()
+            .ok_or_else(|| not_found(&self.rev, file))
+    }
+
+    fn read(&self, file: &str) -> Result<Vec<u8>, io::Error> {
+        if !self.exists(file) {
+            return Err(not_found(&self.rev, file));
+        }
+
+        git(
+            &self.repo,
+            &["cat-file", "blob", &format!("{}:{}", self.rev, file)],
+        )
+    }
+}
+
+fn not_found(rev: &str, file: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} is not a file of {}", file, rev),
+    )
+}
+
+// Run git in repo with args, returning what it prints.
+fn git(repo: &Path, args: &[&str]) -> Result<Vec<u8>, io::Error> {
+    let output = Command::new("git").current_dir(repo).args(args).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} exited with {}: {}",
+            args[0],
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(output.stdout)
+}
@@ -0,0 +1,251 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+// Lines of unchanged context shown around a change, as diff -u does.
+const CONTEXT: usize = 3;
+
+// The position of a hunk in the format of a unified diff range. An empty
+// range is given as the line before it.
+fn hunk_range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{},0", start)
+    } else {
+        format!("{},{}", start + 1, len)
+    }
+}
+
+fn push_line(diff: &mut String, prefix: char, line: &str) {
+    diff.push(prefix);
+    diff.push_str(line);
+    if !line.ends_with('\n') {
+        diff.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Returns a unified diff of changing the file at path from old to new, or an
+/// empty string if they're the same.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let hunk = diff_hunk(old, new);
+    if hunk.is_empty() {
+        return hunk;
+    }
+
+    format!("--- a/{0}\n+++ b/{0}\n{1}", path, hunk)
+}
+
+/// Returns hunk, from diff_hunk, as the diff of path in the format git apply
+/// reads. When new_file is true the diff creates path.
+pub fn git_diff(path: &str, hunk: &str, new_file: bool) -> String {
+    if new_file {
+        format!(
+            "diff --git a/{0} b/{0}\nnew file mode 100644\n--- /dev/null\n+++ b/{0}\n{1}",
+            path, hunk
+        )
+    } else {
+        format!(
+            "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n{1}",
+            path, hunk
+        )
+    }
+}
+
+// How many lines old and new start and end with in common, without
+// counting a line twice.
+pub(crate) fn unchanged_lines(old_lines: &[&str], new_lines: &[&str]) -> (usize, usize) {
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (prefix, suffix)
+}
+
+/// The text replacing a region of a file, which starts at a line and column
+/// and ends just before another, all counted from 1. A region which ends
+/// where it starts is an insertion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub text: String,
+}
+
+/// Returns the replacement of the changed lines of old which changes it to
+/// new. Columns count characters.
+pub fn replacement(old: &str, new: &str) -> Replacement {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let (prefix, suffix) = unchanged_lines(&old_lines, &new_lines);
+
+    let end = old_lines.len() - suffix;
+    let (end_line, end_column) = match old_lines.last() {
+        // Up to the end of a last line without a newline.
+        Some(last) if end == old_lines.len() && !last.ends_with('\n') => {
+            (old_lines.len(), last.chars().count() + 1)
+        }
+        _ => (end + 1, 1),
+    };
+    Replacement {
+        start_line: prefix + 1,
+        start_column: 1,
+        end_line,
+        end_column,
+        text: new_lines[prefix..new_lines.len() - suffix].concat(),
+    }
+}
+
+/// Returns the hunk of a unified diff changing old to new, without the file
+/// names, or an empty string if they're the same. License headers only change
+/// one region of a file so the diff is a single hunk covering everything
+/// between the first and last changed lines.
+pub fn diff_hunk(old: &str, new: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let (prefix, suffix) = unchanged_lines(&old_lines, &new_lines);
+
+    let start = prefix.saturating_sub(CONTEXT);
+    let old_end = (old_lines.len() - suffix + CONTEXT).min(old_lines.len());
+    let new_end = (new_lines.len() - suffix + CONTEXT).min(new_lines.len());
+
+    let mut diff = format!(
+        "@@ -{} +{} @@\n",
+        hunk_range(start, old_end - start),
+        hunk_range(start, new_end - start)
+    );
+    for line in &old_lines[start..prefix] {
+        push_line(&mut diff, ' ', line);
+    }
+    for line in &old_lines[prefix..old_lines.len() - suffix] {
+        push_line(&mut diff, '-', line);
+    }
+    for line in &new_lines[prefix..new_lines.len() - suffix] {
+        push_line(&mut diff, '+', line);
+    }
+    for line in &old_lines[old_lines.len() - suffix..old_end] {
+        push_line(&mut diff, ' ', line);
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!("", unified_diff("a.py", "same\n", "same\n"));
+        assert_eq!(
+            "--- a/a.py\n+++ b/a.py\n@@ -1,3 +1,4 @@\n+# Copyright 2024 The Tester\n import os\n import sys\n \n",
+            unified_diff(
+                "a.py",
+                "import os\nimport sys\n\nprint(os, sys)\n",
+                "# Copyright 2024 The Tester\nimport os\nimport sys\n\nprint(os, sys)\n"
+            )
+        );
+        assert_eq!(
+            "--- a/a.py
++++ b/a.py
+@@ -1,2 +1,2 @@
+-# Copyright 2023 The Tester
++# Copyright 2024 The Tester
+ print('hi')
+\\ No newline at end of file
+",
+            unified_diff(
+                "a.py",
+                "# Copyright 2023 The Tester\nprint('hi')",
+                "# Copyright 2024 The Tester\nprint('hi')"
+            )
+        );
+        assert_eq!(
+            "--- a/a.py\n+++ b/a.py\n@@ -0,0 +1,1 @@\n+# Copyright 2024 The Tester\n",
+            unified_diff("a.py", "", "# Copyright 2024 The Tester\n")
+        );
+    }
+
+    #[test]
+    fn test_replacement() {
+        let header = "# Copyright 2024\n";
+        assert_eq!(
+            Replacement {
+                start_line: 2,
+                start_column: 1,
+                end_line: 2,
+                end_column: 1,
+                text: header.to_string(),
+            },
+            replacement(
+                "#!/bin/sh\necho hi\n",
+                &format!("#!/bin/sh\n{}echo hi\n", header)
+            )
+        );
+        assert_eq!(
+            Replacement {
+                start_line: 1,
+                start_column: 1,
+                end_line: 2,
+                end_column: 1,
+                text: header.to_string(),
+            },
+            replacement(
+                "# Copyright 2019\necho hi\n",
+                &format!("{}echo hi\n", header)
+            )
+        );
+        // A header at the bottom of a file without a final newline.
+        assert_eq!(
+            Replacement {
+                start_line: 2,
+                start_column: 1,
+                end_line: 2,
+                end_column: 7,
+                text: "mov r0\n# Copyright 2024\n".to_string(),
+            },
+            replacement("nop\nmov r0", "nop\nmov r0\n# Copyright 2024\n")
+        );
+    }
+
+    #[test]
+    fn test_git_diff() {
+        assert_eq!(
+            "diff --git a/logo.png.license b/logo.png.license\nnew file mode 100644\n--- /dev/null\n+++ b/logo.png.license\n@@ -0,0 +1,1 @@\n+Copyright 2024 The Tester\n",
+            git_diff(
+                "logo.png.license",
+                &diff_hunk("", "Copyright 2024 The Tester\n"),
+                true
+            )
+        );
+        assert_eq!(
+            "diff --git a/a.py b/a.py\n--- a/a.py\n+++ b/a.py\n@@ -1,1 +1,2 @@\n+# Copyright 2024 The Tester\n import os\n",
+            git_diff(
+                "a.py",
+                &diff_hunk("import os\n", "# Copyright 2024 The Tester\nimport os\n"),
+                false
+            )
+        );
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::process::Command;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+pub(crate) use diff::unchanged_lines;
+pub use diff::{diff_hunk, git_diff, replacement, unified_diff, Replacement};
+pub use path::{long_path, normalize_path, project_path};
+pub use wrap::{display_width, fill, strip_verbatim, VERBATIM};
+
+mod diff;
+mod path;
+mod wrap;
+
+/// A line which starts a paragraph of a template whose line breaks are kept
+/// as they are by remove_column_wrapping.
+pub const NOWRAP_MARKER: &str = "[nowrap]";
+
+/// The lines between these in a template are neither unwrapped nor wrapped.
+pub const VERBATIM_START: &str = "[verbatim]";
+pub const VERBATIM_END: &str = "[/verbatim]";
+
+pub fn remove_column_wrapping(string: &str) -> String {
+    // Some license headers come pre-wrapped to a column width. This undoes
+    // the wrapping by joining the lines of each paragraph with a space, while
+    // preserving the blank lines between paragraphs and the line breaks of
+    // paragraphs starting with the nowrap marker. Verbatim blocks, with their
+    // markers, are left exactly as they are.
+    let body = string.trim_end_matches('\n');
+    let mut unwrapped = String::new();
+    let mut first = true;
+    let mut prev_blank = true;
+    let mut keep_lines = false;
+    let mut verbatim = false;
+    for raw_line in body.split('\n') {
+        let line = raw_line.trim_end();
+        if verbatim || line.trim_start() == VERBATIM_START {
+            verbatim = line.trim_start() != VERBATIM_END;
+            if !first {
+                unwrapped.push('\n');
+            }
+            unwrapped.push_str(raw_line.trim_end_matches('\r'));
+            first = false;
+            // The line after a block starts a new paragraph.
+            prev_blank = !verbatim;
+            keep_lines = false;
+            continue;
+        }
+
+        if prev_blank && line.trim_start() == NOWRAP_MARKER {
+            keep_lines = true;
+            continue;
+        }
+
+        let blank = line.is_empty();
+        if first {
+            unwrapped.push_str(line);
+        } else if blank || prev_blank || keep_lines {
+            unwrapped.push('\n');
+            unwrapped.push_str(line);
+        } else {
+            // Continuation lines keep their indentation, as they always have,
+            // so the headers rendered from indented templates still match.
+            unwrapped.push(' ');
+            unwrapped.push_str(line);
+        }
+
+        first = false;
+        prev_blank = blank;
+        if blank {
+            keep_lines = false;
+        }
+    }
+
+    // A single trailing newline has always been unwrapped to a space, keep
+    // doing so so existing headers still match.
+    match string.len() - body.len() {
+        0 => (),
+        1 => unwrapped.push(' '),
+        trailing => unwrapped.push_str(&"\n".repeat(trailing)),
+    }
+
+    unwrapped
+}
+
+/// Remove the nowrap marker lines of a template which isn't unwrapped.
+pub fn remove_nowrap_markers(string: &str) -> String {
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    let re = MARKER.get_or_init(|| {
+        Regex::new(&format!(
+            r"(?m)^[ \t]*{}[ \t]*(\r?\n|$)",
+            regex::escape(NOWRAP_MARKER)
+        ))
+        .unwrap()
+    });
+    re.replace_all(string, "").to_string()
+}
+
+/// Returns a Command which runs command with the platform's shell.
+pub fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{remove_column_wrapping, remove_nowrap_markers};
+
+    #[test]
+    fn test_remove_column_wrapping() {
+        let content = "\
+some wrapped
+text to unwrap.
+
+The line above
+is an intentional
+line break.
+
+So is this.";
+
+        let expected = "some wrapped text to unwrap.\n\nThe line above \
+        is an intentional line break.\n\nSo is this.";
+        assert_eq!(expected, remove_column_wrapping(content))
+    }
+
+    #[test]
+    fn test_remove_column_wrapping_paragraphs() {
+        // Blank lines with whitespace and CRLF line endings still separate
+        // paragraphs.
+        let content = "This program is free software:\r\nyou can redistribute it.\r\n   \r\nThis program is distributed\n\n\nWITHOUT ANY WARRANTY.\n";
+        let expected = "This program is free software: you can redistribute it.\n\nThis program is distributed\n\n\nWITHOUT ANY WARRANTY. ";
+        assert_eq!(expected, remove_column_wrapping(content));
+
+        let content = "Licensed under the terms of:\n\n[nowrap]\n  1. keep\n  2. these lines\n\njoin\nthese\n\n";
+        let expected =
+            "Licensed under the terms of:\n\n  1. keep\n  2. these lines\n\njoin these\n\n";
+        assert_eq!(expected, remove_column_wrapping(content));
+    }
+
+    #[test]
+    fn test_remove_column_wrapping_indented() {
+        // Indented continuation lines are joined with their indentation.
+        let content = "Copyright (C) 2024\n    Example Corp\n\n  All rights\n  reserved.";
+        let expected = "Copyright (C) 2024     Example Corp\n\n  All rights   reserved.";
+        assert_eq!(expected, remove_column_wrapping(content));
+    }
+
+    #[test]
+    fn test_remove_nowrap_markers() {
+        assert_eq!(
+            "Text\n\n  1. keep\n  2. lines\n",
+            remove_nowrap_markers("Text\n\n[nowrap]\n  1. keep\n  2. lines\n")
+        );
+    }
+
+    #[test]
+    fn test_remove_column_wrapping_verbatim() {
+        let content = "Example Corp\nLegal Department\n[verbatim]\n  Example Corp   \n  1 Main Street\n[/verbatim]\nwrapped\ntext\n";
+        let expected = "Example Corp Legal Department\n[verbatim]\n  Example Corp   \n  1 Main Street\n[/verbatim]\nwrapped text ";
+        assert_eq!(expected, remove_column_wrapping(content));
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+const VERBATIM_PREFIX: &str = r"\\?\";
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+
+// Paths at least this long need the verbatim prefix to be opened on Windows,
+// MAX_PATH is 260 but directories are limited to 248 so files can be created
+// in them.
+#[cfg_attr(not(windows), allow(dead_code))]
+const MAX_PATH: usize = 248;
+
+// Strip the verbatim prefix from a Windows path and use forward slashes.
+fn to_forward_slashes(path: &str) -> String {
+    let path = match path.strip_prefix(VERBATIM_UNC_PREFIX) {
+        Some(rest) => format!(r"\\{}", rest),
+        None => path
+            .strip_prefix(VERBATIM_PREFIX)
+            .unwrap_or(path)
+            .to_string(),
+    };
+
+    path.replace('\\', "/")
+}
+
+// Add the verbatim prefix to an absolute Windows path.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn to_verbatim(absolute: &str) -> String {
+    if absolute.starts_with(VERBATIM_PREFIX) {
+        return absolute.to_string();
+    }
+
+    let absolute = absolute.replace('/', "\\");
+    match absolute.strip_prefix(r"\\") {
+        Some(unc) => format!("{}{}", VERBATIM_UNC_PREFIX, unc),
+        None => format!("{}{}", VERBATIM_PREFIX, absolute),
+    }
+}
+
+/// Returns path as it should be given to the files and excludes regexes. On
+/// Windows backslashes become forward slashes, so one config works on every
+/// platform, and the \\?\ prefix of long paths is removed.
+pub fn normalize_path(path: &str) -> Cow<'_, str> {
+    if cfg!(windows) {
+        Cow::Owned(to_forward_slashes(path))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// Returns path the way baselines and diffs record it, with forward slashes
+/// and without a leading ./, so they match on every platform.
+pub fn project_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    match path.strip_prefix("./") {
+        Some(path) => path.to_string(),
+        None => path,
+    }
+}
+
+/// Returns a path which can be used to open path. On Windows paths too long
+/// for the Windows API are made absolute and given the \\?\ prefix.
+pub fn long_path(path: &str) -> PathBuf {
+    #[cfg(windows)]
+    if path.len() >= MAX_PATH {
+        if let Ok(absolute) = std::path::absolute(path) {
+            return PathBuf::from(to_verbatim(&absolute.to_string_lossy()));
+        }
+    }
+
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_forward_slashes() {
+        assert_eq!(
+            "src/config/mod.rs",
+            to_forward_slashes(r"src\config\mod.rs")
+        );
+        assert_eq!(
+            "C:/work/node_modules/a/index.js",
+            to_forward_slashes(r"\\?\C:\work\node_modules\a\index.js")
+        );
+        assert_eq!(
+            "//server/share/a.rs",
+            to_forward_slashes(r"\\?\UNC\server\share\a.rs")
+        );
+    }
+
+    #[test]
+    fn test_to_verbatim() {
+        assert_eq!(r"\\?\C:\work\a.rs", to_verbatim("C:/work/a.rs"));
+        assert_eq!(
+            r"\\?\UNC\server\share\a.rs",
+            to_verbatim(r"\\server\share\a.rs")
+        );
+        assert_eq!(r"\\?\C:\a.rs", to_verbatim(r"\\?\C:\a.rs"));
+    }
+}
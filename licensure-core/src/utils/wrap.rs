@@ -0,0 +1,221 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use regex::Regex;
+use textwrap::core::Word;
+use textwrap::word_separators::{UnicodeBreakProperties, WordSeparator};
+use textwrap::word_splitters::{HyphenSplitter, WordSplitter};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const SPDX_TAG: &str = "SPDX-License-Identifier:";
+
+/// Starts each line of rendered text which must be reproduced exactly, from
+/// the Unicode private use area so it never occurs in a real template. fill
+/// never wraps these lines and commenters remove it.
+pub const VERBATIM: char = '\u{E002}';
+
+/// Remove the VERBATIM marks from text, for commenters which don't wrap it.
+pub fn strip_verbatim(text: &str) -> String {
+    text.replace(VERBATIM, "")
+}
+
+/// Returns the number of terminal columns the string occupies. Wide
+/// characters (CJK, most emoji) count as two columns and combining marks
+/// count as zero.
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// Wrap text so that no line is wider than width columns as measured by
+/// display_width. Words which are wider than the available space on their own
+/// are broken on grapheme cluster boundaries so that accented characters and
+/// multi-codepoint emoji are never split in half.
+///
+/// Lines starting with VERBATIM are kept as they are, without the mark.
+/// URLs and SPDX-License-Identifier tags are never broken. A URL is kept whole
+/// on its own line when it doesn't fit and an SPDX tag always starts a new line
+/// which runs to the end of the source line, even if that exceeds width.
+pub fn fill(text: &str, width: usize) -> String {
+    let width = width.max(1);
+    let options = textwrap::Options::new(width)
+        .break_words(false)
+        .word_separator(TokenSeparator)
+        .word_splitter(TokenSplitter);
+
+    let mut lines = Vec::new();
+    for source_line in text.split('\n') {
+        if let Some(line) = source_line.strip_prefix(VERBATIM) {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let (prose, tag) = match source_line.find(SPDX_TAG) {
+            Some(idx) if source_line[..idx].trim().is_empty() => ("", Some(source_line)),
+            Some(idx) => (&source_line[..idx], Some(&source_line[idx..])),
+            None => (source_line, None),
+        };
+
+        if tag.is_none() || !prose.is_empty() {
+            for line in textwrap::wrap(prose.trim_end(), &options) {
+                if display_width(&line) <= width || is_unbreakable(line.trim()) {
+                    lines.push(line.into_owned());
+                } else {
+                    lines.append(&mut break_graphemes(&line, width));
+                }
+            }
+        }
+
+        if let Some(tag) = tag {
+            lines.push(tag.trim_end().to_string());
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn is_unbreakable(token: &str) -> bool {
+    thread_local! {
+        static URL: Regex = Regex::new(r"^[<(\[]?([a-zA-Z][a-zA-Z0-9+.-]*://|www\.)\S+$")
+            .expect("url regex didn't compile!");
+    }
+
+    token.starts_with(SPDX_TAG) || URL.with(|re| re.is_match(token))
+}
+
+/// Finds words using the unicode line breaking rules except for URLs, which
+/// the unicode rules would otherwise allow breaking after any slash.
+#[derive(Clone, Copy, Debug)]
+struct TokenSeparator;
+
+impl WordSeparator for TokenSeparator {
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        let mut words = Vec::new();
+        let mut rest = line;
+        while !rest.is_empty() {
+            let word_end = rest.find(' ').unwrap_or(rest.len());
+            let end =
+                word_end + rest[word_end..].len() - rest[word_end..].trim_start_matches(' ').len();
+            let (segment, remainder) = rest.split_at(end);
+
+            if is_unbreakable(&segment[..word_end]) {
+                words.push(Word::from(segment));
+            } else {
+                words.extend(UnicodeBreakProperties.find_words(segment));
+            }
+
+            rest = remainder;
+        }
+
+        Box::new(words.into_iter())
+    }
+}
+
+/// Splits words on hyphens like textwrap does by default, except for URLs.
+#[derive(Clone, Copy, Debug)]
+struct TokenSplitter;
+
+impl WordSplitter for TokenSplitter {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        if is_unbreakable(word) {
+            Vec::new()
+        } else {
+            HyphenSplitter.split_points(word)
+        }
+    }
+}
+
+fn break_graphemes(line: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+
+    for grapheme in line.graphemes(true) {
+        let grapheme_width = display_width(grapheme);
+        if current_width + grapheme_width > width && !current.is_empty() {
+            lines.push(current);
+            current = String::new();
+            current_width = 0;
+        }
+
+        current.push_str(grapheme);
+        current_width += grapheme_width;
+    }
+
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(1, display_width("©"));
+        assert_eq!(4, display_width("著作"));
+        assert_eq!(2, display_width("e\u{301}x"));
+    }
+
+    #[test]
+    fn test_fill_wide_characters() {
+        let wrapped = fill("著作権 著作権 著作権", 14);
+        for line in wrapped.lines() {
+            assert!(display_width(line) <= 14, "{} is too wide", line);
+        }
+        assert_eq!("著作権 著作権\n著作権", wrapped);
+    }
+
+    #[test]
+    fn test_fill_breaks_on_grapheme_boundaries() {
+        let accented = "e\u{301}";
+        let wrapped = fill(&accented.repeat(7), 3);
+        assert_eq!(
+            vec![accented.repeat(3), accented.repeat(3), accented.to_string()],
+            wrapped.lines().collect::<Vec<&str>>()
+        );
+
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let wrapped = fill(&family.repeat(4), 2);
+        for line in wrapped.lines() {
+            assert_eq!(family, line);
+        }
+    }
+
+    #[test]
+    fn test_fill_preserves_newlines() {
+        assert_eq!("a b\n\nc d", fill("a b\n\nc d", 80));
+    }
+
+    #[test]
+    fn test_fill_never_breaks_urls() {
+        let text = "If not, see <https://www.gnu.org/licenses/old-licenses/gpl-2.0-standalone.html> for details.";
+        assert_eq!(
+            "If not, see\n<https://www.gnu.org/licenses/old-licenses/gpl-2.0-standalone.html>\nfor details.",
+            fill(text, 20)
+        );
+    }
+
+    #[test]
+    fn test_fill_keeps_spdx_tag_on_its_own_line() {
+        let text =
+            "Copyright 2024 Example Corp SPDX-License-Identifier: Apache-2.0 WITH LLVM-exception";
+        assert_eq!(
+            "Copyright 2024\nExample Corp\nSPDX-License-Identifier: Apache-2.0 WITH LLVM-exception",
+            fill(text, 20)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fmt;
+
+/// Why a file was skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The file matches one of the excludes.
+    Excluded,
+    /// The file is larger than max_file_size.
+    TooLarge,
+    /// The file is the .license sidecar of another file.
+    Sidecar,
+    /// The file is binary.
+    Binary,
+    /// The file is marked as generated, such as with @generated or DO NOT
+    /// EDIT, and skip_generated is on.
+    Generated,
+    /// The file opts out of getting a header with a licensure: ignore
+    /// pragma.
+    Exempt,
+    /// No license config matches the file.
+    NoMatcher,
+    /// The file has another license or copyright header and skip_existing
+    /// is skip.
+    ForeignCopyright,
+    /// The file is empty or only whitespace and empty_files is skip.
+    Empty,
+    /// Only years are being updated and the file doesn't have the header of
+    /// its license with any years.
+    NoHeader,
+    /// The template for the file couldn't be loaded, with why.
+    TemplateError(String),
+}
+
+impl SkipReason {
+    /// The machine readable name of the reason, as used in JSON reports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::Excluded => "excluded",
+            SkipReason::TooLarge => "too_large",
+            SkipReason::Sidecar => "sidecar",
+            SkipReason::Binary => "binary",
+            SkipReason::Generated => "generated",
+            SkipReason::Exempt => "exempt",
+            SkipReason::NoMatcher => "no_matcher",
+            SkipReason::ForeignCopyright => "foreign_copyright",
+            SkipReason::Empty => "empty",
+            SkipReason::NoHeader => "no_header",
+            SkipReason::TemplateError(_) => "template_error",
+        }
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Excluded => write!(f, "excluded"),
+            SkipReason::TooLarge => write!(f, "too large"),
+            SkipReason::Sidecar => write!(f, "license sidecar of another file"),
+            SkipReason::Binary => write!(f, "binary"),
+            SkipReason::Generated => write!(f, "generated"),
+            SkipReason::Exempt => write!(f, "exempt with licensure: ignore"),
+            SkipReason::NoMatcher => write!(f, "no matching license config"),
+            SkipReason::ForeignCopyright => write!(f, "foreign copyright"),
+            SkipReason::Empty => write!(f, "empty"),
+            SkipReason::NoHeader => write!(f, "no header to update the years of"),
+            SkipReason::TemplateError(e) => write!(f, "template error: {}", e),
+        }
+    }
+}
+
+/// Something a [`Processor`](crate::Processor) found while processing a file.
+/// HeaderMissing and HeaderUpdated name the .license sidecar of files whose
+/// header lives in one.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// A license config matches the file.
+    FileMatched { file: &'a str },
+    /// The file doesn't have the right header, either because it has none or
+    /// it's outdated.
+    HeaderMissing { file: &'a str },
+    /// The header of the file is about to be written, or printed when not
+    /// changing files in place, as shown by diff. Never sent in check mode.
+    HeaderUpdated { file: &'a str, diff: String },
+    /// The file was skipped for reason.
+    FileSkipped { file: &'a str, reason: SkipReason },
+}
+
+/// Whether a [`Processor`](crate::Processor) should go ahead with what an
+/// [`Event`] describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// Don't write the header of a [`Event::HeaderUpdated`] file, which is
+    /// then reported as needing an update. Has no effect on other events.
+    Veto,
+}
+
+/// Receives the events of a [`Processor`](crate::Processor), so custom user
+/// interfaces and policies can be built on top of it.
+pub trait Visitor {
+    fn visit(&mut self, event: &Event) -> Verdict;
+}
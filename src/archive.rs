@@ -0,0 +1,222 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+use licensure_core::Source;
+
+/// The regular files of a tar, gzipped tar, or zip archive, decompressed in
+/// memory. When every file is beneath the same top level directory, as in
+/// most release archives, that directory is stripped from their paths.
+#[derive(Debug, Default)]
+pub struct Archive {
+    files: Vec<String>,
+    contents: HashMap<String, Vec<u8>>,
+}
+
+impl Archive {
+    /// Read the archive at path, telling which kind it is by its content.
+    pub fn open(path: &Path) -> Result<Archive, io::Error> {
+        Archive::from_bytes(&fs::read(path)?)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Archive, io::Error> {
+        let entries = if bytes.starts_with(&[0x1f, 0x8b]) {
+            read_tar(MultiGzDecoder::new(bytes))?
+        } else if bytes.starts_with(b"PK") {
+            read_zip(bytes)?
+        } else if bytes.get(257..262) == Some(b"ustar") {
+            read_tar(bytes)?
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a tar, gzipped tar, or zip archive",
+            ));
+        };
+
+        let mut archive = Archive::default();
+        let prefix = common_dir(&entries);
+        for (path, content) in entries {
+            let path = path[prefix.len()..].to_string();
+            if archive.contents.insert(path.clone(), content).is_none() {
+                archive.files.push(path);
+            }
+        }
+
+        Ok(archive)
+    }
+
+    /// The files of the archive, in the order they're stored.
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+}
+
+impl Source for Archive {
+    fn exists(&self, file: &str) -> bool {
+        self.contents.contains_key(file)
+    }
+
+    fn size(&self, file: &str) -> Result<u64, io::Error> {
+        self.read(file).map(|content| content.len() as u64)
+    }
+
+    fn read(&self, file: &str) -> Result<Vec<u8>, io::Error> {
+        self.contents.get(file).cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not in the archive", file),
+            )
+        })
+    }
+}
+
+// The top level directory, with its slash, every path is beneath, if they're
+// all beneath the same one.
+fn common_dir(entries: &[(String, Vec<u8>)]) -> String {
+    let Some((first, _)) = entries.first() else {
+        return String::new();
+    };
+    let Some((dir, _)) = first.split_once('/') else {
+        return String::new();
+    };
+
+    let prefix = format!("{}/", dir);
+    if entries.iter().all(|(path, _)| path.starts_with(&prefix)) {
+        prefix
+    } else {
+        String::new()
+    }
+}
+
+fn read_tar(reader: impl Read) -> Result<Vec<(String, Vec<u8>)>, io::Error> {
+    let mut entries = Vec::new();
+    for entry in tar::Archive::new(reader).entries()? {
+        let mut entry = entry?;
+        // Directories, links, and devices have nothing to check.
+        let kind = entry.header().entry_type();
+        if !kind.is_file() && !kind.is_contiguous() {
+            continue;
+        }
+
+        let name = entry.path()?.to_string_lossy().to_string();
+        let name = name.strip_prefix("./").unwrap_or(&name).to_string();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        entries.push((name, content));
+    }
+
+    Ok(entries)
+}
+
+fn read_zip(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, io::Error> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes))?;
+    let mut entries = Vec::new();
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        if !file.is_file() {
+            continue;
+        }
+
+        let name = file.name()?.to_string();
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        entries.push((name, content));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use zip::write::SimpleFileOptions;
+    use zip::CompressionMethod;
+
+    use super::*;
+
+    fn tar(entries: &[(&str, tar::EntryType, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, kind, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(*kind);
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, *content).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    // A zip of files, the first stored and the rest deflated.
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (i, (name, content)) in files.iter().enumerate() {
+            let method = if i == 0 {
+                CompressionMethod::Stored
+            } else {
+                CompressionMethod::Deflated
+            };
+            let options = SimpleFileOptions::default().compression_method(method);
+            if name.ends_with('/') {
+                writer.add_directory(*name, options).unwrap();
+            } else {
+                writer.start_file(*name, options).unwrap();
+                writer.write_all(content).unwrap();
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_tar() {
+        let long = format!("pkg/{}.py", "a".repeat(120));
+        let tar = tar(&[
+            ("pkg/", tar::EntryType::Directory, b""),
+            ("pkg/main.py", tar::EntryType::Regular, b"print('hi')\n"),
+            (&long, tar::EntryType::Regular, b"long\n"),
+            ("pkg/link.py", tar::EntryType::Symlink, b""),
+        ]);
+        let archive = Archive::from_bytes(&tar).expect("Can read tar");
+        let long = long.strip_prefix("pkg/").unwrap();
+        assert_eq!(vec!["main.py", long], archive.files());
+        assert_eq!(b"print('hi')\n".to_vec(), archive.read("main.py").unwrap());
+        assert_eq!(5, archive.size(long).unwrap());
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&tar).unwrap();
+        let archive = Archive::from_bytes(&encoder.finish().unwrap()).expect("Can read tar.gz");
+        assert_eq!(vec!["main.py", long], archive.files());
+    }
+
+    #[test]
+    fn test_zip() {
+        let archive = Archive::from_bytes(&zip(&[
+            ("main.py", b"print('hi')\n"),
+            ("src/lib.py", b"x = 1\n"),
+            ("src/", b""),
+        ]))
+        .expect("Can read zip");
+        assert_eq!(vec!["main.py", "src/lib.py"], archive.files());
+        assert_eq!(b"x = 1\n".to_vec(), archive.read("src/lib.py").unwrap());
+        assert!(!archive.exists("src/"));
+
+        assert!(Archive::from_bytes(b"not an archive").is_err());
+    }
+}
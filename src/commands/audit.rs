@@ -0,0 +1,168 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io;
+
+use licensure_core::config::Config;
+use licensure_core::detect;
+use licensure_core::utils::long_path;
+
+use crate::compat;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Finding {
+    // No license information was found at all.
+    Unlicensed,
+    // The file is under a different license than the one configured for it.
+    Unexpected { found: String, expected: String },
+    // The file is under a license which isn't configured for any path.
+    Unconfigured { found: String },
+    // The file claims to be under more than one license.
+    Conflicting { found: Vec<String> },
+}
+
+fn audit_file(found: Vec<String>, expected: Option<&str>, idents: &[&str]) -> Option<Finding> {
+    if found.is_empty() {
+        return Some(Finding::Unlicensed);
+    }
+
+    if found.len() > 1 {
+        return Some(Finding::Conflicting { found });
+    }
+
+    let found = found.into_iter().next().unwrap();
+    match expected {
+        Some(expected) if !detect::same_license(&found, expected) => Some(Finding::Unexpected {
+            found,
+            expected: expected.to_string(),
+        }),
+        Some(_) => None,
+        None if !idents
+            .iter()
+            .any(|ident| detect::same_license(&found, ident)) =>
+        {
+            Some(Finding::Unconfigured { found })
+        }
+        None => None,
+    }
+}
+
+/// Scan every file which isn't excluded, whether or not a license config
+/// matches it, for SPDX tags and license text and report the files whose
+/// licensing disagrees with the config. Returns false if there were any
+/// findings. Incompatible configured licenses are warned about but don't
+/// count as findings.
+pub fn audit(config: &Config, files: &[String]) -> Result<bool, io::Error> {
+    let idents = config.licenses.idents();
+    // Headers without an SPDX tag or a recognized license text are compared
+    // with the licenses which are configured or replaced.
+    let mut similar = idents.clone();
+    similar.extend(config.licenses.replaced_idents());
+    let mut findings = Vec::new();
+
+    for file in files {
+        if config.excludes.is_match(file) {
+            continue;
+        }
+
+        let bytes = fs::read(long_path(file))?;
+        if detect::is_binary(&bytes) {
+            info!("skipping {} because it is a binary file.", file);
+            continue;
+        }
+
+        let content = String::from_utf8_lossy(&bytes);
+        let mut found = detect::detect_licenses(&content);
+        if found.is_empty() {
+            found.extend(detect::similar_license(&content, &similar).map(str::to_string));
+        }
+        // A file can select a different license than its license config with
+        // a pragma.
+        let expected = detect::license_pragma(&content).or_else(|| config.licenses.get_ident(file));
+        if let Some(finding) = audit_file(found, expected, &idents) {
+            findings.push((file, finding));
+        }
+    }
+
+    for conflict in compat::check(config, files) {
+        eprintln!("warning: {}", conflict);
+    }
+
+    let mut unlicensed = Vec::new();
+    for (file, finding) in &findings {
+        match finding {
+            Finding::Unlicensed => unlicensed.push(file),
+            Finding::Unexpected { found, expected } => {
+                eprintln!(
+                    "{}: licensed under {} but expected {}",
+                    file, found, expected
+                )
+            }
+            Finding::Unconfigured { found } => eprintln!(
+                "{}: licensed under {} which is not a configured license",
+                file, found
+            ),
+            Finding::Conflicting { found } => {
+                eprintln!("{}: has conflicting licenses {}", file, found.join(", "))
+            }
+        }
+    }
+
+    if !unlicensed.is_empty() {
+        eprintln!(
+            "The following {} files have no license information.",
+            unlicensed.len()
+        );
+        for file in unlicensed {
+            eprintln!("{}", file);
+        }
+    }
+
+    Ok(findings.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_file() {
+        let idents = ["MIT", "Apache-2.0"];
+        assert_eq!(
+            Some(Finding::Unlicensed),
+            audit_file(vec![], Some("MIT"), &idents)
+        );
+        assert_eq!(None, audit_file(vec!["MIT".into()], Some("MIT"), &idents));
+        assert_eq!(None, audit_file(vec!["Apache-2.0".into()], None, &idents));
+        assert_eq!(
+            Some(Finding::Unexpected {
+                found: "GPL-3.0".into(),
+                expected: "MIT".into()
+            }),
+            audit_file(vec!["GPL-3.0".into()], Some("MIT"), &idents)
+        );
+        assert_eq!(
+            Some(Finding::Unconfigured {
+                found: "GPL-3.0".into()
+            }),
+            audit_file(vec!["GPL-3.0".into()], None, &idents)
+        );
+        assert_eq!(
+            Some(Finding::Conflicting {
+                found: vec!["MIT".into(), "GPL-3.0".into()]
+            }),
+            audit_file(vec!["MIT".into(), "GPL-3.0".into()], Some("MIT"), &idents)
+        );
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+use licensure_core::config::{self, Config};
+use licensure_core::spdx;
+
+// The oldest git with every option licensure passes it, --pathspec-file-nul
+// being the newest of them.
+const MIN_GIT_VERSION: (u32, u32) = (2, 26);
+
+#[derive(Debug, PartialEq, Eq)]
+enum Finding {
+    Ok(String),
+    // Something only some features need, with how to fix it.
+    Warning(String, String),
+    // Something which stops licensure working, with how to fix it.
+    Problem(String, String),
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Finding::Ok(message) => write!(f, "ok: {}", message),
+            Finding::Warning(message, fix) => write!(f, "warning: {}\n  fix: {}", message, fix),
+            Finding::Problem(message, fix) => write!(f, "problem: {}\n  fix: {}", message, fix),
+        }
+    }
+}
+
+// The major and minor version in the output of git --version, such as
+// "git version 2.39.3 (Apple Git-145)".
+fn parse_git_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.trim().strip_prefix("git version ")?;
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+fn check_git() -> Vec<Finding> {
+    let output = match Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => output,
+        Ok(_) | Err(_) => {
+            return vec![Finding::Problem(
+                "git can't be run".to_string(),
+                "install git and make sure it's on your PATH, it's needed to list the project files and for the years and authors of files".to_string(),
+            )]
+        }
+    };
+
+    let output = String::from_utf8_lossy(&output.stdout);
+    let mut findings = vec![match parse_git_version(&output) {
+        Some(version) if version < MIN_GIT_VERSION => Finding::Warning(
+            format!(
+                "{} is older than {}.{}",
+                output.trim(),
+                MIN_GIT_VERSION.0,
+                MIN_GIT_VERSION.1
+            ),
+            "upgrade git, --commit and some other options fail with older versions".to_string(),
+        ),
+        Some(_) => Finding::Ok(output.trim().to_string()),
+        None => Finding::Warning(
+            format!("the git version {} isn't understood", output.trim()),
+            format!(
+                "make sure git is at least {}.{}",
+                MIN_GIT_VERSION.0, MIN_GIT_VERSION.1
+            ),
+        ),
+    }];
+
+    let in_repo = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    findings.push(if in_repo {
+        Finding::Ok("the current directory is in a git repository".to_string())
+    } else {
+        Finding::Warning(
+            "the current directory isn't in a git repository".to_string(),
+            "run licensure from inside the repository, --project, dynamic year ranges, and authors from git history need one".to_string(),
+        )
+    });
+
+    findings
+}
+
+fn check_config(paths: &[PathBuf], profile: Option<&str>) -> (Vec<Finding>, Option<Config>) {
+    let loaded = if paths.is_empty() {
+        config::load_config()
+    } else {
+        config::load_configs(paths)
+    };
+    let mut config = match loaded {
+        Ok(config) => config,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return (
+                vec![Finding::Problem(
+                    "no config file was found".to_string(),
+                    "generate one with licensure --generate-config and fill in its licenses"
+                        .to_string(),
+                )],
+                None,
+            )
+        }
+        Err(e) => {
+            return (
+                vec![Finding::Problem(
+                    format!("the config doesn't load: {}", e),
+                    "correct the config, run licensure config migrate first if it was written for an older licensure".to_string(),
+                )],
+                None,
+            )
+        }
+    };
+
+    let source = match (paths, config::find_config_file()) {
+        ([], Some(path)) => path.display().to_string(),
+        _ => paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+    let mut findings = vec![Finding::Ok(format!("the config {} loads", source))];
+    if let Some(profile) = profile {
+        if let Err(e) = config.use_profile(profile) {
+            findings.push(Finding::Problem(
+                format!("unable to use profile: {}", e),
+                "pass the name of one of the profiles in the config".to_string(),
+            ));
+        }
+    }
+    for deprecation in &config.deprecations {
+        findings.push(Finding::Warning(
+            deprecation.to_string(),
+            "update the config with licensure config migrate".to_string(),
+        ));
+    }
+    if config.licenses.iter().next().is_none() {
+        findings.push(Finding::Problem(
+            "the config has no license configs so no file gets a header".to_string(),
+            "add a license config to the licenses section".to_string(),
+        ));
+    }
+
+    (findings, Some(config))
+}
+
+fn check_spdx(config: &Config, offline: bool) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let registry = match &config.licenses_registry {
+        Some(path) => match spdx::load_registry(path) {
+            Ok(registry) => {
+                findings.push(Finding::Ok(format!(
+                    "the license registry {} defines {} licenses",
+                    path,
+                    registry.len()
+                )));
+                registry
+            }
+            Err(e) => {
+                findings.push(Finding::Problem(
+                    e,
+                    "correct licenses_registry so it's the path of a mapping of license identifiers to their header and text".to_string(),
+                ));
+                Default::default()
+            }
+        },
+        None => Default::default(),
+    };
+
+    let idents = config.licenses.fetched_idents();
+    if idents.is_empty() {
+        findings.push(Finding::Ok(
+            "no license templates are fetched from SPDX".to_string(),
+        ));
+        return findings;
+    }
+
+    // Without a working proxy and CA bundle no license can be fetched.
+    if let Err(e) = spdx::configure(spdx::Settings {
+        ca_bundle: config.ca_bundle.clone(),
+        base_url: config.spdx_base_url.clone(),
+        timeout: config.spdx_timeout,
+        retries: config.spdx_retries,
+        rate_limit: config.spdx_rate_limit,
+        registry,
+        offline,
+    }) {
+        findings.push(Finding::Problem(
+            format!("requests to SPDX can't be set up: {}", e),
+            "correct ca_bundle so it's the path of a PEM file of certificates, and HTTPS_PROXY, HTTP_PROXY, or ALL_PROXY so they're proxy URLs such as http://proxy.example.com:3128".to_string(),
+        ));
+        return findings;
+    }
+    spdx::prefetch(&idents);
+
+    let fix = if offline {
+        "run licensure once without --offline to cache the license".to_string()
+    } else {
+        "check the network and any HTTPS_PROXY, set ca_bundle for a proxy with its own certificate, or set spdx_base_url to a mirror or a directory of license JSON files".to_string()
+    };
+    findings.extend(
+        idents
+            .iter()
+            .map(|ident| match spdx::fetch_license_info(ident) {
+                Ok(_) => Finding::Ok(format!("the {} license is available", ident)),
+                Err(e) => Finding::Problem(
+                    format!("the {} license can't be fetched: {}", ident, e),
+                    fix.clone(),
+                ),
+            }),
+    );
+    findings
+}
+
+fn check_writable(dir: &Path) -> Finding {
+    let probe = dir.join(format!(".licensure-doctor-{}", process::id()));
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            Finding::Ok(format!("{} is writable", dir.display()))
+        }
+        Err(e) => Finding::Problem(
+            format!("unable to write to {}: {}", dir.display(), e),
+            "run licensure as a user who can write to the project, or only check it with --check"
+                .to_string(),
+        ),
+    }
+}
+
+/// Check the environment licensure runs in: git, the config from paths or
+/// the one found from the current directory, fetching the licenses it needs
+/// from SPDX, and writing to the project. Each check is printed with how to
+/// fix it when it fails. Returns false if any check found a problem which
+/// stops licensure working, warnings don't count.
+pub fn doctor(paths: &[PathBuf], profile: Option<&str>, offline: bool) -> bool {
+    let mut findings = check_git();
+    let (config_findings, config) = check_config(paths, profile);
+    findings.extend(config_findings);
+    if let Some(config) = &config {
+        findings.extend(check_spdx(config, offline));
+    }
+    findings.push(check_writable(Path::new(".")));
+
+    for finding in &findings {
+        println!("{}", finding);
+    }
+
+    !findings
+        .iter()
+        .any(|finding| matches!(finding, Finding::Problem(..)))
+}
+
+#[cfg(test)]
+mod tests {
+    use licensure_core::testing::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_git_version() {
+        assert_eq!(Some((2, 43)), parse_git_version("git version 2.43.0\n"));
+        assert_eq!(
+            Some((2, 39)),
+            parse_git_version("git version 2.39.3 (Apple Git-145)")
+        );
+        assert_eq!(
+            Some((2, 45)),
+            parse_git_version("git version 2.45.1.windows.1")
+        );
+        assert_eq!(None, parse_git_version("hub version 2.14.2"));
+    }
+
+    #[test]
+    fn test_check_config() {
+        let dir = TempDir::new("doctor");
+        let paths = [dir.join("licensure.yml")];
+
+        fs::write(&paths[0], "licenses: []\n").expect("Can write config");
+        let (findings, config) = check_config(&paths, None);
+        assert!(config.is_some());
+        assert!(
+            matches!(&findings[1], Finding::Problem(message, _) if message.contains("no license configs"))
+        );
+
+        fs::write(&paths[0], "licenses: [[]]\n").expect("Can write config");
+        let (findings, config) = check_config(&paths, None);
+        assert!(config.is_none());
+        assert!(
+            matches!(&findings[0], Finding::Problem(message, _) if message.contains("doesn't load"))
+        );
+
+        assert!(matches!(check_writable(&dir), Finding::Ok(_)));
+        assert!(matches!(
+            check_writable(&dir.join("missing")),
+            Finding::Problem(..)
+        ));
+    }
+}
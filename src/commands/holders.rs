@@ -0,0 +1,95 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use licensure_core::config::Config;
+use licensure_core::detect;
+use licensure_core::utils::long_path;
+
+// Count the files each copyright holder appears in. Sorted by the number of
+// files, most first, and then by holder.
+fn count_holders(holders_by_file: &[Vec<String>]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for holders in holders_by_file {
+        let mut seen = Vec::new();
+        for holder in holders {
+            if !seen.contains(&holder) {
+                seen.push(holder);
+                *counts.entry(holder).or_default() += 1;
+            }
+        }
+    }
+
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(holder, count)| (holder.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Print every distinct copyright holder found in the existing headers of
+/// files which aren't excluded along with the number of files they hold
+/// copyright over.
+pub fn holders(config: &Config, files: &[String]) -> Result<(), io::Error> {
+    let mut holders_by_file = Vec::new();
+    for file in files {
+        if config.excludes.is_match(file) {
+            continue;
+        }
+
+        let bytes = fs::read(long_path(file))?;
+        if detect::is_binary(&bytes) {
+            info!("skipping {} because it is a binary file.", file);
+            continue;
+        }
+
+        holders_by_file.push(detect::copyright_holders(&String::from_utf8_lossy(&bytes)));
+    }
+
+    let counts = count_holders(&holders_by_file);
+    let width = counts
+        .first()
+        .map_or(0, |(_, count)| count.to_string().len());
+    for (holder, count) in counts {
+        println!("{:>width$} {}", count, holder, width = width);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_holders() {
+        let holders_by_file = vec![
+            vec!["Example Corp".to_string()],
+            vec!["Someone".to_string(), "Example Corp".to_string()],
+            vec!["Someone".to_string(), "Someone".to_string()],
+            vec!["Another".to_string()],
+        ];
+        assert_eq!(
+            vec![
+                ("Example Corp".to_string(), 2),
+                ("Someone".to_string(), 2),
+                ("Another".to_string(), 1),
+            ],
+            count_holders(&holders_by_file)
+        );
+    }
+}
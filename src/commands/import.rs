@@ -0,0 +1,760 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use licensure_core::config;
+use licensure_core::editorconfig;
+
+// The built in templates of addlicense, by the name given to -l, with the
+// SPDX identifier each stands for.
+const ADDLICENSE_TEMPLATES: &[(&str, &str, &str)] = &[
+    (
+        "apache",
+        "Apache-2.0",
+        r#"Copyright {{.Year}} {{.Holder}}
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+"#,
+    ),
+    (
+        "bsd",
+        "BSD-3-Clause",
+        r#"Copyright (c) {{.Year}} {{.Holder}} All rights reserved.
+Use of this source code is governed by a BSD-style
+license that can be found in the LICENSE file.
+"#,
+    ),
+    (
+        "mit",
+        "MIT",
+        r#"Copyright (c) {{.Year}} {{.Holder}}
+
+Use of this source code is governed by an MIT-style
+license that can be found in the LICENSE file or at
+https://opensource.org/licenses/MIT.
+"#,
+    ),
+    (
+        "mpl",
+        "MPL-2.0",
+        r#"This Source Code Form is subject to the terms of the Mozilla Public
+License, v. 2.0. If a copy of the MPL was not distributed with this
+file, You can obtain one at http://mozilla.org/MPL/2.0/.
+"#,
+    ),
+];
+
+// The templates of licenseheaders, by the name given to -t, with the SPDX
+// identifier each stands for.
+const LICENSEHEADERS_TEMPLATES: &[(&str, &str)] = &[
+    ("agpl-v3", "AGPL-3.0-or-later"),
+    ("apache-2", "Apache-2.0"),
+    ("bsd-2", "BSD-2-Clause"),
+    ("bsd-3", "BSD-3-Clause"),
+    ("cc-by-4", "CC-BY-4.0"),
+    ("cc-by-sa-4", "CC-BY-SA-4.0"),
+    ("eclipse-1.0", "EPL-1.0"),
+    ("eclipse-2.0", "EPL-2.0"),
+    ("gpl-v2", "GPL-2.0-or-later"),
+    ("gpl-v3", "GPL-3.0-or-later"),
+    ("lgpl-v2.1", "LGPL-2.1-or-later"),
+    ("lgpl-v3", "LGPL-3.0-or-later"),
+    ("mit", "MIT"),
+    ("mpl-2.0", "MPL-2.0"),
+];
+
+// The comment presets the imported config starts with.
+const COMMENT_PRESETS: &[&str] = &["c-line", "hash", "xml", "dash"];
+
+/// A license header tool whose configuration can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Addlicense,
+    SkywalkingEyes,
+    Licenseheaders,
+}
+
+impl FromStr for Tool {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Tool, String> {
+        match s {
+            "addlicense" => Ok(Tool::Addlicense),
+            "skywalking-eyes" | "license-eye" => Ok(Tool::SkywalkingEyes),
+            "licenseheaders" => Ok(Tool::Licenseheaders),
+            _ => Err(format!(
+                "unknown tool {}, licensure can import addlicense, skywalking-eyes, and licenseheaders",
+                s
+            )),
+        }
+    }
+}
+
+// The parts of another tool's configuration licensure has an equivalent of.
+#[derive(Debug, Default, PartialEq)]
+struct Imported {
+    excludes: Vec<String>,
+    licenses: Vec<ImportedLicense>,
+    // Settings which couldn't be converted, written to the config as
+    // comments.
+    notes: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ImportedLicense {
+    // A regex of the files the license applies to, None for any.
+    files: Option<String>,
+    ident: String,
+    authors: Vec<String>,
+    end_year: Option<String>,
+    // None takes the header from SPDX with auto_template.
+    template: Option<String>,
+}
+
+// Convert a glob of the kind these tools take, where ** crosses directories
+// and a pattern without a / matches a name in any directory, to a regex
+// matching the paths licensure checks. A directory matches everything in
+// it.
+fn glob_regex(glob: &str) -> Result<String, String> {
+    let glob = glob.trim_start_matches("./");
+    let (anchored, glob) = match glob.strip_prefix('/') {
+        Some(glob) => (true, glob),
+        None => (glob.trim_end_matches('/').contains('/'), glob),
+    };
+    let regex = editorconfig::glob_regex(glob.trim_end_matches('/'))
+        .ok_or_else(|| format!("invalid glob {}", glob))?;
+    let prefix = if anchored { r"^(\./)?" } else { "(^|/)" };
+    Ok(format!("{}{}(/|$)", prefix, regex))
+}
+
+// Convert each of globs with glob_regex.
+fn glob_regexes(globs: &[String]) -> Result<Vec<String>, String> {
+    globs.iter().map(|glob| glob_regex(glob)).collect()
+}
+
+// The regex matching any of globs, None if one of them matches every file.
+fn files_regex(globs: &[String]) -> Result<Option<String>, String> {
+    if globs.is_empty()
+        || globs
+            .iter()
+            .any(|glob| matches!(glob.as_str(), "." | "**" | "./"))
+    {
+        return Ok(None);
+    }
+
+    let regexes = glob_regexes(globs)?;
+    Ok(Some(match regexes.as_slice() {
+        [regex] => regex.clone(),
+        _ => format!("({})", regexes.join("|")),
+    }))
+}
+
+// Escape the brackets of text so none of it is taken as a placeholder, then
+// turn each of the placeholders, written in the other tool's syntax, into
+// licensure's.
+fn convert_template(text: &str, placeholders: &[(&str, &str)]) -> String {
+    let mut template = text.replace('[', "[[").replace(']', "]]");
+    for (theirs, ours) in placeholders {
+        let theirs = theirs.replace('[', "[[").replace(']', "]]");
+        template = template.replace(&theirs, ours);
+    }
+    template
+}
+
+// Split a shell command line into words, undoing quotes and escapes.
+fn shell_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                word.extend(chars.by_ref().take_while(|&c| c != '\''));
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => word.extend(chars.next()),
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => word.get_or_insert_with(String::new).extend(chars.next()),
+            c if c.is_whitespace() => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+// The arguments program is run with in content, such as a Makefile, script,
+// or CI workflow, taken from the first line which runs it. Lines which
+// install it, such as go install or pip install, are passed over.
+fn command_args(content: &str, program: &str) -> Option<Vec<String>> {
+    let joined = content.replace("\\\n", " ");
+    joined.lines().find_map(|line| {
+        let words = shell_words(line);
+        // The program may be run by its path or with go run.
+        let start = words.iter().position(|word| {
+            let name = word.rsplit('/').next().unwrap_or(word);
+            name.split('@').next() == Some(program)
+        })?;
+        if start > 0 && matches!(words[start - 1].as_str(), "install" | "get" | "add") {
+            return None;
+        }
+        Some(
+            words[start + 1..]
+                .iter()
+                .take_while(|word| !matches!(word.as_str(), "&&" | "||" | ";" | "|" | "#"))
+                .cloned()
+                .collect(),
+        )
+    })
+}
+
+// Split a flag like -c=Acme or --c=Acme into its name and value.
+fn split_flag(arg: &str) -> Option<(&str, Option<&str>)> {
+    let flag = arg.strip_prefix("--").or_else(|| arg.strip_prefix('-'))?;
+    if flag.is_empty() {
+        return None;
+    }
+    Some(match flag.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (flag, None),
+    })
+}
+
+fn import_addlicense(content: &str, dir: &Path) -> Result<Imported, String> {
+    let args = command_args(content, "addlicense")
+        .ok_or("no addlicense command was found, give the path of the script, Makefile, or CI workflow which runs it")?;
+
+    let mut imported = Imported::default();
+    let mut holder = "Google LLC".to_string();
+    let mut license = "apache".to_string();
+    let mut template_file = None;
+    let mut year = None;
+    let mut spdx = None;
+    let mut paths = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let Some((name, value)) = split_flag(&arg) else {
+            paths.push(arg);
+            continue;
+        };
+        match name {
+            "s" => spdx = Some(value.unwrap_or("true").to_string()),
+            "check" | "v" => (),
+            "c" | "l" | "f" | "y" | "ignore" | "skip" => {
+                let value = match value {
+                    Some(value) => value.to_string(),
+                    None => args
+                        .next()
+                        .ok_or_else(|| format!("addlicense -{} is missing its value", name))?,
+                };
+                match name {
+                    "c" => holder = value,
+                    "l" => license = value,
+                    "f" => template_file = Some(value),
+                    "y" => year = Some(value),
+                    "ignore" => imported.excludes.push(glob_regex(&value)?),
+                    _ => imported.notes.push(format!(
+                        "addlicense -skip {} has no equivalent, exclude the files with those extensions",
+                        value
+                    )),
+                }
+            }
+            _ => imported
+                .notes
+                .push(format!("the addlicense flag -{} has no equivalent", name)),
+        }
+    }
+
+    let builtin = ADDLICENSE_TEMPLATES
+        .iter()
+        .find(|(name, _, _)| *name == license);
+    let ident = builtin.map_or(license.as_str(), |(_, ident, _)| ident);
+    let placeholders = [
+        ("{{.Year}}", "[year]"),
+        ("{{.Holder}}", "[name of author]"),
+        ("{{.SPDXID}}", "[ident]"),
+    ];
+    let text = match (&template_file, builtin) {
+        (Some(path), _) => Some(
+            fs::read_to_string(dir.join(path))
+                .map_err(|e| format!("unable to read the addlicense template {}: {}", path, e))?,
+        ),
+        (None, Some((_, _, text))) => Some(text.to_string()),
+        (None, None) => None,
+    };
+    let template = match (spdx.as_deref(), text) {
+        (Some("only"), _) => Some(
+            "Copyright [year] [name of author]\nSPDX-License-Identifier: [ident]\n".to_string(),
+        ),
+        (Some(_), Some(text)) => Some(format!(
+            "{}\nSPDX-License-Identifier: [ident]\n",
+            convert_template(&text, &placeholders)
+        )),
+        (None, Some(text)) => Some(convert_template(&text, &placeholders)),
+        (_, None) => None,
+    };
+    if template
+        .as_deref()
+        .is_some_and(|template| template.contains("{{"))
+    {
+        imported.notes.push(
+            "the template uses Go template actions licensure doesn't support, rewrite them"
+                .to_string(),
+        );
+    }
+
+    imported.licenses.push(ImportedLicense {
+        files: files_regex(&paths)?,
+        ident: ident.to_string(),
+        authors: vec![holder],
+        end_year: year,
+        template,
+    });
+    Ok(imported)
+}
+
+fn yaml_str<'a>(value: &'a serde_yaml::Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(serde_yaml::Value::as_str)
+}
+
+fn yaml_strings(value: &serde_yaml::Value, key: &str) -> Vec<String> {
+    match value.get(key) {
+        Some(serde_yaml::Value::Sequence(values)) => values
+            .iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect(),
+        Some(serde_yaml::Value::String(value)) => vec![value.clone()],
+        _ => Vec::new(),
+    }
+}
+
+fn import_skywalking_eyes(content: &str) -> Result<Imported, String> {
+    let config: serde_yaml::Value =
+        serde_yaml::from_str(content).map_err(|e| format!("invalid YAML: {}", e))?;
+    let headers = match config.get("header") {
+        Some(serde_yaml::Value::Sequence(headers)) => headers.clone(),
+        Some(header @ serde_yaml::Value::Mapping(_)) => vec![header.clone()],
+        _ => return Err("the config has no header section".to_string()),
+    };
+
+    let mut imported = Imported::default();
+    if config.get("dependency").is_some() {
+        imported.notes.push(
+            "the dependency section has no equivalent, licensure only checks headers".to_string(),
+        );
+    }
+    for header in &headers {
+        let license = header
+            .get("license")
+            .cloned()
+            .unwrap_or(serde_yaml::Value::Null);
+        let ident = match yaml_str(&license, "spdx-id") {
+            Some(ident) => ident.to_string(),
+            None => {
+                imported.notes.push(
+                    "a header has no spdx-id, set the ident of its license config".to_string(),
+                );
+                "NOASSERTION".to_string()
+            }
+        };
+        let software = yaml_str(&license, "software-name").unwrap_or("[[software-name]]");
+        let template = yaml_str(&license, "content").map(|content| {
+            convert_template(
+                content,
+                &[
+                    ("[year]", "[year]"),
+                    ("[owner]", "[name of author]"),
+                    ("[software-name]", software),
+                ],
+            )
+        });
+        if template
+            .as_deref()
+            .is_some_and(|t| t.contains("[[software-name]]"))
+        {
+            imported.notes.push(format!(
+                "the {} template uses [software-name] but no software-name is set",
+                ident
+            ));
+        }
+        if yaml_str(&license, "pattern").is_some() {
+            imported.notes.push(format!(
+                "the pattern of {} has no equivalent, add it to replaces if it should be replaced",
+                ident
+            ));
+        }
+
+        imported
+            .excludes
+            .extend(glob_regexes(&yaml_strings(header, "paths-ignore"))?);
+        imported.licenses.push(ImportedLicense {
+            files: files_regex(&yaml_strings(header, "paths"))?,
+            ident,
+            authors: yaml_strings(&license, "copyright-owner"),
+            end_year: match license.get("copyright-year") {
+                Some(serde_yaml::Value::Number(year)) => Some(year.to_string()),
+                Some(serde_yaml::Value::String(year)) => Some(year.clone()),
+                _ => None,
+            },
+            template,
+        });
+    }
+
+    Ok(imported)
+}
+
+fn import_licenseheaders(content: &str, dir: &Path) -> Result<Imported, String> {
+    let args = command_args(content, "licenseheaders")
+        .ok_or("no licenseheaders command was found, give the path of the script, Makefile, or CI workflow which runs it")?;
+
+    let mut imported = Imported::default();
+    let mut template = None;
+    let mut years = None;
+    let mut owner = None;
+    let mut project = None;
+    let mut url = None;
+    let mut dirs = Vec::new();
+    let mut extensions = Vec::new();
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        let Some((name, value)) = split_flag(&arg) else {
+            imported
+                .notes
+                .push(format!("the licenseheaders argument {} was ignored", arg));
+            continue;
+        };
+        let mut values: Vec<String> = value.map(str::to_string).into_iter().collect();
+        let multiple = matches!(name, "x" | "exclude" | "E" | "ext" | "f" | "files");
+        while values.is_empty() || multiple {
+            match args.next_if(|next| !next.starts_with('-')) {
+                Some(value) => values.push(value),
+                None => break,
+            }
+        }
+        let first = values.first().cloned();
+        match name {
+            "t" | "tmpl" => template = first,
+            "y" | "years" => years = first,
+            "o" | "owner" => owner = first,
+            "n" | "projname" => project = first,
+            "u" | "projurl" => url = first,
+            "d" | "dir" => dirs.extend(values),
+            "f" | "files" => dirs.extend(values),
+            "x" | "exclude" => imported.excludes.extend(glob_regexes(&values)?),
+            "E" | "ext" => extensions.extend(
+                values
+                    .iter()
+                    .map(|ext| regex::escape(ext.trim_start_matches('.'))),
+            ),
+            "v" | "verbose" | "dry" | "safesubst" | "enc" => (),
+            _ => imported.notes.push(format!(
+                "the licenseheaders option -{} has no equivalent",
+                name
+            )),
+        }
+    }
+
+    let template_name = template.ok_or("the licenseheaders command has no -t template")?;
+    let known = LICENSEHEADERS_TEMPLATES
+        .iter()
+        .find(|(name, _)| *name == template_name);
+    let (ident, template) = match known {
+        Some((_, ident)) => {
+            imported.notes.push(format!(
+                "the {} header is taken from SPDX, its wording may differ from licenseheaders' {} template",
+                ident, template_name
+            ));
+            (ident.to_string(), None)
+        }
+        None => {
+            let text = fs::read_to_string(dir.join(&template_name)).map_err(|e| {
+                format!(
+                    "{} isn't a licenseheaders template and can't be read: {}",
+                    template_name, e
+                )
+            })?;
+            imported.notes.push(format!(
+                "the template was read from {}, set the ident of its license config",
+                template_name
+            ));
+            let placeholders = [
+                ("${years}", "[year]"),
+                ("${owner}", "[name of author]"),
+                (
+                    "${projectname}",
+                    project.as_deref().unwrap_or("${projectname}"),
+                ),
+                ("${projecturl}", url.as_deref().unwrap_or("${projecturl}")),
+            ];
+            (
+                "NOASSERTION".to_string(),
+                Some(convert_template(&text, &placeholders)),
+            )
+        }
+    };
+
+    let mut files = files_regex(&dirs)?;
+    if !extensions.is_empty() {
+        let by_extension = format!(r"\.({})$", extensions.join("|"));
+        files = Some(match files {
+            Some(files) => format!("{}.*{}", files, by_extension),
+            None => by_extension,
+        });
+    }
+    imported.licenses.push(ImportedLicense {
+        files,
+        ident,
+        authors: owner.into_iter().collect(),
+        end_year: years,
+        template,
+    });
+    Ok(imported)
+}
+
+// Quote s as a YAML string.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+// Render the config licensure imported from tool's configuration in source.
+fn render_config(imported: &Imported, tool: &str, source: &str) -> String {
+    let mut config = format!(
+        "# Imported from the {} configuration in {} by licensure import.\n# Review it before running licensure, in particular any notes below.\n",
+        tool, source
+    );
+    for note in &imported.notes {
+        config.push_str(&format!("#\n# note: {}\n", note));
+    }
+    config.push_str("\nchange_in_place: true\n");
+
+    if !imported.excludes.is_empty() {
+        config.push_str("\nexcludes:\n");
+        for exclude in &imported.excludes {
+            config.push_str(&format!("  - {}\n", quote(exclude)));
+        }
+    }
+
+    config.push_str("\nlicenses:\n");
+    for license in &imported.licenses {
+        config.push_str(&format!(
+            "  - files: {}\n    ident: {}\n",
+            license.files.as_deref().map_or("any".to_string(), quote),
+            quote(&license.ident)
+        ));
+        if license.authors.is_empty() {
+            config.push_str("    authors: []\n");
+        } else {
+            config.push_str("    authors:\n");
+            for author in &license.authors {
+                config.push_str(&format!("      - name: {}\n", quote(author)));
+            }
+        }
+        if let Some(year) = &license.end_year {
+            config.push_str(&format!("    end_year: {}\n", quote(year)));
+        }
+        // Their templates are used with the line breaks they have.
+        config.push_str("    unwrap_text: false\n");
+        match &license.template {
+            Some(template) => {
+                let chomp = if template.ends_with('\n') { "" } else { "-" };
+                config.push_str(&format!("    template: |{}\n", chomp));
+                for line in template.lines() {
+                    if line.is_empty() {
+                        config.push('\n');
+                    } else {
+                        config.push_str(&format!("      {}\n", line));
+                    }
+                }
+            }
+            None => config.push_str("    auto_template: true\n"),
+        }
+    }
+
+    config.push_str("\ncomments:\n");
+    for preset in COMMENT_PRESETS {
+        config.push_str(&format!("  - preset: {}\n", preset));
+    }
+    config
+}
+
+/// Convert the configuration of tool at path, such as a .licenserc.yaml, or
+/// for tools configured on the command line the script, Makefile, or CI
+/// workflow which runs them, into a licensure config written to output.
+/// Fails if output already exists or the configuration can't be converted
+/// into a config licensure loads.
+pub fn import(tool: Tool, path: &Path, output: &Path) -> Result<(), io::Error> {
+    if output.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "{} already exists, remove it or import to another --output",
+                output.display()
+            ),
+        ));
+    }
+
+    let content = fs::read_to_string(path)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let (name, imported) = match tool {
+        Tool::Addlicense => ("addlicense", import_addlicense(&content, dir)),
+        Tool::SkywalkingEyes => ("skywalking-eyes", import_skywalking_eyes(&content)),
+        Tool::Licenseheaders => ("licenseheaders", import_licenseheaders(&content, dir)),
+    };
+    let imported = imported.map_err(|e| io::Error::other(format!("{}: {}", path.display(), e)))?;
+    let yaml = render_config(&imported, name, &path.display().to_string());
+    config::parse_config(&yaml, "the imported config")?;
+
+    fs::write(output, yaml)?;
+    for note in &imported.notes {
+        println!("note: {}", note);
+    }
+    println!("wrote {}", output.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_regex() {
+        let vendor = regex::Regex::new(&glob_regex("vendor/**").unwrap()).unwrap();
+        assert!(vendor.is_match("vendor/a/b.go"));
+        assert!(vendor.is_match("./vendor/a.go"));
+        assert!(!vendor.is_match("src/vendor/a.go"));
+
+        let dist = regex::Regex::new(&glob_regex("dist").unwrap()).unwrap();
+        assert!(dist.is_match("dist/app.js"));
+        assert!(dist.is_match("web/dist/app.js"));
+        assert!(!dist.is_match("distro/app.js"));
+
+        let markdown = regex::Regex::new(&glob_regex("**/*.md").unwrap()).unwrap();
+        assert!(markdown.is_match("README.md"));
+        assert!(markdown.is_match("docs/a.md"));
+        assert!(!markdown.is_match("a.mdx"));
+
+        assert_eq!(
+            Err("invalid glob src/[ab".to_string()),
+            glob_regex("src/[ab")
+        );
+    }
+
+    #[test]
+    fn test_import_addlicense() {
+        let makefile = "lint:\n\tgo run github.com/google/addlicense@v1.1.1 -c \"Acme, Inc.\" \\\n\t\t-l mit -s -ignore 'vendor/**' -check . && echo done\n";
+        let imported = import_addlicense(makefile, Path::new(".")).unwrap();
+        assert_eq!(vec![glob_regex("vendor/**").unwrap()], imported.excludes);
+        let license = &imported.licenses[0];
+        assert_eq!("MIT", license.ident);
+        assert_eq!(None, license.files);
+        assert_eq!(vec!["Acme, Inc.".to_string()], license.authors);
+        assert_eq!(
+            Some("Copyright (c) [year] [name of author]\n\nUse of this source code is governed by an MIT-style\nlicense that can be found in the LICENSE file or at\nhttps://opensource.org/licenses/MIT.\n\nSPDX-License-Identifier: [ident]\n"),
+            license.template.as_deref()
+        );
+
+        let yaml = render_config(&imported, "addlicense", "Makefile");
+        config::parse_config(&yaml, "test").expect("The imported config loads");
+
+        assert!(import_addlicense("echo hi", Path::new(".")).is_err());
+
+        // The line installing addlicense isn't the one running it.
+        let makefile = "license:\n\tgo install github.com/google/addlicense@latest\n\taddlicense -c \"Acme Corp\" -l mit -y 2024 src\n";
+        let imported = import_addlicense(makefile, Path::new(".")).unwrap();
+        let license = &imported.licenses[0];
+        assert_eq!("MIT", license.ident);
+        assert_eq!(vec!["Acme Corp".to_string()], license.authors);
+        assert_eq!(Some("2024"), license.end_year.as_deref());
+    }
+
+    #[test]
+    fn test_import_skywalking_eyes() {
+        let licenserc = r#"
+header:
+  license:
+    spdx-id: Apache-2.0
+    copyright-owner: Apache Software Foundation
+    software-name: skywalking
+    content: |
+      Copyright [year] [owner]. Part of [software-name].
+      See the NOTICE file [for details].
+  paths:
+    - '**'
+  paths-ignore:
+    - 'dist'
+    - '**/*.md'
+  comment: on-failure
+"#;
+        let imported = import_skywalking_eyes(licenserc).unwrap();
+        assert_eq!(
+            vec![glob_regex("dist").unwrap(), glob_regex("**/*.md").unwrap()],
+            imported.excludes
+        );
+        let license = &imported.licenses[0];
+        assert_eq!("Apache-2.0", license.ident);
+        assert_eq!(None, license.files);
+        assert_eq!(
+            Some("Copyright [year] [name of author]. Part of skywalking.\nSee the NOTICE file [[for details]].\n"),
+            license.template.as_deref()
+        );
+
+        let yaml = render_config(&imported, "skywalking-eyes", ".licenserc.yaml");
+        config::parse_config(&yaml, "test").expect("The imported config loads");
+    }
+
+    #[test]
+    fn test_import_licenseheaders() {
+        let script = "licenseheaders -t lgpl-v3 -o 'Jo Dev' -y 2019-2024 -d src -x '*.json' 'build/*' -E .py\n";
+        let imported = import_licenseheaders(script, Path::new(".")).unwrap();
+        assert_eq!(
+            vec![
+                glob_regex("*.json").unwrap(),
+                glob_regex("build/*").unwrap()
+            ],
+            imported.excludes
+        );
+        let license = &imported.licenses[0];
+        assert_eq!("LGPL-3.0-or-later", license.ident);
+        assert_eq!(None, license.template);
+        assert_eq!(Some("2019-2024"), license.end_year.as_deref());
+        let files = regex::Regex::new(license.files.as_deref().unwrap()).unwrap();
+        assert!(files.is_match("src/pkg/a.py"));
+        assert!(!files.is_match("src/pkg/a.rs"));
+        assert!(!files.is_match("tests/a.py"));
+
+        let yaml = render_config(&imported, "licenseheaders", "Makefile");
+        config::parse_config(&yaml, "test").expect("The imported config loads");
+    }
+}
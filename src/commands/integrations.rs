@@ -0,0 +1,227 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use licensure_core::config::Config;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The licensure commands the snippets run, each without the licensure
+/// itself, so they can be checked against the command line interface.
+pub struct Commands {
+    /// Run by pre-commit with the staged files added.
+    pub pre_commit: Vec<String>,
+    /// Run on pull requests.
+    pub check: Vec<String>,
+    /// Run on a schedule to fix the headers of the whole project.
+    pub fix: Vec<String>,
+    // The config files the commands use.
+    configs: Vec<String>,
+}
+
+impl Commands {
+    /// The commands using the configs and profile this run was given, so
+    /// the snippets check the project the same way.
+    pub fn new(configs: &[&str], profile: Option<&str>) -> Commands {
+        let mut shared: Vec<String> = Vec::new();
+        for config in configs {
+            shared.extend(["--config".to_string(), config.to_string()]);
+        }
+        if let Some(profile) = profile {
+            shared.extend(["--profile".to_string(), profile.to_string()]);
+        }
+        let with = |args: &[&str]| -> Vec<String> {
+            args.iter()
+                .map(|arg| arg.to_string())
+                .chain(shared.iter().cloned())
+                .collect()
+        };
+
+        Commands {
+            pre_commit: with(&["--in-place"]),
+            check: with(&["--check", "--project"]),
+            fix: with(&["--in-place", "--project"]),
+            configs: configs.iter().map(|config| config.to_string()).collect(),
+        }
+    }
+
+    /// Every command, for checking them.
+    pub fn all(&self) -> [&[String]; 3] {
+        [&self.pre_commit, &self.check, &self.fix]
+    }
+}
+
+// Quote args for a shell where they need it.
+fn shell_line(args: &[String]) -> String {
+    let mut line = String::from("licensure");
+    for arg in args {
+        line.push(' ');
+        if !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@+".contains(c))
+        {
+            line.push_str(arg);
+        } else {
+            line.push_str(&format!("'{}'", arg.replace('\'', r"'\''")));
+        }
+    }
+    line
+}
+
+fn pre_commit_config(commands: &Commands) -> String {
+    format!(
+        r#"# .pre-commit-config.yaml
+# Adds missing and outdated headers to the staged files, which fails the
+# commit so the new headers can be reviewed and staged.
+repos:
+  - repo: local
+    hooks:
+      - id: licensure
+        name: licensure
+        entry: {}
+        language: system
+        pass_filenames: true
+"#,
+        shell_line(&commands.pre_commit)
+    )
+}
+
+fn github_workflow(commands: &Commands, cache_licenses: bool) -> String {
+    let install = format!(
+        r#"      - uses: actions/checkout@v4
+        with:
+          # The years and authors of files come from their git history.
+          fetch-depth: 0
+      - name: Install licensure
+        run: cargo install licensure --version {} --locked
+"#,
+        VERSION
+    );
+    let configs: Vec<String> = match commands.configs.as_slice() {
+        [] => vec!["'.licensure.yml'".to_string()],
+        configs => configs
+            .iter()
+            .map(|config| format!("'{}'", config.replace('\'', "''")))
+            .collect(),
+    };
+    let cache = if cache_licenses {
+        format!(
+            r#"      - name: Cache SPDX licenses
+        uses: actions/cache@v4
+        with:
+          path: ~/.cache/licensure/spdx
+          key: licensure-spdx-${{{{ hashFiles({}) }}}}
+"#,
+            configs.join(", ")
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        r#"# .github/workflows/licensure.yml
+# Checks the headers of pull requests, and every week opens a pull request
+# adding missing and outdated headers to the whole project.
+name: license headers
+on:
+  pull_request:
+  schedule:
+    - cron: "0 6 * * 1"
+  workflow_dispatch:
+
+jobs:
+  check:
+    if: github.event_name == 'pull_request'
+    runs-on: ubuntu-latest
+    steps:
+{install}{cache}      - name: Check license headers
+        run: {check}
+
+  fix:
+    if: github.event_name != 'pull_request'
+    runs-on: ubuntu-latest
+    permissions:
+      contents: write
+      pull-requests: write
+    steps:
+{install}{cache}      - name: Update license headers
+        run: {fix}
+      - uses: peter-evans/create-pull-request@v6
+        with:
+          branch: licensure/update-headers
+          commit-message: Update license headers
+          title: Update license headers
+          body: Headers added and updated by `{fix}`.
+"#,
+        install = install,
+        cache = cache,
+        check = shell_line(&commands.check),
+        fix = shell_line(&commands.fix),
+    )
+}
+
+/// Print a pre-commit config and a GitHub Actions workflow, or only the one
+/// asked for, which run commands with the version of licensure running.
+/// Licenses fetched from SPDX for config are cached between workflow runs.
+pub fn integrations(config: &Config, commands: &Commands, pre_commit: bool, github_actions: bool) {
+    let both = !pre_commit && !github_actions;
+    if pre_commit || both {
+        print!("{}", pre_commit_config(commands));
+    }
+    if both {
+        println!();
+    }
+    if github_actions || both {
+        let cache_licenses = !config.licenses.fetched_idents().is_empty();
+        print!("{}", github_workflow(commands, cache_licenses));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippets() {
+        let commands = Commands::new(&["ci/licensure.yml"], Some("backend team"));
+        assert_eq!(
+            "licensure --check --project --config ci/licensure.yml --profile 'backend team'",
+            shell_line(&commands.check)
+        );
+
+        let pre_commit = pre_commit_config(&commands);
+        serde_yaml::from_str::<serde_yaml::Value>(&pre_commit).expect("Valid YAML");
+        assert!(pre_commit.contains(
+            "entry: licensure --in-place --config ci/licensure.yml --profile 'backend team'"
+        ));
+
+        let workflow = github_workflow(&commands, true);
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&workflow).expect("Valid YAML");
+        let steps = &parsed["jobs"]["fix"]["steps"];
+        assert_eq!(
+            Some(
+                "licensure --in-place --project --config ci/licensure.yml --profile 'backend team'"
+            ),
+            steps[3]["run"].as_str()
+        );
+        assert_eq!(
+            Some(format!("cargo install licensure --version {} --locked", VERSION).as_str()),
+            steps[1]["run"].as_str()
+        );
+        assert_eq!(
+            Some("licensure-spdx-${{ hashFiles('ci/licensure.yml') }}"),
+            steps[2]["with"]["key"].as_str()
+        );
+        assert!(!github_workflow(&commands, false).contains("actions/cache"));
+    }
+}
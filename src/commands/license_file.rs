@@ -0,0 +1,122 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use licensure_core::config::Config;
+use licensure_core::spdx;
+
+// A project with a single license keeps it in LICENSE, otherwise each license
+// gets its own file under LICENSES/ as recommended by the REUSE specification.
+fn license_file_paths<'a>(idents: &[&'a str]) -> Vec<(&'a str, PathBuf)> {
+    if idents.len() == 1 {
+        return vec![(idents[0], PathBuf::from("LICENSE"))];
+    }
+
+    idents
+        .iter()
+        .map(|ident| {
+            (
+                *ident,
+                PathBuf::from("LICENSES").join(format!("{}.txt", ident)),
+            )
+        })
+        .collect()
+}
+
+/// Write the full text of every configured license to the project's license
+/// files or, in check mode, verify that those files contain the licenses the
+/// headers refer to. Returns false if any license file is missing or doesn't
+/// match its license.
+pub fn license_file(config: &Config, check: bool) -> Result<bool, io::Error> {
+    let idents = config.licenses.idents();
+    if idents.is_empty() {
+        println!("No licenses are configured, add one to the licenses section of your config.");
+        return Ok(false);
+    }
+
+    spdx::prefetch(&idents);
+
+    let mut in_sync = true;
+    for (ident, path) in license_file_paths(&idents) {
+        let license_info = match spdx::fetch_license_info(ident) {
+            Ok(license_info) => license_info,
+            Err(e) => {
+                eprintln!("{}", e);
+                in_sync = false;
+                continue;
+            }
+        };
+
+        if check {
+            match fs::read_to_string(&path) {
+                Ok(existing) => {
+                    if !spdx::text_matches(&license_info.license_text, &existing) {
+                        eprintln!(
+                            "{} does not contain the {} license text",
+                            path.display(),
+                            ident
+                        );
+                        in_sync = false;
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    eprintln!(
+                        "{} is missing, it should contain the {} license text",
+                        path.display(),
+                        ident
+                    );
+                    in_sync = false;
+                }
+                Err(e) => return Err(e),
+            }
+
+            continue;
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let text = config
+            .licenses
+            .render_license_text(ident, &license_info)
+            .unwrap_or_else(|| license_info.license_text.clone());
+        fs::write(&path, text)?;
+        info!("wrote {} license text to {}", ident, path.display());
+    }
+
+    Ok(in_sync)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_file_paths() {
+        assert_eq!(
+            vec![("MIT", PathBuf::from("LICENSE"))],
+            license_file_paths(&["MIT"])
+        );
+        assert_eq!(
+            vec![
+                ("MIT", PathBuf::from("LICENSES/MIT.txt")),
+                ("Apache-2.0", PathBuf::from("LICENSES/Apache-2.0.txt"))
+            ],
+            license_file_paths(&["MIT", "Apache-2.0"])
+        );
+    }
+}
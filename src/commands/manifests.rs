@@ -0,0 +1,294 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use licensure_core::config::Config;
+use licensure_core::utils::long_path;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum ManifestKind {
+    Cargo,
+    Npm,
+    Python,
+}
+
+impl ManifestKind {
+    fn from_filename(filename: &str) -> Option<ManifestKind> {
+        match filename.rsplit('/').next().unwrap_or_default() {
+            "Cargo.toml" => Some(ManifestKind::Cargo),
+            "package.json" => Some(ManifestKind::Npm),
+            "pyproject.toml" => Some(ManifestKind::Python),
+            _ => None,
+        }
+    }
+
+    // The TOML tables which may hold the license of the package.
+    fn sections(&self) -> &'static [&'static str] {
+        match self {
+            ManifestKind::Cargo => &["package", "workspace.package"],
+            ManifestKind::Python => &["project", "tool.poetry"],
+            ManifestKind::Npm => &[],
+        }
+    }
+
+    fn read_license(&self, content: &str) -> Option<String> {
+        match self {
+            ManifestKind::Npm => json_license(content).map(|m| m.as_str().to_string()),
+            _ => toml_license(content, self.sections()).map(|(_, value)| value.to_string()),
+        }
+    }
+
+    // Returns the content with the license field set to ident, or None if
+    // there is nowhere sensible to put it.
+    fn write_license(&self, content: &str, ident: &str) -> Option<String> {
+        match self {
+            ManifestKind::Npm => write_json_license(content, ident),
+            _ => write_toml_license(content, self.sections(), ident),
+        }
+    }
+}
+
+fn toml_license_re() -> &'static Regex {
+    static TOML_LICENSE: OnceLock<Regex> = OnceLock::new();
+    TOML_LICENSE.get_or_init(|| {
+        Regex::new(r#"^(license\s*=\s*(?:\{\s*text\s*=\s*)?")([^"]*)""#)
+            .expect("toml license regex didn't compile!")
+    })
+}
+
+fn toml_section(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.starts_with('[') {
+        Some(line.trim_matches(|c| c == '[' || c == ']').trim())
+    } else {
+        None
+    }
+}
+
+// Find the license in one of the given tables of a TOML document, returning
+// the line it was found on and its value. A full TOML parser isn't used so
+// that fixes can be made without reformatting the rest of the file.
+fn toml_license<'a>(content: &'a str, sections: &[&str]) -> Option<(usize, &'a str)> {
+    let re = toml_license_re();
+    let mut in_section = false;
+    for (idx, line) in content.lines().enumerate() {
+        if let Some(section) = toml_section(line) {
+            in_section = sections.contains(&section);
+            continue;
+        }
+
+        if in_section {
+            if let Some(caps) = re.captures(line.trim_start()) {
+                return Some((idx, caps.get(2).map_or("", |m| m.as_str())));
+            }
+        }
+    }
+
+    None
+}
+
+fn write_toml_license(content: &str, sections: &[&str], ident: &str) -> Option<String> {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    match toml_license(content, sections) {
+        Some((idx, _)) => {
+            lines[idx] = toml_license_re()
+                .replace(&lines[idx], |caps: &regex::Captures| {
+                    format!("{}{}\"", &caps[1], ident)
+                })
+                .to_string();
+        }
+        None => {
+            // Add the license as the last key of the package table.
+            let start = lines
+                .iter()
+                .position(|line| toml_section(line).is_some_and(|s| s == sections[0]))?;
+            let mut idx = start + 1;
+            for (offset, line) in lines[start + 1..].iter().enumerate() {
+                if toml_section(line).is_some() {
+                    break;
+                }
+
+                if !line.trim().is_empty() {
+                    idx = start + offset + 2;
+                }
+            }
+            lines.insert(idx, format!("license = \"{}\"", ident));
+        }
+    }
+
+    let mut new_content = lines.join("\n");
+    if content.ends_with('\n') {
+        new_content.push('\n');
+    }
+
+    Some(new_content)
+}
+
+fn json_license(content: &str) -> Option<regex::Match<'_>> {
+    // Only a top level string license is supported, the deprecated object and
+    // array forms are reported as missing.
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    value.get("license")?.as_str()?;
+
+    Regex::new(r#""license"\s*:\s*"([^"]*)""#)
+        .expect("json license regex didn't compile!")
+        .captures(content)?
+        .get(1)
+}
+
+fn write_json_license(content: &str, ident: &str) -> Option<String> {
+    if let Some(m) = json_license(content) {
+        let mut new_content = content.to_string();
+        new_content.replace_range(m.range(), ident);
+        return Some(new_content);
+    }
+
+    // Add the license after the package name, matching its indentation.
+    let caps = Regex::new(r#"(?m)^([ \t]*)"name"\s*:\s*"[^"]*",?[ \t]*$"#)
+        .expect("json name regex didn't compile!")
+        .captures(content)?;
+    let name = caps.get(0)?;
+    let indent = &caps[1];
+    let mut new_content = content.to_string();
+    if name.as_str().trim_end().ends_with(',') {
+        new_content.insert_str(
+            name.end(),
+            &format!("\n{}\"license\": \"{}\",", indent, ident),
+        );
+    } else {
+        new_content.insert_str(
+            name.end(),
+            &format!("\n{}\"license\": \"{}\"", indent, ident),
+        );
+        new_content.insert(name.start() + name.as_str().trim_end().len(), ',');
+    }
+
+    Some(new_content)
+}
+
+/// Check that the license declared in every package manifest in files agrees
+/// with the license configured for the manifest's path. If fix is true then
+/// manifests which disagree are updated. Returns false if any manifest is left
+/// disagreeing with the config.
+pub fn manifests(config: &Config, files: &[String], fix: bool) -> Result<bool, io::Error> {
+    let mut in_sync = true;
+
+    for file in files {
+        let kind = match ManifestKind::from_filename(file) {
+            Some(kind) => kind,
+            None => continue,
+        };
+
+        let ident = match config.licenses.get_ident(file) {
+            Some(ident) => ident,
+            None => {
+                info!("skipping {} because no license config matched.", file);
+                continue;
+            }
+        };
+
+        let content = fs::read_to_string(long_path(file))?;
+        let declared = kind.read_license(&content);
+        if declared.as_deref() == Some(ident) {
+            info!("{} declares the configured license {}", file, ident);
+            continue;
+        }
+
+        if fix {
+            if let Some(new_content) = kind.write_license(&content, ident) {
+                fs::write(long_path(file), new_content)?;
+                println!("Updated the license of {} to {}", file, ident);
+                continue;
+            }
+        }
+
+        match declared {
+            Some(declared) => eprintln!(
+                "{} declares the license {} but the configured license is {}",
+                file, declared, ident
+            ),
+            None => eprintln!(
+                "{} does not declare a license, the configured license is {}",
+                file, ident
+            ),
+        }
+        in_sync = false;
+    }
+
+    Ok(in_sync)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CARGO_TOML: &str = r#"[package]
+name = "example"
+version = "0.1.0"
+license = "MIT"
+
+[dependencies]
+license = "not this one"
+"#;
+
+    #[test]
+    fn test_cargo_license() {
+        let kind = ManifestKind::Cargo;
+        assert_eq!(Some("MIT".to_string()), kind.read_license(CARGO_TOML));
+        assert_eq!(
+            CARGO_TOML.replace("license = \"MIT\"", "license = \"Apache-2.0\""),
+            kind.write_license(CARGO_TOML, "Apache-2.0").unwrap()
+        );
+
+        let missing = "[package]\nname = \"example\"\n";
+        assert_eq!(None, kind.read_license(missing));
+        assert_eq!(
+            "[package]\nname = \"example\"\nlicense = \"MIT\"\n",
+            kind.write_license(missing, "MIT").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pyproject_license() {
+        let kind = ManifestKind::Python;
+        let content = "[project]\nname = \"example\"\nlicense = {text = \"GPL-3.0\"}\n";
+        assert_eq!(Some("GPL-3.0".to_string()), kind.read_license(content));
+        assert_eq!(
+            "[project]\nname = \"example\"\nlicense = {text = \"MIT\"}\n",
+            kind.write_license(content, "MIT").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_package_json_license() {
+        let kind = ManifestKind::Npm;
+        let content = "{\n  \"name\": \"example\",\n  \"license\": \"ISC\"\n}\n";
+        assert_eq!(Some("ISC".to_string()), kind.read_license(content));
+        assert_eq!(
+            "{\n  \"name\": \"example\",\n  \"license\": \"MIT\"\n}\n",
+            kind.write_license(content, "MIT").unwrap()
+        );
+
+        let missing = "{\n  \"name\": \"example\"\n}\n";
+        assert_eq!(None, kind.read_license(missing));
+        assert_eq!(
+            "{\n  \"name\": \"example\",\n  \"license\": \"MIT\"\n}\n",
+            kind.write_license(missing, "MIT").unwrap()
+        );
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use licensure_core::config;
+
+/// Rewrite the config file at path to the current schema, printing each
+/// change, or in check mode only report the changes which are needed. Returns
+/// false if the config needed changes in check mode.
+pub fn migrate_config(path: &Path, check: bool) -> Result<bool, io::Error> {
+    let content = fs::read_to_string(path)?;
+    let migration = config::migrate(&content);
+    if migration.changes.is_empty() {
+        println!(
+            "{} is already config version {}",
+            path.display(),
+            config::CONFIG_VERSION
+        );
+        return Ok(true);
+    }
+
+    for change in &migration.changes {
+        println!("{}: {}", path.display(), change);
+    }
+
+    if check {
+        return Ok(false);
+    }
+
+    fs::write(path, migration.content)?;
+    Ok(true)
+}
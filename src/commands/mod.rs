@@ -0,0 +1,40 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+pub use audit::audit;
+pub use doctor::doctor;
+pub use holders::holders;
+pub use import::import;
+pub use integrations::{integrations, Commands};
+pub use license_file::license_file;
+pub use manifests::manifests;
+pub use migrate::migrate_config;
+pub use normalize::normalize;
+pub use notice::notice;
+pub use preview::preview;
+pub use selftest::selftest;
+pub use test_templates::{test_templates, GOLDEN_DIR};
+
+mod audit;
+mod doctor;
+mod holders;
+mod import;
+mod integrations;
+mod license_file;
+mod manifests;
+mod migrate;
+mod normalize;
+mod notice;
+mod preview;
+mod selftest;
+mod test_templates;
@@ -0,0 +1,58 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io;
+
+use licensure_core::config::Config;
+use licensure_core::utils::long_path;
+use licensure_core::Processor;
+
+/// Rewrite the headers of files which are ours but written differently than
+/// the config would write them now, such as with another comment character
+/// or wrapping, to exactly what the config writes. Years are left as they
+/// are. In check mode the files are only reported. Returns false if a header
+/// needs normalizing in check mode or a header can't be rendered.
+pub fn normalize(config: Config, files: &[String], check: bool) -> Result<bool, io::Error> {
+    let excludes = config.excludes.clone();
+    let processor = Processor::new(config);
+    let mut ok = true;
+    for file in files {
+        if excludes.is_match(file) {
+            continue;
+        }
+
+        let Ok(content) = String::from_utf8(fs::read(long_path(file))?) else {
+            info!("skipping {} because it is not UTF-8.", file);
+            continue;
+        };
+
+        match processor.normalize(file, &content) {
+            Ok(None) => (),
+            Ok(Some(_)) if check => {
+                println!("{}: header needs normalizing", file);
+                ok = false;
+            }
+            Ok(Some(normalized)) => {
+                fs::write(long_path(file), normalized)?;
+                println!("normalized {}", file);
+            }
+            Err(e) => {
+                println!("Unable to render a header for {}: {}", file, e);
+                ok = false;
+            }
+        }
+    }
+
+    Ok(ok)
+}
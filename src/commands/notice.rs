@@ -0,0 +1,185 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use licensure_core::config::Config;
+use licensure_core::utils::long_path;
+
+const NOTICE_FILE: &str = "NOTICE";
+
+fn copyright_re() -> &'static Regex {
+    static COPYRIGHT: OnceLock<Regex> = OnceLock::new();
+    COPYRIGHT.get_or_init(|| {
+        Regex::new(r"^Copyright\s+(?:\([cC]\)\s+|©\s+)?([0-9]{4})(?:\s*[,-]\s*([0-9]{4}))?\s+(.+)$")
+            .expect("copyright regex didn't compile!")
+    })
+}
+
+// Copyright holders mapped to the first and last year of their copyright.
+type Holders = BTreeMap<String, (String, String)>;
+
+fn add_copyright_line(holders: &mut Holders, re: &Regex, line: &str) -> bool {
+    let caps = match re.captures(line.trim()) {
+        Some(caps) => caps,
+        None => return false,
+    };
+
+    let start = caps[1].to_string();
+    let end = caps
+        .get(2)
+        .map_or(start.clone(), |m| m.as_str().to_string());
+    let holder = caps[3].trim().to_string();
+    let years = holders
+        .entry(holder)
+        .or_insert((start.clone(), end.clone()));
+    if start < years.0 {
+        years.0 = start;
+    }
+    if end > years.1 {
+        years.1 = end;
+    }
+
+    true
+}
+
+/// Merge the copyright lines of the headers into an existing NOTICE file.
+/// Lines which aren't copyright notices are kept where they are and the
+/// copyright notices are replaced by one line per holder, sorted by holder,
+/// spanning every year found for that holder.
+fn render_notice(existing: &str, copyright_lines: &[String]) -> String {
+    let re = copyright_re();
+    let mut holders = Holders::new();
+    for line in copyright_lines {
+        add_copyright_line(&mut holders, re, line);
+    }
+
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    let mut seen_copyright = false;
+    for line in existing.lines() {
+        if add_copyright_line(&mut holders, re, line) {
+            seen_copyright = true;
+        } else if seen_copyright {
+            after.push(line);
+        } else {
+            before.push(line);
+        }
+    }
+
+    let mut notice: Vec<String> = before.iter().map(|l| l.to_string()).collect();
+    if !seen_copyright && !notice.is_empty() && !notice.last().unwrap().is_empty() {
+        notice.push(String::new());
+    }
+
+    for (holder, (start, end)) in &holders {
+        if start == end {
+            notice.push(format!("Copyright {} {}", start, holder));
+        } else {
+            notice.push(format!("Copyright {}, {} {}", start, end, holder));
+        }
+    }
+
+    notice.extend(after.iter().map(|l| l.to_string()));
+    let mut content = notice.join("\n");
+    content.push('\n');
+    content
+}
+
+/// Generate or update the NOTICE file from the copyright notices of the
+/// headers of files. In check mode the file is not written and false is
+/// returned if it is out of date.
+pub fn notice(config: &Config, files: &[String], check: bool) -> Result<bool, io::Error> {
+    let mut copyright_lines = Vec::new();
+    for file in files {
+        if config.excludes.is_match(file) {
+            continue;
+        }
+
+        let content = fs::read(long_path(file))?;
+        let license = config
+            .licenses
+            .find_for(file, &String::from_utf8_lossy(&content))
+            .map_err(|e| io::Error::other(format!("{}: {}", file, e)))?;
+        if let Some((_, license)) = license {
+            copyright_lines.push(license.get_context(file).copyright_line());
+        }
+    }
+
+    let existing = match fs::read_to_string(NOTICE_FILE) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+
+    let updated = render_notice(&existing, &copyright_lines);
+    if updated == existing {
+        info!("{} is up to date", NOTICE_FILE);
+        return Ok(true);
+    }
+
+    if check {
+        eprintln!(
+            "{} is out of date, run licensure notice to update it",
+            NOTICE_FILE
+        );
+        return Ok(false);
+    }
+
+    fs::write(NOTICE_FILE, updated)?;
+    info!("updated {}", NOTICE_FILE);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_notice_new_file() {
+        let lines = vec![
+            "Copyright 2024 Zed Corp".to_string(),
+            "Copyright 2020, 2023 Acme Inc. <legal@acme.example>".to_string(),
+            "Copyright 2024 Zed Corp".to_string(),
+        ];
+        assert_eq!(
+            "Copyright 2020, 2023 Acme Inc. <legal@acme.example>\nCopyright 2024 Zed Corp\n",
+            render_notice("", &lines)
+        );
+    }
+
+    #[test]
+    fn test_render_notice_merges_existing() {
+        let existing = "Example Project
+Copyright 2019 Zed Corp
+Copyright (c) 2021 Third Party Ltd.
+
+This product includes software developed at Third Party Ltd.
+";
+        let lines = vec!["Copyright 2024 Zed Corp".to_string()];
+        assert_eq!(
+            "Example Project
+Copyright 2021 Third Party Ltd.
+Copyright 2019, 2024 Zed Corp
+
+This product includes software developed at Third Party Ltd.
+",
+            render_notice(existing, &lines)
+        );
+    }
+}
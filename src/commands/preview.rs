@@ -0,0 +1,42 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io::{self, ErrorKind};
+
+use licensure_core::config::Config;
+use licensure_core::utils::long_path;
+use licensure_core::Processor;
+
+/// Print the commented header file would be given, using the license config
+/// for ident when one is given. The file doesn't need to exist but if it does
+/// its shebang is used to pick the commenter. Returns false if no header can
+/// be rendered for file.
+pub fn preview(config: Config, file: &str, ident: Option<&str>) -> Result<bool, io::Error> {
+    let content = match fs::read_to_string(long_path(file)) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+
+    match Processor::new(config).preview(file, &content, ident) {
+        Ok(header) => {
+            print!("{}", header);
+            Ok(true)
+        }
+        Err(e) => {
+            println!("Unable to render a header for {}: {}", file, e);
+            Ok(false)
+        }
+    }
+}
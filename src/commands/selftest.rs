@@ -0,0 +1,92 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use licensure_core::config::Config;
+use licensure_core::Processor;
+
+/// License a sample file of each comment config with every license config,
+/// then check the header is found again once its year is out of date and can
+/// be removed. Returns false, having printed why, if any header doesn't
+/// survive the round trip.
+pub fn selftest(config: Config) -> bool {
+    let processor = Processor::new(config);
+    let samples = processor.config().comments.sample_filenames();
+    if samples.is_empty() {
+        println!("No comment configs are classified by extension or file name, so there is nothing to test.");
+        return false;
+    }
+
+    let mut passed = true;
+    for license in processor.config().licenses.iter() {
+        for sample in &samples {
+            match processor.roundtrip_sample(license, sample) {
+                Ok(()) => info!("{} with {}: ok", sample, license.ident()),
+                Err(e) => {
+                    println!("{} with {}: {}", sample, license.ident(), e);
+                    passed = false;
+                }
+            }
+        }
+    }
+
+    passed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_selftest() {
+        let config = |comments: &str| {
+            Config::from_str(&format!(
+                r##"
+licenses:
+  - files: any
+    ident: MIT
+    authors:
+      - name: The Tester
+    template: "Copyright [year] [name of author], [ident]"
+comments:
+{}"##,
+                comments
+            ))
+            .expect("Static config to be parsable")
+        };
+
+        assert!(selftest(config(
+            r##"
+  - extension: rs
+    commenter:
+      type: line
+      comment_char: "//"
+  - extension: c
+    commenter:
+      type: block
+      start_block_char: "/*\n"
+      end_block_char: "*/\n"
+      per_line_char: " *""##
+        )));
+
+        // Comments written by a command can't be found to be removed.
+        assert!(!selftest(config(
+            r##"
+  - extension: f
+    commenter:
+      type: external
+      command: "sed 's/^/C /'""##
+        )));
+    }
+}
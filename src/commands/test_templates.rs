@@ -0,0 +1,135 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use licensure_core::config::Config;
+use licensure_core::utils::unified_diff;
+use licensure_core::Processor;
+
+pub const GOLDEN_DIR: &str = "tests/headers";
+
+// Headers are rendered in this year, unless the license sets end_year, so the
+// golden files don't go out of date every new year.
+const SAMPLE_YEAR: &str = "2000";
+
+/// Render every license config for a made up file of each comment config and
+/// compare the headers with the golden files under dir, which are named
+/// <position>-<ident>/<file>. With update the golden files are written
+/// instead. Returns false if any header is missing or differs from its golden
+/// file.
+pub fn test_templates(config: Config, dir: &Path, update: bool) -> Result<bool, io::Error> {
+    let processor = Processor::new(config);
+    let samples = processor.config().comments.sample_filenames();
+    if samples.is_empty() {
+        println!("No comment configs are classified by extension or file name, so there is nothing to render.");
+        return Ok(false);
+    }
+
+    let mut passed = true;
+    for (i, license) in processor.config().licenses.iter().enumerate() {
+        let license_dir = dir.join(format!("{}-{}", i, license.ident()));
+        for sample in &samples {
+            let golden = license_dir.join(sample);
+            let header = match processor.render_sample(license, sample, SAMPLE_YEAR) {
+                Ok(header) => header,
+                Err(e) => {
+                    println!("{}: unable to render: {}", golden.display(), e);
+                    passed = false;
+                    continue;
+                }
+            };
+
+            if update {
+                fs::create_dir_all(&license_dir)?;
+                fs::write(&golden, &header)?;
+                info!("wrote {}", golden.display());
+                continue;
+            }
+
+            match fs::read_to_string(&golden) {
+                Ok(expected) if expected == header => {
+                    info!("{} matches", golden.display());
+                }
+                Ok(expected) => {
+                    println!("{} differs:", golden.display());
+                    print!(
+                        "{}",
+                        unified_diff(&golden.to_string_lossy(), &expected, &header)
+                    );
+                    passed = false;
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    println!(
+                        "{} is missing, create it with licensure test-templates --update",
+                        golden.display()
+                    );
+                    passed = false;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    Ok(passed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use licensure_core::testing::TempDir;
+
+    use super::*;
+
+    static CONFIG: &str = r##"
+licenses:
+  - files: any
+    ident: MIT
+    authors:
+      - name: The Tester
+    template: "Copyright [year] [name of author], [ident]"
+comments:
+  - extension: rs
+    commenter:
+      type: line
+      comment_char: "//"
+  - extension: any
+    commenter:
+      type: line
+      comment_char: "#""##;
+
+    #[test]
+    fn test_golden_files() {
+        let tmp = TempDir::new("golden");
+        let dir = tmp.join("golden");
+        let config = || Config::from_str(CONFIG).expect("Static config to be parsable");
+
+        assert!(!test_templates(config(), &dir, false).unwrap());
+        assert!(test_templates(config(), &dir, true).unwrap());
+        assert_eq!(
+            "// Copyright 2000 The Tester, MIT\n",
+            fs::read_to_string(dir.join("0-MIT/example.rs")).unwrap()
+        );
+        assert_eq!(
+            "# Copyright 2000 The Tester, MIT\n",
+            fs::read_to_string(dir.join("0-MIT/example")).unwrap()
+        );
+        assert!(test_templates(config(), &dir, false).unwrap());
+
+        fs::write(dir.join("0-MIT/example.rs"), "// old\n").unwrap();
+        assert!(!test_templates(config(), &dir, false).unwrap());
+    }
+}
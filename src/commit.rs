@@ -0,0 +1,100 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+pub const DEFAULT_MESSAGE: &str = "Update license headers";
+
+/// Stage files and commit them, and only them, to the git repository in repo
+/// with message. With signoff a Signed-off-by trailer for the committer is
+/// added.
+pub fn commit(
+    repo: &Path,
+    files: &[String],
+    message: &str,
+    signoff: bool,
+) -> Result<(), io::Error> {
+    git(
+        repo,
+        &["add", "--pathspec-from-file=-", "--pathspec-file-nul"],
+        files,
+    )?;
+
+    let mut args = vec![
+        "commit",
+        "--quiet",
+        "--message",
+        message,
+        "--pathspec-from-file=-",
+        "--pathspec-file-nul",
+    ];
+    if signoff {
+        args.push("--signoff");
+    }
+    git(repo, &args, files)
+}
+
+// Run git in repo with args and the NUL separated paths on stdin.
+fn git(repo: &Path, args: &[&str], paths: &[String]) -> Result<(), io::Error> {
+    let mut child = Command::new("git")
+        .current_dir(repo)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        for path in paths {
+            stdin.write_all(path.as_bytes())?;
+            stdin.write_all(b"\0")?;
+        }
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "git {} exited with {}",
+            args[0], status
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use licensure_core::testing::GitRepo;
+
+    use super::*;
+
+    #[test]
+    fn test_commit() {
+        let repo = GitRepo::new("commit");
+        fs::write(repo.join("a.py"), "# header\n").unwrap();
+        fs::write(repo.join("b.py"), "untouched\n").unwrap();
+
+        commit(&repo, &["a.py".to_string()], DEFAULT_MESSAGE, true).expect("Can commit");
+
+        assert_eq!(
+            "a.py",
+            repo.git(&["show", "--format=", "--name-only", "HEAD"])
+        );
+        assert_eq!(
+            "Update license headers\n\nSigned-off-by: Tester <tester@example.com>",
+            repo.git(&["log", "-1", "--format=%B"])
+        );
+        assert_eq!("?? b.py", repo.git(&["status", "--porcelain"]));
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use licensure_core::config::Config;
+
+const PERMISSIVE: &[&str] = &[
+    "0BSD",
+    "Apache-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "CC0-1.0",
+    "ISC",
+    "MIT",
+    "Unlicense",
+    "Zlib",
+];
+
+const WEAK_COPYLEFT: &[&str] = &["LGPL-2.1-only", "LGPL-2.1-or-later", "LGPL-3.0", "MPL-2.0"];
+
+// Code under these licenses can only be distributed as part of a work which is
+// itself under a compatible copyleft license.
+const COPYLEFT: &[&str] = &["AGPL-3.0", "GPL-2.0-only", "GPL-2.0-or-later", "GPL-3.0"];
+
+// Pairs of (outer, inner) where code under the inner license can't be
+// included in a work under the outer license beyond the general rule that
+// copyleft code can't be part of a permissive or weak copyleft work.
+const INCOMPATIBLE: &[(&str, &str)] = &[
+    ("GPL-2.0-only", "Apache-2.0"),
+    ("GPL-2.0-only", "LGPL-3.0"),
+    ("GPL-2.0-only", "GPL-3.0"),
+    ("GPL-2.0-only", "AGPL-3.0"),
+    ("GPL-3.0", "GPL-2.0-only"),
+    ("AGPL-3.0", "GPL-2.0-only"),
+];
+
+// Normalize an identifier to the names used in the tables above. The "only"
+// and "or later" variants only matter for the version 2 GNU licenses since
+// there is no later version of the others to upgrade to.
+fn normalize(ident: &str) -> String {
+    let (base, or_later) = if let Some(base) = ident.strip_suffix('+') {
+        (base, true)
+    } else if let Some(base) = ident.strip_suffix("-or-later") {
+        (base, true)
+    } else {
+        (ident.strip_suffix("-only").unwrap_or(ident), false)
+    };
+
+    match base {
+        "GPL-2.0" | "LGPL-2.1" if or_later => format!("{}-or-later", base),
+        "GPL-2.0" | "LGPL-2.1" => format!("{}-only", base),
+        _ => base.to_string(),
+    }
+}
+
+fn is_known(ident: &str) -> bool {
+    PERMISSIVE.contains(&ident) || WEAK_COPYLEFT.contains(&ident) || COPYLEFT.contains(&ident)
+}
+
+/// Returns true if code under the inner license can be distributed as part of
+/// a work under the outer license. License expressions, exceptions, and
+/// licenses we don't know about are always considered compatible.
+pub fn compatible(outer: &str, inner: &str) -> bool {
+    if outer.contains(char::is_whitespace) || inner.contains(char::is_whitespace) {
+        return true;
+    }
+
+    let outer = normalize(outer);
+    let inner = normalize(inner);
+    if outer == inner || !is_known(&outer) || !is_known(&inner) {
+        return true;
+    }
+
+    if INCOMPATIBLE.contains(&(outer.as_str(), inner.as_str())) {
+        return false;
+    }
+
+    !COPYLEFT.contains(&inner.as_str()) || COPYLEFT.contains(&outer.as_str())
+}
+
+/// Files under one license in a directory which is part of a work under a
+/// license it is incompatible with.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub outer_dir: String,
+    pub outer_ident: String,
+    pub inner_dir: String,
+    pub inner_ident: String,
+}
+
+fn display_dir(dir: &str) -> &str {
+    if dir.is_empty() {
+        "."
+    } else {
+        dir
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.outer_dir == self.inner_dir {
+            write!(
+                f,
+                "{} and {} files in {} are incompatible",
+                self.outer_ident,
+                self.inner_ident,
+                display_dir(&self.inner_dir)
+            )
+        } else {
+            write!(
+                f,
+                "{} files in {} are incompatible with the {} files in {}",
+                self.inner_ident,
+                display_dir(&self.inner_dir),
+                self.outer_ident,
+                display_dir(&self.outer_dir)
+            )
+        }
+    }
+}
+
+fn parent_dir(path: &str) -> Option<&str> {
+    if path.is_empty() {
+        None
+    } else {
+        Some(path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(""))
+    }
+}
+
+/// Find incompatible licenses among the configured licenses of files. Files in
+/// the same directory must be compatible with each other and files must be
+/// compatible with the license of their nearest parent directory which
+/// contains licensed files, since those are the closest thing to the license
+/// of the artifact they are a part of.
+pub fn check(config: &Config, files: &[String]) -> Vec<Conflict> {
+    let mut dirs: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for file in files {
+        if config.excludes.is_match(file) {
+            continue;
+        }
+
+        if let (Some(dir), Some(ident)) = (parent_dir(file), config.licenses.get_ident(file)) {
+            dirs.entry(dir).or_default().insert(ident);
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for (dir, idents) in &dirs {
+        for (i, inner) in idents.iter().enumerate() {
+            for other in idents.iter().skip(i + 1) {
+                if !compatible(other, inner) && !compatible(inner, other) {
+                    conflicts.push(Conflict {
+                        outer_dir: dir.to_string(),
+                        outer_ident: other.to_string(),
+                        inner_dir: dir.to_string(),
+                        inner_ident: inner.to_string(),
+                    });
+                }
+            }
+
+            let mut ancestor = parent_dir(dir);
+            while let Some(outer_dir) = ancestor {
+                if let Some(outer_idents) = dirs.get(outer_dir) {
+                    for outer in outer_idents {
+                        if !compatible(outer, inner) {
+                            conflicts.push(Conflict {
+                                outer_dir: outer_dir.to_string(),
+                                outer_ident: outer.to_string(),
+                                inner_dir: dir.to_string(),
+                                inner_ident: inner.to_string(),
+                            });
+                        }
+                    }
+
+                    break;
+                }
+
+                ancestor = parent_dir(outer_dir);
+            }
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatible() {
+        assert!(compatible("Apache-2.0", "MIT"));
+        assert!(compatible("GPL-3.0-only", "Apache-2.0"));
+        assert!(compatible("GPL-3.0-or-later", "GPL-2.0-or-later"));
+        assert!(compatible("AGPL-3.0-only", "GPL-3.0-or-later"));
+        assert!(!compatible("Apache-2.0", "GPL-3.0-only"));
+        assert!(!compatible("MIT", "AGPL-3.0"));
+        assert!(!compatible("GPL-2.0", "Apache-2.0"));
+        assert!(!compatible("GPL-3.0-only", "GPL-2.0-only"));
+        assert!(compatible("GPL-2.0+", "Apache-2.0"));
+        assert!(compatible(
+            "Apache-2.0",
+            "GPL-2.0-only WITH Classpath-exception-2.0"
+        ));
+        assert!(compatible("Apache-2.0", "LicenseRef-Proprietary"));
+    }
+
+    static CONFIG: &str = r##"excludes: []
+licenses:
+  - files: vendor/.*
+    ident: GPL-3.0-only
+    authors:
+      - name: Vendor
+    template: "[ident]"
+  - files: lib/legacy.rs
+    ident: GPL-2.0-only
+    authors:
+      - name: Legacy
+    template: "[ident]"
+  - files: any
+    ident: Apache-2.0
+    authors:
+      - name: Us
+    template: "[ident]"
+comments: []
+"##;
+
+    #[test]
+    fn test_check() {
+        let config: Config = serde_yaml::from_str(CONFIG).expect("Parsing static config");
+        let files: Vec<String> = vec![
+            "main.rs",
+            "vendor/deep/dep.rs",
+            "lib/mod.rs",
+            "lib/legacy.rs",
+            "docs/guide.md",
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+
+        let conflicts = check(&config, &files);
+        assert_eq!(
+            vec![
+                "GPL-2.0-only and Apache-2.0 files in lib are incompatible",
+                "GPL-2.0-only files in lib are incompatible with the Apache-2.0 files in .",
+                "GPL-3.0-only files in vendor/deep are incompatible with the Apache-2.0 files in .",
+            ],
+            conflicts.iter().map(|c| c.to_string()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -16,30 +16,33 @@ extern crate chrono;
 extern crate clap;
 #[macro_use]
 extern crate log;
-extern crate regex;
-extern crate serde;
-extern crate serde_yaml;
-extern crate textwrap;
-extern crate ureq;
-
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::ErrorKind;
-use std::path::Path;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, prelude::*, ErrorKind};
+use std::path::{Path, PathBuf};
 use std::process;
 use std::process::Command;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use clap::{App, Arg, SubCommand};
 
-use chrono::offset::{Offset, Utc};
-use clap::{App, Arg};
+use licensure_core::config::{self, Config, DEFAULT_CONFIG};
+use licensure_core::{spdx, trace, utils, Baseline, GitTree, LicenseStats, Processor, Source};
+use walk::{Traversal, Walk};
 
-use config::DEFAULT_CONFIG;
-use licensure::Licensure;
+mod archive;
+mod commands;
+mod commit;
+mod compat;
+mod refs;
+mod report;
+mod sbom;
+mod walk;
 
-mod comments;
-mod config;
-mod licensure;
-mod template;
-mod utils;
+// The exit status of a run in which some files couldn't be processed, which
+// takes precedence over the status of 1 for files without the right header.
+const EXIT_FAILED_FILES: i32 = 2;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
@@ -47,33 +50,115 @@ const ABOUT: &str = env!("CARGO_PKG_DESCRIPTION");
 const HOMEPAGE: &str = env!("CARGO_PKG_HOMEPAGE");
 
 // FIXME: Possible that we should remove this functionality.
-fn get_project_files() -> Vec<String> {
-    let mut files = git_ls_files(Vec::new());
+fn get_project_files(config: &Config) -> Vec<String> {
+    get_project_walk(config).files
+}
 
-    let mut new_unstaged_files = git_ls_files(vec!["--others", "--exclude-standard"]);
+fn get_project_walk(config: &Config) -> Walk {
+    let root = config.root();
+    let mut files = git_ls_files(root, Vec::new());
+
+    let mut new_unstaged_files = git_ls_files(root, vec!["--others", "--exclude-standard"]);
     files.append(&mut new_unstaged_files);
 
-    // If there is a file symlink to outside the project directory we probably
-    // don't want to modify it (it'd be surprising to have external
-    // modifications), and if it's to within the project then we'll modify it
-    // when we come across the "real" file. Furthermore, allowing symlinks adds
-    // the possibility that we'll have ambiguity (or a it's-never-happy fight)
-    // if the symlink has a different file extension than the file it points at.
-    files.retain(|x| !Path::new(x).is_symlink());
-    files
+    // By default symlinks are skipped. If there is a file symlink to outside
+    // the project directory we probably don't want to modify it (it'd be
+    // surprising to have external modifications), and if it's to within the
+    // project then we'll modify it when we come across the "real" file.
+    // Furthermore, allowing symlinks adds the possibility that we'll have
+    // ambiguity (or a it's-never-happy fight) if the symlink has a different
+    // file extension than the file it points at.
+    Traversal {
+        include_hidden: config.include_hidden,
+        follow_symlinks: config.follow_symlinks,
+        nested_repos: config.nested_repos,
+    }
+    .walk(root, &files)
 }
 
-fn git_ls_files(extra_args: Vec<&str>) -> Vec<String> {
-    match Command::new("git")
-        .arg("ls-files")
-        .args(extra_args)
-        .output()
-    {
+// How the files of nested repositories are licensed, the way the command line
+// asks for the files of the project to be.
+#[derive(Debug, Clone, Copy)]
+struct NestedOptions {
+    check: bool,
+    in_place: bool,
+    patch: bool,
+    fixes: bool,
+    years_only: bool,
+}
+
+// License the project files of the git repository nested in dir, which is
+// relative to root, with the config found from there, as if licensure was run
+// in dir. The files in the stats are relative to dir.
+fn license_nested_repo(
+    root: &Path,
+    dir: &str,
+    options: NestedOptions,
+    baseline: &Baseline,
+) -> Result<LicenseStats, io::Error> {
+    let baseline = baseline.within(dir);
+    let repo = root.join(dir);
+    let mut config = config::load_config_from(&repo)?;
+    warn_spdx_options(&config, dir);
+    if options.in_place {
+        config.change_in_place = true;
+    }
+    let walk = get_project_walk(&config);
+    let mut stats = Processor::new(config)
+        .with_check_mode(options.check)
+        .with_patch(options.patch)
+        .with_fixes(options.fixes)
+        .with_years_only(options.years_only)
+        .with_baseline(baseline.clone())
+        .with_output(Box::new(io::stdout()))
+        .license_files(&walk.files)?;
+    for nested in &walk.nested_repos {
+        match license_nested_repo(&repo, nested, options, &baseline) {
+            Ok(nested_stats) => stats.merge(nested_stats, nested),
+            Err(e) => stats.files_failed.push((nested.clone(), e)),
+        }
+    }
+    Ok(stats)
+}
+
+// Warn that the SPDX options the config of the repository nested in dir sets
+// are ignored, SPDX requests are set up once from the top level config.
+fn warn_spdx_options(config: &Config, dir: &str) {
+    let options = [
+        ("ca_bundle", config.ca_bundle.is_some()),
+        ("spdx_base_url", config.spdx_base_url.is_some()),
+        ("spdx_timeout", config.spdx_timeout.is_some()),
+        ("spdx_retries", config.spdx_retries.is_some()),
+        ("spdx_rate_limit", config.spdx_rate_limit.is_some()),
+        ("licenses_registry", config.licenses_registry.is_some()),
+    ];
+    let set: Vec<&str> = options
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| *name)
+        .collect();
+    if !set.is_empty() {
+        warn!(
+            "ignoring {} in the config of the nested repository {}, only the top level config sets them",
+            set.join(", "),
+            dir
+        );
+    }
+}
+
+// The files git ls-files lists in the repository at root, the current
+// directory when it's empty, relative to root.
+fn git_ls_files(root: &Path, extra_args: Vec<&str>) -> Vec<String> {
+    let mut cmd = Command::new("git");
+    if !root.as_os_str().is_empty() {
+        cmd.current_dir(root);
+    }
+    match cmd.arg("ls-files").args(extra_args).output() {
         Ok(proc) => String::from_utf8(proc.stdout)
             .expect("git ls-files output was not UTF-8!")
             .split('\n')
             // git-ls still returns the removed files that are not committed, so we filter those out.
-            .filter(|s| !s.is_empty() && Path::new(s).exists())
+            .filter(|s| !s.is_empty() && root.join(s).exists())
             .map(str::to_string)
             .collect(),
         Err(e) => {
@@ -84,61 +169,507 @@ fn git_ls_files(extra_args: Vec<&str>) -> Vec<String> {
     }
 }
 
+// Fails if licensure can't be run with args, such as when one of them isn't
+// one of its flags.
+fn check_command(args: &[String]) -> Result<(), String> {
+    let argv = std::iter::once("licensure").chain(args.iter().map(String::as_str));
+    cli("")
+        .get_matches_from_safe(argv)
+        .map(|_| ())
+        .map_err(|e| e.message)
+}
+
+// The --commit, --commit-message, and --signoff args, with commit_help
+// saying what --commit commits.
+fn commit_args<'a, 'b>(commit_help: &'b str) -> [Arg<'a, 'b>; 3] {
+    [
+        Arg::with_name("commit")
+            .long("commit")
+            .conflicts_with("check")
+            .help(commit_help),
+        Arg::with_name("commit-message")
+            .long("commit-message")
+            .takes_value(true)
+            .value_name("MESSAGE")
+            .requires("commit")
+            .help("The message of the commit made by --commit [default: Update license headers]"),
+        Arg::with_name("signoff")
+            .long("signoff")
+            .requires("commit")
+            .help("Add a Signed-off-by trailer to the commit made by --commit"),
+    ]
+}
+
+// The --badge and --metrics-file args, which write what happened to the files
+// to a file for dashboards.
+fn report_file_args<'a, 'b>() -> [Arg<'a, 'b>; 2] {
+    [
+        Arg::with_name("badge")
+            .long("badge")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Write a shields.io endpoint badge of the share of files with their header to FILE"),
+        Arg::with_name("metrics-file")
+            .long("metrics-file")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Write counts of what happened to the files and how long it took to FILE in the Prometheus text format"),
+    ]
+}
+
+// The command line interface with the about text of --help, also used to
+// check the commands licensure integrations generates.
+fn cli(about: &str) -> App<'_, '_> {
+    App::new("licensure")
+    .version(VERSION)
+    .author("Mathew Robinson <chasinglogic@gmail.com>")
+    .about(about)
+    .arg(
+        Arg::with_name("verbose")
+            .short("v")
+            .long("verbose")
+            .multiple(true),
+    )
+    .arg(Arg::with_name("in-place").short("i").long("in-place"))
+    .arg(
+        Arg::with_name("check")
+            .long("check")
+            .help("Checks if any file is not licensed with the given config"),
+    )
+    .arg(
+        Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .possible_values(report::FORMATS)
+            .default_value("human")
+            .help("How to report what happened to each file, json, sarif, and patch are printed to stdout"),
+    )
+    .args(&commit_args("Write headers in place and commit the files changed with git"))
+    .arg(
+        Arg::with_name("group-by")
+            .long("group-by")
+            .takes_value(true)
+            .possible_values(report::GROUPS)
+            .help("Group the files missing or with outdated headers in check output by license, extension, or directory"),
+    )
+    .arg(
+        Arg::with_name("changed-only")
+            .long("changed-only")
+            .conflicts_with("format")
+            .help("Print the files whose header was written in place to stdout, one per line"),
+    )
+    .arg(
+        Arg::with_name("write-changed")
+            .long("write-changed")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Write the files whose header was written in place to FILE, one per line"),
+    )
+    .arg(
+        Arg::with_name("baseline")
+            .long("baseline")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Leave the files whose findings are in the baseline FILE alone, so only new findings fail"),
+    )
+    .arg(
+        Arg::with_name("write-baseline")
+            .long("write-baseline")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Write the findings of this run to the baseline FILE"),
+    )
+    .args(&report_file_args())
+    .arg(
+        Arg::with_name("null")
+            .short("z")
+            .long("null")
+            .help("End each file listed by --changed-only and --write-changed with a NUL byte instead of a newline"),
+    )
+    .arg(
+        Arg::with_name("exclude")
+            .short("e")
+            .long("exclude")
+            .takes_value(true)
+            .value_name("REGEX")
+            .help("A regex which will be used to determine what files to ignore."),
+    )
+    .arg(
+        Arg::with_name("exclude-hidden")
+            .long("exclude-hidden")
+            .help("Skip hidden files and files in hidden directories when finding project files"),
+    )
+    .arg(
+        Arg::with_name("follow-symlinks")
+            .long("follow-symlinks")
+            .help("Follow symlinks when finding project files, each file is still only licensed once"),
+    )
+    .arg(
+        Arg::with_name("project-root")
+            .long("project-root")
+            .takes_value(true)
+            .value_name("DIR")
+            .help("Run as if licensure was started in DIR, paths given on the command line are still relative to the current directory"),
+    )
+    .arg(
+        Arg::with_name("config")
+            .long("config")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("FILE")
+            .help("Use FILE as the config instead of finding .licensure.yml, - reads it from stdin, when given more than once each file is merged over the ones before it"),
+    )
+    .arg(
+        Arg::with_name("profile")
+            .long("profile")
+            .takes_value(true)
+            .value_name("NAME")
+            .help("Use the excludes, licenses, and comments of the named profile from the config file"),
+    )
+    .arg(
+        Arg::with_name("trace")
+            .long("trace")
+            .takes_value(true)
+            .value_name("GLOB")
+            .help("Print every decision made about the files matching GLOB to stderr, such as the matchers they were checked against and what was found in their headers"),
+    )
+    .arg(
+        Arg::with_name("offline")
+            .long("offline")
+            .help("Never fetch licenses from SPDX, only use licenses cached by earlier runs or a local spdx_base_url directory"),
+    )
+    .arg(
+        Arg::with_name("deny-deprecated")
+            .long("deny-deprecated")
+            .help("Fail if the config uses deprecated keys instead of only warning about them"),
+    )
+    .arg(Arg::with_name("project").long("project").short("p").help(
+        "When specified will license the current project files as returned by git ls-files",
+    ))
+    .arg(
+        Arg::with_name("ref-range")
+            .long("ref-range")
+            .takes_value(true)
+            .value_name("OLD..NEW")
+            .conflicts_with_all(&["in-place", "commit", "FILES"])
+            .help("Check the project files added or modified between the commits OLD and NEW, such as in a pre-push hook"),
+    )
+    .arg(
+        Arg::with_name("diff-base")
+            .long("diff-base")
+            .takes_value(true)
+            .value_name("REF")
+            .conflicts_with_all(&["in-place", "commit", "FILES", "ref-range"])
+            .help("Check only the findings the changes since the current branch left REF add or touch, such as on a pull request"),
+    )
+    .arg(
+        Arg::with_name("git-ref")
+            .long("git-ref")
+            .takes_value(true)
+            .value_name("REF")
+            .conflicts_with_all(&["in-place", "commit", "FILES", "diff-base"])
+            .help("Check the files of REF, read with git instead of from the working tree, such as in a bare repository"),
+    )
+    .arg(
+        Arg::with_name("archive")
+            .long("archive")
+            .takes_value(true)
+            .value_name("FILE")
+            .conflicts_with_all(&["in-place", "commit", "FILES", "git-ref", "ref-range", "diff-base"])
+            .help("Check the files of a tar, gzipped tar, or zip archive, such as a release artifact"),
+    )
+    .arg(
+        Arg::with_name("generate-config")
+            .long("generate-config")
+            .help("Generate a default licensure config file"),
+    )
+    .arg(
+        Arg::with_name("FILES")
+            .multiple(true)
+            .help("Files to license, ignored if --project is supplied"),
+    )
+    .subcommand(
+        SubCommand::with_name("apply")
+            .about("License the project files beneath the given paths")
+            .arg(Arg::with_name("in-place").short("i").long("in-place"))
+            .arg(
+                Arg::with_name("check")
+                    .long("check")
+                    .help("Checks if any file is not licensed with the given config"),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .long("format")
+                    .takes_value(true)
+                    .possible_values(report::FORMATS)
+                    .default_value("human")
+                    .help("How to report what happened to each file, json, sarif, and patch are printed to stdout"),
+            )
+            .args(&commit_args("Write headers in place and commit the files changed with git"))
+            .arg(
+                Arg::with_name("group-by")
+                    .long("group-by")
+                    .takes_value(true)
+                    .possible_values(report::GROUPS)
+                    .help("Group the files missing or with outdated headers in check output by license, extension, or directory"),
+            )
+            .arg(
+                Arg::with_name("changed-only")
+                    .long("changed-only")
+                    .conflicts_with("format")
+                    .help("Print the files whose header was written in place to stdout, one per line"),
+            )
+            .arg(
+                Arg::with_name("write-changed")
+                    .long("write-changed")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("Write the files whose header was written in place to FILE, one per line"),
+            )
+            .arg(
+                Arg::with_name("baseline")
+                    .long("baseline")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("Leave the files whose findings are in the baseline FILE alone, so only new findings fail"),
+            )
+            .arg(
+                Arg::with_name("write-baseline")
+                    .long("write-baseline")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("Write the findings of this run to the baseline FILE"),
+            )
+            .args(&report_file_args())
+            .arg(
+                Arg::with_name("null")
+                    .short("z")
+                    .long("null")
+                    .help("End each file listed by --changed-only and --write-changed with a NUL byte instead of a newline"),
+            )
+            .arg(
+                Arg::with_name("max-depth")
+                    .long("max-depth")
+                    .takes_value(true)
+                    .value_name("DEPTH")
+                    .help("Only license files at most DEPTH directories beneath each path, files directly inside a path are at depth 1"),
+            )
+            .arg(
+                Arg::with_name("PATHS")
+                    .multiple(true)
+                    .help("Files and directories to license, defaults to the whole project"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("audit")
+            .about("Scans every project file for SPDX tags and license text and reports files under unexpected licenses or with no license information"),
+    )
+    .subcommand(
+        SubCommand::with_name("bump-years")
+            .about("Update the years of headers which are otherwise right in place, leaving every other file alone")
+            .arg(
+                Arg::with_name("check")
+                    .long("check")
+                    .help("Checks if any header has outdated years without updating it"),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .long("format")
+                    .takes_value(true)
+                    .possible_values(report::FORMATS)
+                    .default_value("human")
+                    .help("How to report what happened to each file, json, sarif, and patch are printed to stdout"),
+            )
+            .args(&commit_args("Commit the files whose years were updated with git"))
+            .arg(
+                Arg::with_name("max-depth")
+                    .long("max-depth")
+                    .takes_value(true)
+                    .value_name("DEPTH")
+                    .help("Only update files at most DEPTH directories beneath each path, files directly inside a path are at depth 1"),
+            )
+            .arg(
+                Arg::with_name("PATHS")
+                    .multiple(true)
+                    .help("Files and directories to update, defaults to the whole project"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("config")
+            .about("Manage the config file")
+            .subcommand(
+                SubCommand::with_name("migrate")
+                    .about("Rewrite the config file to the current config version, printing each change")
+                    .arg(
+                        Arg::with_name("check")
+                            .long("check")
+                            .help("Report the changes the config file needs instead of making them"),
+                    ),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("doctor")
+            .about("Check git, the config, access to SPDX, and write permissions, and print how to fix any problems"),
+    )
+    .subcommand(
+        SubCommand::with_name("holders")
+            .about("Lists every copyright holder found in the existing headers of project files with the number of files they appear in"),
+    )
+    .subcommand(
+        SubCommand::with_name("import")
+            .about("Convert the configuration of addlicense, skywalking-eyes, or licenseheaders into a licensure config")
+            .arg(
+                Arg::with_name("from")
+                    .long("from")
+                    .takes_value(true)
+                    .required(true)
+                    .possible_values(&["addlicense", "skywalking-eyes", "license-eye", "licenseheaders"])
+                    .help("The tool to import the configuration of"),
+            )
+            .arg(
+                Arg::with_name("output")
+                    .long("output")
+                    .takes_value(true)
+                    .default_value(".licensure.yml")
+                    .help("Where to write the config, which mustn't exist yet"),
+            )
+            .arg(
+                Arg::with_name("PATH")
+                    .required(true)
+                    .help("The tool's config file, such as .licenserc.yaml, or for addlicense and licenseheaders the script, Makefile, or CI workflow which runs it"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("integrations")
+            .about("Print a pre-commit config and a GitHub Actions workflow which run licensure with this config, checking pull requests and fixing headers every week")
+            .arg(
+                Arg::with_name("pre-commit")
+                    .long("pre-commit")
+                    .help("Only print the pre-commit config"),
+            )
+            .arg(
+                Arg::with_name("github-actions")
+                    .long("github-actions")
+                    .help("Only print the GitHub Actions workflow"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("license-file")
+            .about("Write the full text of the configured licenses to LICENSE, or to LICENSES/<ident>.txt when there is more than one")
+            .arg(
+                Arg::with_name("check")
+                    .long("check")
+                    .help("Checks that the license files exist and match the configured licenses instead of writing them"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("manifests")
+            .about("Checks that the license declared in Cargo.toml, package.json, and pyproject.toml files matches the configured license for their path")
+            .arg(
+                Arg::with_name("fix")
+                    .long("fix")
+                    .help("Update manifests which don't declare the configured license"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("notice")
+            .about("Add the copyright notices of the project's license headers to the NOTICE file")
+            .arg(
+                Arg::with_name("check")
+                    .long("check")
+                    .help("Checks that the NOTICE file is up to date instead of writing it"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("normalize")
+            .about("Rewrite headers which differ from the configured header only in formatting to the configured form")
+            .arg(
+                Arg::with_name("check")
+                    .long("check")
+                    .help("Report the files whose headers need normalizing instead of rewriting them"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("preview")
+            .about("Print the commented header a file would be given without changing it")
+            .arg(
+                Arg::with_name("file")
+                    .long("file")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .required(true)
+                    .help("The file to render the header for, which doesn't need to exist"),
+            )
+            .arg(
+                Arg::with_name("license")
+                    .long("license")
+                    .takes_value(true)
+                    .value_name("IDENT")
+                    .help("Use the license config with this ident instead of the one matching the file"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("sbom")
+            .about("Print a bill of materials listing the license and copyright of every project file")
+            .arg(
+                Arg::with_name("format")
+                    .long("format")
+                    .takes_value(true)
+                    .possible_values(sbom::FORMATS)
+                    .default_value("spdx-json")
+                    .help("The format of the bill of materials"),
+            )
+            .arg(
+                Arg::with_name("output")
+                    .short("o")
+                    .long("output")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("Write the bill of materials to FILE instead of stdout"),
+            ),
+    )
+    .subcommand(
+        SubCommand::with_name("selftest")
+            .about("License a sample file of each comment config and check the header is found again and can be removed"),
+    )
+    .subcommand(
+        SubCommand::with_name("test-templates")
+            .about("Render every license config for a sample file of each comment config and compare the headers with golden files")
+            .arg(
+                Arg::with_name("update")
+                    .long("update")
+                    .help("Write the golden files instead of comparing with them"),
+            )
+            .arg(
+                Arg::with_name("dir")
+                    .long("dir")
+                    .takes_value(true)
+                    .value_name("DIR")
+                    .default_value(commands::GOLDEN_DIR)
+                    .help("The directory holding the golden files"),
+            ),
+    )
+}
+
 fn main() {
-    let matches = App::new("licensure")
-        .version(VERSION)
-        .author("Mathew Robinson <chasinglogic@gmail.com>")
-        .about(
-            format!(
-                "{}
+    let started = Instant::now();
+    let about = format!(
+        "{}
 
 {}
 
 More information is available at: {}",
-                ABOUT,
-                AUTHORS.replace(':', ", "),
-                HOMEPAGE
-            )
-            .as_str(),
-        )
-        .arg(
-            Arg::with_name("verbose")
-                .short("v")
-                .long("verbose")
-                .multiple(true),
-        )
-        .arg(Arg::with_name("in-place").short("i").long("in-place"))
-        .arg(
-            Arg::with_name("check")
-                .long("check")
-                .help("Checks if any file is not licensed with the given config"),
-        )
-        .arg(
-            Arg::with_name("exclude")
-                .short("e")
-                .long("exclude")
-                .takes_value(true)
-                .value_name("REGEX")
-                .help("A regex which will be used to determine what files to ignore."),
-        )
-        .arg(Arg::with_name("project").long("project").short("p").help(
-            "When specified will license the current project files as returned by git ls-files",
-        ))
-        .arg(
-            Arg::with_name("generate-config")
-                .long("generate-config")
-                .help("Generate a default licensure config file"),
-        )
-        .arg(
-            Arg::with_name("FILES")
-                .multiple(true)
-                .help("Files to license, ignored if --project is supplied"),
-        )
-        .get_matches();
+        ABOUT,
+        AUTHORS.replace(':', ", "),
+        HOMEPAGE
+    );
+    let matches = cli(&about).get_matches();
 
+    let mut loggers: Vec<Box<dyn simplelog::SharedLogger>> = Vec::new();
     match matches.occurrences_of("verbose") {
         0 => (),
-        x => simplelog::SimpleLogger::init(
+        x => loggers.push(simplelog::SimpleLogger::new(
             if x >= 3 {
                 simplelog::LevelFilter::Trace
             } else if x >= 2 {
@@ -151,10 +682,58 @@ More information is available at: {}",
                 .set_thread_level(simplelog::LevelFilter::Debug)
                 .set_target_level(simplelog::LevelFilter::Debug)
                 .set_location_level(simplelog::LevelFilter::Trace)
-                .set_time_offset(Utc.fix())
+                .add_filter_ignore_str(trace::TARGET)
                 .build(),
-        )
-        .unwrap(),
+        )),
+    };
+
+    if let Some(glob) = matches.value_of("trace") {
+        if let Err(e) = trace::configure(glob) {
+            println!("Unable to trace {}: {}", glob, e);
+            process::exit(1);
+        }
+        // The trace goes to stderr as it is, apart from the output of the
+        // command.
+        loggers.push(simplelog::WriteLogger::new(
+            simplelog::LevelFilter::Trace,
+            simplelog::ConfigBuilder::new()
+                .set_max_level(simplelog::LevelFilter::Off)
+                .set_time_level(simplelog::LevelFilter::Off)
+                .set_thread_level(simplelog::LevelFilter::Off)
+                .set_target_level(simplelog::LevelFilter::Off)
+                .set_location_level(simplelog::LevelFilter::Off)
+                .add_filter_allow_str(trace::TARGET)
+                .build(),
+            io::stderr(),
+        ));
+    }
+
+    if !loggers.is_empty() {
+        simplelog::CombinedLogger::init(loggers).unwrap();
+    }
+
+    // Paths given on the command line are relative to the directory licensure
+    // was run from but everything else works relative to the project root.
+    let cwd = env::current_dir().unwrap_or_default();
+    let root = match matches.value_of("project-root") {
+        Some(root) => match fs::canonicalize(root).and_then(|root| {
+            env::set_current_dir(&root)?;
+            Ok(root)
+        }) {
+            Ok(root) => root,
+            Err(e) => {
+                println!("Unable to use {} as the project root: {}", root, e);
+                process::exit(1);
+            }
+        },
+        None => cwd.clone(),
+    };
+    let from_cwd = |path: &str| {
+        if root == cwd {
+            path.to_string()
+        } else {
+            walk::relative_to(path, &cwd, &root)
+        }
     };
 
     if matches.is_present("generate-config") {
@@ -174,17 +753,123 @@ More information is available at: {}",
         process::exit(0);
     }
 
-    let files: Vec<String> = if matches.is_present("project") {
-        get_project_files()
+    // Old configs may not load at all so they're migrated before loading.
+    if let Some(sub_matches) = matches.subcommand_matches("config") {
+        let Some(sub_matches) = sub_matches.subcommand_matches("migrate") else {
+            println!("{}", sub_matches.usage());
+            process::exit(1);
+        };
+        let Some(path) = config::find_config_file() else {
+            println!("No config file found, generate one with licensure --generate-config");
+            process::exit(1);
+        };
+
+        match commands::migrate_config(&path, sub_matches.is_present("check")) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                println!("Failed to migrate {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+    }
+
+    // Importing writes a config so it's done before one is loaded.
+    if let Some(sub_matches) = matches.subcommand_matches("import") {
+        let tool = match sub_matches.value_of("from").unwrap_or_default().parse() {
+            Ok(tool) => tool,
+            Err(e) => {
+                println!("{}", e);
+                process::exit(1);
+            }
+        };
+        let path = from_cwd(sub_matches.value_of("PATH").unwrap_or_default());
+        let output = sub_matches.value_of("output").unwrap_or(".licensure.yml");
+        if let Err(e) = commands::import(tool, Path::new(&path), Path::new(&from_cwd(output))) {
+            println!("Failed to import {}: {}", path, e);
+            process::exit(1);
+        }
+        process::exit(0);
+    }
+
+    // With --git-ref, or the NEW of --ref-range, the files and, if it has
+    // one, the config of the ref are checked instead of the working tree.
+    if let Some(name) = matches.subcommand_name() {
+        for source in ["git-ref", "ref-range", "archive", "diff-base"] {
+            if matches.is_present(source) {
+                println!("--{} can't be used with {}", source, name);
+                process::exit(1);
+            }
+        }
+    }
+    let range = matches
+        .value_of("ref-range")
+        .map(|range| match refs::parse_range(range) {
+            Ok(range) => range,
+            Err(e) => {
+                println!("{}", e);
+                process::exit(1);
+            }
+        });
+    let rev = matches
+        .value_of("git-ref")
+        // A deleted branch has no NEW to read, and changes nothing.
+        .or(range.map(|(_, new)| new).filter(|new| !refs::is_null(new)));
+    let tree = rev.map(|rev| match GitTree::open(Path::new("."), rev) {
+        Ok(tree) => tree,
+        Err(e) => {
+            println!("Unable to read the files of {}: {}", rev, e);
+            process::exit(1);
+        }
+    });
+    let archive = matches.value_of("archive").map(|path| {
+        match archive::Archive::open(Path::new(&from_cwd(path))) {
+            Ok(archive) => archive,
+            Err(e) => {
+                println!("Unable to read the archive {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    });
+    let config_paths: Vec<PathBuf> = matches
+        .values_of("config")
+        .map(|paths| {
+            paths
+                .map(|path| match path {
+                    // stdin isn't a path relative to anything.
+                    "-" => PathBuf::from(path),
+                    _ => PathBuf::from(from_cwd(path)),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // The config may not load so doctor loads it itself to report why.
+    if matches.subcommand_matches("doctor").is_some() {
+        let passed = commands::doctor(
+            &config_paths,
+            matches.value_of("profile"),
+            matches.is_present("offline"),
+        );
+        process::exit(if passed { 0 } else { 1 });
+    }
+
+    // Configs given with --config win over the one of the ref.
+    let tree_config = tree.as_ref().filter(|tree| tree.exists(".licensure.yml"));
+    let loaded = if !config_paths.is_empty() {
+        config::load_configs(&config_paths)
+    } else if let Some(tree) = tree_config {
+        tree.read(".licensure.yml")
+            .and_then(|content| {
+                String::from_utf8(content).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+            })
+            .and_then(|content| {
+                let source = format!("{}:.licensure.yml", rev.unwrap());
+                config::parse_config(&content, &source)
+            })
     } else {
-        matches
-            .values_of("FILES")
-            .expect("ERROR: Must provide files to license either as matches or via --project")
-            .map(str::to_string)
-            .collect()
+        config::load_config()
     };
-
-    let mut config = match config::load_config() {
+    let mut config = match loaded {
         Ok(c) => c,
         Err(e) => {
             if ErrorKind::NotFound == e.kind() {
@@ -197,45 +882,523 @@ More information is available at: {}",
         }
     };
 
+    for deprecation in &config.deprecations {
+        eprintln!("warning: {}", deprecation);
+    }
+    if matches.is_present("deny-deprecated") && !config.deprecations.is_empty() {
+        println!("The config uses deprecated keys and --deny-deprecated was given");
+        process::exit(1);
+    }
+
+    let registry = match &config.licenses_registry {
+        Some(path) => spdx::load_registry(path).unwrap_or_else(|e| {
+            println!("{}", e);
+            process::exit(1);
+        }),
+        None => Default::default(),
+    };
+    let configured = spdx::configure(spdx::Settings {
+        ca_bundle: config.ca_bundle.clone(),
+        base_url: config.spdx_base_url.clone(),
+        timeout: config.spdx_timeout,
+        retries: config.spdx_retries,
+        rate_limit: config.spdx_rate_limit,
+        registry,
+        offline: matches.is_present("offline"),
+    });
+    // Only the licenses fetched from SPDX need the network.
+    if let Err(e) = configured {
+        if !config.licenses.fetched_idents().is_empty() {
+            println!("Unable to fetch licenses from SPDX: {}", e);
+            process::exit(1);
+        }
+        debug!("unable to set up SPDX requests: {}", e);
+    }
+
+    if let Some(profile) = matches.value_of("profile") {
+        if let Err(e) = config.use_profile(profile) {
+            println!("Unable to use profile: {}", e);
+            process::exit(1);
+        }
+    }
+
+    if matches.is_present("exclude-hidden") {
+        config.include_hidden = false;
+    }
+
+    if matches.is_present("follow-symlinks") {
+        config.follow_symlinks = true;
+    }
+
+    if matches.subcommand_matches("audit").is_some() {
+        match commands::audit(&config, &get_project_files(&config)) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                println!("Failed to audit project files: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("integrations") {
+        let configs: Vec<&str> = matches.values_of("config").into_iter().flatten().collect();
+        if configs.contains(&"-") {
+            println!(
+                "A config read from stdin can't be used by integrations, give its path instead"
+            );
+            process::exit(1);
+        }
+        let generated = commands::Commands::new(&configs, matches.value_of("profile"));
+        for args in generated.all() {
+            if let Err(e) = check_command(args) {
+                println!("Generated an invalid licensure command {:?}: {}", args, e);
+                process::exit(1);
+            }
+        }
+
+        commands::integrations(
+            &config,
+            &generated,
+            sub_matches.is_present("pre-commit"),
+            sub_matches.is_present("github-actions"),
+        );
+        process::exit(0);
+    }
+
+    if matches.subcommand_matches("holders").is_some() {
+        if let Err(e) = commands::holders(&config, &get_project_files(&config)) {
+            println!("Failed to read project files: {}", e);
+            process::exit(1);
+        }
+
+        process::exit(0);
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("license-file") {
+        match commands::license_file(&config, sub_matches.is_present("check")) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                println!("Failed to write license files: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("manifests") {
+        match commands::manifests(
+            &config,
+            &get_project_files(&config),
+            sub_matches.is_present("fix"),
+        ) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                println!("Failed to check package manifests: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("notice") {
+        match commands::notice(
+            &config,
+            &get_project_files(&config),
+            sub_matches.is_present("check"),
+        ) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                println!("Failed to update NOTICE file: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("normalize") {
+        let files = get_project_files(&config);
+        match commands::normalize(config, &files, sub_matches.is_present("check")) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                println!("Failed to normalize headers: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("preview") {
+        let file = from_cwd(sub_matches.value_of("file").unwrap());
+        match commands::preview(config, &file, sub_matches.value_of("license")) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                println!("Failed to read {}: {}", file, e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if matches.subcommand_matches("selftest").is_some() {
+        if commands::selftest(config) {
+            process::exit(0);
+        }
+        process::exit(1);
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("test-templates") {
+        // The default directory is in the project root.
+        let dir = match sub_matches.occurrences_of("dir") {
+            0 => commands::GOLDEN_DIR.to_string(),
+            _ => from_cwd(sub_matches.value_of("dir").unwrap()),
+        };
+        match commands::test_templates(config, Path::new(&dir), sub_matches.is_present("update")) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                println!("Failed to test templates: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("sbom") {
+        let name = env::current_dir()
+            .ok()
+            .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "project".to_string());
+        let files = get_project_files(&config);
+        let document = match sbom::scan(config, &name, &files) {
+            Ok(document) => document,
+            Err(e) => {
+                println!("Failed to scan project files: {}", e);
+                process::exit(1);
+            }
+        };
+
+        let rendered = sbom::render(&document, sub_matches.value_of("format").unwrap());
+        match sub_matches.value_of("output") {
+            Some(output) => {
+                if let Err(e) = fs::write(output, rendered) {
+                    println!("Unable to write {}: {}", output, e);
+                    process::exit(1);
+                }
+            }
+            None => println!("{}", rendered),
+        }
+
+        process::exit(0);
+    }
+
+    // bump-years is apply limited to the years of existing headers.
+    let years_only = matches.subcommand_matches("bump-years").is_some();
+    let apply_matches = matches
+        .subcommand_matches("apply")
+        .or_else(|| matches.subcommand_matches("bump-years"));
+    // With --diff-base only the files the branch changed are checked.
+    let changes = matches.value_of("diff-base").map(|base| {
+        match refs::branch_changes(Path::new("."), base) {
+            Ok(changes) => changes,
+            Err(e) => {
+                println!("Failed to find the changes since {}: {}", base, e);
+                process::exit(1);
+            }
+        }
+    });
+    let (files, nested_repos) = if let Some(sub_matches) = apply_matches {
+        let paths: Vec<String> = match sub_matches.values_of("PATHS") {
+            Some(paths) => paths.map(from_cwd).collect(),
+            None => vec![".".to_string()],
+        };
+        let max_depth = sub_matches
+            .value_of("max-depth")
+            .map(|depth| match depth.parse() {
+                Ok(depth) => depth,
+                Err(_) => {
+                    println!("--max-depth must be a number, got: {}", depth);
+                    process::exit(1);
+                }
+            });
+
+        // Paths which are files are licensed with this config even when
+        // they're in a nested repository, only directories select those.
+        let dirs: Vec<String> = paths
+            .iter()
+            .filter(|path| !Path::new(path).is_file())
+            .cloned()
+            .collect();
+        let walk = get_project_walk(&config);
+        (
+            walk::select(&walk.files, &paths, max_depth),
+            walk::select(&walk.nested_repos, &dirs, None),
+        )
+    } else if let Some(archive) = &archive {
+        let files = archive
+            .files()
+            .iter()
+            .filter(|file| config.include_hidden || !walk::is_hidden(Path::new(file)))
+            .cloned()
+            .collect();
+        (files, Vec::new())
+    } else if let Some(changes) = &changes {
+        let files = get_project_walk(&config)
+            .files
+            .into_iter()
+            .filter(|file| changes.contains_key(&utils::project_path(file)))
+            .collect();
+        (files, Vec::new())
+    } else if matches.is_present("ref-range") || tree.is_some() {
+        let files = match &tree {
+            Some(tree) => tree
+                .files()
+                .iter()
+                .filter(|file| config.include_hidden || !walk::is_hidden(Path::new(file)))
+                .cloned()
+                .collect(),
+            None => get_project_walk(&config).files,
+        };
+        let files = match range {
+            Some((old, new)) => {
+                let changed = match refs::changed_files(Path::new("."), old, new) {
+                    Ok(changed) => changed,
+                    Err(e) => {
+                        println!(
+                            "Failed to find the files changed in {}..{}: {}",
+                            old, new, e
+                        );
+                        process::exit(1);
+                    }
+                };
+                // Only files which are still project files, so excludes and
+                // the rest of the walk apply, are checked.
+                files
+                    .into_iter()
+                    .filter(|file| changed.iter().any(|changed| changed == file))
+                    .collect()
+            }
+            None => files,
+        };
+        (files, Vec::new())
+    } else if matches.is_present("project") {
+        let walk = get_project_walk(&config);
+        (walk.files, walk.nested_repos)
+    } else {
+        let files = matches
+            .values_of("FILES")
+            .expect("ERROR: Must provide files to license either as matches or via --project")
+            .map(from_cwd)
+            .collect();
+        (files, Vec::new())
+    };
+
     if let Some(exclude) = matches.value_of("exclude") {
-        config.add_exclude(exclude);
+        if let Err(e) = config.add_exclude(exclude) {
+            println!("Failed to add --exclude: {}", e);
+            process::exit(1);
+        }
     }
 
-    if matches.is_present("in-place") {
+    let is_present =
+        |name| matches.is_present(name) || apply_matches.is_some_and(|sub| sub.is_present(name));
+    let check = is_present("check")
+        || matches.is_present("ref-range")
+        || changes.is_some()
+        || tree.is_some()
+        || archive.is_some();
+
+    let commit = is_present("commit");
+    let in_place = is_present("in-place") || commit || (years_only && !check);
+    if in_place {
         config.change_in_place = true;
     }
 
-    let licensure = Licensure::new(config).with_check_mode(matches.is_present("check"));
+    for conflict in compat::check(&config, &files) {
+        eprintln!("warning: {}", conflict);
+    }
+
+    let group_by = apply_matches
+        .and_then(|sub| sub.value_of("group-by"))
+        .or_else(|| matches.value_of("group-by"))
+        .map(|group_by| match group_by {
+            "license" => report::GroupBy::License(
+                files
+                    .iter()
+                    .filter_map(|file| {
+                        let ident = config.licenses.get_ident(file)?;
+                        Some((file.clone(), ident.to_string()))
+                    })
+                    .collect(),
+            ),
+            "extension" => report::GroupBy::Extension,
+            _ => report::GroupBy::Directory,
+        });
+
+    // The format given to apply wins over the default of the top level one.
+    let format = apply_matches
+        .filter(|sub| sub.occurrences_of("format") > 0)
+        .unwrap_or(&matches)
+        .value_of("format")
+        .unwrap();
+    let patch = format == "patch";
+    // Machine readable reports carry a fix for each finding.
+    let fixes = matches!(format, "json" | "sarif");
+
+    let value_of = |name| {
+        apply_matches
+            .and_then(|sub| sub.value_of(name))
+            .or_else(|| matches.value_of(name))
+    };
+    let baseline = match value_of("baseline") {
+        Some(path) => {
+            let path = from_cwd(path);
+            match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| Baseline::parse(&content, &path))
+            {
+                Ok(baseline) => baseline,
+                Err(e) => {
+                    println!(
+                        "Failed to read the baseline {}, write one with --write-baseline: {}",
+                        path, e
+                    );
+                    process::exit(1);
+                }
+            }
+        }
+        None => Baseline::default(),
+    };
+
+    let mut licensure = Processor::new(config)
+        .with_check_mode(check)
+        .with_patch(patch)
+        .with_fixes(fixes)
+        .with_years_only(years_only)
+        .with_baseline(baseline.clone())
+        .with_output(Box::new(io::stdout()));
+    if let Some(tree) = tree {
+        licensure = licensure.with_source(Box::new(tree));
+    }
+    if let Some(archive) = archive {
+        licensure = licensure.with_source(Box::new(archive));
+    }
+    if let Some(changes) = changes {
+        licensure = licensure.with_changes(changes);
+    }
     match licensure.license_files(&files) {
         Err(e) => {
             println!("Failed to license files: {}", e);
             process::exit(1);
         }
-        Ok(stats) => {
-            if matches.is_present("check")
-                && !(stats.files_not_licensed.is_empty()
-                    && stats.files_needing_license_update.is_empty())
-            {
-                if !stats.files_needing_license_update.is_empty() {
-                    eprintln!(
-                        "The following {} files' licenses need to be updated",
-                        stats.files_needing_license_update.len()
-                    );
-                    for file in stats.files_needing_license_update {
-                        eprintln!("{}", file);
-                    }
+        Ok(mut stats) => {
+            // Files of nested repositories belong to other repositories so
+            // only the files of this one are committed.
+            let committed = stats.files_updated.clone();
+            let options = NestedOptions {
+                check,
+                in_place,
+                patch,
+                fixes,
+                years_only,
+            };
+            for dir in &nested_repos {
+                match license_nested_repo(Path::new(""), dir, options, &baseline) {
+                    Ok(nested_stats) => stats.merge(nested_stats, dir),
+                    Err(e) => stats.files_failed.push((dir.clone(), e)),
                 }
+            }
 
-                if !stats.files_not_licensed.is_empty() {
-                    eprintln!(
-                        "The following {} files were not licensed with the given config.",
-                        stats.files_not_licensed.len()
-                    );
-                    for file in stats.files_not_licensed {
-                        eprintln!("{}", file);
-                    }
+            match format {
+                "json" => println!("{}", report::render_json(&stats)),
+                "sarif" => println!("{}", report::render_sarif(&stats)),
+                "patch" => print!("{}", report::render_patch(&stats)),
+                _ => report::print_human(&stats, check, group_by.as_ref()),
+            }
+
+            let null = is_present("null");
+            if is_present("changed-only") {
+                print!("{}", report::changed_files(&stats, null));
+            }
+            let write_changed = apply_matches
+                .and_then(|sub| sub.value_of("write-changed"))
+                .or_else(|| matches.value_of("write-changed"));
+            if let Some(path) = write_changed {
+                let path = from_cwd(path);
+                if let Err(e) = fs::write(&path, report::changed_files(&stats, null)) {
+                    println!("Failed to write the changed files to {}: {}", path, e);
+                    process::exit(1);
+                }
+            }
+
+            let badge = apply_matches
+                .and_then(|sub| sub.value_of("badge"))
+                .or_else(|| matches.value_of("badge"));
+            if let Some(path) = badge {
+                let path = from_cwd(path);
+                if let Err(e) = fs::write(&path, report::render_badge(&stats)) {
+                    println!("Failed to write the badge to {}: {}", path, e);
+                    process::exit(1);
+                }
+            }
+
+            let metrics_file = apply_matches
+                .and_then(|sub| sub.value_of("metrics-file"))
+                .or_else(|| matches.value_of("metrics-file"));
+            if let Some(path) = metrics_file {
+                let path = from_cwd(path);
+                let finished = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let metrics = report::render_metrics(&stats, started.elapsed(), finished);
+                // Written to a temporary file and renamed so the collector
+                // never reads a partly written file.
+                let tmp = format!("{}.{}.tmp", path, process::id());
+                if let Err(e) = fs::write(&tmp, metrics).and_then(|_| fs::rename(&tmp, &path)) {
+                    let _ = fs::remove_file(&tmp);
+                    println!("Failed to write the metrics to {}: {}", path, e);
+                    process::exit(1);
+                }
+            }
+
+            let write_baseline = value_of("write-baseline");
+            if let Some(path) = write_baseline {
+                let path = from_cwd(path);
+                let baseline = Baseline::from_stats(&stats);
+                if let Err(e) = fs::write(&path, baseline.render() + "\n") {
+                    println!("Failed to write the baseline to {}: {}", path, e);
+                    process::exit(1);
+                }
+                eprintln!("Wrote {} findings to the baseline {}", baseline.len(), path);
+            }
+
+            if commit && !committed.is_empty() {
+                let message = apply_matches
+                    .and_then(|sub| sub.value_of("commit-message"))
+                    .or_else(|| matches.value_of("commit-message"))
+                    .unwrap_or(commit::DEFAULT_MESSAGE);
+                if let Err(e) =
+                    commit::commit(Path::new("."), &committed, message, is_present("signoff"))
+                {
+                    println!("Failed to commit the licensed files: {}", e);
+                    process::exit(1);
                 }
+            }
 
+            // Files with no header are left alone by bump-years so they
+            // don't fail its check, and the findings recorded by a new
+            // baseline don't fail the run writing it.
+            let baselining = write_baseline.is_some();
+            let failed = (check
+                && !((years_only || stats.files_not_licensed.is_empty())
+                    && (baselining || stats.files_needing_license_update.is_empty())
+                    && (baselining || stats.files_with_wide_headers.is_empty())))
+                || !((baselining || stats.files_with_other_header.is_empty())
+                    && stats.template_errors.is_empty());
+            if !stats.files_failed.is_empty() {
+                process::exit(EXIT_FAILED_FILES);
+            }
+            if failed {
                 process::exit(1);
             }
         }
@@ -248,6 +1411,14 @@ mod test {
 
     #[test]
     fn test_get_project_files() {
-        assert!(!get_project_files().is_empty())
+        assert!(!get_project_files(&Config::default()).is_empty())
+    }
+
+    #[test]
+    fn test_integration_commands() {
+        let generated = commands::Commands::new(&["ci/licensure.yml"], Some("docs"));
+        for args in generated.all() {
+            assert_eq!(Ok(()), check_command(args), "{:?} isn't valid", args);
+        }
     }
 }
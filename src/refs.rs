@@ -0,0 +1,170 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use licensure_core::changes::{parse_diff, Change};
+
+/// Split a range written as OLD..NEW into OLD and NEW.
+pub fn parse_range(range: &str) -> Result<(&str, &str), String> {
+    match range.split_once("..") {
+        Some((old, new)) if !old.is_empty() && !new.is_empty() && !new.starts_with('.') => {
+            Ok((old, new))
+        }
+        _ => Err(format!(
+            "--ref-range must look like OLD..NEW, got: {}",
+            range
+        )),
+    }
+}
+
+/// The files which were added or modified between the commits old and new
+/// in the git repository in repo, relative to repo. Hooks give an old commit
+/// of all zeros for branches which don't exist yet, every file of new counts
+/// as changed for those, and a new commit of all zeros for deleted branches,
+/// which change nothing.
+pub fn changed_files(repo: &Path, old: &str, new: &str) -> Result<Vec<String>, io::Error> {
+    if is_null(new) {
+        return Ok(Vec::new());
+    }
+    if is_null(old) {
+        return git(repo, &["ls-tree", "-r", "-z", "--name-only", new]);
+    }
+
+    git(
+        repo,
+        &[
+            "diff",
+            "--name-only",
+            "-z",
+            "--relative",
+            "--no-renames",
+            "--diff-filter=AM",
+            old,
+            new,
+        ],
+    )
+}
+
+/// The lines of each file added or modified since the current branch of the
+/// git repository in repo left base, including changes which aren't
+/// committed yet, relative to repo.
+pub fn branch_changes(repo: &Path, base: &str) -> Result<HashMap<String, Change>, io::Error> {
+    let merge_base = output(repo, &["merge-base", base, "HEAD"])?;
+    let diff = output(
+        repo,
+        &[
+            "diff",
+            "-U0",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-renames",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            "--diff-filter=AM",
+            "--relative",
+            merge_base.trim(),
+        ],
+    )?;
+    Ok(parse_diff(&diff))
+}
+
+/// Whether commit is all zeros, as hooks give for a branch which doesn't
+/// exist on one side.
+pub fn is_null(commit: &str) -> bool {
+    commit.chars().all(|c| c == '0')
+}
+
+// Run git in repo with args, returning what it prints. Paths are printed as
+// they are instead of quoted with octal escapes.
+fn output(repo: &Path, args: &[&str]) -> Result<String, io::Error> {
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args(["-c", "core.quotePath=false"])
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} exited with {}: {}",
+            args[0],
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    String::from_utf8(output.stdout).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// Run git in repo with args, returning the NUL separated paths it prints.
+fn git(repo: &Path, args: &[&str]) -> Result<Vec<String>, io::Error> {
+    Ok(output(repo, args)?
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use licensure_core::testing::GitRepo;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(Ok(("main", "HEAD")), parse_range("main..HEAD"));
+        assert!(parse_range("main").is_err());
+        assert!(parse_range("main..").is_err());
+        assert!(parse_range("main...HEAD").is_err());
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let repo = GitRepo::new("refs");
+        fs::write(repo.join("a.py"), "a\n").unwrap();
+        fs::write(repo.join("b.py"), "b\n").unwrap();
+        repo.commit_all("first");
+        let old = repo.git(&["rev-parse", "HEAD"]);
+        fs::write(repo.join("b.py"), "changed\n").unwrap();
+        fs::write(repo.join("c.py"), "c\n").unwrap();
+        fs::remove_file(repo.join("a.py")).unwrap();
+        repo.commit_all("second");
+
+        // Deleted files have nothing to check.
+        assert_eq!(
+            vec!["b.py", "c.py"],
+            changed_files(&repo, &old, "HEAD").unwrap()
+        );
+        assert_eq!(
+            vec!["b.py", "c.py"],
+            changed_files(&repo, &"0".repeat(40), "HEAD").unwrap()
+        );
+        assert!(changed_files(&repo, "HEAD", &"0".repeat(40))
+            .unwrap()
+            .is_empty());
+        assert!(changed_files(&repo, "missing", "HEAD").is_err());
+
+        // Changes which aren't committed yet count too.
+        fs::write(repo.join("c.py"), "c\nd\n").unwrap();
+        let changes = branch_changes(&repo, &old).unwrap();
+        assert_eq!(Some(&Change::Added), changes.get("c.py"));
+        assert_eq!(Some(&Change::Lines(vec![(1, 1)])), changes.get("b.py"));
+        assert_eq!(2, changes.len());
+        assert!(branch_changes(&repo, "missing").is_err());
+    }
+}
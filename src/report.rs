@@ -0,0 +1,733 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::json;
+
+use licensure_core::utils::Replacement;
+use licensure_core::{Fix, LicenseStats, Rule, SkipReason};
+
+pub const FORMATS: &[&str] = &["human", "json", "sarif", "patch"];
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GROUPS: &[&str] = &["license", "extension", "directory"];
+
+/// How the files missing or with outdated headers are grouped when checking.
+pub enum GroupBy {
+    /// By the license identifier of each file.
+    License(HashMap<String, String>),
+    Extension,
+    Directory,
+}
+
+impl GroupBy {
+    fn key(&self, file: &str) -> String {
+        let path = Path::new(file);
+        match self {
+            GroupBy::License(idents) => idents
+                .get(file)
+                .cloned()
+                .unwrap_or_else(|| "no license".to_string()),
+            GroupBy::Extension => match path.extension() {
+                Some(extension) => format!(".{}", extension.to_string_lossy()),
+                None => "no extension".to_string(),
+            },
+            GroupBy::Directory => match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy().to_string(),
+                _ => ".".to_string(),
+            },
+        }
+    }
+}
+
+// The rule each file with a finding was found by.
+fn rules(stats: &LicenseStats) -> HashMap<&str, Rule> {
+    stats
+        .findings
+        .iter()
+        // Files with wide headers are listed with the line, see print_human.
+        .filter(|(_, rule)| *rule != Rule::WideHeader)
+        .map(|(file, rule)| (file.as_str(), *rule))
+        .collect()
+}
+
+// A file followed by the rule it was found by, if any.
+fn file_line(file: &str, rules: &HashMap<&str, Rule>) -> String {
+    match rules.get(file) {
+        Some(rule) => format!("{}: {}", file, rule),
+        None => file.to_string(),
+    }
+}
+
+// Print files one per line, or under a heading for each group with the
+// largest groups first.
+fn print_files(files: &[String], rules: &HashMap<&str, Rule>, group_by: Option<&GroupBy>) {
+    let group_by = match group_by {
+        Some(group_by) => group_by,
+        None => {
+            for file in files {
+                eprintln!("{}", file_line(file, rules));
+            }
+            return;
+        }
+    };
+
+    let mut groups: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+    for file in files {
+        groups.entry(group_by.key(file)).or_default().push(file);
+    }
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by_key(|(_, files)| Reverse(files.len()));
+    for (key, files) in groups {
+        eprintln!("{} ({}):", key, files.len());
+        for file in files {
+            eprintln!("  {}", file_line(file, rules));
+        }
+    }
+}
+
+/// Print what happened to the files of a run to stderr. Files skipped because
+/// they're excluded or no license config matches them are usually numerous
+/// and expected so they're only counted. When checking, the files missing or
+/// with outdated headers are grouped by group_by if it's given. Files with a
+/// finding are followed by the ID of the rule it was found by.
+pub fn print_human(stats: &LicenseStats, check: bool, group_by: Option<&GroupBy>) {
+    let rules = rules(stats);
+    for warning in &stats.warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for (file, reason) in &stats.files_skipped {
+        match reason {
+            SkipReason::Excluded | SkipReason::NoMatcher => {
+                *counts.entry(reason.to_string()).or_default() += 1
+            }
+            // Reported along with the error below.
+            SkipReason::TemplateError(_) => (),
+            _ => eprintln!("{}: skipped: {}", file, reason),
+        }
+    }
+    for (reason, count) in counts {
+        eprintln!("{} files skipped: {}", count, reason);
+    }
+
+    if !stats.files_with_other_header.is_empty() {
+        eprintln!(
+            "The following {} files already have a license header which skip_existing doesn't allow replacing.",
+            stats.files_with_other_header.len()
+        );
+        for file in &stats.files_with_other_header {
+            eprintln!("{}", file_line(file, &rules));
+        }
+    }
+
+    if !stats.files_suppressed.is_empty() {
+        eprintln!(
+            "The following {} files were left alone because their finding is suppressed.",
+            stats.files_suppressed.len()
+        );
+        for (file, rule) in &stats.files_suppressed {
+            eprintln!("{}: {}", file, rule);
+        }
+    }
+
+    // A baseline of a legacy project can hold thousands of files.
+    if !stats.files_baselined.is_empty() {
+        eprintln!(
+            "{} files were left alone because their finding is in the baseline.",
+            stats.files_baselined.len()
+        );
+    }
+
+    if !stats.files_pre_existing.is_empty() {
+        eprintln!(
+            "{} files were left alone because their finding is in a header the changes being checked don't touch.",
+            stats.files_pre_existing.len()
+        );
+    }
+
+    for (error, files) in &stats.template_errors {
+        eprintln!("{}", error);
+        eprintln!(
+            "The following {} files were not licensed because of it.",
+            files.len()
+        );
+        for file in files {
+            eprintln!("{}", file);
+        }
+    }
+
+    if !stats.files_failed.is_empty() {
+        eprintln!(
+            "The following {} files couldn't be processed.",
+            stats.files_failed.len()
+        );
+        for (file, error) in &stats.files_failed {
+            eprintln!("{}: {}", file, error);
+        }
+    }
+
+    if !check {
+        return;
+    }
+
+    if !stats.files_needing_license_update.is_empty() {
+        eprintln!(
+            "The following {} files' licenses need to be updated",
+            stats.files_needing_license_update.len()
+        );
+        print_files(&stats.files_needing_license_update, &rules, group_by);
+    }
+
+    if !stats.files_not_licensed.is_empty() {
+        eprintln!(
+            "The following {} files were not licensed with the given config.",
+            stats.files_not_licensed.len()
+        );
+        print_files(&stats.files_not_licensed, &rules, group_by);
+    }
+
+    if !stats.files_with_wide_headers.is_empty() {
+        eprintln!(
+            "The following {} files have header lines wider than columns, licensure normalize rewraps the headers which are ours.",
+            stats.files_with_wide_headers.len()
+        );
+        for (file, line) in &stats.files_with_wide_headers {
+            eprintln!("{}:{}: {}", file, line, Rule::WideHeader);
+        }
+    }
+}
+
+/// The files whose header was written in place, each followed by a newline
+/// or a NUL byte when null is true.
+pub fn changed_files(stats: &LicenseStats, null: bool) -> String {
+    let terminator = if null { '\0' } else { '\n' };
+    stats
+        .files_updated
+        .iter()
+        .map(|file| format!("{}{}", file, terminator))
+        .collect()
+}
+
+/// Render the changes to the files needing an update as one patch git apply
+/// reads.
+pub fn render_patch(stats: &LicenseStats) -> String {
+    stats
+        .patches
+        .iter()
+        .map(|patch| patch.to_string())
+        .collect()
+}
+
+/// Render what happened to the files of a run as JSON. needs_update lists the
+/// files whose header was missing or outdated, which were also updated unless
+/// checking. findings gives the rule each of them, and each file with another
+/// header it isn't allowed, was found by, suppressed the suppressed ones,
+/// baselined those in the baseline, and pre_existing those in headers the
+/// changes being checked don't touch. fixes gives the header each file
+/// needing an update should have and the text replacing a region of the
+/// file, its start and end given as lines and columns counted from 1 with the
+/// end exclusive, which fixes it.
+pub fn render_json(stats: &LicenseStats) -> String {
+    let findings = |findings: &[(String, Rule)]| -> Vec<serde_json::Value> {
+        findings
+            .iter()
+            .map(|(file, rule)| {
+                json!({ "file": file, "rule": rule.id(), "description": rule.description() })
+            })
+            .collect()
+    };
+    let skipped: Vec<_> = stats
+        .files_skipped
+        .iter()
+        .map(|(file, reason)| json!({ "file": file, "reason": reason.as_str() }))
+        .collect();
+    let template_errors: Vec<_> = stats
+        .template_errors
+        .iter()
+        .map(|(error, files)| json!({ "error": error, "files": files }))
+        .collect();
+    let wide_headers: Vec<_> = stats
+        .files_with_wide_headers
+        .iter()
+        .map(|(file, line)| json!({ "file": file, "line": line }))
+        .collect();
+    let failed: Vec<_> = stats
+        .files_failed
+        .iter()
+        .map(|(file, error)| json!({ "file": file, "error": error.to_string() }))
+        .collect();
+
+    serde_json::to_string_pretty(&json!({
+        "needs_update": stats.files_needing_license_update,
+        "updated": stats.files_updated,
+        "already_licensed": stats.files_already_licensed,
+        "not_licensed": stats.files_not_licensed,
+        "other_header": stats.files_with_other_header,
+        "findings": findings(&stats.findings),
+        "suppressed": findings(&stats.files_suppressed),
+        "baselined": findings(&stats.files_baselined),
+        "pre_existing": findings(&stats.files_pre_existing),
+        "fixes": stats.fixes.iter().map(fix_json).collect::<Vec<_>>(),
+        "wide_headers": wide_headers,
+        "skipped": skipped,
+        "template_errors": template_errors,
+        "failed": failed,
+        "warnings": stats.warnings,
+    }))
+    .expect("report is valid JSON")
+}
+
+fn fix_json(fix: &Fix) -> serde_json::Value {
+    let Replacement {
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+        text,
+    } = &fix.replacement;
+    json!({
+        "file": fix.file,
+        "rule": fix.rule.id(),
+        "header": fix.header,
+        "replacement": {
+            "start": { "line": start_line, "column": start_column },
+            "end": { "line": end_line, "column": end_column },
+            "text": text,
+        },
+    })
+}
+
+// The path of file as a SARIF artifact location.
+fn sarif_uri(file: &str) -> String {
+    let file = file.replace('\\', "/");
+    file.strip_prefix("./").unwrap_or(&file).to_string()
+}
+
+fn fix_description(rule: Rule) -> &'static str {
+    match rule {
+        Rule::MissingHeader => "Add the license header",
+        Rule::OutdatedYear => "Update the years of the license header",
+        Rule::WrongLicense => "Replace the header with the one of the right license",
+        Rule::ForeignCopyright => "Replace the other license header",
+        Rule::WideHeader => "Rewrap the license header",
+    }
+}
+
+// A fix as SARIF gives it.
+fn sarif_fix(fix: &Fix) -> serde_json::Value {
+    let replacement = &fix.replacement;
+    json!({
+        "description": { "text": fix_description(fix.rule) },
+        "artifactChanges": [{
+            "artifactLocation": { "uri": sarif_uri(&fix.file) },
+            "replacements": [{
+                "deletedRegion": {
+                    "startLine": replacement.start_line,
+                    "startColumn": replacement.start_column,
+                    "endLine": replacement.end_line,
+                    "endColumn": replacement.end_column,
+                },
+                "insertedContent": { "text": replacement.text },
+            }],
+        }],
+    })
+}
+
+// What a rule finds, in a sentence.
+fn rule_help(rule: Rule) -> &'static str {
+    match rule {
+        Rule::MissingHeader => "The file has no license header, or none where it belongs.",
+        Rule::OutdatedYear => "The file's license header has outdated years.",
+        Rule::WrongLicense => {
+            "The file has the header of another license, or of an earlier version of its template."
+        }
+        Rule::ForeignCopyright => "The file has a license header which isn't the project's.",
+        Rule::WideHeader => {
+            "The file's license and copyright comments have a line wider than columns."
+        }
+    }
+}
+
+// Why a file with a finding was left alone.
+enum LeftAlone {
+    Suppressed,
+    Unchanged,
+}
+
+/// Render the findings of a run as a SARIF log, for code scanning tools,
+/// with every rule so their IDs are known even when nothing is found.
+/// Findings with a fix carry it. Suppressed findings are included with an
+/// external suppression, as their suppression lives in the config, and
+/// findings in the baseline or in headers the changes being checked don't
+/// touch with the unchanged baseline state.
+pub fn render_sarif(stats: &LicenseStats) -> String {
+    let rules: Vec<_> = Rule::ALL
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id(),
+                "name": rule.description(),
+                "shortDescription": { "text": rule.description() },
+                "fullDescription": { "text": rule_help(*rule) },
+            })
+        })
+        .collect();
+    let suppressed = stats
+        .files_suppressed
+        .iter()
+        .map(|finding| (finding, Some(LeftAlone::Suppressed)));
+    let unchanged = stats
+        .files_baselined
+        .iter()
+        .chain(&stats.files_pre_existing)
+        .map(|finding| (finding, Some(LeftAlone::Unchanged)));
+    let fixes: HashMap<&str, &Fix> = stats
+        .fixes
+        .iter()
+        .map(|fix| (fix.file.as_str(), fix))
+        .collect();
+    let results: Vec<_> = stats
+        .findings
+        .iter()
+        .map(|finding| (finding, None))
+        .chain(suppressed)
+        .chain(unchanged)
+        .map(|((file, rule), left_alone)| {
+            let mut result = json!({
+                "ruleId": rule.id(),
+                "ruleIndex": Rule::ALL.iter().position(|r| r == rule),
+                "level": "error",
+                "message": { "text": rule_help(*rule) },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": sarif_uri(file) },
+                    },
+                }],
+            });
+            match left_alone {
+                Some(LeftAlone::Suppressed) => {
+                    result["suppressions"] = json!([{ "kind": "external" }])
+                }
+                Some(LeftAlone::Unchanged) => result["baselineState"] = json!("unchanged"),
+                None => {
+                    if let Some(fix) = fixes.get(file.as_str()).filter(|fix| fix.rule == *rule) {
+                        result["locations"][0]["physicalLocation"]["region"] =
+                            json!({ "startLine": fix.replacement.start_line });
+                        result["fixes"] = json!([sarif_fix(fix)]);
+                    }
+                }
+            }
+            result
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "licensure",
+                    "version": VERSION,
+                    "informationUri": "https://github.com/chasinglogic/licensure",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    }))
+    .expect("report is valid JSON")
+}
+
+// The files a license config applied to whose header was checked.
+fn checked_files(stats: &LicenseStats) -> usize {
+    stats.files_already_licensed.len()
+        + stats.files_needing_license_update.len()
+        + stats.files_with_other_header.len()
+}
+
+/// Render a shields.io endpoint badge of the share of checked files which
+/// have their header once the run is over, rounded down so a project missing
+/// any header is never shown at 100%.
+pub fn render_badge(stats: &LicenseStats) -> String {
+    let covered = stats.files_already_licensed.len() + stats.files_updated.len();
+    let (message, color) = match (covered * 100).checked_div(checked_files(stats)) {
+        None => ("no files".to_string(), "lightgrey"),
+        Some(percent) => {
+            let color = match percent {
+                100 => "brightgreen",
+                90.. => "green",
+                75.. => "yellowgreen",
+                50.. => "yellow",
+                25.. => "orange",
+                _ => "red",
+            };
+            (format!("{}%", percent), color)
+        }
+    };
+
+    serde_json::to_string(&json!({
+        "schemaVersion": 1,
+        "label": "license headers",
+        "message": message,
+        "color": color,
+    }))
+    .expect("badge is valid JSON")
+}
+
+/// Render counts of what happened to the files of a run, how long it took,
+/// and when it finished, as seconds since the epoch, in the Prometheus text
+/// format for the node exporter's textfile collector. Noncompliant files are
+/// those found missing a header, with an outdated one, or with another
+/// license's, including those this run updated.
+pub fn render_metrics(stats: &LicenseStats, duration: Duration, finished: u64) -> String {
+    let metrics: [(&str, &str, f64); 7] = [
+        (
+            "files_checked",
+            "Files a license config applied to whose header was checked.",
+            checked_files(stats) as f64,
+        ),
+        (
+            "files_updated",
+            "Files whose header was written in place.",
+            stats.files_updated.len() as f64,
+        ),
+        (
+            "files_noncompliant",
+            "Files missing a header, with an outdated one, or with another license's.",
+            (stats.files_needing_license_update.len() + stats.files_with_other_header.len()) as f64,
+        ),
+        (
+            "files_skipped",
+            "Files which were skipped, including excluded ones.",
+            stats.files_skipped.len() as f64,
+        ),
+        (
+            "files_failed",
+            "Files which couldn't be processed.",
+            stats.files_failed.len() as f64,
+        ),
+        (
+            "duration_seconds",
+            "How long the run took.",
+            duration.as_secs_f64(),
+        ),
+        (
+            "last_run_timestamp_seconds",
+            "When the run finished, in seconds since the epoch.",
+            finished as f64,
+        ),
+    ];
+
+    metrics
+        .iter()
+        .map(|(name, help, value)| {
+            format!(
+                "# HELP licensure_{name} {help}\n# TYPE licensure_{name} gauge\nlicensure_{name} {value}\n"
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_by() {
+        let idents = HashMap::from([("src/main.rs".to_string(), "MIT".to_string())]);
+        assert_eq!("MIT", GroupBy::License(idents.clone()).key("src/main.rs"));
+        assert_eq!("no license", GroupBy::License(idents).key("README"));
+        assert_eq!(".rs", GroupBy::Extension.key("src/main.rs"));
+        assert_eq!("no extension", GroupBy::Extension.key("Makefile"));
+        assert_eq!(
+            "src/commands",
+            GroupBy::Directory.key("src/commands/mod.rs")
+        );
+        assert_eq!(".", GroupBy::Directory.key("build.rs"));
+    }
+
+    #[test]
+    fn test_render_json() {
+        let mut stats = LicenseStats::default();
+        stats
+            .files_needing_license_update
+            .push("src/main.rs".to_string());
+        stats
+            .findings
+            .push(("src/main.rs".to_string(), Rule::OutdatedYear));
+        stats
+            .files_skipped
+            .push(("logo.png".to_string(), SkipReason::Binary));
+        stats.files_skipped.push((
+            "gen.rs".to_string(),
+            SkipReason::TemplateError("no template".to_string()),
+        ));
+
+        let report: serde_json::Value =
+            serde_json::from_str(&render_json(&stats)).expect("report is JSON");
+        assert_eq!(json!(["src/main.rs"]), report["needs_update"]);
+        assert_eq!(
+            json!([{ "file": "src/main.rs", "rule": "LIC002", "description": "outdated year" }]),
+            report["findings"]
+        );
+        assert_eq!(
+            json!([
+                { "file": "logo.png", "reason": "binary" },
+                { "file": "gen.rs", "reason": "template_error" },
+            ]),
+            report["skipped"]
+        );
+        assert_eq!(json!([]), report["template_errors"]);
+    }
+
+    #[test]
+    fn test_render_sarif() {
+        let mut stats = LicenseStats::default();
+        stats
+            .findings
+            .push(("./src/main.rs".to_string(), Rule::MissingHeader));
+        stats
+            .findings
+            .push(("vendor/lib.c".to_string(), Rule::ForeignCopyright));
+        stats.fixes.push(Fix {
+            file: "./src/main.rs".to_string(),
+            rule: Rule::MissingHeader,
+            header: "// Copyright 2024\n".to_string(),
+            replacement: Replacement {
+                start_line: 1,
+                start_column: 1,
+                end_line: 1,
+                end_column: 1,
+                text: "// Copyright 2024\n".to_string(),
+            },
+        });
+        stats
+            .files_suppressed
+            .push(("third_party/a.c".to_string(), Rule::OutdatedYear));
+        stats
+            .files_baselined
+            .push(("legacy.c".to_string(), Rule::MissingHeader));
+
+        let log: serde_json::Value =
+            serde_json::from_str(&render_sarif(&stats)).expect("log is JSON");
+        let run = &log["runs"][0];
+        assert_eq!(
+            json!(["LIC001", "LIC002", "LIC003", "LIC004", "LIC005"]),
+            json!(run["tool"]["driver"]["rules"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|rule| rule["id"].clone())
+                .collect::<Vec<_>>())
+        );
+        assert_eq!(json!("LIC004"), run["results"][1]["ruleId"]);
+        assert_eq!(json!(3), run["results"][1]["ruleIndex"]);
+        assert_eq!(json!(null), run["results"][1]["suppressions"]);
+        assert_eq!(
+            json!([{ "kind": "external" }]),
+            run["results"][2]["suppressions"]
+        );
+        assert_eq!(json!("unchanged"), run["results"][3]["baselineState"]);
+        assert_eq!(
+            json!("src/main.rs"),
+            run["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"]
+        );
+        let change = &run["results"][0]["fixes"][0]["artifactChanges"][0];
+        assert_eq!(json!("src/main.rs"), change["artifactLocation"]["uri"]);
+        assert_eq!(
+            json!({
+                "deletedRegion": { "startLine": 1, "startColumn": 1, "endLine": 1, "endColumn": 1 },
+                "insertedContent": { "text": "// Copyright 2024\n" },
+            }),
+            change["replacements"][0]
+        );
+        assert_eq!(json!(null), run["results"][1]["fixes"]);
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let mut stats = LicenseStats::default();
+        assert_eq!("", changed_files(&stats, false));
+
+        stats.files_updated.push("a.rs".to_string());
+        stats.files_updated.push("b c.rs".to_string());
+        assert_eq!("a.rs\nb c.rs\n", changed_files(&stats, false));
+        assert_eq!("a.rs\0b c.rs\0", changed_files(&stats, true));
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        let mut stats = LicenseStats::default();
+        stats.files_needing_license_update.push("a.rs".to_string());
+        stats.files_updated.push("a.rs".to_string());
+        stats.files_already_licensed.push("b.rs".to_string());
+        stats
+            .files_skipped
+            .push(("logo.png".to_string(), SkipReason::Binary));
+
+        let metrics = render_metrics(&stats, Duration::from_millis(1500), 1700000000);
+        assert!(metrics.starts_with(
+            "# HELP licensure_files_checked Files a license config applied to whose header was checked.\n# TYPE licensure_files_checked gauge\nlicensure_files_checked 2\n"
+        ));
+        assert!(metrics.contains("\nlicensure_files_updated 1\n"));
+        assert!(metrics.contains("\nlicensure_files_noncompliant 1\n"));
+        assert!(metrics.contains("\nlicensure_files_skipped 1\n"));
+        assert!(metrics.contains("\nlicensure_files_failed 0\n"));
+        assert!(metrics.contains("\nlicensure_duration_seconds 1.5\n"));
+        assert!(metrics.ends_with("\nlicensure_last_run_timestamp_seconds 1700000000\n"));
+    }
+
+    #[test]
+    fn test_render_badge() {
+        let badge = |stats: &LicenseStats| -> serde_json::Value {
+            serde_json::from_str(&render_badge(stats)).expect("badge is JSON")
+        };
+
+        let mut stats = LicenseStats::default();
+        assert_eq!(
+            json!({
+                "schemaVersion": 1,
+                "label": "license headers",
+                "message": "no files",
+                "color": "lightgrey",
+            }),
+            badge(&stats)
+        );
+
+        stats.files_already_licensed = (0..199).map(|i| format!("{}.rs", i)).collect();
+        stats
+            .files_needing_license_update
+            .push("new.rs".to_string());
+        assert_eq!("99%", badge(&stats)["message"]);
+        assert_eq!("green", badge(&stats)["color"]);
+
+        // Files updated in place have their header by the end of the run.
+        stats.files_updated.push("new.rs".to_string());
+        assert_eq!("100%", badge(&stats)["message"]);
+        assert_eq!("brightgreen", badge(&stats)["color"]);
+
+        stats.files_updated.clear();
+        stats
+            .files_with_other_header
+            .extend((0..200).map(|i| format!("{}.c", i)));
+        assert_eq!("49%", badge(&stats)["message"]);
+        assert_eq!("orange", badge(&stats)["color"]);
+    }
+}
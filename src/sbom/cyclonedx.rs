@@ -0,0 +1,240 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use serde::Serialize;
+
+use super::{Document, FileRecord};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Bom<'a> {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    serial_number: String,
+    version: u32,
+    metadata: Metadata<'a>,
+    components: Vec<Component<'a>>,
+}
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    timestamp: String,
+    tools: Tools,
+    component: Component<'a>,
+}
+
+#[derive(Serialize)]
+struct Tools {
+    components: Vec<Tool>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+struct Component<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hashes: Vec<Hash<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<LicenseChoice<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    evidence: Option<Evidence<'a>>,
+}
+
+#[derive(Serialize)]
+struct Hash<'a> {
+    alg: &'static str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum LicenseChoice<'a> {
+    License { id: &'a str },
+    Expression(&'a str),
+}
+
+#[derive(Serialize)]
+struct Evidence<'a> {
+    licenses: Vec<LicenseChoice<'a>>,
+    copyright: Vec<Copyright<'a>>,
+}
+
+#[derive(Serialize)]
+struct Copyright<'a> {
+    text: &'a str,
+}
+
+// Compound SPDX expressions such as "MIT OR Apache-2.0" must be given as an
+// expression, a single identifier as a license.
+fn license_choice(ident: &str) -> LicenseChoice<'_> {
+    if ident.contains(char::is_whitespace) {
+        LicenseChoice::Expression(ident)
+    } else {
+        LicenseChoice::License { id: ident }
+    }
+}
+
+fn component(record: &FileRecord) -> Component<'_> {
+    let evidence = match (&record.ident, &record.copyright) {
+        (Some(ident), Some(copyright)) if record.has_header => Some(Evidence {
+            licenses: vec![license_choice(ident)],
+            copyright: vec![Copyright { text: copyright }],
+        }),
+        _ => None,
+    };
+
+    Component {
+        kind: "file",
+        bom_ref: format!("file:{}", record.path),
+        name: &record.path,
+        hashes: vec![Hash {
+            alg: "SHA-1",
+            content: &record.sha1,
+        }],
+        licenses: record
+            .ident
+            .as_deref()
+            .map(license_choice)
+            .into_iter()
+            .collect(),
+        evidence,
+    }
+}
+
+// Format a digest as a name based (version 5) UUID.
+fn uuid(digest: &[u8; 20]) -> String {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x50;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Render a CycloneDX 1.5 JSON BOM with one file component per record. The
+/// configured license is recorded as the component's license and, when the
+/// file carries its header, the header's license and copyright notice are
+/// recorded as evidence.
+pub fn render(document: &Document) -> String {
+    let mut hasher = sha1_smol::Sha1::new();
+    hasher.update(document.created.to_rfc3339().as_bytes());
+    for record in &document.files {
+        hasher.update(record.sha1.as_bytes());
+    }
+
+    let bom = Bom {
+        bom_format: "CycloneDX",
+        spec_version: "1.5",
+        serial_number: format!("urn:uuid:{}", uuid(&hasher.digest().bytes())),
+        version: 1,
+        metadata: Metadata {
+            timestamp: document.created.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            tools: Tools {
+                components: vec![Tool {
+                    kind: "application",
+                    name: "licensure",
+                    version: env!("CARGO_PKG_VERSION"),
+                }],
+            },
+            component: Component {
+                kind: "application",
+                bom_ref: document.name.clone(),
+                name: &document.name,
+                hashes: Vec::new(),
+                licenses: Vec::new(),
+                evidence: None,
+            },
+        },
+        components: document.files.iter().map(component).collect(),
+    };
+
+    serde_json::to_string_pretty(&bom).expect("CycloneDX BOM is always serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use chrono::Utc;
+
+    use super::*;
+
+    #[test]
+    fn test_render_cyclonedx() {
+        let document = Document {
+            name: "example".to_string(),
+            created: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+            files: vec![
+                FileRecord {
+                    path: "src/main.rs".to_string(),
+                    sha1: "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_string(),
+                    ident: Some("MIT OR Apache-2.0".to_string()),
+                    copyright: Some("Copyright 2024 Example".to_string()),
+                    has_header: true,
+                },
+                FileRecord {
+                    path: "build.sh".to_string(),
+                    sha1: "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_string(),
+                    ident: Some("MIT".to_string()),
+                    copyright: Some("Copyright 2024 Example".to_string()),
+                    has_header: false,
+                },
+            ],
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&render(&document)).unwrap();
+        assert_eq!("CycloneDX", json["bomFormat"]);
+        assert!(json["serialNumber"]
+            .as_str()
+            .unwrap()
+            .starts_with("urn:uuid:"));
+        assert_eq!("2024-05-01T12:00:00Z", json["metadata"]["timestamp"]);
+
+        let main = &json["components"][0];
+        assert_eq!("file", main["type"]);
+        assert_eq!("SHA-1", main["hashes"][0]["alg"]);
+        assert_eq!("MIT OR Apache-2.0", main["licenses"][0]["expression"]);
+        assert_eq!(
+            "Copyright 2024 Example",
+            main["evidence"]["copyright"][0]["text"]
+        );
+
+        let build = &json["components"][1];
+        assert_eq!("MIT", build["licenses"][0]["license"]["id"]);
+        assert!(build.get("evidence").is_none());
+    }
+
+    #[test]
+    fn test_uuid() {
+        let id = uuid(&[0xff; 20]);
+        assert_eq!("ffffffff-ffff-5fff-bfff-ffffffffffff", id);
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io;
+
+use chrono::{DateTime, Utc};
+
+use licensure_core::config::Config;
+use licensure_core::utils::long_path;
+use licensure_core::Processor;
+
+mod cyclonedx;
+mod spdx;
+
+pub const FORMATS: &[&str] = &["spdx-json", "cyclonedx-json"];
+
+/// What licensure knows about the licensing of a single file.
+pub struct FileRecord {
+    pub path: String,
+    pub sha1: String,
+    // The license and copyright notice the config says this file is under.
+    pub ident: Option<String>,
+    pub copyright: Option<String>,
+    // Whether the file actually carries the header for that license.
+    pub has_header: bool,
+}
+
+/// Everything needed to render a bill of materials in any format.
+pub struct Document {
+    pub name: String,
+    pub created: DateTime<Utc>,
+    pub files: Vec<FileRecord>,
+}
+
+/// Scan files and build a Document describing the license and copyright of
+/// each one which isn't excluded.
+pub fn scan(config: Config, name: &str, files: &[String]) -> Result<Document, io::Error> {
+    let mut records = Vec::new();
+    for file in files {
+        if config.excludes.is_match(file) {
+            continue;
+        }
+
+        let content = fs::read(long_path(file))?;
+        let context = config
+            .licenses
+            .find_for(file, &String::from_utf8_lossy(&content))
+            .map_err(|e| io::Error::other(format!("{}: {}", file, e)))?
+            .map(|(_, license)| license.get_context(file));
+        records.push(FileRecord {
+            path: file.clone(),
+            sha1: sha1_smol::Sha1::from(&content).digest().to_string(),
+            ident: context.as_ref().map(|c| c.ident.clone()),
+            copyright: context.as_ref().map(|c| c.copyright_line()),
+            has_header: false,
+        });
+    }
+
+    let stats = Processor::new(config)
+        .with_check_mode(true)
+        .license_files(files)?;
+    for record in &mut records {
+        record.has_header = stats.files_already_licensed.contains(&record.path);
+    }
+
+    Ok(Document {
+        name: name.to_string(),
+        created: Utc::now(),
+        files: records,
+    })
+}
+
+/// Render the document in the given format, one of FORMATS.
+pub fn render(document: &Document, format: &str) -> String {
+    match format {
+        "spdx-json" => spdx::render(document),
+        "cyclonedx-json" => cyclonedx::render(document),
+        _ => unreachable!("unknown SBOM format {}", format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use licensure_core::testing::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_scan_license_pragma() {
+        let dir = TempDir::new("sbom");
+        let a = dir.write("a.rs", "fn main() {}\n");
+        let b = dir.write("b.rs", "// licensure: license=Apache-2.0\n");
+
+        let config = Config::from_str(
+            r##"
+licenses:
+  - files: any
+    ident: MIT
+    authors:
+      - name: The Tester
+    end_year: "2024"
+    template: "Copyright [year] [name of author]"
+  - files: none
+    ident: Apache-2.0
+    authors:
+      - name: Another Tester
+    end_year: "2024"
+    template: "Copyright [year] [name of author]""##,
+        )
+        .expect("Static config to be parsable");
+        let document = scan(config, "example", &[a, b]).unwrap();
+        let idents: Vec<_> = document.files.iter().map(|f| f.ident.as_deref()).collect();
+        assert_eq!(vec![Some("MIT"), Some("Apache-2.0")], idents);
+        assert_eq!(
+            Some("Copyright 2024 Another Tester"),
+            document.files[1].copyright.as_deref()
+        );
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use serde::Serialize;
+
+use super::{Document, FileRecord};
+
+const NOASSERTION: &str = "NOASSERTION";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxDocument<'a> {
+    spdx_version: &'static str,
+    data_license: &'static str,
+    #[serde(rename = "SPDXID")]
+    spdx_id: &'static str,
+    name: &'a str,
+    document_namespace: String,
+    creation_info: CreationInfo,
+    files: Vec<SpdxFile<'a>>,
+    relationships: Vec<Relationship>,
+}
+
+#[derive(Serialize)]
+struct CreationInfo {
+    created: String,
+    creators: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxFile<'a> {
+    file_name: String,
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    checksums: Vec<Checksum<'a>>,
+    license_concluded: &'a str,
+    license_info_in_files: Vec<&'a str>,
+    copyright_text: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Checksum<'a> {
+    algorithm: &'static str,
+    checksum_value: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Relationship {
+    spdx_element_id: &'static str,
+    relationship_type: &'static str,
+    related_spdx_element: String,
+}
+
+fn spdx_file(idx: usize, record: &FileRecord) -> SpdxFile<'_> {
+    let in_file = if record.has_header {
+        record.ident.as_deref()
+    } else {
+        None
+    };
+
+    SpdxFile {
+        file_name: format!("./{}", record.path.trim_start_matches("./")),
+        spdx_id: format!("SPDXRef-File-{}", idx),
+        checksums: vec![Checksum {
+            algorithm: "SHA1",
+            checksum_value: &record.sha1,
+        }],
+        license_concluded: record.ident.as_deref().unwrap_or(NOASSERTION),
+        license_info_in_files: vec![in_file.unwrap_or(NOASSERTION)],
+        copyright_text: if record.has_header {
+            record.copyright.as_deref().unwrap_or(NOASSERTION)
+        } else {
+            NOASSERTION
+        },
+    }
+}
+
+/// Render an SPDX 2.3 JSON document with one file entry per record.
+pub fn render(document: &Document) -> String {
+    let files: Vec<SpdxFile> = document
+        .files
+        .iter()
+        .enumerate()
+        .map(|(idx, record)| spdx_file(idx, record))
+        .collect();
+
+    // The namespace must be unique per document so it's derived from the
+    // contents of every file and the time of creation.
+    let mut hasher = sha1_smol::Sha1::new();
+    hasher.update(document.created.to_rfc3339().as_bytes());
+    for record in &document.files {
+        hasher.update(record.sha1.as_bytes());
+    }
+
+    let spdx = SpdxDocument {
+        spdx_version: "SPDX-2.3",
+        data_license: "CC0-1.0",
+        spdx_id: "SPDXRef-DOCUMENT",
+        name: &document.name,
+        document_namespace: format!(
+            "https://spdx.org/spdxdocs/{}-{}",
+            document.name,
+            hasher.digest()
+        ),
+        creation_info: CreationInfo {
+            created: document.created.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            creators: vec![format!("Tool: licensure-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        relationships: files
+            .iter()
+            .map(|f| Relationship {
+                spdx_element_id: "SPDXRef-DOCUMENT",
+                relationship_type: "DESCRIBES",
+                related_spdx_element: f.spdx_id.clone(),
+            })
+            .collect(),
+        files,
+    };
+
+    serde_json::to_string_pretty(&spdx).expect("SPDX document is always serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use chrono::Utc;
+
+    use super::*;
+
+    #[test]
+    fn test_render_spdx() {
+        let document = Document {
+            name: "example".to_string(),
+            created: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+            files: vec![
+                FileRecord {
+                    path: "src/main.rs".to_string(),
+                    sha1: "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_string(),
+                    ident: Some("MIT".to_string()),
+                    copyright: Some("Copyright 2024 Example".to_string()),
+                    has_header: true,
+                },
+                FileRecord {
+                    path: "build.sh".to_string(),
+                    sha1: "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_string(),
+                    ident: Some("MIT".to_string()),
+                    copyright: Some("Copyright 2024 Example".to_string()),
+                    has_header: false,
+                },
+            ],
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&render(&document)).unwrap();
+        assert_eq!("SPDX-2.3", json["spdxVersion"]);
+        assert_eq!("2024-05-01T12:00:00Z", json["creationInfo"]["created"]);
+        assert_eq!("./src/main.rs", json["files"][0]["fileName"]);
+        assert_eq!("MIT", json["files"][0]["licenseInfoInFiles"][0]);
+        assert_eq!("Copyright 2024 Example", json["files"][0]["copyrightText"]);
+        assert_eq!("MIT", json["files"][1]["licenseConcluded"]);
+        assert_eq!("NOASSERTION", json["files"][1]["licenseInfoInFiles"][0]);
+        assert_eq!("NOASSERTION", json["files"][1]["copyrightText"]);
+        assert_eq!(
+            "SPDXRef-File-1",
+            json["relationships"][1]["relatedSpdxElement"]
+        );
+    }
+}
@@ -0,0 +1,375 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use licensure_core::config::NestedRepoPolicy;
+
+/// Controls which files are found when walking the project.
+#[derive(Debug, Clone, Copy)]
+pub struct Traversal {
+    pub include_hidden: bool,
+    pub follow_symlinks: bool,
+    pub nested_repos: NestedRepoPolicy,
+}
+
+/// The files found by a [`Traversal`] and, when nested repositories use
+/// their own config, the directories of the nested repositories which were
+/// left to be licensed separately.
+#[derive(Debug, Default)]
+pub struct Walk {
+    pub files: Vec<String>,
+    pub nested_repos: Vec<String>,
+}
+
+/// Whether any component of path, relative to the root of a walk, starts
+/// with a dot.
+pub fn is_hidden(path: &Path) -> bool {
+    path.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        name.starts_with('.') && name != "." && name != ".."
+    })
+}
+
+impl Traversal {
+    /// Expand paths, relative to dir, into the files they contain. Hidden
+    /// files are dropped unless include_hidden is set and symlinks are
+    /// skipped unless follow_symlinks is set, in which case every file or
+    /// directory is only visited once no matter how many links point at it,
+    /// which also stops symlink loops. Git directories are never visited and
+    /// the directories of nested git repositories, such as submodules, are
+    /// handled as nested_repos says. The files and nested repositories found
+    /// are relative to dir too, which is empty for the directory licensure
+    /// runs in.
+    pub fn walk(&self, dir: &Path, paths: &[String]) -> Walk {
+        let mut seen = HashSet::new();
+        let mut walk = Walk::default();
+        let cwd = env::current_dir().unwrap_or_default().join(dir);
+        for path in paths {
+            let path = dir.join(path);
+            let root = walk_root(&path, dir, &cwd);
+            self.visit(root, &path, &mut seen, &mut walk);
+        }
+
+        if !dir.as_os_str().is_empty() {
+            let relative = |path: &mut String| {
+                if let Ok(stripped) = Path::new(path.as_str()).strip_prefix(dir) {
+                    *path = stripped.to_string_lossy().to_string();
+                }
+            };
+            walk.files.iter_mut().for_each(relative);
+            walk.nested_repos.iter_mut().for_each(relative);
+        }
+        walk
+    }
+
+    fn visit(&self, root: &Path, path: &Path, seen: &mut HashSet<PathBuf>, walk: &mut Walk) {
+        if path.file_name().is_some_and(|name| name == ".git") {
+            debug!("skipping {} because it is a git directory.", path.display());
+            return;
+        }
+
+        if !self.include_hidden && is_hidden(path.strip_prefix(root).unwrap_or(path)) {
+            debug!("skipping {} because it is hidden.", path.display());
+            return;
+        }
+
+        if path.is_symlink() && !self.follow_symlinks {
+            debug!("skipping {} because it is a symlink.", path.display());
+            return;
+        }
+
+        let canonical = match fs::canonicalize(path) {
+            Ok(canonical) => canonical,
+            Err(e) => {
+                info!("skipping {}: {}", path.display(), e);
+                return;
+            }
+        };
+
+        if !seen.insert(canonical) {
+            debug!(
+                "skipping {} because it was already visited.",
+                path.display()
+            );
+            return;
+        }
+
+        if !path.is_dir() {
+            walk.files.push(path.to_string_lossy().to_string());
+            return;
+        }
+
+        if path.join(".git").exists() {
+            match self.nested_repos {
+                NestedRepoPolicy::Parent => (),
+                NestedRepoPolicy::Skip => {
+                    info!(
+                        "skipping {} because it is a nested git repository.",
+                        path.display()
+                    );
+                    return;
+                }
+                NestedRepoPolicy::OwnConfig => {
+                    walk.nested_repos.push(path.to_string_lossy().to_string());
+                    return;
+                }
+            }
+        }
+
+        let mut entries: Vec<PathBuf> = match fs::read_dir(path) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+            Err(e) => {
+                info!("skipping {}: {}", path.display(), e);
+                return;
+            }
+        };
+        entries.sort();
+
+        for entry in entries {
+            let entry = entry.strip_prefix("./").unwrap_or(&entry);
+            self.visit(root, entry, seen, walk);
+        }
+    }
+}
+
+// The directory only the part of path beneath which is checked for hidden
+// components: the project, dir for relative paths and project for absolute
+// ones within it, or else the path itself, so a project or path given inside
+// a hidden directory, such as ~/.cache, is still walked.
+fn walk_root<'a>(path: &'a Path, dir: &'a Path, project: &'a Path) -> &'a Path {
+    if path.is_relative() {
+        dir
+    } else if path.starts_with(project) {
+        project
+    } else {
+        path
+    }
+}
+
+// Strip the "./" prefixes and trailing slashes people use when writing paths
+// so they can be compared with the paths git gives us.
+fn normalize(path: &str) -> &str {
+    let mut path = path;
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+
+    match path.trim_end_matches('/') {
+        "" | "." => "",
+        path => path,
+    }
+}
+
+/// Select the files which are beneath any of paths, no more than max_depth
+/// directories deep where a file directly inside a path has a depth of 1.
+/// Paths which are files are always selected, even if they aren't in files.
+pub fn select(files: &[String], paths: &[String], max_depth: Option<usize>) -> Vec<String> {
+    let mut selected: Vec<String> = Vec::new();
+    for path in paths {
+        if Path::new(path).is_file() {
+            if !selected.contains(path) {
+                selected.push(path.clone());
+            }
+            continue;
+        }
+
+        let dir = normalize(path);
+        for file in files {
+            let file_path = normalize(file);
+            let relative = if dir.is_empty() {
+                Some(file_path)
+            } else {
+                file_path
+                    .strip_prefix(dir)
+                    .and_then(|rest| rest.strip_prefix('/'))
+            };
+
+            let depth = match relative {
+                Some(relative) => Path::new(relative).components().count(),
+                None => continue,
+            };
+
+            if max_depth.is_some_and(|max| depth > max) || selected.contains(file) {
+                continue;
+            }
+
+            selected.push(file.clone());
+        }
+    }
+
+    selected
+}
+
+// Lexically remove . and .. components from path for paths which don't exist
+// and so can't be canonicalized.
+fn clean(path: &Path) -> PathBuf {
+    let mut cleaned = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                cleaned.pop();
+            }
+            c => cleaned.push(c),
+        }
+    }
+
+    cleaned
+}
+
+/// Rewrite a path given relative to cwd so it is relative to root, which
+/// should be absolute, so matchers and reports see the same path whatever
+/// directory licensure was run from. Paths outside of root are made absolute.
+pub fn relative_to(path: &str, cwd: &Path, root: &Path) -> String {
+    let absolute = cwd.join(path);
+    let absolute = fs::canonicalize(&absolute).unwrap_or_else(|_| clean(&absolute));
+    match absolute.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.to_string_lossy().to_string(),
+        Err(_) => absolute.to_string_lossy().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use licensure_core::testing::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_is_hidden() {
+        assert!(is_hidden(Path::new(".github/workflows/ci.yml")));
+        assert!(is_hidden(Path::new("src/.env")));
+        assert!(!is_hidden(Path::new("./src/main.rs")));
+        assert!(!is_hidden(Path::new("../crate/src/main.rs")));
+    }
+
+    #[test]
+    fn test_select() {
+        let files: Vec<String> = vec![
+            "build.rs",
+            "src/main.rs",
+            "src/config/mod.rs",
+            "src_extra/lib.rs",
+            "include/a/b/c.h",
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+        let paths = |ps: &[&str]| ps.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            vec!["src/main.rs", "src/config/mod.rs", "include/a/b/c.h"],
+            select(&files, &paths(&["./src/", "include"]), None)
+        );
+        assert_eq!(
+            vec!["src/main.rs"],
+            select(&files, &paths(&["src"]), Some(1))
+        );
+        assert_eq!(
+            vec!["build.rs", "src/main.rs", "src_extra/lib.rs"],
+            select(&files, &paths(&["."]), Some(2))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_relative_to() {
+        let root = Path::new("/does/not/exist/project");
+        let cwd = root.join("src");
+        assert_eq!("src/main.rs", relative_to("main.rs", &cwd, root));
+        assert_eq!(".", relative_to("..", &cwd, root));
+        assert_eq!("README.md", relative_to("./../README.md", &cwd, root));
+        assert_eq!(
+            "/elsewhere/file.rs",
+            relative_to("/elsewhere/file.rs", &cwd, root)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_files() {
+        // The directory walked being hidden itself doesn't hide its files.
+        let tmp = TempDir::new("walk");
+        let dir = tmp.join(".walk");
+        fs::create_dir_all(dir.join("src/.hidden")).unwrap();
+        fs::write(dir.join("src/main.rs"), "").unwrap();
+        fs::write(dir.join("src/.hidden/secret.rs"), "").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("src/loop")).unwrap();
+        std::os::unix::fs::symlink(dir.join("src/main.rs"), dir.join("link.rs")).unwrap();
+
+        let path = |p: &str| dir.join(p).to_string_lossy().to_string();
+        let root = vec![dir.to_string_lossy().to_string()];
+
+        let traversal = Traversal {
+            include_hidden: true,
+            follow_symlinks: false,
+            nested_repos: NestedRepoPolicy::Parent,
+        };
+        assert_eq!(
+            vec![path("src/.hidden/secret.rs"), path("src/main.rs")],
+            traversal.walk(Path::new(""), &root).files
+        );
+
+        // The link to main.rs is visited first and the loop back to the root
+        // directory is only followed once.
+        let traversal = Traversal {
+            include_hidden: false,
+            follow_symlinks: true,
+            nested_repos: NestedRepoPolicy::Parent,
+        };
+        assert_eq!(
+            vec![path("link.rs")],
+            traversal.walk(Path::new(""), &root).files
+        );
+    }
+
+    #[test]
+    fn test_nested_repos() {
+        let dir = TempDir::new("nested");
+        fs::create_dir_all(dir.join("sub/.git")).unwrap();
+        fs::write(dir.join("sub/.git/HEAD"), "").unwrap();
+        fs::write(dir.join("sub/lib.rs"), "").unwrap();
+        fs::write(dir.join("main.rs"), "").unwrap();
+
+        let path = |p: &str| dir.join(p).to_string_lossy().to_string();
+        let paths = vec![path("main.rs"), path("sub")];
+        let traversal = |nested_repos| Traversal {
+            include_hidden: true,
+            follow_symlinks: false,
+            nested_repos,
+        };
+
+        let walk = traversal(NestedRepoPolicy::Parent).walk(Path::new(""), &paths);
+        assert_eq!(vec![path("main.rs"), path("sub/lib.rs")], walk.files);
+        assert!(walk.nested_repos.is_empty());
+
+        let walk = traversal(NestedRepoPolicy::Skip).walk(Path::new(""), &paths);
+        assert_eq!(vec![path("main.rs")], walk.files);
+        assert!(walk.nested_repos.is_empty());
+
+        let walk = traversal(NestedRepoPolicy::OwnConfig).walk(Path::new(""), &paths);
+        assert_eq!(vec![path("main.rs")], walk.files);
+        assert_eq!(vec![path("sub")], walk.nested_repos);
+
+        // Paths within a directory are given and found relative to it.
+        let paths = vec!["main.rs".to_string(), "sub".to_string()];
+        let walk = traversal(NestedRepoPolicy::OwnConfig).walk(&dir, &paths);
+        assert_eq!(vec!["main.rs"], walk.files);
+        assert_eq!(vec!["sub"], walk.nested_repos);
+    }
+}
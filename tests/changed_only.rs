@@ -0,0 +1,56 @@
+// Copyright (C) 2024 Mathew Robinson <chasinglogic@gmail.com>
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::process::Command;
+
+use licensure_core::testing::TempDir;
+
+const CONFIG: &str = r##"licenses:
+  - files: any
+    ident: MIT
+    authors:
+      - name: The Tester
+    template: "Copyright [year] [name of author]"
+comments:
+  - extension: sh
+    commenter:
+      type: line
+      comment_char: "#"
+"##;
+
+#[test]
+fn test_changed_only_prints_only_the_changed_files() {
+    let dir = TempDir::new("changed-only");
+    fs::write(dir.join(".licensure.yml"), CONFIG).expect("Can write config");
+    // A shebang is kept above the header and mustn't show up in the output.
+    fs::write(dir.join("a.sh"), "#!/bin/sh\necho a\n").expect("Can write file");
+    fs::write(dir.join("b.sh"), "echo b\n").expect("Can write file");
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_licensure"))
+            .current_dir(&dir)
+            .args(args)
+            .output()
+            .expect("Can run licensure");
+        assert!(output.status.success(), "licensure {:?} failed", args);
+        String::from_utf8(output.stdout).expect("stdout is UTF-8")
+    };
+
+    assert_eq!(
+        "a.sh\0",
+        run(&["--in-place", "--changed-only", "--null", "a.sh"])
+    );
+    assert_eq!("b.sh\n", run(&["--in-place", "--changed-only", "b.sh"]));
+    assert_eq!("", run(&["--in-place", "--changed-only", "a.sh", "b.sh"]));
+}